@@ -0,0 +1,351 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::fs::Fs;
+use crate::kernel::load_module;
+use crate::{Chip, Error, Offset, Result};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// The gpio-aggregator driver in sysfs.
+const DRIVER_DIR: &str = "/sys/bus/platform/drivers/gpio-aggregator";
+
+/// The platform devices in sysfs.
+const DEVICES_DIR: &str = "/sys/bus/platform/devices";
+
+/// A **gpio-aggregator** device, presenting lines of simulated chips as a
+/// new gpiochip.
+///
+/// Created by [`Sim::aggregate`], and removed before the simulator is removed,
+/// or by [`remove`].
+///
+/// [`remove`]: Aggregator::remove
+/// [`Sim::aggregate`]: crate::Sim::aggregate
+#[derive(Debug)]
+pub struct Aggregator {
+    /// The filesystem containing sysfs.
+    fs: Arc<dyn Fs>,
+
+    /// The name of the platform device.
+    dev_name: String,
+
+    /// The name of the aggregated gpiochip.
+    chip_name: String,
+
+    /// The aggregated lines, identified by the label of their chip and their
+    /// offset on that chip, in the order they appear on the gpiochip.
+    lines: Vec<(String, Offset)>,
+
+    /// Set once the device has been removed.
+    removed: AtomicBool,
+}
+
+impl Aggregator {
+    pub(crate) fn create(fs: Arc<dyn Fs>, lines: &[(&Chip, Offset)]) -> Result<Aggregator> {
+        if lines.is_empty() {
+            return Err(Error::InvalidConfig("aggregator has no lines".into()));
+        }
+        for &(chip, offset) in lines {
+            check_offset(chip, offset)?;
+            let label = &chip.config().label;
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return Err(Error::InvalidConfig(format!(
+                    "chip label {:?} cannot be aggregated",
+                    label
+                )));
+            }
+        }
+        let driver = Path::new(DRIVER_DIR);
+        if !fs.exists(driver) {
+            load_module("gpio-aggregator")?;
+        }
+        let before = aggregator_devices(fs.as_ref())?;
+        let lines: Vec<(String, Offset)> = lines
+            .iter()
+            .map(|&(chip, offset)| (chip.config().label.clone(), offset))
+            .collect();
+        fs.write(&driver.join("new_device"), spec(&lines).as_bytes())?;
+        // a device created concurrently by another process could be
+        // mistaken for this one, so take the first new device
+        let dev_name = aggregator_devices(fs.as_ref())?
+            .difference(&before)
+            .next()
+            .cloned()
+            .ok_or_else(|| Error::UnexpectedValue("no aggregator device created".into()))?;
+        let mut agg = Aggregator {
+            fs,
+            dev_name,
+            chip_name: String::new(),
+            lines,
+            removed: AtomicBool::new(false),
+        };
+        // the chip is added once the device has been probed
+        let dev_dir = Path::new(DEVICES_DIR).join(&agg.dev_name);
+        for _ in 0..10 {
+            if let Some(chip_name) = agg
+                .fs
+                .list_dir(&dev_dir)?
+                .into_iter()
+                .find(|e| e.starts_with("gpiochip"))
+            {
+                agg.chip_name = chip_name;
+                return Ok(agg);
+            }
+            sleep(Duration::from_millis(100));
+        }
+        Err(Error::InvalidConfig(format!(
+            "aggregator {} has no gpiochip, so its lines were not found or are in use",
+            agg.dev_name
+        )))
+    }
+
+    /// The name of the platform device.
+    ///
+    /// e.g. `gpio-aggregator.0`
+    pub fn dev_name(&self) -> &str {
+        &self.dev_name
+    }
+
+    /// The name of the aggregated gpiochip.
+    ///
+    /// e.g. `gpiochip3`
+    pub fn chip_name(&self) -> &str {
+        &self.chip_name
+    }
+
+    /// The path to the aggregated gpiochip.
+    ///
+    /// e.g. `/dev/gpiochip3`
+    pub fn dev_path(&self) -> PathBuf {
+        Path::new("/dev").join(&self.chip_name)
+    }
+
+    /// The aggregated lines, identified by the label of their chip and their
+    /// offset on that chip.
+    ///
+    /// The index of a line in the list is its offset on the aggregated chip.
+    pub fn lines(&self) -> &[(String, Offset)] {
+        &self.lines
+    }
+
+    /// Remove the device.
+    ///
+    /// The device is otherwise removed with the simulator.
+    pub fn remove(&self) -> Result<()> {
+        if self.removed.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.fs
+            .write(
+                &Path::new(DRIVER_DIR).join("delete_device"),
+                self.dev_name.as_bytes(),
+            )
+            .map_err(Error::IoError)
+    }
+}
+
+impl Drop for Aggregator {
+    fn drop(&mut self) {
+        let _ = self.remove();
+    }
+}
+
+/// The aggregators created over the lines of a simulator, removed when
+/// dropped.
+#[derive(Debug, Default)]
+pub(crate) struct Aggregators(Mutex<Vec<Arc<Aggregator>>>);
+
+impl Aggregators {
+    pub(crate) fn push(&self, agg: Arc<Aggregator>) {
+        self.0.lock().unwrap().push(agg);
+    }
+
+    /// Remove the aggregators, ignoring errors.
+    pub(crate) fn remove(&self) {
+        let mut aggs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for agg in aggs.drain(..) {
+            let _ = agg.remove();
+        }
+    }
+}
+
+impl Drop for Aggregators {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+// The names of the gpio-aggregator devices.
+fn aggregator_devices(fs: &dyn Fs) -> io::Result<BTreeSet<String>> {
+    Ok(fs
+        .list_dir(Path::new(DEVICES_DIR))?
+        .into_iter()
+        .filter(|e| e.starts_with("gpio-aggregator."))
+        .collect())
+}
+
+// The new_device specification for the lines, e.g. "left 0,2 right 1".
+//
+// The driver sorts the offsets of each chip in the specification, so each
+// run of ascending offsets on the same chip is specified separately to
+// preserve the order of the lines.
+fn spec(lines: &[(String, Offset)]) -> String {
+    let mut spec = String::new();
+    let mut prev: Option<&(String, Offset)> = None;
+    for line in lines {
+        match prev {
+            Some(p) if p.0 == line.0 && p.1 < line.1 => spec.push(','),
+            _ => {
+                if prev.is_some() {
+                    spec.push(' ');
+                }
+                spec.push_str(&line.0);
+                spec.push(' ');
+            }
+        }
+        spec.push_str(&line.1.to_string());
+        prev = Some(line);
+    }
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FsDir;
+    use crate::{builder, Bank, Memory};
+
+    // A filesystem that emulates the gpio-aggregator sysfs interface.
+    #[derive(Debug, Default)]
+    struct FakeFs(Mutex<FakeState>);
+
+    #[derive(Debug, Default)]
+    struct FakeState {
+        devices: BTreeSet<String>,
+        writes: Vec<String>,
+        probe: bool,
+    }
+
+    impl Fs for FakeFs {
+        fn create_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn remove_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            path == Path::new(DRIVER_DIR)
+        }
+
+        fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            let data = String::from_utf8_lossy(data).to_string();
+            state.writes.push(format!(
+                "{} {}",
+                path.file_name().unwrap().to_str().unwrap(),
+                data
+            ));
+            if path.ends_with("new_device") {
+                let dev_name = format!("gpio-aggregator.{}", state.writes.len());
+                state.devices.insert(dev_name);
+            } else if !state.devices.remove(&data) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            Ok(())
+        }
+
+        fn read_to_string(&self, _path: &Path) -> io::Result<String> {
+            unimplemented!()
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+            let state = self.0.lock().unwrap();
+            if path == Path::new(DEVICES_DIR) {
+                let mut entries = vec!["serial8250".to_string()];
+                entries.extend(state.devices.iter().cloned());
+                return Ok(entries);
+            }
+            let dev_name = path.file_name().unwrap().to_str().unwrap();
+            if !state.devices.contains(dev_name) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let mut entries = vec!["driver".to_string()];
+            if state.probe {
+                entries.push("gpiochip7".into());
+            }
+            Ok(entries)
+        }
+
+        fn open_dir(&self, _path: &Path) -> io::Result<Box<dyn FsDir>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn format_spec() {
+        let line = |label: &str, offset| (label.to_string(), offset);
+        assert_eq!(spec(&[line("left", 3)]), "left 3");
+        assert_eq!(
+            spec(&[line("left", 0), line("left", 2), line("right", 1)]),
+            "left 0,2 right 1"
+        );
+        assert_eq!(
+            spec(&[line("left", 3), line("left", 1), line("left", 2)]),
+            "left 3 left 1,2"
+        );
+    }
+
+    #[test]
+    fn create() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live()
+            .unwrap();
+        let (left, right) = (&sim.chips()[0], &sim.chips()[1]);
+        let fs = Arc::new(FakeFs::default());
+        fs.0.lock().unwrap().probe = true;
+        let agg = Aggregator::create(fs.clone(), &[(left, 5), (right, 1), (left, 2)]).unwrap();
+        assert_eq!(agg.dev_name(), "gpio-aggregator.1");
+        assert_eq!(agg.chip_name(), "gpiochip7");
+        assert_eq!(agg.dev_path(), Path::new("/dev/gpiochip7"));
+        assert_eq!(
+            agg.lines(),
+            &[
+                ("left".to_string(), 5),
+                ("right".to_string(), 1),
+                ("left".to_string(), 2)
+            ]
+        );
+        let aggs = Aggregators::default();
+        aggs.push(Arc::new(agg));
+        drop(aggs);
+        assert_eq!(
+            fs.0.lock().unwrap().writes,
+            vec![
+                "new_device left 5 right 1 left 2",
+                "delete_device gpio-aggregator.1"
+            ]
+        );
+        assert!(fs.0.lock().unwrap().devices.is_empty());
+
+        assert!(Aggregator::create(fs.clone(), &[]).is_err());
+        assert!(Aggregator::create(fs.clone(), &[(right, 4)]).is_err());
+        assert_eq!(fs.0.lock().unwrap().writes.len(), 2);
+
+        // the device is removed if the chip is never added
+        fs.0.lock().unwrap().probe = false;
+        assert!(Aggregator::create(fs.clone(), &[(right, 0)]).is_err());
+        assert!(fs.0.lock().unwrap().devices.is_empty());
+    }
+}
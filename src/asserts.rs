@@ -0,0 +1,201 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Assertions on the state of simulated lines.
+//!
+//! The [`assert_level!`] and [`assert_pull!`] macros check the level or pull
+//! of a line, and on failure panic with a message identifying the chip and
+//! line, the expected and actual values, and the most recent pulls applied to
+//! the line, if the chip is [recording pulls]:
+//!
+//! ```text
+//! assertion failed: level of gpiochip0 (left) line 3 "led": expected high, got low
+//! recent pulls on line 3:
+//!   1.204ms high
+//!   5.871ms low
+//! ```
+//!
+//! The expected value may be `High` or `Low`, or any expression evaluating to a
+//! [`Level`]:
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::{assert_level, assert_pull, Bank};
+//!
+//! let sim = gpiosim::builder()
+//!     .with_bank(Bank::new(8, "left").name(3, "led"))
+//!     .live()?;
+//! let c = &sim.chips()[0];
+//! c.record_pulls(true);
+//! c.pullup(3)?;
+//! assert_pull!(c, 3, High);
+//! assert_level!(c, 3, gpiosim::Level::High);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [recording pulls]: crate::Chip::record_pulls
+//! [`Level`]: crate::Level
+
+use crate::{Chip, Level, Offset};
+
+/// The number of recent pulls included in a failure message.
+const RECENT_PULLS: usize = 8;
+
+/// Assert that a line of a [`Chip`] is at a level.
+///
+/// See the [`asserts`](crate::asserts) module.
+///
+/// [`Chip`]: crate::Chip
+#[macro_export]
+macro_rules! assert_level {
+    ($chip:expr, $offset:expr, High $(,)?) => {
+        $crate::assert_level!($chip, $offset, $crate::Level::High)
+    };
+    ($chip:expr, $offset:expr, Low $(,)?) => {
+        $crate::assert_level!($chip, $offset, $crate::Level::Low)
+    };
+    ($chip:expr, $offset:expr, $level:expr $(,)?) => {
+        $crate::asserts::assert_line(&$chip, $offset, $level, $crate::asserts::Check::Level)
+    };
+}
+
+/// Assert that the simulated pull on a line of a [`Chip`] is a level.
+///
+/// See the [`asserts`](crate::asserts) module.
+///
+/// [`Chip`]: crate::Chip
+#[macro_export]
+macro_rules! assert_pull {
+    ($chip:expr, $offset:expr, High $(,)?) => {
+        $crate::assert_pull!($chip, $offset, $crate::Level::High)
+    };
+    ($chip:expr, $offset:expr, Low $(,)?) => {
+        $crate::assert_pull!($chip, $offset, $crate::Level::Low)
+    };
+    ($chip:expr, $offset:expr, $level:expr $(,)?) => {
+        $crate::asserts::assert_line(&$chip, $offset, $level, $crate::asserts::Check::Pull)
+    };
+}
+
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub enum Check {
+    Level,
+    Pull,
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_line(chip: &Chip, offset: Offset, expected: Level, check: Check) {
+    if let Err(msg) = check_line(chip, offset, expected, check) {
+        panic!("{}", msg);
+    }
+}
+
+// Check the line, returning the failure message if it does not match.
+fn check_line(chip: &Chip, offset: Offset, expected: Level, check: Check) -> Result<(), String> {
+    let (what, actual) = match check {
+        Check::Level => ("level", chip.get_level(offset)),
+        Check::Pull => ("pull", chip.get_pull(offset)),
+    };
+    let cfg = chip.config();
+    let mut line = format!(
+        "{} of {} ({}) line {}",
+        what, chip.chip_name, cfg.label, offset
+    );
+    if let Some(name) = &cfg.line_config(offset).name {
+        line += &format!(" {:?}", name);
+    }
+    let mut msg = match actual {
+        Ok(actual) if actual == expected => return Ok(()),
+        Ok(actual) => format!(
+            "assertion failed: {}: expected {}, got {}",
+            line, expected, actual
+        ),
+        Err(e) => format!("assertion failed: could not read {}: {}", line, e),
+    };
+    let pulls: Vec<_> = chip
+        .pull_history()
+        .into_iter()
+        .filter(|r| r.offset == offset)
+        .collect();
+    if pulls.is_empty() {
+        msg += &format!("\nno pulls recorded on line {}", offset);
+    } else {
+        msg += &format!("\nrecent pulls on line {}:", offset);
+        for r in &pulls[pulls.len().saturating_sub(RECENT_PULLS)..] {
+            msg += &format!("\n  {:?} {}", r.time, r.pull);
+        }
+    }
+    Err(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn message() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(Bank::new(8, "left").name(3, "led"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert!(check_line(c, 3, Level::Low, Check::Level).is_ok());
+
+        let msg = check_line(c, 3, Level::High, Check::Pull).unwrap_err();
+        let lines: Vec<&str> = msg.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "assertion failed: pull of {} (left) line 3 \"led\": expected high, got low",
+                c.chip_name
+            )
+        );
+        assert_eq!(lines[1], "no pulls recorded on line 3");
+
+        c.record_pulls(true);
+        for _ in 0..10 {
+            c.toggle(3).unwrap();
+        }
+        c.pullup(2).unwrap();
+        mem.drive(c, 3, Some(Level::High)).unwrap();
+        let msg = check_line(c, 3, Level::Low, Check::Level).unwrap_err();
+        let lines: Vec<&str> = msg.lines().collect();
+        assert!(lines[0].ends_with("expected low, got high"));
+        assert_eq!(lines[1], "recent pulls on line 3:");
+        assert_eq!(lines.len(), 2 + RECENT_PULLS);
+        assert!(lines[2..]
+            .iter()
+            .all(|l| l.ends_with("high") || l.ends_with("low")));
+
+        let msg = check_line(c, 8, Level::Low, Check::Level).unwrap_err();
+        assert!(msg.starts_with(&format!(
+            "assertion failed: could not read level of {} (left) line 8: ",
+            c.chip_name
+        )));
+    }
+
+    #[test]
+    fn macros() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        assert_level!(c, 1, High);
+        assert_pull!(c, 1, High);
+        assert_level!(c, 2, Low);
+        assert_pull!(c, 2, Level::Low,);
+        let res =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| assert_level!(c, 1, Low)));
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Aggregator, Bank, Chip, Error, Level, Offset, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A provider of live simulators.
+///
+/// The default backend is the [`Kernel`], which uses the **gpio-sim** kernel module.
+/// The [`Mockup`] backend uses the older **gpio-mockup** module, for kernels
+/// that lack gpio-sim.
+/// The [`Memory`] backend provides a pure in-memory simulator.
+///
+/// [`Kernel`]: crate::Kernel
+/// [`Memory`]: crate::Memory
+/// [`Mockup`]: crate::Mockup
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// Create a simulator with the given name and banks, and take it live.
+    fn live(&self, name: &str, banks: &[Bank]) -> Result<Arc<dyn Device>>;
+
+    /// Check that the banks can be simulated, prior to taking them live.
+    ///
+    /// The default implementation accepts any banks.
+    fn validate(&self, _banks: &[Bank]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A live simulator device created by a [`Backend`].
+///
+/// The chips within the device are identified by the index of the
+/// corresponding bank in the simulator configuration.
+pub trait Device: fmt::Debug + Send + Sync {
+    /// The name of the device.
+    ///
+    /// e.g. `gpio-sim.0`
+    fn dev_name(&self) -> &str;
+
+    /// The name of the gpiochip simulated by a bank.
+    ///
+    /// e.g. `gpiochip0`
+    fn chip_name(&self, bank: usize) -> &str;
+
+    /// The path to the gpiochip simulated by a bank.
+    ///
+    /// e.g. `/dev/gpiochip0`
+    fn dev_path(&self, bank: usize) -> PathBuf {
+        Path::new("/dev").join(self.chip_name(bank))
+    }
+
+    /// The path to the gpiochip simulated by a bank in sysfs, if any.
+    ///
+    /// e.g. `/sys/devices/platform/gpio-sim.0/gpiochip0`
+    ///
+    /// The default implementation returns `None`.
+    fn sysfs_path(&self, _bank: usize) -> Option<&Path> {
+        None
+    }
+
+    /// The path to the simulator in configfs, if any.
+    ///
+    /// e.g. `/sys/kernel/config/gpio-sim/my_sim`
+    ///
+    /// The default implementation returns `None`.
+    fn configfs_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Create a **gpio-aggregator** device over lines of the chips of the
+    /// device.
+    ///
+    /// Called by [`Sim::aggregate`], which checks the chips are those of the
+    /// device.
+    ///
+    /// The default implementation does not support aggregators, so returns
+    /// [`Error::InvalidConfig`].
+    ///
+    /// [`Sim::aggregate`]: crate::Sim::aggregate
+    fn aggregate(&self, _lines: &[(&Chip, Offset)]) -> Result<Aggregator> {
+        Err(Error::InvalidConfig(
+            "aggregators require the Kernel backend".into(),
+        ))
+    }
+
+    /// Pull a line to simulate the line being externally driven.
+    fn set_pull(&self, bank: usize, offset: Offset, pull: Level) -> Result<()>;
+
+    /// Pull several lines, in order, stopping at the first error.
+    ///
+    /// The default implementation pulls each line in turn using [`set_pull`].
+    ///
+    /// [`set_pull`]: Device::set_pull
+    fn set_pulls(&self, bank: usize, pulls: &[(Offset, Level)]) -> Result<()> {
+        for &(offset, pull) in pulls {
+            self.set_pull(bank, offset, pull)?;
+        }
+        Ok(())
+    }
+
+    /// Get the current state of the simulated external pull on a line.
+    fn get_pull(&self, bank: usize, offset: Offset) -> Result<Level>;
+
+    /// Get the current level of a line.
+    fn get_level(&self, bank: usize, offset: Offset) -> Result<Level>;
+
+    /// Returns true if the simulator is still live.
+    ///
+    /// A simulator may be removed externally, e.g. by another process or by
+    /// unloading the kernel module, in which case operations on it should
+    /// return [`Error::SimRemoved`].
+    ///
+    /// The default implementation always returns true.
+    ///
+    /// [`Error::SimRemoved`]: crate::Error::SimRemoved
+    fn is_alive(&self) -> bool {
+        true
+    }
+
+    /// Remove the simulator.
+    ///
+    /// Called when the [`Sim`] is dropped, so errors are ignored.
+    ///
+    /// [`Sim`]: crate::Sim
+    fn remove(&self);
+
+    /// Release the simulator, so it is no longer removed by [`remove`].
+    ///
+    /// The default implementation does nothing, so the simulator is still
+    /// removed.
+    ///
+    /// [`remove`]: Device::remove
+    fn release(&self) {}
+}
@@ -0,0 +1,109 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Measure the latency from setting a pull on a gpio-sim line to the edge
+//! event being reported via the GPIO uAPI.
+
+use gpiosim::latency::Bench;
+use gpiosim::Simpleton;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "\
+usage: gpiosim-latency [options]
+
+options:
+    --iterations <n>                 the number of samples (default 1000)
+    --timeout <ms>                   how long to wait for each event (default 100)";
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    iterations: usize,
+    timeout: Duration,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        iterations: 1000,
+        timeout: Duration::from_millis(100),
+    };
+    let mut args = args.iter();
+    while let Some(opt) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or(format!("{} requires a value", opt))?
+                .parse::<u64>()
+                .map_err(|_| format!("invalid value for {}", opt))
+        };
+        match opt.as_str() {
+            "--iterations" => opts.iterations = value()? as usize,
+            "--timeout" => opts.timeout = Duration::from_millis(value()?),
+            "-h" | "--help" => return Err(String::new()),
+            _ => return Err(format!("unknown option {:?}", opt)),
+        }
+    }
+    Ok(opts)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let opts = match parse(&args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            if e.is_empty() {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            eprintln!("gpiosim-latency: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let res = Simpleton::builder(1).live().and_then(|s| {
+        Bench::new(s.chip(), 0)
+            .with_iterations(opts.iterations)
+            .with_timeout(opts.timeout)
+            .run()
+    });
+    match res {
+        Ok(report) => {
+            println!("{}", report);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("gpiosim-latency: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        assert_eq!(
+            parse(&[]),
+            Ok(Options {
+                iterations: 1000,
+                timeout: Duration::from_millis(100),
+            })
+        );
+        assert_eq!(
+            parse(&args(&["--timeout", "5", "--iterations", "20"])),
+            Ok(Options {
+                iterations: 20,
+                timeout: Duration::from_millis(5),
+            })
+        );
+        assert!(parse(&args(&["--iterations"])).is_err());
+        assert!(parse(&args(&["--iterations", "x"])).is_err());
+        assert!(parse(&args(&["--verbose"])).is_err());
+        assert_eq!(parse(&args(&["--help"])), Err(String::new()));
+    }
+}
@@ -0,0 +1,678 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A command line tool to inspect the gpio-sims on the host.
+//!
+//! The tool operates on live simulators, whichever process created them.
+
+use gpiosim::{Bank, Builder, Chip, Kernel, Level, Offset, Sim, SimState, Soak, Stress, Summary};
+use serde_json::json;
+use std::fs;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+usage: gpiosim [--json] <command> [args]
+
+options:
+    --json                           print the output as JSON
+
+commands:
+    list                             list the simulated chips
+    level <chip> [offset...]         print the levels of lines
+    watch [options] <chip> [offset...]
+                                     print changes to the levels and pulls of lines
+    save <sim> <file>                save the configuration and pulls of a sim
+    load [--name <name>] <file>      create a sim from a saved file
+    stress [options]                 repeatedly create and remove sims
+    soak [options]                   exercise sims in a loop, checking for leaks
+
+watch options:
+    --interval <ms>                  the interval between samples (default 1)
+
+stress options:
+    --chips <n>                      the number of chips in each sim (default 1)
+    --lines <n>                      the number of lines on each chip (default 8)
+    --threads <n>                    the number of concurrent threads (default 1)
+    --iterations <n>                 the total number of sims (default 100)
+
+soak options:
+    --chips <n>                      the number of chips in each sim (default 1)
+    --lines <n>                      the number of lines on each chip (default 8)
+    --cycles <n>                     the maximum number of cycles (default 100)
+    --duration <s>                   the maximum duration in seconds (default none)
+
+A chip may be identified by its name, e.g. gpiochip3, its path, e.g.
+/dev/gpiochip3, or its label.
+If no offsets are specified then all the lines of the chip are selected.
+With --json, watch prints each change as a line of JSON, stress and soak
+print a JSON object, and the other commands print a single JSON array.
+Soak exits with failure if the sims failed or leaked resources.
+A loaded sim takes the name it was saved with unless --name is specified,
+and remains live until removed, e.g. by examples/clean_sims.sh.";
+
+#[derive(Debug, PartialEq)]
+struct Cli {
+    /// Print the output as JSON.
+    json: bool,
+
+    command: Command,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    List,
+    Level {
+        chip: String,
+        offsets: Vec<Offset>,
+    },
+    Watch {
+        chip: String,
+        offsets: Vec<Offset>,
+        interval: Duration,
+    },
+    Save {
+        sim: String,
+        file: String,
+    },
+    Load {
+        name: Option<String>,
+        file: String,
+    },
+    Stress {
+        chips: usize,
+        lines: u32,
+        threads: usize,
+        iterations: usize,
+    },
+    Soak {
+        chips: usize,
+        lines: u32,
+        cycles: usize,
+        duration: Option<Duration>,
+    },
+}
+
+fn parse(args: &[String]) -> Result<Cli, String> {
+    let mut json = false;
+    let mut args = args;
+    while let Some((opt, rest)) = args.split_first() {
+        match opt.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => return Err(String::new()),
+            _ if opt.starts_with("--") => return Err(format!("unknown option {}", opt)),
+            _ => break,
+        }
+        args = rest;
+    }
+    parse_command(args, &mut json).map(|command| Cli { json, command })
+}
+
+// Parse the command and its arguments.
+//
+// For backward compatibility, watch also accepts --json as a watch option.
+fn parse_command(args: &[String], json: &mut bool) -> Result<Command, String> {
+    let (cmd, args) = args.split_first().ok_or("no command specified")?;
+    match cmd.as_str() {
+        "list" => {
+            if let Some(arg) = args.first() {
+                return Err(format!("unexpected argument {:?}", arg));
+            }
+            Ok(Command::List)
+        }
+        "level" => {
+            let (chip, offsets) = parse_lines(args)?;
+            Ok(Command::Level { chip, offsets })
+        }
+        "watch" => {
+            let mut interval = Duration::from_millis(1);
+            let mut args = args;
+            while let Some((opt, rest)) = args.split_first() {
+                match opt.as_str() {
+                    "--json" => *json = true,
+                    "--interval" => {
+                        let (ms, rest) = rest.split_first().ok_or("--interval requires a value")?;
+                        let ms: u64 = ms
+                            .parse()
+                            .map_err(|_| format!("invalid interval {:?}", ms))?;
+                        interval = Duration::from_millis(ms.max(1));
+                        args = rest;
+                        continue;
+                    }
+                    _ if opt.starts_with("--") => return Err(format!("unknown option {}", opt)),
+                    _ => break,
+                }
+                args = rest;
+            }
+            let (chip, offsets) = parse_lines(args)?;
+            Ok(Command::Watch {
+                chip,
+                offsets,
+                interval,
+            })
+        }
+        "save" => match args {
+            [sim, file] => Ok(Command::Save {
+                sim: sim.clone(),
+                file: file.clone(),
+            }),
+            _ => Err("save requires a sim and a file".into()),
+        },
+        "load" => match args {
+            [file] => Ok(Command::Load {
+                name: None,
+                file: file.clone(),
+            }),
+            [opt, name, file] if opt == "--name" => Ok(Command::Load {
+                name: Some(name.clone()),
+                file: file.clone(),
+            }),
+            _ => Err("load requires a file, optionally preceded by --name <name>".into()),
+        },
+        "stress" => {
+            let [chips, lines, threads, iterations] = parse_numbers(
+                args,
+                ["--chips", "--lines", "--threads", "--iterations"],
+                [1, 8, 1, 100],
+            )?;
+            Ok(Command::Stress {
+                chips: chips as usize,
+                lines: lines as u32,
+                threads: threads as usize,
+                iterations: iterations as usize,
+            })
+        }
+        "soak" => {
+            let [chips, lines, cycles, duration] = parse_numbers(
+                args,
+                ["--chips", "--lines", "--cycles", "--duration"],
+                [1, 8, 100, 0],
+            )?;
+            Ok(Command::Soak {
+                chips: chips as usize,
+                lines: lines as u32,
+                cycles: cycles as usize,
+                duration: (duration > 0).then(|| Duration::from_secs(duration)),
+            })
+        }
+        "help" | "-h" | "--help" => Err(String::new()),
+        _ => Err(format!("unknown command {:?}", cmd)),
+    }
+}
+
+// Parse options with numeric values, returning the values in the order of
+// the names, or the defaults if not specified.
+fn parse_numbers<const N: usize>(
+    args: &[String],
+    names: [&str; N],
+    mut values: [u64; N],
+) -> Result<[u64; N], String> {
+    let mut args = args.iter();
+    while let Some(opt) = args.next() {
+        let n = names
+            .iter()
+            .position(|name| name == opt)
+            .ok_or_else(|| format!("unknown option {:?}", opt))?;
+        let value = args.next().ok_or(format!("{} requires a value", opt))?;
+        values[n] = value
+            .parse()
+            .map_err(|_| format!("invalid value for {}", opt))?;
+    }
+    Ok(values)
+}
+
+// Parse a chip followed by optional offsets.
+fn parse_lines(args: &[String]) -> Result<(String, Vec<Offset>), String> {
+    let (chip, offsets) = args.split_first().ok_or("no chip specified")?;
+    let offsets = offsets
+        .iter()
+        .map(|o| o.parse().map_err(|_| format!("invalid offset {:?}", o)))
+        .collect::<Result<_, _>>()?;
+    Ok((chip.clone(), offsets))
+}
+
+// All the live simulators on the host.
+fn sims() -> Result<Vec<Sim>, String> {
+    let kernel = Kernel::default();
+    let names = kernel.sims().map_err(|e| e.to_string())?;
+    // sims that are not live, or are removed while searching, are ignored
+    Ok(names
+        .iter()
+        .filter_map(|name| kernel.attach(name).ok())
+        .collect())
+}
+
+// Find the chip, returning its sim and its index in the sim.
+fn find_chip(id: &str) -> Result<(Sim, usize), String> {
+    for sim in sims()? {
+        let found = sim.chips().iter().position(|c| {
+            c.chip_name == id || c.dev_path().as_os_str() == id || c.config().label == id
+        });
+        if let Some(index) = found {
+            return Ok((sim, index));
+        }
+    }
+    Err(format!("cannot find chip {:?}", id))
+}
+
+// Check the offsets are valid for the chip, defaulting to all its lines.
+fn select_lines(chip: &Chip, offsets: Vec<Offset>) -> Result<Vec<Offset>, String> {
+    let num_lines = chip.config().num_lines;
+    if let Some(offset) = offsets.iter().find(|&&o| o >= num_lines) {
+        return Err(format!(
+            "offset {} out of range for {} with {} lines",
+            offset, chip.chip_name, num_lines
+        ));
+    }
+    if offsets.is_empty() {
+        return Ok((0..num_lines).collect());
+    }
+    Ok(offsets)
+}
+
+// Print the chips of the sims, as a single JSON array if json.
+fn print_chips(sims: &[Sim], json: bool) {
+    if json {
+        let chips: Vec<_> = sims
+            .iter()
+            .flat_map(|sim| {
+                sim.chips().iter().map(move |c| {
+                    json!({
+                        "chip": c.chip_name,
+                        "path": c.dev_path(),
+                        "label": c.config().label,
+                        "num_lines": c.config().num_lines,
+                        "sim": sim.name(),
+                    })
+                })
+            })
+            .collect();
+        println!("{}", serde_json::Value::Array(chips));
+        return;
+    }
+    for sim in sims {
+        for c in sim.chips() {
+            println!(
+                "{} [{}] ({} lines) {}",
+                c.chip_name,
+                c.config().label,
+                c.config().num_lines,
+                sim.name()
+            );
+        }
+    }
+}
+
+fn list(json: bool) -> Result<(), String> {
+    print_chips(&sims()?, json);
+    Ok(())
+}
+
+fn level(chip: &str, offsets: Vec<Offset>, json: bool) -> Result<(), String> {
+    let (sim, index) = find_chip(chip)?;
+    let chip = &sim.chips()[index];
+    let levels = select_lines(chip, offsets)?
+        .into_iter()
+        .map(|offset| Ok((offset, chip.get_level(offset).map_err(|e| e.to_string())?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    if json {
+        let levels: Vec<_> = levels
+            .iter()
+            .map(|&(offset, level)| json!({ "offset": offset, "level": level }))
+            .collect();
+        println!("{}", serde_json::Value::Array(levels));
+        return Ok(());
+    }
+    for (offset, level) in levels {
+        println!("{} {}", offset, level);
+    }
+    Ok(())
+}
+
+fn save(sim: &str, file: &str) -> Result<(), String> {
+    let sim = Kernel::default()
+        .attach(sim)
+        .map_err(|e| format!("cannot find sim {:?}: {}", sim, e))?;
+    let state = sim.state().map_err(|e| e.to_string())?;
+    fs::write(file, state.to_json() + "\n").map_err(|e| format!("cannot write {}: {}", file, e))
+}
+
+// Create the sim from the file, and leave it live after exiting.
+fn load(name: Option<String>, file: &str, json: bool) -> Result<(), String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+    let state = SimState::from_json(&text).map_err(|e| e.to_string())?;
+    let mut builder = state.builder();
+    if let Some(name) = name {
+        builder.with_name(name);
+    }
+    let sim = builder.live().map_err(|e| e.to_string())?;
+    print_chips(std::slice::from_ref(&sim), json);
+    sim.detach();
+    Ok(())
+}
+
+// A builder for a sim with the chips, each with the lines.
+fn sim_builder(chips: usize, lines: u32, label: &str) -> Builder {
+    let mut builder = gpiosim::builder();
+    builder.with_banks((0..chips).map(|i| Bank::new(lines, format!("{}{}", label, i))));
+    builder
+}
+
+fn soak(
+    chips: usize,
+    lines: u32,
+    cycles: usize,
+    duration: Option<Duration>,
+    json: bool,
+) -> Result<(), String> {
+    let builder = sim_builder(chips, lines, "soak");
+    let mut soak = Soak::new(&builder);
+    soak.with_cycles(cycles);
+    if let Some(duration) = duration {
+        soak.with_duration(duration);
+    }
+    let report = soak.run();
+    if json {
+        println!(
+            "{}",
+            json!({
+                "cycles": report.cycles,
+                "failures": report.failures,
+                "leaked": report.leaked,
+                "fd_growth": report.fd_growth(),
+                "rss_growth_kb": report.rss_growth_kb(),
+                "samples": report.samples.iter().map(|s| json!({
+                    "cycle": s.cycle,
+                    "elapsed": s.elapsed.as_secs_f64(),
+                    "fds": s.fds,
+                    "rss_kb": s.rss_kb,
+                })).collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        println!("{}", report);
+    }
+    if !report.is_clean() {
+        return Err("soak test failed".into());
+    }
+    Ok(())
+}
+
+fn stress(chips: usize, lines: u32, threads: usize, iterations: usize, json: bool) {
+    let builder = sim_builder(chips, lines, "stress");
+    let report = Stress::new(&builder)
+        .with_threads(threads)
+        .with_iterations(iterations)
+        .run();
+    if !json {
+        println!("{}", report);
+        return;
+    }
+    let summary = |s: Summary| {
+        json!({
+            "min": s.min.as_secs_f64(),
+            "mean": s.mean.as_secs_f64(),
+            "median": s.median.as_secs_f64(),
+            "p99": s.p99.as_secs_f64(),
+            "max": s.max.as_secs_f64(),
+        })
+    };
+    println!(
+        "{}",
+        json!({
+            "iterations": report.iterations,
+            "elapsed": report.elapsed.as_secs_f64(),
+            "create": summary(report.create_summary()),
+            "teardown": summary(report.teardown_summary()),
+            "failures": report.failures,
+        })
+    );
+}
+
+/// A change to a line attribute observed by watch.
+struct Change<'a> {
+    time: Duration,
+    chip: &'a str,
+    offset: Offset,
+    attr: &'a str,
+    value: Level,
+}
+
+impl Change<'_> {
+    fn print(&self, json: bool) {
+        if json {
+            println!(
+                "{}",
+                json!({
+                    "time": self.time.as_secs_f64(),
+                    "chip": self.chip,
+                    "offset": self.offset,
+                    "attr": self.attr,
+                    "value": self.value,
+                })
+            );
+        } else {
+            println!(
+                "{:>12.6} {} {} {} {}",
+                self.time.as_secs_f64(),
+                self.chip,
+                self.offset,
+                self.attr,
+                self.value
+            );
+        }
+    }
+}
+
+// Print the initial levels and pulls of the lines, at time zero, and then any
+// changes to them, until the sim is removed.
+fn watch(chip: &str, offsets: Vec<Offset>, json: bool, interval: Duration) -> Result<(), String> {
+    let (sim, index) = find_chip(chip)?;
+    let chip = &sim.chips()[index];
+    let offsets = select_lines(chip, offsets)?;
+    let sample = |offset| -> Result<[Level; 2], String> {
+        let level = chip.get_level(offset).map_err(|e| e.to_string())?;
+        let pull = chip.get_pull(offset).map_err(|e| e.to_string())?;
+        Ok([level, pull])
+    };
+    let start = Instant::now();
+    let mut prev: Vec<Option<[Level; 2]>> = vec![None; offsets.len()];
+    loop {
+        let time = start.elapsed();
+        for (n, &offset) in offsets.iter().enumerate() {
+            let curr = sample(offset)?;
+            for (i, attr) in ["level", "pull"].iter().enumerate() {
+                if prev[n].map(|p| p[i]) != Some(curr[i]) {
+                    Change {
+                        time,
+                        chip: &chip.chip_name,
+                        offset,
+                        attr,
+                        value: curr[i],
+                    }
+                    .print(json);
+                }
+            }
+            prev[n] = Some(curr);
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Cli { json, command } = match parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            if e.is_empty() {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            eprintln!("gpiosim: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let res = match command {
+        Command::List => list(json),
+        Command::Level { chip, offsets } => level(&chip, offsets, json),
+        Command::Watch {
+            chip,
+            offsets,
+            interval,
+        } => watch(&chip, offsets, json, interval),
+        Command::Save { sim, file } => save(&sim, &file),
+        Command::Load { name, file } => load(name, &file, json),
+        Command::Stress {
+            chips,
+            lines,
+            threads,
+            iterations,
+        } => {
+            stress(chips, lines, threads, iterations, json);
+            Ok(())
+        }
+        Command::Soak {
+            chips,
+            lines,
+            cycles,
+            duration,
+        } => soak(chips, lines, cycles, duration, json),
+    };
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gpiosim: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn command(args: &[String]) -> Result<Command, String> {
+        parse(args).map(|cli| cli.command)
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(command(&args(&["list"])), Ok(Command::List));
+        assert!(parse(&args(&["list", "gpiochip0"])).is_err());
+        assert_eq!(
+            command(&args(&["level", "gpiochip0", "1", "3"])),
+            Ok(Command::Level {
+                chip: "gpiochip0".into(),
+                offsets: vec![1, 3]
+            })
+        );
+        assert!(parse(&args(&["level"])).is_err());
+        assert!(parse(&args(&["level", "gpiochip0", "x"])).is_err());
+        assert_eq!(
+            command(&args(&["watch", "left"])),
+            Ok(Command::Watch {
+                chip: "left".into(),
+                offsets: vec![],
+                interval: Duration::from_millis(1),
+            })
+        );
+        assert_eq!(
+            parse(&args(&["watch", "--interval", "10", "--json", "left", "2"])),
+            Ok(Cli {
+                json: true,
+                command: Command::Watch {
+                    chip: "left".into(),
+                    offsets: vec![2],
+                    interval: Duration::from_millis(10),
+                }
+            })
+        );
+        assert!(parse(&args(&["watch", "--interval"])).is_err());
+        assert!(parse(&args(&["watch", "--verbose", "left"])).is_err());
+        assert_eq!(
+            command(&args(&["save", "sim", "sim.json"])),
+            Ok(Command::Save {
+                sim: "sim".into(),
+                file: "sim.json".into(),
+            })
+        );
+        assert!(parse(&args(&["save", "sim"])).is_err());
+        assert_eq!(
+            command(&args(&["load", "sim.json"])),
+            Ok(Command::Load {
+                name: None,
+                file: "sim.json".into(),
+            })
+        );
+        assert_eq!(
+            command(&args(&["load", "--name", "copy", "sim.json"])),
+            Ok(Command::Load {
+                name: Some("copy".into()),
+                file: "sim.json".into(),
+            })
+        );
+        assert!(parse(&args(&["load", "--name", "sim.json"])).is_err());
+        assert!(parse(&args(&["load", "--verbose", "x", "sim.json"])).is_err());
+        assert_eq!(
+            command(&args(&["stress"])),
+            Ok(Command::Stress {
+                chips: 1,
+                lines: 8,
+                threads: 1,
+                iterations: 100,
+            })
+        );
+        assert_eq!(
+            command(&args(&["stress", "--threads", "4", "--lines", "64"])),
+            Ok(Command::Stress {
+                chips: 1,
+                lines: 64,
+                threads: 4,
+                iterations: 100,
+            })
+        );
+        assert!(parse(&args(&["stress", "--chips"])).is_err());
+        assert!(parse(&args(&["stress", "--chips", "x"])).is_err());
+        assert!(parse(&args(&["stress", "4"])).is_err());
+        assert_eq!(
+            command(&args(&["soak", "--duration", "60"])),
+            Ok(Command::Soak {
+                chips: 1,
+                lines: 8,
+                cycles: 100,
+                duration: Some(Duration::from_secs(60)),
+            })
+        );
+        assert_eq!(
+            command(&args(&["soak", "--cycles", "5"])),
+            Ok(Command::Soak {
+                chips: 1,
+                lines: 8,
+                cycles: 5,
+                duration: None,
+            })
+        );
+        assert_eq!(
+            parse(&args(&["--json", "list"])),
+            Ok(Cli {
+                json: true,
+                command: Command::List
+            })
+        );
+        assert_eq!(
+            parse(&args(&["level", "gpiochip0"])).map(|cli| cli.json),
+            Ok(false)
+        );
+        assert!(parse(&args(&["--json"])).is_err());
+        assert!(parse(&args(&["--verbose", "list"])).is_err());
+        assert_eq!(parse(&args(&["--help"])), Err(String::new()));
+        assert!(parse(&args(&["frobnicate"])).is_err());
+        assert!(parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::random::Rng;
+use crate::timing::sleep_until;
+use crate::{Chip, Distribution, Level, Offset, Result};
+use std::time::Instant;
+
+/// A profile of the contact bounce applied to pull transitions.
+///
+/// When applied to a transition the line is pulled to the new level, then
+/// bounces back to the previous level and returns to the new level the
+/// configured number of times, with the time between each change drawn
+/// from the interval distribution.
+///
+/// The random intervals are reproducible from the seed.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Bounce, Distribution, Level};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(1, "btn")).live()?;
+/// let interval = Distribution::Uniform {
+///     min: Duration::from_micros(100),
+///     max: Duration::from_micros(500),
+/// };
+/// let mut bounce = Bounce::new(3, interval, 42);
+/// sim.chips()[0].set_pull_bouncy(0, Level::High, &mut bounce)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bounce {
+    /// The number of times the line bounces back to the previous level.
+    pub bounces: u32,
+
+    /// The distribution of the time between the changes in level.
+    pub interval: Distribution,
+
+    rng: Rng,
+}
+
+impl Bounce {
+    /// Create a bounce profile.
+    pub fn new(bounces: u32, interval: Distribution, seed: u64) -> Bounce {
+        Bounce {
+            bounces,
+            interval,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Pull the line to the level, bouncing as it does.
+    pub(crate) fn apply(&mut self, chip: &Chip, offset: Offset, pull: Level) -> Result<()> {
+        chip.set_pull(offset, pull)?;
+        let mut deadline = Instant::now();
+        for _ in 0..self.bounces {
+            deadline += self.rng.duration(&self.interval);
+            sleep_until(deadline);
+            chip.set_pull(offset, pull.toggle())?;
+            deadline += self.rng.duration(&self.interval);
+            sleep_until(deadline);
+            chip.set_pull(offset, pull)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Button, Memory};
+    use std::time::Duration;
+
+    #[test]
+    fn bouncy() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "bounce"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let interval = Distribution::Uniform {
+            min: Duration::ZERO,
+            max: Duration::from_micros(50),
+        };
+        let mut bounce = Bounce::new(3, interval, 7);
+        c.set_pull_bouncy(0, Level::High, &mut bounce).unwrap();
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        assert_eq!(c.stats()[0].pulls, 7);
+
+        let mut button = Button::new(c, 1).unwrap();
+        button.with_bounce(Some(Bounce::new(2, interval, 7)));
+        button.click(Duration::ZERO).unwrap();
+        assert!(!button.is_pressed().unwrap());
+        // initial release, then a bouncy press and release
+        assert_eq!(c.stats()[1].pulls, 11);
+    }
+}
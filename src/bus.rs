@@ -0,0 +1,337 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{check_word_width, BitOrder, Chip, Edge, Error, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+
+/// A parallel bus formed from an ordered group of lines on a chip.
+///
+/// Values written to the bus are presented to the consumer by pulling the lines,
+/// and values read from the bus are the current levels of the lines, so the
+/// bus may be used to emulate either side of a parallel interface.
+///
+/// The bus does not borrow the chip, but remains valid only while the
+/// simulator containing the chip is live.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, BitOrder, Bus, Level};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(9, "bus")).live()?;
+/// let mut bus = Bus::new(&sim.chips()[0], &[0, 1, 2, 3, 4, 5, 6, 7])?;
+/// bus.with_order(BitOrder::MsbFirst).with_strobe(8, Level::High)?;
+/// bus.write(0xa5)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Bus {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    /// The data lines, in order.
+    offsets: Vec<Offset>,
+
+    /// The mapping of bits onto the data lines.
+    order: BitOrder,
+
+    /// The optional strobe line, and its active level.
+    strobe: Option<(Offset, Level)>,
+}
+
+impl Bus {
+    /// Create a bus from an ordered group of lines on the chip.
+    ///
+    /// The bus may contain at most 64 lines.
+    pub fn new(chip: &Chip, offsets: &[Offset]) -> Result<Bus> {
+        check_word_width(offsets)?;
+        for &offset in offsets {
+            check_offset(chip, offset)?;
+        }
+        Ok(Bus {
+            chip: chip.share(),
+            offsets: offsets.to_vec(),
+            order: BitOrder::default(),
+            strobe: None,
+        })
+    }
+
+    /// Set the mapping of bits onto the data lines.
+    ///
+    /// The default is [`BitOrder::LsbFirst`].
+    pub fn with_order(&mut self, order: BitOrder) -> &mut Self {
+        self.order = order;
+        self
+    }
+
+    /// Add a strobe line that is pulsed after each value is written.
+    ///
+    /// The strobe is pulled to the active level once the data lines have been
+    /// set, and is then returned to the inactive level.
+    ///
+    /// Returns [`Error::InvalidConfig`], leaving the bus unchanged, if the
+    /// strobe is out of range for the chip or is one of the data lines.
+    pub fn with_strobe(&mut self, offset: Offset, active: Level) -> Result<&mut Self> {
+        check_offset(&self.chip, offset)?;
+        if self.offsets.contains(&offset) {
+            return Err(Error::InvalidConfig(format!(
+                "strobe {} is also a data line of the bus",
+                offset
+            )));
+        }
+        self.strobe = Some((offset, active));
+        Ok(self)
+    }
+
+    /// The chip containing the bus.
+    pub fn chip(&self) -> &Chip {
+        &self.chip
+    }
+
+    /// The data lines, in order.
+    pub fn offsets(&self) -> &[Offset] {
+        &self.offsets
+    }
+
+    /// The number of data lines.
+    pub fn width(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// The mapping of bits onto the data lines.
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// The strobe line and its active level, if any.
+    pub fn strobe(&self) -> Option<(Offset, Level)> {
+        self.strobe
+    }
+
+    /// Write a value to the bus by pulling the data lines.
+    ///
+    /// Bits of the value beyond the width of the bus are ignored.
+    pub fn write(&self, value: u64) -> Result<()> {
+        self.chip.set_word(&self.offsets, value, self.order)?;
+        if let Some((offset, active)) = self.strobe {
+            self.chip.set_pull(offset, active)?;
+            self.chip.set_pull(offset, active.toggle())?;
+        }
+        Ok(())
+    }
+
+    /// Read the value on the bus from the levels of the data lines.
+    pub fn read(&self) -> Result<u64> {
+        self.chip.get_word(&self.offsets, self.order)
+    }
+}
+
+/// An emulator of a peripheral that latches the values written to a [`Bus`]
+/// by the consumer.
+///
+/// The consumer drives the data lines and the strobe line of the bus, and the
+/// value on the data lines is latched on each matching edge of the strobe.
+///
+/// The latched values are recorded in a history that the test can assert on.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Bus, Edge, Latch, Level};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(9, "lcd")).live()?;
+/// let mut bus = Bus::new(&sim.chips()[0], &[0, 1, 2, 3, 4, 5, 6, 7])?;
+/// bus.with_strobe(8, Level::High)?;
+/// let latch = Latch::new(&bus, Edge::Falling)?;
+///
+/// // ... consumer writes 0x38 to the bus ...
+///
+/// latch.sync();
+/// assert_eq!(latch.history(), [0x38]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Latch {
+    /// The values latched so far.
+    history: Arc<Mutex<Vec<u64>>>,
+
+    monitor: Monitor,
+}
+
+impl Latch {
+    /// Start latching values written to the bus on the given edge of the bus strobe.
+    ///
+    /// The bus must have a strobe line.
+    pub fn new(bus: &Bus, edge: Edge) -> Result<Latch> {
+        let (strobe, _) = bus
+            .strobe
+            .ok_or_else(|| Error::InvalidConfig("bus has no strobe line".into()))?;
+        let mut offsets = bus.offsets.clone();
+        offsets.push(strobe);
+        let history = Arc::new(Mutex::new(Vec::new()));
+        let monitor = {
+            let history = history.clone();
+            let data = bus.offsets.clone();
+            let order = bus.order;
+            Monitor::spawn(&bus.chip, &offsets, move |_, prev, curr| {
+                let width = data.len();
+                if edge.matches(prev[width], curr[width]) {
+                    let value = order
+                        .bits(&data)
+                        .zip(&curr[..width])
+                        .filter(|(_, &level)| level == Level::High)
+                        .fold(0, |value, ((bit, _), _)| value | 1 << bit);
+                    history.lock().unwrap().push(value);
+                }
+            })?
+        };
+        Ok(Latch { history, monitor })
+    }
+
+    /// The values latched so far, oldest first.
+    pub fn history(&self) -> Vec<u64> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// The most recently latched value, if any.
+    pub fn last(&self) -> Option<u64> {
+        self.history.lock().unwrap().last().copied()
+    }
+
+    /// Remove and return the values latched so far, oldest first.
+    pub fn take(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.history.lock().unwrap())
+    }
+
+    /// Wait until the current state of the bus has been sampled.
+    ///
+    /// Call after the consumer has written to the bus to ensure the write has
+    /// been latched.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+pub(crate) fn check_offset(chip: &Chip, offset: Offset) -> Result<()> {
+    if offset >= chip.config().num_lines {
+        return Err(Error::InvalidConfig(format!(
+            "offset {} is out of range for chip with {} lines",
+            offset,
+            chip.config().num_lines
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Edge, Memory};
+
+    #[test]
+    fn new() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(10, "bus"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let bus = Bus::new(c, &[3, 1, 2]).unwrap();
+        assert_eq!(bus.offsets(), [3, 1, 2]);
+        assert_eq!(bus.width(), 3);
+        assert_eq!(bus.order(), BitOrder::LsbFirst);
+        assert_eq!(bus.strobe(), None);
+        assert_eq!(bus.chip().chip_name, c.chip_name);
+        assert!(Bus::new(c, &[3, 10]).is_err());
+        let wide: Vec<Offset> = (0..65).map(|i| i % 10).collect();
+        assert!(Bus::new(c, &wide).is_err());
+    }
+
+    #[test]
+    fn write() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(10, "bus"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut bus = Bus::new(c, &[0, 1, 2, 3]).unwrap();
+        bus.write(0x3).unwrap();
+        assert_eq!(c.get_word(&[0, 1, 2, 3], BitOrder::LsbFirst).unwrap(), 0x3);
+        bus.with_order(BitOrder::MsbFirst)
+            .with_strobe(9, Level::High)
+            .unwrap();
+        bus.write(0x3).unwrap();
+        assert_eq!(c.get_word(&[0, 1, 2, 3], BitOrder::LsbFirst).unwrap(), 0xc);
+        // strobe pulsed and left inactive
+        assert_eq!(c.get_pull(9).unwrap(), Level::Low);
+        assert_eq!(c.stats()[9].pulls, 2);
+
+        // invalid strobes are rejected before any line is written
+        assert!(bus.with_strobe(10, Level::High).is_err());
+        assert!(bus.with_strobe(2, Level::High).is_err());
+        assert_eq!(bus.strobe(), Some((9, Level::High)));
+    }
+
+    #[test]
+    fn latch() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(10, "bus"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut bus = Bus::new(c, &[0, 1, 2, 3]).unwrap();
+        assert!(Latch::new(&bus, Edge::Falling).is_err());
+        bus.with_order(BitOrder::MsbFirst)
+            .with_strobe(8, Level::High)
+            .unwrap();
+        let latch = Latch::new(&bus, Edge::Falling).unwrap();
+        let write = |value: u64| {
+            for (idx, &offset) in [0, 1, 2, 3].iter().enumerate() {
+                let level = if value & (0x8 >> idx) == 0 {
+                    Level::Low
+                } else {
+                    Level::High
+                };
+                mem.drive(c, offset, Some(level)).unwrap();
+            }
+            latch.sync();
+            mem.drive(c, 8, Some(Level::High)).unwrap();
+            latch.sync();
+            mem.drive(c, 8, Some(Level::Low)).unwrap();
+            latch.sync();
+        };
+        write(0x3);
+        assert_eq!(latch.last(), Some(0x3));
+        write(0xa);
+        write(0x5);
+        assert_eq!(latch.history(), [0x3, 0xa, 0x5]);
+        assert_eq!(latch.take(), [0x3, 0xa, 0x5]);
+        assert!(latch.history().is_empty());
+        // data changes without a strobe are ignored
+        mem.drive(c, 0, Some(Level::High)).unwrap();
+        latch.sync();
+        assert_eq!(latch.last(), None);
+    }
+
+    #[test]
+    fn read() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(10, "bus"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut bus = Bus::new(c, &[4, 5, 6, 7]).unwrap();
+        mem.drive(c, 4, Some(Level::High)).unwrap();
+        mem.drive(c, 6, Some(Level::High)).unwrap();
+        assert_eq!(bus.read().unwrap(), 0x5);
+        bus.with_order(BitOrder::MsbFirst);
+        assert_eq!(bus.read().unwrap(), 0xa);
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Bounce, Chip, Level, Offset, Result};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// The time a button is held for each click of a double click.
+const CLICK: Duration = Duration::from_millis(50);
+
+/// The time between the clicks of a double click.
+const CLICK_GAP: Duration = Duration::from_millis(100);
+
+/// A simulated push button on a line.
+///
+/// The button pulls the line to its active level while pressed, and to the
+/// inactive level while released.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Button, Level};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(1, "btn")).live()?;
+/// let mut button = Button::new(&sim.chips()[0], 0)?;
+/// button.with_active(Level::Low)?;
+/// button.click(Duration::from_millis(20))?;
+/// button.long_press(Duration::from_secs(2))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Button {
+    /// The chip containing the line.
+    chip: Chip,
+
+    offset: Offset,
+
+    /// The level of the line while the button is pressed.
+    active: Level,
+
+    /// The contact bounce applied when pressing and releasing.
+    bounce: Mutex<Option<Bounce>>,
+}
+
+impl Button {
+    /// Create a button on the line, initially released.
+    ///
+    /// The button is active high by default.
+    pub fn new(chip: &Chip, offset: Offset) -> Result<Button> {
+        chip.set_pull(offset, Level::Low)?;
+        Ok(Button {
+            chip: chip.share(),
+            offset,
+            active: Level::High,
+            bounce: Mutex::new(None),
+        })
+    }
+
+    /// Set the level of the line while the button is pressed, and release the
+    /// button.
+    pub fn with_active(&mut self, active: Level) -> Result<&mut Self> {
+        self.active = active;
+        self.release()?;
+        Ok(self)
+    }
+
+    /// Set the contact bounce applied when pressing and releasing.
+    ///
+    /// By default the button does not bounce.
+    pub fn with_bounce(&mut self, bounce: Option<Bounce>) -> &mut Self {
+        self.bounce = Mutex::new(bounce);
+        self
+    }
+
+    /// The level of the line while the button is pressed.
+    pub fn active(&self) -> Level {
+        self.active
+    }
+
+    /// Returns true if the button is currently pressed.
+    pub fn is_pressed(&self) -> Result<bool> {
+        Ok(self.chip.get_pull(self.offset)? == self.active)
+    }
+
+    /// Press the button, and leave it pressed.
+    pub fn press(&self) -> Result<()> {
+        self.set(self.active)
+    }
+
+    /// Release the button.
+    pub fn release(&self) -> Result<()> {
+        self.set(self.active.toggle())
+    }
+
+    fn set(&self, pull: Level) -> Result<()> {
+        match self.bounce.lock().unwrap().as_mut() {
+            Some(bounce) => bounce.apply(&self.chip, self.offset, pull),
+            None => self.chip.set_pull(self.offset, pull),
+        }
+    }
+
+    /// Press the button, hold it for the duration, then release it.
+    pub fn click(&self, duration: Duration) -> Result<()> {
+        self.press()?;
+        thread::sleep(duration);
+        self.release()
+    }
+
+    /// Click the button twice in quick succession.
+    ///
+    /// Each click is 50ms, separated by 100ms.
+    pub fn double_click(&self) -> Result<()> {
+        self.click(CLICK)?;
+        thread::sleep(CLICK_GAP);
+        self.click(CLICK)
+    }
+
+    /// Press and hold the button for the duration, then release it.
+    ///
+    /// Equivalent to [`click`], but intended for durations long enough to
+    /// trigger long press handling.
+    ///
+    /// [`click`]: Button::click
+    pub fn long_press(&self, duration: Duration) -> Result<()> {
+        self.click(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::time::Instant;
+
+    #[test]
+    fn gestures() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(1, "btn"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut button = Button::new(c, 0).unwrap();
+        assert_eq!(button.active(), Level::High);
+        assert!(!button.is_pressed().unwrap());
+        button.press().unwrap();
+        assert!(button.is_pressed().unwrap());
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        button.with_active(Level::Low).unwrap();
+        assert!(!button.is_pressed().unwrap());
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        button.press().unwrap();
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+        button.release().unwrap();
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+
+        let pulls = c.stats()[0].pulls;
+        let start = Instant::now();
+        button.click(Duration::from_millis(5)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        button.double_click().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(205));
+        button.long_press(Duration::from_millis(10)).unwrap();
+        assert_eq!(c.stats()[0].pulls - pulls, 8);
+        assert!(!button.is_pressed().unwrap());
+    }
+}
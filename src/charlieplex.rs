@@ -0,0 +1,192 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Error, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A monitor of a charlieplexed LED matrix driven by the consumer.
+///
+/// Each ordered pair of lines has an LED, which is lit while the consumer
+/// drives the anode line high and the cathode line low.
+/// The consumer tri-states the remaining lines by reconfiguring them as
+/// inputs.
+///
+/// The monitor distinguishes driven lines from tri-stated lines by probing
+/// them, alternately pulling each line low and high and checking whether the
+/// level follows the pull.
+/// The probing does not update the chip stats.
+///
+/// As the consumer typically lights the LEDs in turn, the LEDs lit within a
+/// sampling window, 20ms by default, are reported as lit.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Charlieplex};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(3, "leds")).live()?;
+/// let matrix = Charlieplex::new(&sim.chips()[0], &[0, 1, 2])?;
+///
+/// // ... consumer lights the LED from line 0 to line 2 ...
+///
+/// matrix.sync();
+/// assert_eq!(matrix.lit(), [(0, 2)]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Charlieplex {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The number of lines.
+    lines: usize,
+
+    /// The period over which LEDs are reported as lit.
+    window: Duration,
+
+    /// The time of the most recent sample.
+    sampled: Instant,
+
+    /// The time each LED was last seen lit, indexed by anode * lines + cathode.
+    last_lit: Vec<Option<Instant>>,
+}
+
+impl Charlieplex {
+    /// Start monitoring the charlieplexed lines.
+    pub fn new(chip: &Chip, lines: &[Offset]) -> Result<Charlieplex> {
+        if lines.len() < 2 {
+            return Err(Error::InvalidConfig(
+                "charlieplexing requires at least 2 lines".into(),
+            ));
+        }
+        let n = lines.len();
+        let state = Arc::new(Mutex::new(State {
+            lines: n,
+            window: Duration::from_millis(20),
+            sampled: Instant::now(),
+            last_lit: vec![None; n * n],
+        }));
+        let monitor = {
+            let state = state.clone();
+            let lines = lines.to_vec();
+            Monitor::poll(chip, move |chip| {
+                let drive = probe(chip, &lines)?;
+                let now = Instant::now();
+                let mut state = state.lock().unwrap();
+                state.sampled = now;
+                for (a, &anode) in drive.iter().enumerate() {
+                    for (c, &cathode) in drive.iter().enumerate() {
+                        if anode == Some(Level::High) && cathode == Some(Level::Low) {
+                            state.last_lit[a * n + c] = Some(now);
+                        }
+                    }
+                }
+                Ok(())
+            })?
+        };
+        Ok(Charlieplex { state, monitor })
+    }
+
+    /// Set the period over which LEDs are reported as lit.
+    ///
+    /// This should be at least the refresh period of the matrix.
+    /// A zero window reports only the LEDs lit in the most recent sample.
+    pub fn with_window(&mut self, window: Duration) -> &mut Self {
+        self.state.lock().unwrap().window = window;
+        self
+    }
+
+    /// The LEDs lit within the sampling window, as (anode, cathode) indices
+    /// into the lines, in order.
+    pub fn lit(&self) -> Vec<(usize, usize)> {
+        let state = self.state.lock().unwrap();
+        let since = state.sampled.checked_sub(state.window);
+        state
+            .last_lit
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| match (t, since) {
+                (Some(t), Some(since)) => *t >= since,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .map(|(i, _)| (i / state.lines, i % state.lines))
+            .collect()
+    }
+
+    /// Returns true if the LED was lit within the sampling window.
+    pub fn is_lit(&self, anode: usize, cathode: usize) -> bool {
+        self.lit().contains(&(anode, cathode))
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+// Determine the level each line is driven to, or None if it is tri-stated.
+fn probe(chip: &Chip, lines: &[Offset]) -> Result<Vec<Option<Level>>> {
+    let mut drive = Vec::with_capacity(lines.len());
+    for &offset in lines {
+        chip.dev.set_pull(chip.bank, offset, Level::Low)?;
+        let low = chip.dev.get_level(chip.bank, offset)?;
+        chip.dev.set_pull(chip.bank, offset, Level::High)?;
+        let high = chip.dev.get_level(chip.bank, offset)?;
+        chip.dev.set_pull(chip.bank, offset, Level::Low)?;
+        drive.push(if low == high { Some(low) } else { None });
+    }
+    Ok(drive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn lit() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(3, "leds"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut matrix = Charlieplex::new(c, &[0, 1, 2]).unwrap();
+        matrix.with_window(Duration::ZERO);
+        matrix.sync();
+        assert!(matrix.lit().is_empty());
+        let drive = |matrix: &Charlieplex, levels: [Option<Level>; 3]| {
+            for (offset, level) in levels.into_iter().enumerate() {
+                mem.drive(c, offset as Offset, level).unwrap();
+            }
+            matrix.sync();
+        };
+        drive(&matrix, [Some(Level::High), None, Some(Level::Low)]);
+        assert_eq!(matrix.lit(), [(0, 2)]);
+        drive(&matrix, [None, Some(Level::Low), Some(Level::High)]);
+        assert_eq!(matrix.lit(), [(2, 1)]);
+        drive(
+            &matrix,
+            [Some(Level::Low), Some(Level::Low), Some(Level::High)],
+        );
+        assert_eq!(matrix.lit(), [(2, 0), (2, 1)]);
+        assert!(matrix.is_lit(2, 0));
+        assert!(!matrix.is_lit(0, 2));
+        // LEDs lit in turn within the window are all reported
+        matrix.with_window(Duration::from_secs(10));
+        drive(&matrix, [None, None, None]);
+        assert_eq!(matrix.lit(), [(0, 2), (2, 0), (2, 1)]);
+        // probing leaves the stats and pulls untouched
+        assert!(c.stats().iter().all(|s| s.pulls == 0));
+        assert!(Charlieplex::new(c, &[0]).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Bank, Chip, Error, Level, Offset, Result};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The chips simulating a bank split by [`Builder::with_bank_chunking`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Layout {
+    /// The label of the bank.
+    pub(crate) label: String,
+
+    /// The number of lines in the bank.
+    pub(crate) num_lines: u32,
+
+    /// The number of lines on each chip, apart from the last.
+    pub(crate) chunk_lines: u32,
+
+    /// The indices of the chips in the simulator.
+    pub(crate) chips: Range<usize>,
+}
+
+/// The number of chips simulating a bank of num_lines when split into chips
+/// of at most max_lines.
+pub(crate) fn chunk_count(num_lines: u32, max_lines: Option<u32>) -> u32 {
+    match max_lines {
+        Some(max_lines) if max_lines != 0 && num_lines > max_lines => {
+            (num_lines + max_lines - 1) / max_lines
+        }
+        _ => 1,
+    }
+}
+
+/// Split any banks wider than max_lines, returning the banks to take live
+/// and the layouts of the banks that were split.
+pub(crate) fn split_banks(banks: &[Bank], max_lines: Option<u32>) -> (Vec<Bank>, Vec<Layout>) {
+    let max_lines = match max_lines {
+        Some(max_lines) => max_lines,
+        None => return (banks.to_vec(), Vec::new()),
+    };
+    let mut split = Vec::new();
+    let mut layouts = Vec::new();
+    for bank in banks {
+        let chunks = bank.split(max_lines);
+        if chunks.len() > 1 {
+            layouts.push(Layout {
+                label: bank.label.clone(),
+                num_lines: bank.num_lines,
+                chunk_lines: chunks[0].num_lines,
+                chips: split.len()..split.len() + chunks.len(),
+            });
+        }
+        split.extend(chunks);
+    }
+    (split, layouts)
+}
+
+/// A bank simulated by one or more chips, addressed by the offsets of the
+/// lines within the bank.
+///
+/// This maps the offsets of banks split by [`Builder::with_bank_chunking`]
+/// onto the chips simulating them, so callers can address the lines of
+/// very wide banks as if they were on a single chip.
+///
+/// The chunked chip does not borrow the simulator, but remains valid only
+/// while the simulator is live.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Level};
+///
+/// let sim = gpiosim::builder()
+///     .with_bank(&Bank::new(3000, "expander"))
+///     .with_bank_chunking(1024)
+///     .live()?;
+/// let expander = sim.chunked("expander").unwrap();
+/// assert_eq!(expander.chips().len(), 3);
+/// expander.set_pull(2500, Level::High)?;
+/// assert_eq!(sim.chips()[2].get_pull(452)?, Level::High);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Builder::with_bank_chunking`]: crate::Builder::with_bank_chunking
+#[derive(Debug)]
+pub struct ChunkedChip {
+    /// The label of the bank.
+    label: String,
+
+    /// The number of lines in the bank.
+    num_lines: u32,
+
+    /// The number of lines on each chip, apart from the last.
+    chunk_lines: u32,
+
+    /// The chips simulating the bank, in offset order.
+    chips: Vec<Chip>,
+}
+
+impl ChunkedChip {
+    pub(crate) fn new(layout: &Layout, chips: &[Chip]) -> ChunkedChip {
+        ChunkedChip {
+            label: layout.label.clone(),
+            num_lines: layout.num_lines,
+            chunk_lines: layout.chunk_lines,
+            chips: chips[layout.chips.clone()]
+                .iter()
+                .map(Chip::share)
+                .collect(),
+        }
+    }
+
+    // The bank simulated by a single chip.
+    pub(crate) fn single(chip: &Chip) -> ChunkedChip {
+        ChunkedChip {
+            label: chip.config().label.clone(),
+            num_lines: chip.config().num_lines,
+            chunk_lines: chip.config().num_lines,
+            chips: vec![chip.share()],
+        }
+    }
+
+    /// The label of the bank.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The number of lines in the bank.
+    pub fn num_lines(&self) -> u32 {
+        self.num_lines
+    }
+
+    /// The chips simulating the bank, in offset order.
+    pub fn chips(&self) -> &[Chip] {
+        &self.chips
+    }
+
+    /// The chip simulating a line, and the offset of the line on that chip.
+    pub fn locate(&self, offset: Offset) -> Result<(&Chip, Offset)> {
+        if offset >= self.num_lines {
+            return Err(Error::InvalidConfig(format!(
+                "offset {} is out of range for bank {:?} with {} lines",
+                offset, self.label, self.num_lines
+            )));
+        }
+        let idx = offset / self.chunk_lines;
+        Ok((&self.chips[idx as usize], offset % self.chunk_lines))
+    }
+
+    /// The offset within the bank of a line on one of its chips.
+    ///
+    /// Returns `None` if the chip does not simulate part of the bank, or the
+    /// offset is out of range for the chip.
+    pub fn bank_offset(&self, chip: &Chip, offset: Offset) -> Option<Offset> {
+        let idx = self
+            .chips
+            .iter()
+            .position(|c| Arc::ptr_eq(&c.dev, &chip.dev) && c.bank == chip.bank)?;
+        if offset >= self.chips[idx].cfg.num_lines {
+            return None;
+        }
+        Some(idx as Offset * self.chunk_lines + offset)
+    }
+
+    /// Pull a line to simulate the line being externally driven.
+    pub fn set_pull(&self, offset: Offset, pull: Level) -> Result<()> {
+        let (chip, offset) = self.locate(offset)?;
+        chip.set_pull(offset, pull)
+    }
+
+    /// Pull a line up to simulate the line being externally driven high.
+    pub fn pullup(&self, offset: Offset) -> Result<()> {
+        self.set_pull(offset, Level::High)
+    }
+
+    /// Pull a line down to simulate the line being externally driven low.
+    pub fn pulldown(&self, offset: Offset) -> Result<()> {
+        self.set_pull(offset, Level::Low)
+    }
+
+    /// Toggle the pull on a line, returning the new pull.
+    pub fn toggle(&self, offset: Offset) -> Result<Level> {
+        let (chip, offset) = self.locate(offset)?;
+        chip.toggle(offset)
+    }
+
+    /// Get the current state of the simulated external pull on a line.
+    pub fn get_pull(&self, offset: Offset) -> Result<Level> {
+        let (chip, offset) = self.locate(offset)?;
+        chip.get_pull(offset)
+    }
+
+    /// Get the current level of a line.
+    pub fn get_level(&self, offset: Offset) -> Result<Level> {
+        let (chip, offset) = self.locate(offset)?;
+        chip.get_level(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Direction, Memory};
+
+    #[test]
+    fn split() {
+        let mut bank = Bank::new(10, "wide");
+        bank.name(1, "one")
+            .name(7, "seven")
+            .hog(4, "hogster", Direction::OutputHigh)
+            .pull(9, Level::High)
+            .tag(5, "mid");
+        assert_eq!(bank.split(10), vec![bank.clone()]);
+        assert_eq!(bank.split(0), vec![bank.clone()]);
+
+        let chunks = bank.split(4);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks.iter().map(|b| b.num_lines).collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        assert_eq!(
+            chunks.iter().map(|b| b.label.as_str()).collect::<Vec<_>>(),
+            vec!["wide.0", "wide.1", "wide.2"]
+        );
+        assert_eq!(chunks[0].names()[&1], "one");
+        assert_eq!(chunks[1].names()[&3], "seven");
+        assert_eq!(chunks[1].hogs()[&0].direction, Direction::OutputHigh);
+        assert_eq!(chunks[1].lines_with_tag("mid"), vec![1]);
+        assert_eq!(chunks[2].line_config(1).pull, Some(Level::High));
+
+        let (banks, layouts) = split_banks(&[Bank::new(2, "narrow"), bank], Some(4));
+        assert_eq!(banks.len(), 4);
+        assert_eq!(
+            layouts,
+            vec![Layout {
+                label: "wide".into(),
+                num_lines: 10,
+                chunk_lines: 4,
+                chips: 1..4,
+            }]
+        );
+
+        // lines beyond the bank are dropped rather than panicking
+        let mut bank = Bank::new(10, "wide");
+        bank.name(12, "x")
+            .hog(10, "hogster", Direction::Input)
+            .pull(11, Level::High)
+            .tag(13, "beyond")
+            .name(9, "last");
+        let chunks = bank.split(4);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].names().len(), 1);
+        assert_eq!(chunks[2].names()[&1], "last");
+        assert!(chunks
+            .iter()
+            .flat_map(|c| c.line_configs())
+            .all(|(_, cfg)| cfg.hog.is_none() && cfg.pull.is_none() && cfg.tags.is_empty()));
+
+        // and rejected by the builder
+        let res = builder()
+            .with_bank(Bank::new(10, "w").name(12, "x"))
+            .with_bank_chunking(4)
+            .validate();
+        match res {
+            Err(Error::InvalidConfig(msg)) => {
+                assert_eq!(msg, "name on offset 12 exceeds the 10 lines of bank \"w\"")
+            }
+            _ => panic!("unexpected result {:?}", res),
+        }
+        assert_eq!(
+            builder()
+                .with_bank(Bank::new(10, "w").name(12, "x"))
+                .with_bank_chunking(4)
+                .plan()
+                .len(),
+            builder()
+                .with_bank(&Bank::new(10, "w"))
+                .with_bank_chunking(4)
+                .plan()
+                .len()
+        );
+    }
+
+    #[test]
+    fn chunked() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "narrow"))
+            .with_bank(Bank::new(3000, "expander").pull(2999, Level::High))
+            .with_bank_chunking(1024)
+            .live()
+            .unwrap();
+        assert_eq!(sim.chips().len(), 4);
+        assert_eq!(sim.chips()[3].config().num_lines, 952);
+
+        let expander = sim.chunked("expander").unwrap();
+        assert_eq!(expander.label(), "expander");
+        assert_eq!(expander.num_lines(), 3000);
+        assert_eq!(expander.chips().len(), 3);
+        assert_eq!(expander.get_pull(2999).unwrap(), Level::High);
+        expander.set_pull(1030, Level::High).unwrap();
+        assert_eq!(sim.chips()[2].get_pull(6).unwrap(), Level::High);
+        assert_eq!(expander.toggle(1030).unwrap(), Level::Low);
+        assert_eq!(expander.get_level(1030).unwrap(), Level::Low);
+        let (chip, offset) = expander.locate(2048).unwrap();
+        assert_eq!((chip.config().label.as_str(), offset), ("expander.2", 0));
+        assert_eq!(expander.bank_offset(&sim.chips()[3], 5), Some(2053));
+        assert_eq!(expander.bank_offset(&sim.chips()[0], 1), None);
+        assert_eq!(expander.bank_offset(&sim.chips()[1], 1024), None);
+        assert_eq!(expander.bank_offset(&sim.chips()[3], 952), None);
+        assert!(expander.get_pull(3000).is_err());
+
+        let narrow = sim.chunked("narrow").unwrap();
+        assert_eq!(narrow.chips().len(), 1);
+        narrow.pullup(1).unwrap();
+        assert_eq!(sim.chips()[0].get_pull(1).unwrap(), Level::High);
+
+        assert!(sim.chunked("missing").is_none());
+    }
+}
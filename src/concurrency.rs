@@ -0,0 +1,185 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Error, Result};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The limit on the simulators live in the process.
+static SIMS: Semaphore = Semaphore::new();
+
+/// Set the maximum number of simulators that may be live concurrently in the
+/// process.
+///
+/// Once the limit is reached, [`Builder::live`] waits for a simulator to be
+/// dropped, up to the timeout set by [`set_concurrent_sims_timeout`], e.g. to
+/// prevent highly parallel test runs exhausting the gpio-sim or gpiolib
+/// resources of small targets.
+/// Simulators already live are not affected by lowering the limit.
+///
+/// The limit applies to all simulators taken live by the process, including
+/// those held by a [`SimPool`], so a pool larger than the limit blocks.
+/// Simulators attached by the [`Kernel`] are not counted.
+///
+/// Passing `None`, the default, removes the limit.
+///
+/// [`Builder::live`]: crate::Builder::live
+/// [`Kernel`]: crate::Kernel
+/// [`SimPool`]: crate::SimPool
+pub fn set_max_concurrent_sims(limit: Option<usize>) {
+    SIMS.set_limit(limit);
+}
+
+/// The maximum number of simulators that may be live concurrently in the
+/// process, if limited.
+pub fn max_concurrent_sims() -> Option<usize> {
+    SIMS.lock().limit
+}
+
+/// Set how long [`Builder::live`] waits for a simulator to be dropped when
+/// the limit set by [`set_max_concurrent_sims`] has been reached.
+///
+/// If the wait times out then `live` returns [`Error::SimLimitReached`].
+/// A zero timeout fails immediately, rather than waiting.
+///
+/// Passing `None`, the default, waits indefinitely.
+///
+/// [`Builder::live`]: crate::Builder::live
+pub fn set_concurrent_sims_timeout(timeout: Option<Duration>) {
+    SIMS.lock().timeout = timeout;
+}
+
+/// A reservation of one of the concurrent simulators, released when dropped.
+#[derive(Debug)]
+pub(crate) struct SimPermit(&'static Semaphore);
+
+impl SimPermit {
+    /// Reserve a simulator, waiting if the limit has been reached.
+    pub(crate) fn acquire() -> Result<SimPermit> {
+        SIMS.acquire()?;
+        Ok(SimPermit(&SIMS))
+    }
+}
+
+impl Drop for SimPermit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+#[derive(Debug)]
+struct Semaphore {
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The number of reservations currently held.
+    active: usize,
+
+    /// The maximum number of reservations that may be held, if limited.
+    limit: Option<usize>,
+
+    /// How long to wait for a reservation, if not indefinitely.
+    timeout: Option<Duration>,
+}
+
+impl Semaphore {
+    const fn new() -> Semaphore {
+        Semaphore {
+            state: Mutex::new(State {
+                active: 0,
+                limit: None,
+                timeout: None,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // the state remains consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_limit(&self, limit: Option<usize>) {
+        self.lock().limit = limit;
+        self.released.notify_all();
+    }
+
+    fn acquire(&self) -> Result<()> {
+        let mut state = self.lock();
+        let deadline = state.timeout.map(|t| Instant::now() + t);
+        loop {
+            let limit = match state.limit {
+                Some(limit) if state.active >= limit => limit,
+                _ => break,
+            };
+            state = match deadline {
+                None => self.released.wait(state).unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::SimLimitReached(limit));
+                    }
+                    self.released
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+        state.active += 1;
+        Ok(())
+    }
+
+    fn release(&self) {
+        self.lock().active -= 1;
+        self.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn semaphore() {
+        let sem = Arc::new(Semaphore::new());
+        sem.acquire().unwrap();
+        sem.acquire().unwrap();
+        assert_eq!(sem.lock().active, 2);
+
+        sem.set_limit(Some(2));
+        sem.lock().timeout = Some(Duration::ZERO);
+        assert!(matches!(sem.acquire(), Err(Error::SimLimitReached(2))));
+        sem.lock().timeout = Some(Duration::from_millis(10));
+        let start = Instant::now();
+        assert!(matches!(sem.acquire(), Err(Error::SimLimitReached(2))));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        sem.lock().timeout = None;
+        let waiter = {
+            let sem = sem.clone();
+            thread::spawn(move || sem.acquire().is_ok())
+        };
+        thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        sem.release();
+        assert!(waiter.join().unwrap());
+        assert_eq!(sem.lock().active, 2);
+
+        let waiter = {
+            let sem = sem.clone();
+            thread::spawn(move || sem.acquire().is_ok())
+        };
+        thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        sem.set_limit(None);
+        assert!(waiter.join().unwrap());
+        assert_eq!(sem.lock().active, 3);
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Chip, Level, Sim};
+use std::fmt::{self, Write};
+
+impl Sim {
+    /// Describe the simulator as a human-readable table.
+    ///
+    /// Each chip is described by its name, label and dev path, followed by a
+    /// row for each line with its name, hog, tags, and current pull and level.
+    /// Pulls and levels that cannot be read, e.g. as the simulator has been
+    /// removed, are shown as `?`.
+    ///
+    /// The same description is produced by the [`Display`](fmt::Display)
+    /// implementation, so a failing test can simply print the simulator.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "sim {}", self.name);
+        for c in &self.chips {
+            describe_chip(&mut out, c);
+        }
+        out
+    }
+}
+
+impl fmt::Display for Sim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+const HEADINGS: [&str; 6] = ["offset", "name", "hog", "tags", "pull", "level"];
+
+fn describe_chip(out: &mut String, c: &Chip) {
+    let cfg = c.config();
+    let _ = writeln!(
+        out,
+        "chip {} ({}) {}",
+        c.chip_name,
+        cfg.label,
+        c.dev_path.display()
+    );
+    let level = |l: crate::Result<Level>| match l {
+        Ok(Level::High) => "high",
+        Ok(Level::Low) => "low",
+        Err(_) => "?",
+    };
+    let rows: Vec<[String; 6]> = (0..cfg.num_lines)
+        .map(|offset| {
+            let line = cfg.line_config(offset);
+            [
+                offset.to_string(),
+                line.name.clone().unwrap_or_default(),
+                line.hog
+                    .as_ref()
+                    .map(|h| format!("{} ({})", h.consumer, h.direction.as_str()))
+                    .unwrap_or_default(),
+                line.tags.join(","),
+                level(c.dev.get_pull(c.bank, offset)).into(),
+                level(c.dev.get_level(c.bank, offset)).into(),
+            ]
+        })
+        .collect();
+    let mut widths = HEADINGS.map(str::len);
+    for row in &rows {
+        for (w, col) in widths.iter_mut().zip(row) {
+            *w = (*w).max(col.chars().count());
+        }
+    }
+    write_row(out, &widths, &HEADINGS);
+    for row in &rows {
+        write_row(out, &widths, row);
+    }
+}
+
+fn write_row<S: AsRef<str>>(out: &mut String, widths: &[usize], row: &[S]) {
+    let mut line = String::from(" ");
+    for (i, (w, col)) in widths.iter().zip(row).enumerate() {
+        // right align the offsets
+        if i == 0 {
+            let _ = write!(line, " {:>w$}", col.as_ref(), w = w);
+        } else {
+            let _ = write!(line, "  {:w$}", col.as_ref(), w = w);
+        }
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder, Bank, Direction, Level, Memory};
+
+    #[test]
+    fn describe() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_name("desc")
+            .with_backend(mem.clone())
+            .with_bank(
+                Bank::new(3, "left")
+                    .name(0, "LED0")
+                    .hog(2, "hogster", Direction::OutputLow)
+                    .tag(0, "leds")
+                    .tag(0, "debug"),
+            )
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        mem.drive(c, 0, Some(Level::High)).unwrap();
+        let xdesc = format!(
+            "sim desc
+chip {} (left) {}
+  offset  name  hog                   tags        pull  level
+       0  LED0                        leds,debug  low   high
+       1                                          high  high
+       2        hogster (output-low)              low   low
+",
+            c.chip_name,
+            c.dev_path().display()
+        );
+        assert_eq!(sim.describe(), xdesc);
+        assert_eq!(sim.to_string(), xdesc);
+    }
+}
@@ -0,0 +1,350 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of DHT11 and DHT22 humidity and temperature sensors.
+//!
+//! The consumer requests a reading by pulling the data line low, as an
+//! open-drain output, and then releasing it.
+//! The emulated sensor responds with the 40-bit reading encoded in the widths
+//! of the high pulses on the line.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::dht::{Dht, Model, Timing};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(1, "dht")).live()?;
+//! let sensor = Dht::new(&sim.chips()[0], 0, Model::Dht22, Timing::default())?;
+//! sensor.set_reading(45.2, -3.7);
+//!
+//! // ... consumer reads humidity 45.2% and temperature -3.7C ...
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::timing::sleep_until;
+use crate::{Chip, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The model of sensor, which determines the encoding of the reading.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Model {
+    /// Integral and decimal bytes for each of humidity and temperature.
+    ///
+    /// Negative temperatures set the top bit of the temperature decimal byte.
+    Dht11,
+
+    /// 16-bit humidity and temperature in tenths, with the top bit of the
+    /// temperature indicating a negative value.
+    Dht22,
+}
+
+/// An error to inject into the responses of the sensor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fault {
+    /// The checksum does not match the reading.
+    BadChecksum,
+
+    /// The sensor does not respond to the start signal.
+    NoResponse,
+}
+
+/// The timing of the sensor protocol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timing {
+    /// Low pulses at least this long are treated as a start signal.
+    pub start: Duration,
+
+    /// The delay from the end of the start signal to the response.
+    pub response_wait: Duration,
+
+    /// The width of each of the low and high pulses preceding the data bits.
+    pub response: Duration,
+
+    /// The width of the low pulse preceding each bit.
+    pub bit_low: Duration,
+
+    /// The width of the high pulse encoding a 0.
+    pub zero_high: Duration,
+
+    /// The width of the high pulse encoding a 1.
+    pub one_high: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing {
+            start: Duration::from_micros(800),
+            response_wait: Duration::from_micros(30),
+            response: Duration::from_micros(80),
+            bit_low: Duration::from_micros(50),
+            zero_high: Duration::from_micros(27),
+            one_high: Duration::from_micros(70),
+        }
+    }
+}
+
+impl Timing {
+    /// The standard timings slowed by the factor.
+    pub fn scaled(factor: u32) -> Timing {
+        let t = Timing::default();
+        Timing {
+            start: t.start * factor,
+            response_wait: t.response_wait * factor,
+            response: t.response * factor,
+            bit_low: t.bit_low * factor,
+            zero_high: t.zero_high * factor,
+            one_high: t.one_high * factor,
+        }
+    }
+}
+
+/// An emulated DHT11 or DHT22 sensor.
+#[derive(Debug)]
+pub struct Dht {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct State {
+    model: Model,
+
+    timing: Timing,
+
+    /// The relative humidity, in percent.
+    humidity: f32,
+
+    /// The temperature, in degrees Celsius.
+    temperature: f32,
+
+    fault: Option<Fault>,
+
+    /// The number of responses sent.
+    responses: usize,
+}
+
+impl Dht {
+    /// Start emulating a sensor on the line.
+    ///
+    /// The line is pulled high, as per the bus pull-up.
+    /// The initial reading is 0% humidity and 0C.
+    pub fn new(chip: &Chip, offset: Offset, model: Model, timing: Timing) -> Result<Dht> {
+        chip.set_pull(offset, Level::High)?;
+        let state = Arc::new(Mutex::new(State {
+            model,
+            timing,
+            humidity: 0.0,
+            temperature: 0.0,
+            fault: None,
+            responses: 0,
+        }));
+        let monitor = {
+            let state = state.clone();
+            let mut fall = None;
+            Monitor::spawn(chip, &[offset], move |chip, _, curr| {
+                let now = Instant::now();
+                if curr[0] == Level::Low {
+                    fall = Some(now);
+                    return;
+                }
+                let width = match fall.take() {
+                    Some(t) => now - t,
+                    None => return,
+                };
+                let waveform = {
+                    let mut state = state.lock().unwrap();
+                    if width < state.timing.start || state.fault == Some(Fault::NoResponse) {
+                        return;
+                    }
+                    state.responses += 1;
+                    state.waveform()
+                };
+                let mut deadline = now;
+                for (level, duration) in waveform {
+                    sleep_until(deadline);
+                    _ = chip.set_pull(offset, level);
+                    deadline += duration;
+                }
+            })?
+        };
+        Ok(Dht { state, monitor })
+    }
+
+    /// Set the reading reported in subsequent responses.
+    ///
+    /// The humidity is in percent and the temperature in degrees Celsius.
+    pub fn set_reading(&self, humidity: f32, temperature: f32) {
+        let mut state = self.state.lock().unwrap();
+        state.humidity = humidity;
+        state.temperature = temperature;
+    }
+
+    /// Set the fault to inject into subsequent responses.
+    pub fn set_fault(&self, fault: Option<Fault>) {
+        self.state.lock().unwrap().fault = fault;
+    }
+
+    /// The number of responses sent.
+    pub fn responses(&self) -> usize {
+        self.state.lock().unwrap().responses
+    }
+
+    /// Wait until the current state of the line has been sampled, and any
+    /// response completed.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+impl State {
+    // The five bytes of the response.
+    fn data(&self) -> [u8; 5] {
+        let mut data = [0; 5];
+        match self.model {
+            Model::Dht11 => {
+                let h = (self.humidity * 10.0).round() as u16;
+                let t = (self.temperature.abs() * 10.0).round() as u16;
+                data[0] = (h / 10) as u8;
+                data[1] = (h % 10) as u8;
+                data[2] = (t / 10) as u8;
+                data[3] = (t % 10) as u8;
+                if self.temperature < 0.0 {
+                    data[3] |= 0x80;
+                }
+            }
+            Model::Dht22 => {
+                let h = (self.humidity * 10.0).round() as u16;
+                let mut t = (self.temperature.abs() * 10.0).round() as u16;
+                if self.temperature < 0.0 {
+                    t |= 0x8000;
+                }
+                data[..2].copy_from_slice(&h.to_be_bytes());
+                data[2..4].copy_from_slice(&t.to_be_bytes());
+            }
+        }
+        data[4] = data[..4].iter().fold(0_u8, |sum, b| sum.wrapping_add(*b));
+        if self.fault == Some(Fault::BadChecksum) {
+            data[4] = data[4].wrapping_add(1);
+        }
+        data
+    }
+
+    // The levels the line is pulled to, and for how long, to send the response.
+    fn waveform(&self) -> Vec<(Level, Duration)> {
+        let t = &self.timing;
+        let mut w = vec![
+            (Level::High, t.response_wait),
+            (Level::Low, t.response),
+            (Level::High, t.response),
+        ];
+        for byte in self.data() {
+            for bit in (0..8).rev() {
+                w.push((Level::Low, t.bit_low));
+                if byte & (1 << bit) == 0 {
+                    w.push((Level::High, t.zero_high));
+                } else {
+                    w.push((Level::High, t.one_high));
+                }
+            }
+        }
+        w.push((Level::Low, t.bit_low));
+        w.push((Level::High, Duration::ZERO));
+        w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    fn state(model: Model, humidity: f32, temperature: f32) -> State {
+        State {
+            model,
+            timing: Timing::default(),
+            humidity,
+            temperature,
+            fault: None,
+            responses: 0,
+        }
+    }
+
+    // Decode the bytes from the widths of the high pulses.
+    fn decode(w: &[(Level, Duration)]) -> Vec<u8> {
+        let t = Timing::default();
+        let bits: Vec<bool> = w[3..83]
+            .chunks(2)
+            .map(|c| {
+                assert_eq!(c[0], (Level::Low, t.bit_low));
+                c[1].1 == t.one_high
+            })
+            .collect();
+        bits.chunks(8)
+            .map(|b| b.iter().fold(0, |byte, &bit| byte << 1 | bit as u8))
+            .collect()
+    }
+
+    #[test]
+    fn dht22() {
+        let s = state(Model::Dht22, 65.2, 35.1);
+        assert_eq!(s.data(), [0x02, 0x8c, 0x01, 0x5f, 0xee]);
+        let s = state(Model::Dht22, 45.2, -10.1);
+        assert_eq!(s.data(), [0x01, 0xc4, 0x80, 0x65, 0xaa]);
+    }
+
+    #[test]
+    fn dht11() {
+        let s = state(Model::Dht11, 45.0, 23.4);
+        assert_eq!(s.data(), [45, 0, 23, 4, 72]);
+        let s = state(Model::Dht11, 50.5, -1.5);
+        assert_eq!(s.data(), [50, 5, 1, 0x85, 0xbd]);
+    }
+
+    #[test]
+    fn waveform() {
+        let mut s = state(Model::Dht22, 65.2, 35.1);
+        let w = s.waveform();
+        assert_eq!(w.len(), 85);
+        assert_eq!(w[1], (Level::Low, Timing::default().response));
+        assert_eq!(decode(&w), s.data());
+        assert_eq!(w[84].0, Level::High);
+        s.fault = Some(Fault::BadChecksum);
+        assert_eq!(decode(&s.waveform()), [0x02, 0x8c, 0x01, 0x5f, 0xef]);
+    }
+
+    #[test]
+    fn respond() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(1, "dht"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let sensor = Dht::new(c, 0, Model::Dht22, Timing::scaled(5)).unwrap();
+        sensor.set_reading(65.2, 35.1);
+        let start = |width| {
+            mem.drive(c, 0, Some(Level::Low)).unwrap();
+            sensor.sync();
+            thread::sleep(width);
+            mem.drive(c, 0, None).unwrap();
+            sensor.sync();
+        };
+        // too short to be a start signal
+        start(Duration::ZERO);
+        assert_eq!(sensor.responses(), 0);
+        start(Duration::from_millis(10));
+        assert_eq!(sensor.responses(), 1);
+        // the initial pull, then each segment of the response
+        assert_eq!(c.stats()[0].pulls, 86);
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        sensor.set_fault(Some(Fault::NoResponse));
+        start(Duration::from_millis(10));
+        assert_eq!(sensor.responses(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::Sim;
+use std::fmt::Write;
+
+impl Sim {
+    /// Render the topology of the simulator as a Graphviz DOT graph.
+    ///
+    /// Each chip is rendered as a cluster containing its lines, labelled with
+    /// the line name and hog, if any.
+    ///
+    /// The graph can be rendered using the Graphviz tools, e.g. `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&[], &[])
+    }
+
+    // Render the topology, with additional labelled nodes outside the chips,
+    // and labelled edges between the nodes.
+    pub(crate) fn to_dot_with(
+        &self,
+        nodes: &[(String, String)],
+        edges: &[(String, String, String)],
+    ) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph {} {{", quote(&self.name));
+        let _ = writeln!(out, "    rankdir=LR;");
+        let _ = writeln!(out, "    node [shape=box];");
+        for c in &self.chips {
+            let cfg = c.config();
+            let _ = writeln!(
+                out,
+                "    subgraph {} {{",
+                quote(&format!("cluster_{}", c.chip_name))
+            );
+            let _ = writeln!(
+                out,
+                "        label={};",
+                quote(&format!("{} ({})", c.chip_name, cfg.label))
+            );
+            for offset in 0..cfg.num_lines {
+                let line = cfg.line_config(offset);
+                let mut label = offset.to_string();
+                if let Some(name) = &line.name {
+                    label += ": ";
+                    label += name;
+                }
+                let mut attrs = String::new();
+                if let Some(hog) = &line.hog {
+                    label += &format!("\nhog: {} ({})", hog.consumer, hog.direction.as_str());
+                    attrs += ", style=filled, fillcolor=lightgrey";
+                }
+                let _ = writeln!(
+                    out,
+                    "        {} [label={}{}];",
+                    quote(&node_id(&c.chip_name, offset)),
+                    quote(&label),
+                    attrs
+                );
+            }
+            let _ = writeln!(out, "    }}");
+        }
+        for (id, label) in nodes {
+            let _ = writeln!(
+                out,
+                "    {} [label={}, shape=ellipse];",
+                quote(id),
+                quote(label)
+            );
+        }
+        for (from, to, label) in edges {
+            let _ = write!(out, "    {} -> {}", quote(from), quote(to));
+            if !label.is_empty() {
+                let _ = write!(out, " [label={}]", quote(label));
+            }
+            out += ";\n";
+        }
+        out += "}\n";
+        out
+    }
+}
+
+// The DOT node identifier for a line.
+pub(crate) fn node_id(chip_name: &str, offset: u32) -> String {
+    format!("{}:{}", chip_name, offset)
+}
+
+// Quote a string as a DOT identifier.
+pub(crate) fn quote(s: &str) -> String {
+    let mut q = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => q += "\\\"",
+            '\\' => q += "\\\\",
+            '\n' => q += "\\n",
+            _ => q.push(ch),
+        }
+    }
+    q.push('"');
+    q
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder, Bank, Direction, Memory};
+
+    #[test]
+    fn to_dot() {
+        let sim = builder()
+            .with_name("topo")
+            .with_backend(Memory::new())
+            .with_bank(Bank::new(3, "left").name(0, "LED\"0\"").hog(
+                2,
+                "hogster",
+                Direction::OutputLow,
+            ))
+            .with_bank(&Bank::new(1, "right"))
+            .live()
+            .unwrap();
+        let c0 = &sim.chips()[0].chip_name;
+        let c1 = &sim.chips()[1].chip_name;
+        let xdot = format!(
+            r#"digraph "topo" {{
+    rankdir=LR;
+    node [shape=box];
+    subgraph "cluster_{c0}" {{
+        label="{c0} (left)";
+        "{c0}:0" [label="0: LED\"0\""];
+        "{c0}:1" [label="1"];
+        "{c0}:2" [label="2\nhog: hogster (output-low)", style=filled, fillcolor=lightgrey];
+    }}
+    subgraph "cluster_{c1}" {{
+        label="{c1} (right)";
+        "{c1}:0" [label="0"];
+    }}
+}}
+"#,
+            c0 = c0,
+            c1 = c1
+        );
+        assert_eq!(sim.to_dot(), xdot);
+    }
+}
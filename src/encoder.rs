@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::timing::sleep_until;
+use crate::{Chip, Jitter, Level, Offset, Result};
+use std::time::{Duration, Instant};
+
+/// The phases of the A and B lines, in clockwise order.
+const PHASES: [(Level, Level); 4] = [
+    (Level::Low, Level::Low),
+    (Level::High, Level::Low),
+    (Level::High, Level::High),
+    (Level::Low, Level::High),
+];
+
+/// A simulated quadrature rotary encoder.
+///
+/// The encoder drives the A and B lines via their pulls, with A leading B
+/// when rotating clockwise.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Encoder};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(2, "enc")).live()?;
+/// let mut enc = Encoder::new(&sim.chips()[0], 0, 1)?;
+/// enc.with_period(Duration::from_millis(2));
+/// enc.rotate_cw(3)?;
+/// enc.rotate_ccw(1)?;
+/// assert_eq!(enc.position(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Encoder {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    a: Offset,
+
+    b: Offset,
+
+    /// The time between successive phase transitions.
+    period: Duration,
+
+    /// The number of phase transitions per detent.
+    detent: u32,
+
+    /// The current phase, as an index into PHASES.
+    phase: usize,
+
+    /// The number of detents rotated clockwise, less those counterclockwise.
+    position: i64,
+
+    /// The jitter applied to the phase transitions.
+    jitter: Jitter,
+}
+
+impl Encoder {
+    /// Create an encoder on the A and B lines, pulling both lines low.
+    ///
+    /// The default timing is a 1ms period between phase transitions, and 4
+    /// transitions, a full quadrature cycle, per detent.
+    pub fn new(chip: &Chip, a: Offset, b: Offset) -> Result<Encoder> {
+        chip.set_pull(a, Level::Low)?;
+        chip.set_pull(b, Level::Low)?;
+        Ok(Encoder {
+            chip: chip.share(),
+            a,
+            b,
+            period: Duration::from_millis(1),
+            detent: 4,
+            phase: 0,
+            position: 0,
+            jitter: Jitter::default(),
+        })
+    }
+
+    /// Set the time between successive phase transitions.
+    pub fn with_period(&mut self, period: Duration) -> &mut Self {
+        self.period = period;
+        self
+    }
+
+    /// Set the number of phase transitions per detent.
+    ///
+    /// Typically 4, 2 or 1.
+    pub fn with_detent(&mut self, transitions: u32) -> &mut Self {
+        self.detent = transitions.max(1);
+        self
+    }
+
+    /// Set the jitter applied to the phase transitions.
+    ///
+    /// There is no jitter by default.
+    pub fn with_jitter(&mut self, jitter: Jitter) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The number of detents rotated clockwise, less those rotated counterclockwise.
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// Rotate clockwise by a number of detents.
+    pub fn rotate_cw(&mut self, steps: u32) -> Result<()> {
+        for _ in 0..steps {
+            self.rotate(1)?;
+        }
+        Ok(())
+    }
+
+    /// Rotate counterclockwise by a number of detents.
+    pub fn rotate_ccw(&mut self, steps: u32) -> Result<()> {
+        for _ in 0..steps {
+            self.rotate(PHASES.len() - 1)?;
+        }
+        Ok(())
+    }
+
+    // Rotate one detent, advancing the phase by delta on each transition.
+    fn rotate(&mut self, delta: usize) -> Result<()> {
+        let start = Instant::now();
+        for n in 0..self.detent {
+            if n != 0 {
+                sleep_until(self.jitter.apply(start + self.period * n));
+            }
+            let prev = PHASES[self.phase];
+            self.phase = (self.phase + delta) % PHASES.len();
+            let (a, b) = PHASES[self.phase];
+            if prev.0 != a {
+                self.chip.set_pull(self.a, a)?;
+            } else {
+                self.chip.set_pull(self.b, b)?;
+            }
+        }
+        self.position += if delta == 1 { 1 } else { -1 };
+        sleep_until(start + self.period * self.detent);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use Level::*;
+
+    #[test]
+    fn rotate() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "enc"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut enc = Encoder::new(c, 0, 1).unwrap();
+        enc.with_period(Duration::ZERO).with_detent(1);
+        let phase = || (c.get_pull(0).unwrap(), c.get_pull(1).unwrap());
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            enc.rotate_cw(1).unwrap();
+            seen.push(phase());
+        }
+        assert_eq!(seen, [(High, Low), (High, High), (Low, High), (Low, Low)]);
+        seen.clear();
+        for _ in 0..4 {
+            enc.rotate_ccw(1).unwrap();
+            seen.push(phase());
+        }
+        assert_eq!(seen, [(Low, High), (High, High), (High, Low), (Low, Low)]);
+        assert_eq!(enc.position(), 0);
+
+        enc.with_detent(4);
+        let start = c.stats();
+        enc.rotate_cw(3).unwrap();
+        assert_eq!(enc.position(), 3);
+        assert_eq!(phase(), (Low, Low));
+        // each transition changes exactly one line
+        let end = c.stats();
+        assert_eq!(end[0].pulls - start[0].pulls, 6);
+        assert_eq!(end[1].pulls - start[1].pulls, 6);
+    }
+
+    #[test]
+    fn period() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "enc"))
+            .live()
+            .unwrap();
+        let mut enc = Encoder::new(&sim.chips()[0], 0, 1).unwrap();
+        enc.with_period(Duration::from_millis(1)).with_detent(2);
+        let start = Instant::now();
+        enc.rotate_ccw(2).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(4));
+        assert_eq!(enc.position(), -2);
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of attribute fds currently held.
+static OPEN: AtomicUsize = AtomicUsize::new(0);
+
+/// The maximum number of attribute fds held at any one time.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The maximum number of attribute fds that may be held, or 0 for no limit.
+static LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+/// The attribute file descriptors held by the crate.
+///
+/// These are the sysfs directories held open by live chips, one per chip,
+/// through which the pulls and levels of the lines are accessed.
+/// With the `io-uring` feature, the pull attribute of each line written by
+/// [`Chip::set_pulls`] is also held open, for subsequent batches, until the
+/// chip is removed.
+///
+/// [`Chip::set_pulls`]: crate::Chip::set_pulls
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FdStats {
+    /// The number of fds currently held.
+    pub open: usize,
+
+    /// The maximum number of fds held at any one time.
+    pub peak: usize,
+
+    /// The maximum number of fds that may be held, if limited.
+    pub limit: Option<usize>,
+}
+
+/// The attribute file descriptors currently held by the crate.
+///
+/// Descriptors held by the backend only while performing an operation, such
+/// as those used to create and remove the simulator in configfs, are not
+/// included.
+pub fn fd_stats() -> FdStats {
+    let limit = LIMIT.load(Ordering::Relaxed);
+    FdStats {
+        open: OPEN.load(Ordering::Relaxed),
+        peak: PEAK.load(Ordering::Relaxed),
+        limit: if limit == 0 { None } else { Some(limit) },
+    }
+}
+
+/// Set the maximum number of attribute file descriptors that may be held by
+/// the crate.
+///
+/// Taking a simulator live fails if it would exceed the limit, so a process
+/// creating many chips fails early and cleanly rather than running out of
+/// descriptors elsewhere.
+/// Descriptors already held are not affected by lowering the limit.
+///
+/// Passing `None`, the default, removes the limit.
+pub fn set_fd_limit(limit: Option<usize>) {
+    LIMIT.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// A reservation of one attribute fd, released when dropped.
+#[derive(Debug)]
+pub(crate) struct FdToken(());
+
+impl FdToken {
+    /// Reserve an fd, failing if that would exceed the limit.
+    pub(crate) fn acquire() -> io::Result<FdToken> {
+        let res = OPEN.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |open| {
+            let limit = LIMIT.load(Ordering::Relaxed);
+            if limit != 0 && open >= limit {
+                None
+            } else {
+                Some(open + 1)
+            }
+        });
+        match res {
+            Ok(open) => {
+                PEAK.fetch_max(open + 1, Ordering::Relaxed);
+                Ok(FdToken(()))
+            }
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "attribute fd limit of {} reached",
+                    LIMIT.load(Ordering::Relaxed)
+                ),
+            )),
+        }
+    }
+}
+
+impl Drop for FdToken {
+    fn drop(&mut self) {
+        OPEN.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit() {
+        let base = fd_stats().open;
+        let a = FdToken::acquire().unwrap();
+        let b = FdToken::acquire().unwrap();
+        let stats = fd_stats();
+        assert_eq!(stats.open, base + 2);
+        assert!(stats.peak >= base + 2);
+        assert_eq!(stats.limit, None);
+
+        set_fd_limit(Some(base + 2));
+        assert_eq!(fd_stats().limit, Some(base + 2));
+        let err = FdToken::acquire().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("attribute fd limit of {} reached", base + 2)
+        );
+        drop(a);
+        let c = FdToken::acquire().unwrap();
+        assert_eq!(fd_stats().open, base + 2);
+        set_fd_limit(None);
+        drop(b);
+        drop(c);
+        assert_eq!(fd_stats().open, base);
+    }
+}
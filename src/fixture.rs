@@ -0,0 +1,324 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Builder, Chip, Error, Logic, Offset, Result, Sim, WiredAnd, Wiring};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// A line in a fixture, identified by the index of its bank in the builder
+/// and its offset within the bank.
+pub type FixtureLine = (usize, Offset);
+
+/// A simulator, with its wiring and attached emulators, created and torn down
+/// as one unit.
+///
+/// The fixture is described by a [`FixtureBuilder`], and created when that is
+/// taken live.
+/// When the fixture is dropped the emulators are dropped first, stopping any
+/// threads driving them, then the wiring, and finally the simulator.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Fixture, Heartbeat, Logic};
+/// use std::time::Duration;
+///
+/// let mut builder = gpiosim::builder();
+/// builder
+///     .with_bank(&Bank::new(8, "controller"))
+///     .with_bank(&Bank::new(8, "peripheral"));
+/// let fixture = Fixture::builder(&builder)
+///     .connect((0, 3), (1, 5))
+///     .component(Logic::Not, &[(1, 6)], (0, 4), Duration::ZERO)
+///     .wired_and("irq", &[(0, 0), (1, 0)], &[(0, 1)])
+///     .attach("wdt", |sim| {
+///         Heartbeat::new(&sim.chips()[1], 7, Duration::from_millis(100))
+///     })
+///     .live()?;
+///
+/// // ... test the consumer ...
+///
+/// fixture.bus("irq").unwrap().driver().pull_low();
+/// assert_eq!(fixture.emulator::<Heartbeat>("wdt").unwrap().failures(), 0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Fixture {
+    /// The attached emulators, by name.
+    emulators: HashMap<String, Box<dyn Any + Send>>,
+
+    /// The open-drain buses, by name.
+    buses: HashMap<String, WiredAnd>,
+
+    wiring: Wiring,
+
+    sim: Sim,
+}
+
+impl fmt::Debug for Fixture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut emulators: Vec<_> = self.emulators.keys().collect();
+        emulators.sort();
+        f.debug_struct("Fixture")
+            .field("emulators", &emulators)
+            .field("buses", &self.buses)
+            .field("wiring", &self.wiring)
+            .field("sim", &self.sim)
+            .finish()
+    }
+}
+
+impl Fixture {
+    /// Start describing a fixture around the simulator described by the
+    /// builder.
+    pub fn builder(builder: &Builder) -> FixtureBuilder {
+        FixtureBuilder {
+            builder: builder.clone(),
+            rules: Vec::new(),
+            emulators: Vec::new(),
+        }
+    }
+
+    /// The simulator.
+    pub fn sim(&self) -> &Sim {
+        &self.sim
+    }
+
+    /// The chips being simulated.
+    pub fn chips(&self) -> &[Chip] {
+        self.sim.chips()
+    }
+
+    /// The wiring between the lines.
+    pub fn wiring(&self) -> &Wiring {
+        &self.wiring
+    }
+
+    /// The named open-drain bus.
+    pub fn bus(&self, name: &str) -> Option<&WiredAnd> {
+        self.buses.get(name)
+    }
+
+    /// The named emulator, if it has the type.
+    pub fn emulator<T: Any>(&self, name: &str) -> Option<&T> {
+        self.emulators.get(name)?.downcast_ref()
+    }
+
+    /// Wait until the wiring has propagated the current levels of the lines.
+    pub fn sync(&self) {
+        self.wiring.sync()
+    }
+}
+
+type Attach = Box<dyn Fn(&Sim) -> Result<Box<dyn Any + Send>>>;
+
+/// A builder of [`Fixture`]s.
+///
+/// Collects the simulator configuration, wiring rules and emulators, and then
+/// creates the fixture when taken live.
+pub struct FixtureBuilder {
+    builder: Builder,
+
+    rules: Vec<Rule>,
+
+    /// The functions creating the emulators, by name.
+    emulators: Vec<(String, Attach)>,
+}
+
+#[derive(Clone, Debug)]
+enum Rule {
+    Component {
+        logic: Logic,
+        from: Vec<FixtureLine>,
+        to: FixtureLine,
+        delay: Duration,
+    },
+    WiredAnd {
+        name: String,
+        drivers: Vec<FixtureLine>,
+        sinks: Vec<FixtureLine>,
+    },
+}
+
+impl fmt::Debug for FixtureBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixtureBuilder")
+            .field("builder", &self.builder)
+            .field("rules", &self.rules)
+            .field(
+                "emulators",
+                &self.emulators.iter().map(|e| &e.0).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl FixtureBuilder {
+    /// Connect a source line to a sink line, as per [`Wiring::connect`].
+    pub fn connect(&mut self, from: FixtureLine, to: FixtureLine) -> &mut Self {
+        self.component(Logic::Buffer, &[from], to, Duration::ZERO)
+    }
+
+    /// Add a virtual component, as per [`Wiring::component`].
+    pub fn component(
+        &mut self,
+        logic: Logic,
+        from: &[FixtureLine],
+        to: FixtureLine,
+        delay: Duration,
+    ) -> &mut Self {
+        self.rules.push(Rule::Component {
+            logic,
+            from: from.to_vec(),
+            to,
+            delay,
+        });
+        self
+    }
+
+    /// Add a named open-drain bus, as per [`Wiring::wired_and`].
+    pub fn wired_and<N: Into<String>>(
+        &mut self,
+        name: N,
+        drivers: &[FixtureLine],
+        sinks: &[FixtureLine],
+    ) -> &mut Self {
+        self.rules.push(Rule::WiredAnd {
+            name: name.into(),
+            drivers: drivers.to_vec(),
+            sinks: sinks.to_vec(),
+        });
+        self
+    }
+
+    /// Attach a named emulator, or generator, to the fixture.
+    ///
+    /// The emulator is created by the function once the simulator is live and
+    /// wired, in the order attached.
+    pub fn attach<N, F, T>(&mut self, name: N, create: F) -> &mut Self
+    where
+        N: Into<String>,
+        F: Fn(&Sim) -> Result<T> + 'static,
+        T: Any + Send,
+    {
+        self.emulators.push((
+            name.into(),
+            Box::new(move |sim| Ok(Box::new(create(sim)?) as Box<dyn Any + Send>)),
+        ));
+        self
+    }
+
+    /// Take the fixture live.
+    ///
+    /// If any part of the fixture cannot be created then the parts already
+    /// created are torn down.
+    pub fn live(&mut self) -> Result<Fixture> {
+        let sim = self.builder.live()?;
+        let mut wiring = Wiring::new();
+        let mut buses = HashMap::new();
+        for rule in &self.rules {
+            match rule {
+                Rule::Component {
+                    logic,
+                    from,
+                    to,
+                    delay,
+                } => {
+                    let (to, to_offset) = line(&sim, *to)?;
+                    wiring.component(*logic, &lines(&sim, from)?, to, to_offset, *delay)?;
+                }
+                Rule::WiredAnd {
+                    name,
+                    drivers,
+                    sinks,
+                } => {
+                    let bus = wiring.wired_and(&lines(&sim, drivers)?, &lines(&sim, sinks)?)?;
+                    buses.insert(name.clone(), bus);
+                }
+            }
+        }
+        let mut emulators = HashMap::new();
+        for (name, create) in &self.emulators {
+            emulators.insert(name.clone(), create(&sim)?);
+        }
+        Ok(Fixture {
+            emulators,
+            buses,
+            wiring,
+            sim,
+        })
+    }
+}
+
+// Find the chip containing a fixture line.
+fn line(sim: &Sim, (bank, offset): FixtureLine) -> Result<(&Chip, Offset)> {
+    match sim.chips().get(bank) {
+        Some(chip) => Ok((chip, offset)),
+        None => Err(Error::InvalidConfig(format!(
+            "fixture has no bank {}",
+            bank
+        ))),
+    }
+}
+
+fn lines<'a>(sim: &'a Sim, lines: &[FixtureLine]) -> Result<Vec<(&'a Chip, Offset)>> {
+    lines.iter().map(|&l| line(sim, l)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Level, Memory};
+
+    #[test]
+    fn live() {
+        let mem = Memory::new();
+        let mut b = builder();
+        b.with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(4, "right"));
+        let mut fb = Fixture::builder(&b);
+        fb.connect((0, 0), (1, 0))
+            .component(Logic::Not, &[(0, 1)], (1, 1), Duration::ZERO)
+            .wired_and("irq", &[(0, 2)], &[(1, 2)])
+            .attach("answer", |_| Ok(42_u32))
+            .attach("labels", |sim| {
+                Ok(sim
+                    .chips()
+                    .iter()
+                    .map(|c| c.config().label.clone())
+                    .collect::<Vec<_>>())
+            });
+        let f = fb.live().unwrap();
+        assert_eq!(f.chips().len(), 2);
+        assert_eq!(f.wiring().len(), 3);
+        assert_eq!(f.emulator::<u32>("answer"), Some(&42));
+        assert!(f.emulator::<u8>("answer").is_none());
+        assert!(f.emulator::<u32>("question").is_none());
+        assert_eq!(
+            f.emulator::<Vec<String>>("labels").unwrap(),
+            &["left", "right"]
+        );
+        let (c0, c1) = (&f.chips()[0], &f.chips()[1]);
+        assert_eq!(c1.get_pull(1).unwrap(), Level::High);
+        mem.drive(c0, 0, Some(Level::High)).unwrap();
+        f.sync();
+        assert_eq!(c1.get_pull(0).unwrap(), Level::High);
+        assert_eq!(c1.get_pull(2).unwrap(), Level::High);
+        let d = f.bus("irq").unwrap().driver();
+        d.pull_low();
+        f.sync();
+        assert_eq!(c1.get_pull(2).unwrap(), Level::Low);
+        assert!(f.bus("nmi").is_none());
+        drop(d);
+
+        let h = c0.handle();
+        drop(f);
+        assert!(matches!(h.pullup(0), Err(Error::SimRemoved)));
+
+        fb.connect((2, 0), (0, 3));
+        assert!(matches!(fb.live(), Err(Error::InvalidConfig(_))));
+    }
+}
@@ -0,0 +1,694 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::fds::FdToken;
+use std::ffi::CString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "io-uring")]
+use {crate::uring::Ring, std::collections::HashMap, std::os::unix::io::RawFd, std::sync::Mutex};
+
+/// The filesystem operations used by the [`Kernel`] backend.
+///
+/// This allows the backend logic to be exercised without the kernel.
+///
+/// [`Kernel`]: crate::Kernel
+pub(crate) trait Fs: fmt::Debug + Send + Sync {
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// The names of the entries in a directory.
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<String>>;
+
+    /// Open a directory for repeated access to the files within it.
+    fn open_dir(&self, path: &Path) -> io::Result<Box<dyn FsDir>>;
+
+    /// The target of a symlink.
+    ///
+    /// The default implementation has no symlinks, so always fails.
+    fn read_link(&self, _path: &Path) -> io::Result<PathBuf> {
+        Err(io::ErrorKind::InvalidInput.into())
+    }
+
+    /// The major and minor numbers of a character device, following
+    /// symlinks.
+    ///
+    /// The default implementation has no devices, so always fails.
+    fn device_number(&self, _path: &Path) -> io::Result<(u32, u32)> {
+        Err(io::ErrorKind::NotFound.into())
+    }
+}
+
+/// An open directory, providing access to the files within it.
+pub(crate) trait FsDir: fmt::Debug + Send + Sync {
+    fn write(&self, path: &str, data: &[u8]) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &str) -> io::Result<String>;
+
+    /// Write to several files, in order, stopping at the first error.
+    ///
+    /// The default implementation writes each file in turn.
+    fn write_batch(&self, writes: &[(&str, &[u8])]) -> io::Result<()> {
+        write_each(self, writes)
+    }
+}
+
+fn write_each<D: FsDir + ?Sized>(dir: &D, writes: &[(&str, &[u8])]) -> io::Result<()> {
+    for &(path, data) in writes {
+        dir.write(path, data)?;
+    }
+    Ok(())
+}
+
+/// The host filesystem.
+///
+/// With the `log` feature, directory creation and removal, and attribute
+/// reads and writes, are logged at the debug level.
+#[derive(Debug, Default)]
+pub(crate) struct HostFs;
+
+impl Fs for HostFs {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let res = retry_busy(|| fs::create_dir(path));
+        debug_op(FsOpKind::CreateDir, path, None, &res);
+        res
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let res = retry_busy(|| fs::remove_dir(path));
+        debug_op(FsOpKind::RemoveDir, path, None, &res);
+        res
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let res = retry_transient(|| fs::write(path, data));
+        debug_op(FsOpKind::Write, path, Some(data), &res);
+        res
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let res = retry_transient(|| fs::read_to_string(path));
+        debug_op(FsOpKind::Read, path, read_value(&res), &res);
+        res
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+        fs::read_dir(path)?
+            .map(|e| Ok(e?.file_name().to_string_lossy().into_owned()))
+            .collect()
+    }
+
+    fn open_dir(&self, path: &Path) -> io::Result<Box<dyn FsDir>> {
+        let token = FdToken::acquire()?;
+        let cpath = CString::new(path.as_os_str().as_bytes())?;
+        let flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+        // SAFETY: cpath is nul terminated.
+        let fd = unsafe { libc::open(cpath.as_ptr(), flags) };
+        Ok(Box::new(HostDir {
+            dir: owned_fd(fd)?,
+            path: path.into(),
+            _token: token,
+            #[cfg(feature = "io-uring")]
+            batch: Mutex::default(),
+        }))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn device_number(&self, path: &Path) -> io::Result<(u32, u32)> {
+        let md = fs::metadata(path)?;
+        if !md.file_type().is_char_device() {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let dev = md.rdev();
+        // SAFETY: pure functions of dev, only declared unsafe by older libc.
+        #[allow(unused_unsafe)]
+        let (major, minor) = unsafe { (libc::major(dev), libc::minor(dev)) };
+        Ok((major, minor))
+    }
+}
+
+/// A directory in the host filesystem, counted in the [`fd_stats`].
+///
+/// The directory is opened once, and the files within it are opened relative
+/// to it, so each access resolves only the path within the directory, and
+/// cannot be redirected by the directory being replaced.
+///
+/// [`fd_stats`]: crate::fd_stats
+#[derive(Debug)]
+struct HostDir {
+    dir: File,
+    path: PathBuf,
+    _token: FdToken,
+    #[cfg(feature = "io-uring")]
+    batch: Mutex<Batch>,
+}
+
+/// The state for writing batches to the files of a directory using io_uring.
+#[cfg(feature = "io-uring")]
+#[derive(Debug, Default)]
+struct Batch {
+    /// The ring, once created.
+    ring: Option<Ring>,
+
+    /// Set if io_uring is unsupported or disabled, so batches are written
+    /// file by file.
+    unavailable: bool,
+
+    /// The files written in batches, kept open for subsequent batches.
+    files: HashMap<String, (File, FdToken)>,
+}
+
+impl HostDir {
+    // Open an existing file within the directory, not following a final
+    // symlink.
+    fn open(&self, path: &str, flags: libc::c_int) -> io::Result<File> {
+        let path = CString::new(path)?;
+        let flags = flags | libc::O_CLOEXEC | libc::O_NOFOLLOW;
+        // SAFETY: the dir fd is valid for the lifetime of self, and path is
+        // nul terminated.
+        let fd = unsafe { libc::openat(self.dir.as_raw_fd(), path.as_ptr(), flags) };
+        owned_fd(fd)
+    }
+}
+
+impl FsDir for HostDir {
+    fn write(&self, path: &str, data: &[u8]) -> io::Result<()> {
+        // attributes cannot be created, so no O_CREAT
+        let res = retry_transient(|| {
+            self.open(path, libc::O_WRONLY | libc::O_TRUNC)?
+                .write_all(data)
+        });
+        debug_op(FsOpKind::Write, &self.path.join(path), Some(data), &res);
+        res
+    }
+
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        let res = retry_transient(|| {
+            let mut s = String::new();
+            self.open(path, libc::O_RDONLY)?.read_to_string(&mut s)?;
+            Ok(s)
+        });
+        debug_op(
+            FsOpKind::Read,
+            &self.path.join(path),
+            read_value(&res),
+            &res,
+        );
+        res
+    }
+
+    // Submit the batch as a single io_uring submission, falling back to
+    // writing file by file if io_uring is unavailable or the files cannot be
+    // kept open within the fd limit.
+    #[cfg(feature = "io-uring")]
+    fn write_batch(&self, writes: &[(&str, &[u8])]) -> io::Result<()> {
+        use std::collections::hash_map::Entry;
+
+        let mut batch = self.batch.lock().unwrap();
+        if batch.ring.is_none() && !batch.unavailable {
+            match Ring::new() {
+                Ok(ring) => batch.ring = Some(ring),
+                Err(_) => batch.unavailable = true,
+            }
+        }
+        let Batch { ring, files, .. } = &mut *batch;
+        let ring = match ring {
+            Some(ring) => ring,
+            None => return write_each(self, writes),
+        };
+        for &(path, _) in writes {
+            if let Entry::Vacant(e) = files.entry(path.to_string()) {
+                let token = match FdToken::acquire() {
+                    Ok(token) => token,
+                    Err(_) => return write_each(self, writes),
+                };
+                e.insert((self.open(path, libc::O_WRONLY)?, token));
+            }
+        }
+        let fd_writes: Vec<(RawFd, &[u8])> = writes
+            .iter()
+            .map(|&(path, data)| (files[path].0.as_raw_fd(), data))
+            .collect();
+        // the pulls are idempotent, so the whole batch may be resubmitted
+        let res = retry_transient(|| ring.write_all(&fd_writes));
+        if ring.is_broken() {
+            // the state of the ring is unknown, so discard it
+            batch.ring = None;
+            batch.unavailable = true;
+            return write_each(self, writes);
+        }
+        for &(path, data) in writes {
+            debug_op(FsOpKind::Write, &self.path.join(path), Some(data), &res);
+        }
+        res
+    }
+}
+
+/// The number of times an attribute access is retried after a transient
+/// error.
+const TRANSIENT_RETRIES: u32 = 5;
+
+/// The delay before retrying an attribute access that would block, doubled
+/// for each subsequent retry.
+const TRANSIENT_BACKOFF: Duration = Duration::from_millis(1);
+
+// Perform an attribute access, retrying a bounded number of times if it is
+// interrupted by a signal, or would block.
+//
+// Interrupted accesses are retried immediately, while those that would block
+// are retried after a backoff.
+fn retry_transient<T, F>(mut access: F) -> io::Result<T>
+where
+    F: FnMut() -> io::Result<T>,
+{
+    let mut backoff = TRANSIENT_BACKOFF;
+    let mut retries = 0;
+    loop {
+        match access() {
+            Err(e) if retries < TRANSIENT_RETRIES => match e.kind() {
+                io::ErrorKind::Interrupted => {}
+                io::ErrorKind::WouldBlock => {
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                _ => return Err(e),
+            },
+            res => return res,
+        }
+        retries += 1;
+    }
+}
+
+/// The number of times a directory operation is retried while the directory
+/// is busy.
+const BUSY_RETRIES: u32 = 8;
+
+/// The delay before retrying a busy directory operation, doubled for each
+/// subsequent retry.
+const BUSY_BACKOFF: Duration = Duration::from_millis(1);
+
+// Perform a directory operation, retrying a bounded number of times, with
+// backoff, while the directory is busy.
+//
+// The kernel may briefly hold a reference to a configfs directory while a
+// simulator is being created or destroyed, so rapidly recreating or removing
+// a simulator can fail with EBUSY.
+fn retry_busy<F>(mut op: F) -> io::Result<()>
+where
+    F: FnMut() -> io::Result<()>,
+{
+    let mut backoff = BUSY_BACKOFF;
+    for _ in 0..BUSY_RETRIES {
+        match op() {
+            Err(e) if e.raw_os_error() == Some(libc::EBUSY) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            res => return res,
+        }
+    }
+    op()
+}
+
+// The value read by a read operation, if it succeeded.
+fn read_value(res: &io::Result<String>) -> Option<&[u8]> {
+    res.as_ref().ok().map(|v| v.as_bytes())
+}
+
+// Log an operation on the host filesystem at the debug level.
+#[cfg(feature = "log")]
+fn debug_op<T>(kind: FsOpKind, path: &Path, value: Option<&[u8]>, res: &io::Result<T>) {
+    if log::log_enabled!(log::Level::Debug) {
+        let value = value.map(String::from_utf8_lossy);
+        log::debug!(
+            "{}",
+            FsOp {
+                kind,
+                path,
+                value: value.as_deref(),
+                error: res.as_ref().err(),
+            }
+        );
+    }
+}
+
+#[cfg(not(feature = "log"))]
+fn debug_op<T>(_kind: FsOpKind, _path: &Path, _value: Option<&[u8]>, _res: &io::Result<T>) {}
+
+// Wait, with the same bounded backoff as busy directory operations, for a
+// removed path to disappear.
+//
+// Returns false if the path still exists once the retries are exhausted.
+pub(crate) fn wait_removed(fs: &dyn Fs, path: &Path) -> bool {
+    let mut backoff = BUSY_BACKOFF;
+    for _ in 0..BUSY_RETRIES {
+        if !fs.exists(path) {
+            return true;
+        }
+        thread::sleep(backoff);
+        backoff *= 2;
+    }
+    !fs.exists(path)
+}
+
+// Take ownership of an fd returned by open or openat.
+fn owned_fd(fd: libc::c_int) -> io::Result<File> {
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a newly opened fd, owned by nothing else.
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+/// The kind of a filesystem operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FsOpKind {
+    /// Create a directory.
+    CreateDir,
+
+    /// Remove a directory.
+    RemoveDir,
+
+    /// Read an attribute file.
+    Read,
+
+    /// Write an attribute file.
+    Write,
+}
+
+/// A configfs or sysfs operation performed by the [`Kernel`] backend.
+///
+/// The `Display` form is a single line suitable for debug logging.
+///
+/// [`Kernel`]: crate::Kernel
+#[derive(Debug)]
+pub struct FsOp<'a> {
+    /// The kind of operation.
+    pub kind: FsOpKind,
+
+    /// The path operated on.
+    pub path: &'a Path,
+
+    /// The value read or written, if any.
+    pub value: Option<&'a str>,
+
+    /// The error returned by the operation, if it failed.
+    pub error: Option<&'a io::Error>,
+}
+
+impl fmt::Display for FsOp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            FsOpKind::CreateDir => "mkdir",
+            FsOpKind::RemoveDir => "rmdir",
+            FsOpKind::Read => "read",
+            FsOpKind::Write => "write",
+        };
+        write!(f, "{} {}", kind, self.path.display())?;
+        if let Some(value) = self.value {
+            write!(f, " {:?}", value)?;
+        }
+        if let Some(err) = self.error {
+            write!(f, " failed: {}", err)?;
+        }
+        Ok(())
+    }
+}
+
+/// A logger of filesystem operations.
+pub(crate) type Logger = Arc<dyn Fn(&FsOp) + Send + Sync>;
+
+/// A filesystem that logs the operations performed on another.
+pub(crate) struct LoggingFs {
+    pub(crate) fs: Arc<dyn Fs>,
+    pub(crate) logger: Logger,
+}
+
+impl fmt::Debug for LoggingFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingFs").field("fs", &self.fs).finish()
+    }
+}
+
+fn log_op<T>(
+    logger: &Logger,
+    kind: FsOpKind,
+    path: &Path,
+    value: Option<&str>,
+    res: &io::Result<T>,
+) {
+    logger(&FsOp {
+        kind,
+        path,
+        value,
+        error: res.as_ref().err(),
+    });
+}
+
+impl Fs for LoggingFs {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let res = self.fs.create_dir(path);
+        log_op(&self.logger, FsOpKind::CreateDir, path, None, &res);
+        res
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let res = self.fs.remove_dir(path);
+        log_op(&self.logger, FsOpKind::RemoveDir, path, None, &res);
+        res
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.fs.exists(path)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let res = self.fs.write(path, data);
+        let value = String::from_utf8_lossy(data);
+        log_op(&self.logger, FsOpKind::Write, path, Some(&value), &res);
+        res
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let res = self.fs.read_to_string(path);
+        let value = res.as_ref().ok().map(|v| v.as_str());
+        log_op(&self.logger, FsOpKind::Read, path, value, &res);
+        res
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+        self.fs.list_dir(path)
+    }
+
+    fn open_dir(&self, path: &Path) -> io::Result<Box<dyn FsDir>> {
+        Ok(Box::new(LoggingDir {
+            dir: self.fs.open_dir(path)?,
+            path: path.into(),
+            logger: self.logger.clone(),
+        }))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.fs.read_link(path)
+    }
+
+    fn device_number(&self, path: &Path) -> io::Result<(u32, u32)> {
+        self.fs.device_number(path)
+    }
+}
+
+/// A directory that logs the operations performed on the files within it.
+struct LoggingDir {
+    dir: Box<dyn FsDir>,
+    path: PathBuf,
+    logger: Logger,
+}
+
+impl fmt::Debug for LoggingDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingDir")
+            .field("dir", &self.dir)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl FsDir for LoggingDir {
+    fn write(&self, path: &str, data: &[u8]) -> io::Result<()> {
+        let res = self.dir.write(path, data);
+        let value = String::from_utf8_lossy(data);
+        let path = self.path.join(path);
+        log_op(&self.logger, FsOpKind::Write, &path, Some(&value), &res);
+        res
+    }
+
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        let res = self.dir.read_to_string(path);
+        let value = res.as_ref().ok().map(|v| v.as_str());
+        let path = self.path.join(path);
+        log_op(&self.logger, FsOpKind::Read, &path, value, &res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry() {
+        let failing = |kind: io::ErrorKind, failures: u32| {
+            let mut attempts = 0;
+            let res = retry_transient(|| {
+                attempts += 1;
+                if attempts > failures {
+                    Ok(attempts)
+                } else {
+                    Err(io::Error::from(kind))
+                }
+            });
+            (res, attempts)
+        };
+        assert_eq!(failing(io::ErrorKind::Interrupted, 3).0.unwrap(), 4);
+        assert_eq!(failing(io::ErrorKind::WouldBlock, 2).0.unwrap(), 3);
+
+        // retries are bounded
+        let (res, attempts) = failing(io::ErrorKind::Interrupted, 100);
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert_eq!(attempts, 1 + TRANSIENT_RETRIES);
+
+        // other errors are not retried
+        let (res, attempts) = failing(io::ErrorKind::NotFound, 1);
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(attempts, 1);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn debug_log() {
+        use std::sync::Mutex;
+
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl log::Log for Recorder {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                assert_eq!(record.level(), log::Level::Debug);
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+        log::set_logger(&RECORDER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let root = std::env::temp_dir().join(format!("gpiosim-debug-log-{}", std::process::id()));
+        let dir = root.join("sim");
+        HostFs.create_dir(&root).unwrap();
+        HostFs.create_dir(&dir).unwrap();
+        HostFs.write(&dir.join("label"), b"left").unwrap();
+        assert_eq!(HostFs.read_to_string(&dir.join("label")).unwrap(), "left");
+        let d = HostFs.open_dir(&dir).unwrap();
+        d.write("label", b"right").unwrap();
+        assert!(d.read_to_string("missing").is_err());
+        drop(d);
+        fs::remove_file(dir.join("label")).unwrap();
+        HostFs.remove_dir(&dir).unwrap();
+        HostFs.remove_dir(&root).unwrap();
+
+        // filter out operations from other tests
+        let dir = dir.display();
+        let ops: Vec<String> = RECORDER
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|op| op.contains(&*root.display().to_string()))
+            .cloned()
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                format!("mkdir {}", root.display()),
+                format!("mkdir {}", dir),
+                format!("write {}/label \"left\"", dir),
+                format!("read {}/label \"left\"", dir),
+                format!("write {}/label \"right\"", dir),
+                format!(
+                    "read {}/missing failed: {}",
+                    dir,
+                    io::Error::from_raw_os_error(libc::ENOENT)
+                ),
+                format!("rmdir {}", dir),
+                format!("rmdir {}", root.display()),
+            ]
+        );
+    }
+
+    #[test]
+    fn device_number() {
+        assert_eq!(
+            HostFs.device_number(Path::new("/dev/null")).unwrap(),
+            (1, 3)
+        );
+        let err = HostFs.device_number(Path::new("/dev")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn retry_busy_dirs() {
+        let failing = |errno: i32, failures: u32| {
+            let mut attempts = 0;
+            let res = retry_busy(|| {
+                attempts += 1;
+                if attempts > failures {
+                    Ok(())
+                } else {
+                    Err(io::Error::from_raw_os_error(errno))
+                }
+            });
+            (res, attempts)
+        };
+        let (res, attempts) = failing(libc::EBUSY, 3);
+        assert!(res.is_ok());
+        assert_eq!(attempts, 4);
+
+        // retries are bounded
+        let (res, attempts) = failing(libc::EBUSY, 100);
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBUSY));
+        assert_eq!(attempts, 1 + BUSY_RETRIES);
+
+        // other errors are not retried
+        let (res, attempts) = failing(libc::ENOTEMPTY, 1);
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ENOTEMPTY));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::random::Rng;
+use crate::timing::sleep_until;
+use crate::{Chip, Distribution, Error, Level, Lines, Offset, Result};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A pull applied by a [`Fuzzer`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FuzzEvent {
+    /// The delay from the previous event.
+    pub delay: Duration,
+
+    /// The line pulled.
+    pub offset: Offset,
+
+    /// The pull applied.
+    pub pull: Level,
+}
+
+impl fmt::Display for FuzzEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{:?} {} {}", self.delay, self.offset, self.pull)
+    }
+}
+
+/// A generator of random pulls on a selection of lines.
+///
+/// Each event pulls a randomly selected line to a random level, after a
+/// random delay drawn from the delay distribution.
+/// The sequence of events is fully reproducible from the seed, and is
+/// returned by [`run`] so it can be logged, or replayed with [`replay`].
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Fuzzer};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "fuzz")).live()?;
+/// let c = &sim.chips()[0];
+/// let fuzzer = Fuzzer::new(c, 0..4, 1234)?;
+/// let events = fuzzer.run(1000)?;
+///
+/// // ... if the consumer misbehaves, report the seed or dump the events ...
+///
+/// Fuzzer::replay(c, &events)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`run`]: Fuzzer::run
+/// [`replay`]: Fuzzer::replay
+#[derive(Debug)]
+pub struct Fuzzer {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    offsets: Vec<Offset>,
+
+    /// The distribution of the delays between events.
+    delay: Distribution,
+
+    seed: u64,
+}
+
+impl Fuzzer {
+    /// Create a fuzzer for a selection of lines, seeded with the seed.
+    ///
+    /// The delays are uniformly distributed between 0 and 1ms by default.
+    pub fn new<L: Into<Lines>>(chip: &Chip, lines: L, seed: u64) -> Result<Fuzzer> {
+        let offsets = lines.into().offsets(&chip.cfg);
+        if offsets.is_empty() {
+            return Err(Error::InvalidConfig(
+                "fuzzer requires at least one line".into(),
+            ));
+        }
+        for &offset in &offsets {
+            check_offset(chip, offset)?;
+        }
+        Ok(Fuzzer {
+            chip: chip.share(),
+            offsets,
+            delay: Distribution::Uniform {
+                min: Duration::ZERO,
+                max: Duration::from_millis(1),
+            },
+            seed,
+        })
+    }
+
+    /// Set the distribution of the delays between events.
+    pub fn with_delay(&mut self, delay: Distribution) -> &mut Self {
+        self.delay = delay;
+        self
+    }
+
+    /// The seed of the fuzzer.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Generate the sequence of events without applying them.
+    ///
+    /// The sequence depends only on the seed, lines and delay distribution.
+    pub fn generate(&self, events: usize) -> Vec<FuzzEvent> {
+        let mut rng = Rng::new(self.seed);
+        (0..events)
+            .map(|_| {
+                let delay = rng.duration(&self.delay);
+                let offset = self.offsets[rng.up_to(self.offsets.len() as u64 - 1) as usize];
+                let pull = if rng.next_u64() & 1 != 0 {
+                    Level::High
+                } else {
+                    Level::Low
+                };
+                FuzzEvent {
+                    delay,
+                    offset,
+                    pull,
+                }
+            })
+            .collect()
+    }
+
+    /// Generate and apply a sequence of events, returning the sequence.
+    pub fn run(&self, events: usize) -> Result<Vec<FuzzEvent>> {
+        let events = self.generate(events);
+        Fuzzer::replay(&self.chip, &events)?;
+        Ok(events)
+    }
+
+    /// Apply a sequence of events to the chip.
+    ///
+    /// The delays are measured from the start of the sequence, so timing
+    /// errors do not accumulate.
+    pub fn replay(chip: &Chip, events: &[FuzzEvent]) -> Result<()> {
+        let mut deadline = Instant::now();
+        for e in events {
+            deadline += e.delay;
+            sleep_until(deadline);
+            chip.set_pull(e.offset, e.pull)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn reproducible() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "fuzz"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut f1 = Fuzzer::new(c, [1, 3], 42).unwrap();
+        let delay = Distribution::Uniform {
+            min: Duration::from_micros(10),
+            max: Duration::from_micros(50),
+        };
+        f1.with_delay(delay);
+        let mut f2 = Fuzzer::new(c, [1, 3], 42).unwrap();
+        f2.with_delay(delay);
+        let s1 = f1.generate(100);
+        assert_eq!(s1, f2.generate(100));
+        assert_eq!(f1.seed(), 42);
+        assert_ne!(s1, Fuzzer::new(c, [1, 3], 43).unwrap().generate(100));
+        assert!(s1.iter().all(|e| e.offset == 1 || e.offset == 3));
+        assert!(s1.iter().any(|e| e.offset == 1));
+        assert!(s1.iter().any(|e| e.pull == Level::High));
+        assert!(s1.iter().any(|e| e.pull == Level::Low));
+        assert!(s1
+            .iter()
+            .all(|e| e.delay >= Duration::from_micros(10) && e.delay <= Duration::from_micros(50)));
+
+        let events = f1.run(100).unwrap();
+        assert_eq!(events, s1);
+        let stats = c.stats();
+        assert_eq!(stats[1].pulls + stats[3].pulls, 100);
+        let last = |offset| {
+            events
+                .iter()
+                .rev()
+                .find(|e| e.offset == offset)
+                .unwrap()
+                .pull
+        };
+        assert_eq!(c.get_pull(1).unwrap(), last(1));
+        assert_eq!(c.get_pull(3).unwrap(), last(3));
+
+        let e = FuzzEvent {
+            delay: Duration::from_micros(15),
+            offset: 3,
+            pull: Level::High,
+        };
+        assert_eq!(e.to_string(), "+15µs 3 high");
+        assert!(Fuzzer::new(c, 4, 1).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Chip, Level, Offset, Result};
+
+/// A temporary pull on a line, set by [`Chip::pull_guard`].
+///
+/// The pull the line had before the guard was created is restored when the
+/// guard is dropped, or explicitly by [`restore`], which reports any error
+/// restoring the pull.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Level};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "guard")).live()?;
+/// let c = &sim.chips()[0];
+/// {
+///     let _guard = c.pull_guard(2, Level::High)?;
+///     // ... test the consumer with line 2 high ...
+/// }
+/// assert_eq!(c.get_pull(2)?, Level::Low);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`restore`]: PullGuard::restore
+#[derive(Debug)]
+#[must_use = "the pull is restored when the guard is dropped"]
+pub struct PullGuard<'a> {
+    chip: &'a Chip,
+
+    offset: Offset,
+
+    /// The pull to restore, or None once restored.
+    prev: Option<Level>,
+}
+
+impl<'a> PullGuard<'a> {
+    pub(crate) fn new(chip: &'a Chip, offset: Offset, pull: Level) -> Result<PullGuard<'a>> {
+        let prev = chip.dev.get_pull(chip.bank, offset)?;
+        chip.set_pull(offset, pull)?;
+        Ok(PullGuard {
+            chip,
+            offset,
+            prev: Some(prev),
+        })
+    }
+
+    /// The line being pulled.
+    pub fn offset(&self) -> Offset {
+        self.offset
+    }
+
+    /// The pull that will be restored.
+    pub fn previous(&self) -> Level {
+        // prev is only taken when consumed or dropped.
+        self.prev.unwrap()
+    }
+
+    /// Restore the previous pull on the line.
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_pull()
+    }
+
+    fn restore_pull(&mut self) -> Result<()> {
+        match self.prev.take() {
+            Some(prev) => self.chip.set_pull(self.offset, prev),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PullGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.restore_pull();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder, Bank, Level, Memory};
+
+    #[test]
+    fn restore() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "guard"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        {
+            let g = c.pull_guard(1, Level::Low).unwrap();
+            assert_eq!(g.offset(), 1);
+            assert_eq!(g.previous(), Level::High);
+            assert_eq!(c.get_pull(1).unwrap(), Level::Low);
+            let g2 = c.pull_guard(2, Level::High).unwrap();
+            assert_eq!(c.get_level(2).unwrap(), Level::High);
+            g2.restore().unwrap();
+            assert_eq!(c.get_pull(2).unwrap(), Level::Low);
+        }
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+        assert_eq!(c.stats()[1].pulls, 3);
+        assert!(c.pull_guard(4, Level::High).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Level, Offset, Result};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A monitor of a heartbeat, or watchdog kick, output driven by the consumer.
+///
+/// The heartbeat fails if the consumer does not toggle the line within the
+/// maximum interval of the previous edge, or of the monitor starting.
+/// Each failure is counted once, no matter how long the heartbeat remains
+/// stopped, and is reported to the callback, if any.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Heartbeat};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(1, "wdt")).live()?;
+/// let mut hb = Heartbeat::new(&sim.chips()[0], 0, Duration::from_millis(500))?;
+/// hb.with_callback(|gap| eprintln!("heartbeat stopped for {:?}", gap));
+///
+/// // ... consumer kicks the watchdog for a while ...
+///
+/// assert_eq!(hb.failures(), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Heartbeat {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+type Callback = Box<dyn FnMut(Duration) + Send>;
+
+struct State {
+    /// The maximum interval between edges.
+    max_interval: Duration,
+
+    /// The current level of the line.
+    level: Level,
+
+    /// The time of the most recent edge, or of the monitor starting.
+    last: Instant,
+
+    /// The longest interval between edges.
+    longest: Duration,
+
+    /// Set once the current interval has been counted as a failure.
+    failed: bool,
+
+    /// The number of times the heartbeat has failed.
+    failures: usize,
+
+    /// Called with the interval since the last edge when the heartbeat fails.
+    callback: Option<Callback>,
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State")
+            .field("max_interval", &self.max_interval)
+            .field("level", &self.level)
+            .field("last", &self.last)
+            .field("longest", &self.longest)
+            .field("failed", &self.failed)
+            .field("failures", &self.failures)
+            .finish()
+    }
+}
+
+impl State {
+    fn update(&mut self, level: Level, now: Instant) {
+        let interval = now - self.last;
+        if level != self.level {
+            self.level = level;
+            self.last = now;
+            self.longest = self.longest.max(interval);
+            self.failed = false;
+        } else if interval > self.max_interval && !self.failed {
+            self.failed = true;
+            self.failures += 1;
+            if let Some(cb) = self.callback.as_mut() {
+                cb(interval);
+            }
+        }
+    }
+}
+
+impl Heartbeat {
+    /// Start monitoring the heartbeat on the line.
+    pub fn new(chip: &Chip, offset: Offset, max_interval: Duration) -> Result<Heartbeat> {
+        let level = chip.dev.get_level(chip.bank, offset)?;
+        let state = Arc::new(Mutex::new(State {
+            max_interval,
+            level,
+            last: Instant::now(),
+            longest: Duration::ZERO,
+            failed: false,
+            failures: 0,
+            callback: None,
+        }));
+        let monitor = {
+            let state = state.clone();
+            Monitor::poll(chip, move |chip| {
+                let level = chip.dev.get_level(chip.bank, offset)?;
+                state.lock().unwrap().update(level, Instant::now());
+                Ok(())
+            })?
+        };
+        Ok(Heartbeat { state, monitor })
+    }
+
+    /// Set a callback to be called, from the monitor thread, when the heartbeat
+    /// fails.
+    ///
+    /// The callback is passed the interval since the last edge.
+    pub fn with_callback<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(Duration) + Send + 'static,
+    {
+        self.state.lock().unwrap().callback = Some(Box::new(callback));
+        self
+    }
+
+    /// The number of times the heartbeat has failed.
+    pub fn failures(&self) -> usize {
+        self.state.lock().unwrap().failures
+    }
+
+    /// Returns true if the line has toggled within the maximum interval.
+    pub fn is_alive(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.last.elapsed() <= state.max_interval
+    }
+
+    /// The longest completed interval between edges.
+    pub fn longest_interval(&self) -> Duration {
+        self.state.lock().unwrap().longest
+    }
+
+    /// Clear the failures and longest interval, and restart the current
+    /// interval.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.last = Instant::now();
+        state.longest = Duration::ZERO;
+        state.failed = false;
+        state.failures = 0;
+    }
+
+    /// Wait until the current state of the line has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    #[test]
+    fn update() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let gaps = Arc::new(Mutex::new(Vec::new()));
+        let mut state = State {
+            max_interval: Duration::from_millis(100),
+            level: Level::Low,
+            last: start,
+            longest: Duration::ZERO,
+            failed: false,
+            failures: 0,
+            callback: {
+                let gaps = gaps.clone();
+                Some(Box::new(move |gap| gaps.lock().unwrap().push(gap)))
+            },
+        };
+        state.update(Level::High, ms(50));
+        state.update(Level::High, ms(100));
+        state.update(Level::Low, ms(120));
+        assert_eq!(state.failures, 0);
+        assert_eq!(state.longest, Duration::from_millis(70));
+        state.update(Level::Low, ms(221));
+        state.update(Level::Low, ms(300));
+        assert_eq!(state.failures, 1);
+        state.update(Level::High, ms(310));
+        assert_eq!(state.longest, Duration::from_millis(190));
+        state.update(Level::High, ms(411));
+        assert_eq!(state.failures, 2);
+        assert_eq!(
+            *gaps.lock().unwrap(),
+            [Duration::from_millis(101), Duration::from_millis(101)]
+        );
+    }
+
+    #[test]
+    fn stopped() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(1, "wdt"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let hb = Heartbeat::new(c, 0, Duration::from_millis(50)).unwrap();
+        let mut level = Level::Low;
+        for _ in 0..4 {
+            thread::sleep(Duration::from_millis(10));
+            level = level.toggle();
+            mem.drive(c, 0, Some(level)).unwrap();
+            hb.sync();
+        }
+        assert!(hb.is_alive());
+        assert_eq!(hb.failures(), 0);
+        assert!(hb.longest_interval() >= Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(100));
+        hb.sync();
+        assert!(!hb.is_alive());
+        assert_eq!(hb.failures(), 1);
+        hb.reset();
+        assert!(hb.is_alive());
+        assert_eq!(hb.failures(), 0);
+    }
+}
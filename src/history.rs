@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Level, Offset};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A pull applied to a line via the [`Chip`] API, as recorded in the pull
+/// history.
+///
+/// [`Chip`]: crate::Chip
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PullRecord {
+    /// The time the pull was applied, relative to the start of recording.
+    pub time: Duration,
+
+    /// The line pulled.
+    pub offset: Offset,
+
+    /// The pull applied.
+    pub pull: Level,
+}
+
+impl fmt::Display for PullRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} {}", self.time, self.offset, self.pull)
+    }
+}
+
+/// The pull history of a chip, shared by all references to the chip.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    /// Set while recording, to avoid locking the records otherwise.
+    recording: AtomicBool,
+
+    /// The start of recording, and the pulls since.
+    records: Mutex<Option<(Instant, Vec<PullRecord>)>>,
+}
+
+impl History {
+    pub(crate) fn start(&self) {
+        *self.records.lock().unwrap() = Some((Instant::now(), Vec::new()));
+        self.recording.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn stop(&self) {
+        self.recording.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn records(&self) -> Vec<PullRecord> {
+        match &*self.records.lock().unwrap() {
+            Some((_, records)) => records.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn record(&self, offset: Offset, pull: Level) {
+        if !self.recording.load(Ordering::Acquire) {
+            return;
+        }
+        let now = Instant::now();
+        if let Some((start, records)) = &mut *self.records.lock().unwrap() {
+            records.push(PullRecord {
+                time: now - *start,
+                offset,
+                pull,
+            });
+        }
+    }
+}
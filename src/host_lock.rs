@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+
+/// The name of the lock file within the runtime directory.
+const LOCK_FILE: &str = "gpiosim.lock";
+
+/// The lock held by the process, while any simulators require it.
+static HELD: Mutex<Option<Weak<HostLock>>> = Mutex::new(None);
+
+/// The path to the lock file used to serialize the use of gpio-sim between
+/// processes.
+///
+/// The file is in `/run`, if writable, else in the `XDG_RUNTIME_DIR`.
+/// Returns `None` if neither is available.
+///
+/// See [`Builder::with_exclusive_host_lock`].
+///
+/// [`Builder::with_exclusive_host_lock`]: crate::Builder::with_exclusive_host_lock
+pub fn host_lock_path() -> Option<PathBuf> {
+    let run = Path::new("/run");
+    if is_writable(run) {
+        return Some(run.join(LOCK_FILE));
+    }
+    env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|d| d.is_dir())
+        .map(|d| d.join(LOCK_FILE))
+}
+
+fn is_writable(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut path = dir.as_os_str().as_bytes().to_vec();
+    path.push(0);
+    // SAFETY: path is nul terminated.
+    unsafe { libc::access(path.as_ptr() as *const libc::c_char, libc::W_OK) == 0 }
+}
+
+/// An exclusive advisory lock on the host lock file, released when dropped.
+#[derive(Debug)]
+pub(crate) struct HostLock {
+    /// The locked file, unlocked when closed.
+    _file: File,
+}
+
+impl HostLock {
+    /// Acquire the host lock, waiting for any other process holding it to
+    /// release it.
+    ///
+    /// The lock is shared by all the simulators within the process.
+    pub(crate) fn acquire() -> io::Result<Arc<HostLock>> {
+        let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(lock) = held.as_ref().and_then(Weak::upgrade) {
+            return Ok(lock);
+        }
+        let path = host_lock_path().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no runtime directory for the host lock",
+            )
+        })?;
+        let lock = Arc::new(HostLock::lock(&path, true)?);
+        *held = Some(Arc::downgrade(&lock));
+        Ok(lock)
+    }
+
+    // Lock the file, creating it if necessary, optionally waiting for it to
+    // be unlocked.
+    fn lock(path: &Path, wait: bool) -> io::Result<HostLock> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let op = if wait {
+            libc::LOCK_EX
+        } else {
+            libc::LOCK_EX | libc::LOCK_NB
+        };
+        loop {
+            // SAFETY: the fd is valid for the lifetime of the file.
+            if unsafe { libc::flock(file.as_raw_fd(), op) } == 0 {
+                return Ok(HostLock { _file: file });
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn exclusive() {
+        let path = env::temp_dir().join(format!("gpiosim-lock-{}", std::process::id()));
+        let lock = HostLock::lock(&path, false).unwrap();
+        let err = HostLock::lock(&path, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        drop(lock);
+        let lock = HostLock::lock(&path, false).unwrap();
+        drop(lock);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn shared_within_process() {
+        let path = match host_lock_path() {
+            Some(path) => path,
+            None => return,
+        };
+        let mut builder = builder();
+        builder
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .with_exclusive_host_lock();
+        let s1 = builder.live().unwrap();
+        let s2 = builder.live().unwrap();
+        assert!(path.exists());
+        // the lock is held on behalf of both sims
+        assert!(HostLock::lock(&path, false).is_err());
+        drop(s1);
+        assert!(HostLock::lock(&path, false).is_err());
+        drop(s2);
+        assert!(HostLock::lock(&path, false).is_ok());
+    }
+}
@@ -0,0 +1,391 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of I2C slave devices on simulated lines.
+//!
+//! The consumer acts as a bit-banged I2C master, driving the SCL and SDA lines
+//! as open-drain outputs, and the emulated slave answers by pulling SDA.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::i2c::Slave;
+//! use std::sync::{Arc, Mutex};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(2, "i2c")).live()?;
+//! let regs = Arc::new(Mutex::new([0_u8; 256]));
+//! let slave = Slave::new(&sim.chips()[0], 0, 1, 0x50, regs.clone())?;
+//!
+//! // ... consumer writes 0x42 to register 0x10 ...
+//!
+//! slave.sync();
+//! assert_eq!(regs.lock().unwrap()[0x10], 0x42);
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::{Chip, Edge, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+
+/// The registers of an emulated I2C slave.
+///
+/// The first byte of each write transfer sets the register pointer.
+/// Subsequent bytes written, and any bytes read, access the register addressed
+/// by the pointer, which is then incremented.
+pub trait RegisterMap: Send {
+    /// Read the value of a register.
+    fn read(&mut self, reg: u8) -> u8;
+
+    /// Write a value to a register.
+    fn write(&mut self, reg: u8, value: u8);
+}
+
+impl RegisterMap for [u8; 256] {
+    fn read(&mut self, reg: u8) -> u8 {
+        self[reg as usize]
+    }
+
+    fn write(&mut self, reg: u8, value: u8) {
+        self[reg as usize] = value;
+    }
+}
+
+impl<T: RegisterMap> RegisterMap for Arc<Mutex<T>> {
+    fn read(&mut self, reg: u8) -> u8 {
+        self.lock().unwrap().read(reg)
+    }
+
+    fn write(&mut self, reg: u8, value: u8) {
+        self.lock().unwrap().write(reg, value)
+    }
+}
+
+/// An emulated I2C slave device.
+///
+/// The slave samples the SCL and SDA lines, so the master must hold each level
+/// for at least a few sample periods.
+/// Clock stretching and 10-bit addressing are not supported.
+#[derive(Debug)]
+pub struct Slave {
+    monitor: Monitor,
+}
+
+impl Slave {
+    /// Start emulating a slave with the 7-bit address on the SCL and SDA lines.
+    ///
+    /// Both lines are pulled high, as per the I2C bus pull-ups.
+    pub fn new<R: RegisterMap + 'static>(
+        chip: &Chip,
+        scl: Offset,
+        sda: Offset,
+        address: u8,
+        regs: R,
+    ) -> Result<Slave> {
+        chip.set_pull(scl, Level::High)?;
+        chip.set_pull(sda, Level::High)?;
+        let mut state = State {
+            address,
+            regs: Box::new(regs),
+            phase: Phase::Idle,
+            bits: 0,
+            byte: 0,
+            pointer: 0,
+            first: false,
+            acked: false,
+        };
+        let monitor = Monitor::spawn(chip, &[scl, sda], move |chip, prev, curr| {
+            if let Some(level) = state.update(prev, curr) {
+                _ = chip.set_pull(sda, level);
+            }
+        })?;
+        Ok(Slave { monitor })
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Phase {
+    /// Waiting for a start condition.
+    Idle,
+
+    /// Receiving the address byte.
+    Address,
+
+    /// Acknowledging the address.
+    AddressAck { read: bool },
+
+    /// Receiving a data byte.
+    Write,
+
+    /// Acknowledging a data byte.
+    WriteAck,
+
+    /// Transmitting a data byte.
+    Read,
+
+    /// Waiting for the master to acknowledge a data byte.
+    ReadAck,
+}
+
+struct State {
+    /// The address of the slave.
+    address: u8,
+
+    regs: Box<dyn RegisterMap>,
+
+    phase: Phase,
+
+    /// The number of bits received or transmitted in the current byte.
+    bits: u8,
+
+    /// The byte being received or transmitted.
+    byte: u8,
+
+    /// The register pointer.
+    pointer: u8,
+
+    /// True until the first byte of a write, the register pointer, is received.
+    first: bool,
+
+    /// True if the master acknowledged the last byte read.
+    acked: bool,
+}
+
+impl State {
+    // Update the state given the change in the SCL and SDA levels.
+    //
+    // Returns the level SDA should be pulled to, if that changes.
+    fn update(&mut self, prev: &[Level], curr: &[Level]) -> Option<Level> {
+        let (scl, sda) = (curr[0], curr[1]);
+        if prev[0] == Level::High && scl == Level::High && prev[1] != sda {
+            if sda == Level::Low {
+                // start, or repeated start
+                self.phase = Phase::Address;
+                self.bits = 0;
+                self.byte = 0;
+            } else {
+                // stop
+                self.phase = Phase::Idle;
+            }
+            return None;
+        }
+        if Edge::Rising.matches(prev[0], scl) {
+            self.clock_rising(sda);
+            None
+        } else if Edge::Falling.matches(prev[0], scl) {
+            self.clock_falling()
+        } else {
+            None
+        }
+    }
+
+    // The master has clocked a bit onto SDA.
+    fn clock_rising(&mut self, sda: Level) {
+        match self.phase {
+            Phase::Address | Phase::Write => {
+                self.byte = self.byte << 1 | (sda == Level::High) as u8;
+                self.bits += 1;
+            }
+            Phase::ReadAck => self.acked = sda == Level::Low,
+            _ => {}
+        }
+    }
+
+    // The slave may change SDA.
+    fn clock_falling(&mut self) -> Option<Level> {
+        match self.phase {
+            Phase::Address if self.bits == 8 => {
+                if self.byte >> 1 != self.address {
+                    self.phase = Phase::Idle;
+                    return None;
+                }
+                self.phase = Phase::AddressAck {
+                    read: self.byte & 1 == 1,
+                };
+                Some(Level::Low)
+            }
+            Phase::AddressAck { read: true } => Some(self.load()),
+            Phase::AddressAck { read: false } => {
+                self.first = true;
+                self.receive()
+            }
+            Phase::Write if self.bits == 8 => {
+                if self.first {
+                    self.pointer = self.byte;
+                    self.first = false;
+                } else {
+                    self.regs.write(self.pointer, self.byte);
+                    self.pointer = self.pointer.wrapping_add(1);
+                }
+                self.phase = Phase::WriteAck;
+                Some(Level::Low)
+            }
+            Phase::WriteAck => self.receive(),
+            Phase::Read => {
+                self.bits += 1;
+                if self.bits == 8 {
+                    self.phase = Phase::ReadAck;
+                    Some(Level::High)
+                } else {
+                    Some(self.bit())
+                }
+            }
+            Phase::ReadAck if self.acked => Some(self.load()),
+            Phase::ReadAck => {
+                self.phase = Phase::Idle;
+                Some(Level::High)
+            }
+            _ => None,
+        }
+    }
+
+    // Release SDA and prepare to receive a byte.
+    fn receive(&mut self) -> Option<Level> {
+        self.phase = Phase::Write;
+        self.bits = 0;
+        self.byte = 0;
+        Some(Level::High)
+    }
+
+    // Load the next byte to transmit and return the level of its first bit.
+    fn load(&mut self) -> Level {
+        self.byte = self.regs.read(self.pointer);
+        self.pointer = self.pointer.wrapping_add(1);
+        self.bits = 0;
+        self.acked = false;
+        self.phase = Phase::Read;
+        self.bit()
+    }
+
+    // The level of the current bit being transmitted, MSB first.
+    fn bit(&self) -> Level {
+        if self.byte & (0x80 >> self.bits) == 0 {
+            Level::Low
+        } else {
+            Level::High
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    const SCL: Offset = 0;
+    const SDA: Offset = 1;
+
+    // A bit-banged master driving the lines as open-drain outputs.
+    struct Master<'a> {
+        mem: &'a Memory,
+        chip: &'a Chip,
+        slave: &'a Slave,
+    }
+
+    impl Master<'_> {
+        fn set(&self, offset: Offset, level: Level) {
+            let driven = match level {
+                Level::High => None,
+                Level::Low => Some(Level::Low),
+            };
+            self.mem.drive(self.chip, offset, driven).unwrap();
+            self.slave.sync();
+        }
+
+        fn start(&self) {
+            self.set(SDA, Level::High);
+            self.set(SCL, Level::High);
+            self.set(SDA, Level::Low);
+            self.set(SCL, Level::Low);
+        }
+
+        fn stop(&self) {
+            self.set(SDA, Level::Low);
+            self.set(SCL, Level::High);
+            self.set(SDA, Level::High);
+        }
+
+        fn clock(&self, bit: Level) -> Level {
+            self.set(SDA, bit);
+            self.set(SCL, Level::High);
+            let level = self.chip.get_level(SDA).unwrap();
+            self.set(SCL, Level::Low);
+            level
+        }
+
+        // Write a byte and return true if it was acked.
+        fn write(&self, byte: u8) -> bool {
+            for bit in (0..8).rev() {
+                let level = if byte & (1 << bit) == 0 {
+                    Level::Low
+                } else {
+                    Level::High
+                };
+                self.clock(level);
+            }
+            self.clock(Level::High) == Level::Low
+        }
+
+        fn read(&self, ack: bool) -> u8 {
+            let mut byte = 0;
+            for _ in 0..8 {
+                byte = byte << 1 | (self.clock(Level::High) == Level::High) as u8;
+            }
+            self.clock(if ack { Level::Low } else { Level::High });
+            byte
+        }
+    }
+
+    #[test]
+    fn write_read() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "i2c"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let regs = Arc::new(Mutex::new([0_u8; 256]));
+        regs.lock().unwrap()[0x20] = 0xa5;
+        regs.lock().unwrap()[0x21] = 0x3c;
+        let slave = Slave::new(c, SCL, SDA, 0x50, regs.clone()).unwrap();
+        assert_eq!(c.get_pull(SCL).unwrap(), Level::High);
+        assert_eq!(c.get_pull(SDA).unwrap(), Level::High);
+        let m = Master {
+            mem: &mem,
+            chip: c,
+            slave: &slave,
+        };
+
+        // write two registers
+        m.start();
+        assert!(m.write(0x50 << 1));
+        assert!(m.write(0x10));
+        assert!(m.write(0x42));
+        assert!(m.write(0x43));
+        m.stop();
+        assert_eq!(regs.lock().unwrap()[0x10], 0x42);
+        assert_eq!(regs.lock().unwrap()[0x11], 0x43);
+
+        // read two registers, using a repeated start
+        m.start();
+        assert!(m.write(0x50 << 1));
+        assert!(m.write(0x20));
+        m.start();
+        assert!(m.write(0x50 << 1 | 1));
+        assert_eq!(m.read(true), 0xa5);
+        assert_eq!(m.read(false), 0x3c);
+        m.stop();
+        assert_eq!(c.get_level(SDA).unwrap(), Level::High);
+
+        // other addresses are not acked
+        m.start();
+        assert!(!m.write(0x51 << 1));
+        m.stop();
+    }
+}
@@ -0,0 +1,300 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Generation of infrared remote control signals on simulated lines.
+//!
+//! A [`Transmitter`] emulates the output of an IR receiver module, such as a
+//! TSOP38238, pulling the line to its active level for each mark, i.e. burst
+//! of carrier, and to the inactive level for each space.
+//!
+//! The [`nec`] and [`rc5`] functions encode frames of those protocols as
+//! sequences of [`Symbol`]s, which may also be modified before sending to
+//! test decoder tolerances.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::ir::Transmitter;
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(1, "ir")).live()?;
+//! let tx = Transmitter::new(&sim.chips()[0], 0)?;
+//!
+//! // press and hold a button for a couple of repeats
+//! tx.send_nec(0x04, 0x08, 2)?;
+//! tx.send_rc5(0x05, 0x35, false)?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::timing::sleep_until;
+use crate::{Chip, Jitter, Level, Offset, Result};
+use std::time::{Duration, Instant};
+
+/// An element of an IR signal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symbol {
+    /// The carrier is present for the duration.
+    Mark(Duration),
+
+    /// The carrier is absent for the duration.
+    Space(Duration),
+}
+
+impl Symbol {
+    /// The duration of the symbol.
+    pub fn duration(&self) -> Duration {
+        match self {
+            Symbol::Mark(d) | Symbol::Space(d) => *d,
+        }
+    }
+}
+
+/// The NEC timing unit.
+const NEC_UNIT: Duration = Duration::from_nanos(562_500);
+
+/// The period between the starts of successive NEC frames and repeats.
+const NEC_PERIOD: Duration = Duration::from_millis(108);
+
+/// The RC5 half bit period.
+const RC5_HALF_BIT: Duration = Duration::from_nanos(888_889);
+
+/// The period between the starts of successive RC5 frames.
+const RC5_PERIOD: Duration = Duration::from_nanos(113_777_778);
+
+/// Encode an NEC frame with the 8-bit address and command.
+///
+/// The address and command are each followed by their inverse.
+pub fn nec(address: u8, command: u8) -> Vec<Symbol> {
+    nec_extended(u16::from(!address) << 8 | u16::from(address), command)
+}
+
+/// Encode an extended NEC frame with the 16-bit address and 8-bit command.
+///
+/// The address is sent in place of the address and its inverse, low byte
+/// first, and the command is followed by its inverse.
+pub fn nec_extended(address: u16, command: u8) -> Vec<Symbol> {
+    let mut symbols = vec![Symbol::Mark(NEC_UNIT * 16), Symbol::Space(NEC_UNIT * 8)];
+    let data = u32::from(address) | u32::from(command) << 16 | u32::from(!command) << 24;
+    for n in 0..32 {
+        symbols.push(Symbol::Mark(NEC_UNIT));
+        let space = if data & (1 << n) != 0 { 3 } else { 1 };
+        symbols.push(Symbol::Space(NEC_UNIT * space));
+    }
+    symbols.push(Symbol::Mark(NEC_UNIT));
+    symbols
+}
+
+/// Encode an NEC repeat code, sent while a button is held.
+pub fn nec_repeat() -> Vec<Symbol> {
+    vec![
+        Symbol::Mark(NEC_UNIT * 16),
+        Symbol::Space(NEC_UNIT * 4),
+        Symbol::Mark(NEC_UNIT),
+    ]
+}
+
+/// Encode an RC5 frame with the 5-bit address and 6-bit command.
+///
+/// Commands up to 127 are supported, with the second start bit carrying the
+/// inverse of bit 6, as per extended RC5.
+/// The toggle bit should be inverted for each new button press.
+pub fn rc5(address: u8, command: u8, toggle: bool) -> Vec<Symbol> {
+    let mut bits = vec![true, command & 0x40 == 0, toggle];
+    bits.extend((0..5).rev().map(|n| address & (1 << n) != 0));
+    bits.extend((0..6).rev().map(|n| command & (1 << n) != 0));
+    // Manchester encoded, with a 1 being a space then a mark.
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for half in bits.into_iter().flat_map(|b| [!b, b]) {
+        match (symbols.last_mut(), half) {
+            (Some(Symbol::Mark(d)), true) | (Some(Symbol::Space(d)), false) => *d += RC5_HALF_BIT,
+            (None, false) => {}
+            (_, true) => symbols.push(Symbol::Mark(RC5_HALF_BIT)),
+            (_, false) => symbols.push(Symbol::Space(RC5_HALF_BIT)),
+        }
+    }
+    symbols
+}
+
+/// Sends IR signals to the consumer by pulling a line.
+#[derive(Debug)]
+pub struct Transmitter {
+    /// The chip containing the line.
+    chip: Chip,
+
+    offset: Offset,
+
+    /// The level of the line during marks.
+    active: Level,
+
+    /// The jitter applied to the symbol edges.
+    jitter: Jitter,
+}
+
+impl Transmitter {
+    /// Create a transmitter on the line, and pull the line to idle.
+    ///
+    /// The line is active low by default, as per typical IR receiver modules.
+    pub fn new(chip: &Chip, offset: Offset) -> Result<Transmitter> {
+        chip.set_pull(offset, Level::High)?;
+        Ok(Transmitter {
+            chip: chip.share(),
+            offset,
+            active: Level::Low,
+            jitter: Jitter::default(),
+        })
+    }
+
+    /// Set the level of the line during marks, and pull the line to idle.
+    pub fn with_active(&mut self, active: Level) -> Result<&mut Self> {
+        self.active = active;
+        self.chip.set_pull(self.offset, active.toggle())?;
+        Ok(self)
+    }
+
+    /// Set the jitter applied to the symbol edges.
+    ///
+    /// There is no jitter by default.
+    pub fn with_jitter(&mut self, jitter: Jitter) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The level of the line during marks.
+    pub fn active(&self) -> Level {
+        self.active
+    }
+
+    /// Send the symbols, returning the line to idle at the end.
+    pub fn send(&self, symbols: &[Symbol]) -> Result<()> {
+        let mut deadline = Instant::now();
+        for s in symbols {
+            let level = match s {
+                Symbol::Mark(_) => self.active,
+                Symbol::Space(_) => self.active.toggle(),
+            };
+            self.chip.set_pull(self.offset, level)?;
+            deadline += s.duration();
+            sleep_until(self.jitter.apply(deadline));
+        }
+        self.chip.set_pull(self.offset, self.active.toggle())
+    }
+
+    /// Send an NEC frame followed by a number of repeat codes, as if the
+    /// button were held.
+    ///
+    /// Successive frames and repeats start 108ms apart.
+    pub fn send_nec(&self, address: u8, command: u8, repeats: u32) -> Result<()> {
+        let start = Instant::now();
+        self.send(&nec(address, command))?;
+        let repeat = nec_repeat();
+        for n in 1..=repeats {
+            sleep_until(start + NEC_PERIOD * n);
+            self.send(&repeat)?;
+        }
+        Ok(())
+    }
+
+    /// Send an RC5 frame.
+    ///
+    /// The line is then held idle for the remainder of the frame period, so
+    /// successive frames are correctly spaced.
+    pub fn send_rc5(&self, address: u8, command: u8, toggle: bool) -> Result<()> {
+        let start = Instant::now();
+        self.send(&rc5(address, command, toggle))?;
+        sleep_until(start + RC5_PERIOD);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    fn units(symbols: &[Symbol], unit: Duration) -> Vec<i64> {
+        symbols
+            .iter()
+            .map(|s| {
+                let n = (s.duration().as_nanos() as f64 / unit.as_nanos() as f64).round() as i64;
+                match s {
+                    Symbol::Mark(_) => n,
+                    Symbol::Space(_) => -n,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nec_frame() {
+        let frame = units(&nec(0x04, 0x08), NEC_UNIT);
+        assert_eq!(frame.len(), 2 + 64 + 1);
+        assert_eq!(frame[..2], [16, -8]);
+        let bits: Vec<bool> = frame[2..66]
+            .chunks(2)
+            .map(|c| {
+                assert_eq!(c[0], 1);
+                c[1] == -3
+            })
+            .collect();
+        let byte = |n: usize| {
+            bits[n * 8..n * 8 + 8]
+                .iter()
+                .rev()
+                .fold(0_u8, |b, &bit| b << 1 | bit as u8)
+        };
+        assert_eq!(
+            [byte(0), byte(1), byte(2), byte(3)],
+            [0x04, 0xfb, 0x08, 0xf7]
+        );
+        assert_eq!(frame[66], 1);
+
+        let frame = units(&nec_extended(0x1234, 0x08), NEC_UNIT);
+        // low byte of the address first, LSB first
+        assert_eq!(frame[2..6], [1, -1, 1, -1]);
+        assert_eq!(frame[6..8], [1, -3]);
+        assert_eq!(units(&nec_repeat(), NEC_UNIT), [16, -4, 1]);
+    }
+
+    #[test]
+    fn rc5_frame() {
+        // 1 1 0 00101 110101
+        let frame = units(&rc5(0x05, 0x35, false), RC5_HALF_BIT);
+        assert_eq!(
+            frame,
+            [1, -1, 2, -1, 1, -1, 1, -2, 2, -2, 1, -1, 1, -1, 2, -2, 2, -2, 1]
+        );
+        assert_eq!(frame.iter().map(|n| n.abs()).sum::<i64>(), 27);
+        // extended commands clear the second start bit
+        let frame = units(&rc5(0x00, 0x40, true), RC5_HALF_BIT);
+        assert_eq!(frame[..3], [2, -2, 2]);
+    }
+
+    #[test]
+    fn send() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(1, "ir"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let tx = Transmitter::new(c, 0).unwrap();
+        assert_eq!(tx.active(), Level::Low);
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        let start = Instant::now();
+        tx.send(&[
+            Symbol::Mark(Duration::from_millis(2)),
+            Symbol::Space(Duration::from_millis(1)),
+            Symbol::Mark(Duration::from_millis(2)),
+        ])
+        .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        // the initial idle, three symbols, and the final idle
+        assert_eq!(c.stats()[0].pulls, 5);
+
+        let start = Instant::now();
+        tx.send_nec(0x01, 0x02, 1).unwrap();
+        assert!(start.elapsed() >= NEC_PERIOD);
+        assert_eq!(c.stats()[0].pulls, 5 + 68 + 4);
+    }
+}
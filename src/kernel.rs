@@ -0,0 +1,1442 @@
+// SPDX-FileCopyrightText: 2022 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::fs::{wait_removed, Fs, FsDir, FsOp, HostFs, LoggingFs};
+use crate::{
+    Aggregator, Backend, Bank, Chip, Device, Direction, Error, KernelLimits, Level, Offset, Result,
+    Sim,
+};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep};
+use std::time::Duration;
+
+/// The default location of the gpio-sim in configfs.
+const CONFIGFS_DIR: &str = "/sys/kernel/config/gpio-sim";
+
+/// The maximum number of threads used to set up the banks of a simulator.
+const MAX_SETUP_THREADS: usize = 8;
+
+/// The minimum number of configfs operations to set up a simulator for which
+/// the banks are set up in parallel.
+const PARALLEL_SETUP_OPS: usize = 64;
+
+/// The backend provided by the Linux **gpio-sim** kernel module.
+///
+/// Simulators are configured via *configfs* and the chips manipulated via *sysfs*.
+///
+/// This is the default backend, other than on kernels prior to 5.19, which
+/// lack gpio-sim, where the [`Mockup`](crate::Mockup) backend is used.
+#[derive(Clone, Debug)]
+pub struct Kernel {
+    /// The filesystem containing configfs and sysfs.
+    fs: Arc<dyn Fs>,
+
+    /// Accept chips whose device is a symlink to the chip.
+    resolve_symlinks: bool,
+}
+
+impl Default for Kernel {
+    fn default() -> Kernel {
+        Kernel {
+            fs: Arc::new(HostFs),
+            resolve_symlinks: false,
+        }
+    }
+}
+
+impl Kernel {
+    #[cfg(test)]
+    pub(crate) fn with_fs(fs: Arc<dyn Fs>) -> Kernel {
+        Kernel {
+            fs,
+            resolve_symlinks: false,
+        }
+    }
+
+    /// Accept a chip whose path in /dev is a symlink, if the symlink resolves
+    /// to the device of the chip.
+    ///
+    /// By default a symlink in place of the device of a chip is rejected with
+    /// [`Error::DeviceMasked`], as it may refer to some other chip.
+    /// For hosts that intentionally symlink gpiochip names, this resolves the
+    /// symlink and checks that the device number of its target matches that
+    /// of the chip in sysfs.
+    /// The [`Chip::dev_path`](crate::Chip::dev_path) is then the target of the
+    /// symlink.
+    pub fn resolve_symlinks(mut self, resolve: bool) -> Kernel {
+        self.resolve_symlinks = resolve;
+        self
+    }
+
+    /// The names of the simulators in configfs, in name order.
+    ///
+    /// This includes simulators created by other processes, and simulators
+    /// that are not live.
+    pub fn sims(&self) -> Result<Vec<String>> {
+        let dir = find_configfs(self.fs.as_ref())?;
+        let mut names = self.fs.list_dir(&dir)?;
+        names.sort();
+        Ok(names)
+    }
+
+    /// Attach to a live simulator that was not created by this process, or
+    /// not by this backend.
+    ///
+    /// The configuration of the chips is read back from configfs, so the
+    /// label, number of lines, names and hogs of each [`Bank`] are available
+    /// from [`Chip::config`](crate::Chip::config), but not any tags or initial
+    /// pulls.
+    /// The simulator must have its banks named `bank0`, `bank1`, etc, as per
+    /// simulators created by this crate.
+    ///
+    /// The simulator remains owned by its creator, so it is not removed when
+    /// the returned [`Sim`] is dropped.
+    pub fn attach(&self, name: &str) -> Result<Sim> {
+        let dir = find_configfs(self.fs.as_ref())?.join(name);
+        if !self.fs.exists(&dir) {
+            return Err(Error::InvalidConfig(format!(
+                "no simulator named {:?}",
+                name
+            )));
+        }
+        let mut dev = KernelDevice {
+            fs: self.fs.clone(),
+            dir,
+            banks: Vec::new(),
+            dev_name: String::new(),
+            chips: Vec::new(),
+            removed: AtomicBool::new(false),
+            owned: AtomicBool::new(false),
+            resolve_symlinks: self.resolve_symlinks,
+        };
+        if dev.read_attr(&dev.dir, "live")? != "1" {
+            return Err(Error::InvalidConfig(format!(
+                "simulator {:?} is not live",
+                name
+            )));
+        }
+        dev.read_banks()?;
+        dev.read_attrs()?;
+        let banks = dev.banks.clone();
+        Ok(Sim::new(name.into(), &banks, Arc::new(dev)))
+    }
+
+    /// Detect the limits the kernel places on simulated chips.
+    ///
+    /// The limits reflect the chips on the system at the time of detection,
+    /// including those of other simulators.
+    pub fn limits(&self) -> KernelLimits {
+        KernelLimits::detect(self.fs.as_ref())
+    }
+
+    /// Log every configfs and sysfs operation performed by the backend.
+    ///
+    /// The logger is called after each directory creation and removal, and each
+    /// attribute read and write, with the path, the value, and any error.
+    /// This is intended to help diagnose failures, such as the kernel rejecting
+    /// a configuration, and may be used to capture the operations, or forward
+    /// them elsewhere:
+    ///
+    /// ```
+    /// use gpiosim::{Bank, Kernel};
+    ///
+    /// let mut builder = gpiosim::builder();
+    /// builder
+    ///     .with_backend(Kernel::default().with_logger(|op| eprintln!("gpiosim: {}", op)))
+    ///     .with_bank(&Bank::new(8, "left"));
+    /// ```
+    ///
+    /// With the `log` feature, the operations are also logged at the debug
+    /// level, without requiring a logger.
+    pub fn with_logger<F>(mut self, logger: F) -> Kernel
+    where
+        F: Fn(&FsOp) + Send + Sync + 'static,
+    {
+        self.fs = Arc::new(LoggingFs {
+            fs: self.fs,
+            logger: Arc::new(logger),
+        });
+        self
+    }
+}
+
+impl Backend for Kernel {
+    fn live(&self, name: &str, banks: &[Bank]) -> Result<Arc<dyn Device>> {
+        let dir = find_configfs(self.fs.as_ref())?.join(name);
+        if self.fs.exists(&dir) {
+            return Err(Error::SimulatorExists(name.into()));
+        }
+        self.fs.create_dir(&dir)?;
+
+        let mut dev = KernelDevice {
+            fs: self.fs.clone(),
+            dir,
+            banks: banks.to_vec(),
+            dev_name: String::new(),
+            chips: Vec::new(),
+            removed: AtomicBool::new(false),
+            owned: AtomicBool::new(true),
+            resolve_symlinks: self.resolve_symlinks,
+        };
+        dev.live()?;
+        Ok(Arc::new(dev))
+    }
+
+    fn validate(&self, banks: &[Bank]) -> Result<()> {
+        self.limits().check(banks)
+    }
+}
+
+/// A configfs operation performed when taking a simulator live.
+///
+/// The `Display` form is the equivalent shell command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfigOp {
+    /// Create a directory.
+    CreateDir(PathBuf),
+
+    /// Write a value to an attribute file.
+    Write(PathBuf, String),
+}
+
+impl fmt::Display for ConfigOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOp::CreateDir(path) => write!(f, "mkdir {}", path.display()),
+            ConfigOp::Write(path, value) => write!(f, "echo {:?} > {}", value, path.display()),
+        }
+    }
+}
+
+/// Determine the configfs operations the [`Kernel`] backend would perform to
+/// take the simulator live.
+///
+/// Assumes configfs is mounted at the default location.
+pub(crate) fn plan(name: &str, banks: &[Bank]) -> Vec<ConfigOp> {
+    let fs = Arc::new(PlanFs::default());
+    let dir = Path::new(CONFIGFS_DIR).join(name);
+    let _ = fs.create_dir(&dir);
+    let dev = KernelDevice {
+        fs: fs.clone(),
+        dir,
+        banks: banks.to_vec(),
+        dev_name: String::new(),
+        chips: Vec::new(),
+        // nothing to cleanup
+        removed: AtomicBool::new(true),
+        owned: AtomicBool::new(false),
+        resolve_symlinks: false,
+    };
+    let _ = dev.setup_configfs(1);
+    let _ = dev.write_attr(&dev.dir, "live", "1");
+    let ops = fs.ops.lock().unwrap().clone();
+    ops
+}
+
+/// A filesystem that records, rather than performs, directory creation and writes.
+#[derive(Debug, Default)]
+struct PlanFs {
+    ops: Mutex<Vec<ConfigOp>>,
+    dirs: Mutex<HashSet<PathBuf>>,
+}
+
+impl Fs for PlanFs {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.dirs.lock().unwrap().insert(path.into());
+        self.ops
+            .lock()
+            .unwrap()
+            .push(ConfigOp::CreateDir(path.into()));
+        Ok(())
+    }
+
+    fn remove_dir(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.dirs.lock().unwrap().contains(path)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.ops.lock().unwrap().push(ConfigOp::Write(
+            path.into(),
+            String::from_utf8_lossy(data).into(),
+        ));
+        Ok(())
+    }
+
+    fn read_to_string(&self, _path: &Path) -> io::Result<String> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn list_dir(&self, _path: &Path) -> io::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn open_dir(&self, _path: &Path) -> io::Result<Box<dyn FsDir>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// A live gpio-sim.
+#[derive(Debug)]
+struct KernelDevice {
+    /// The filesystem containing configfs and sysfs.
+    fs: Arc<dyn Fs>,
+
+    /// Path to the gpio-sim in configfs.
+    dir: PathBuf,
+
+    /// The configuration of the chips.
+    banks: Vec<Bank>,
+
+    /// The name of the device in sysfs.
+    dev_name: String,
+
+    /// The details of the live chips.
+    chips: Vec<KernelChip>,
+
+    /// Set once the gpio-sim has been removed.
+    removed: AtomicBool,
+
+    /// Set if the gpio-sim was created by the device, and so is removed with
+    /// it, until released.
+    owned: AtomicBool,
+
+    /// Accept chips whose device is a symlink to the chip.
+    resolve_symlinks: bool,
+}
+
+#[derive(Debug)]
+struct KernelChip {
+    /// The name of the gpiochip in /dev and sysfs.
+    chip_name: String,
+
+    /// The path to the chip in /dev.
+    dev_path: PathBuf,
+
+    /// The path to the chip directory in /sys/device/platform.
+    sysfs_path: PathBuf,
+
+    /// The chip directory in /sys/device/platform.
+    sysfs_dir: Box<dyn FsDir>,
+}
+
+impl KernelDevice {
+    fn live(&mut self) -> Result<()> {
+        self.setup_configfs(self.setup_threads())?;
+        self.write_attr(&self.dir, "live", "1")?;
+        self.read_attrs()
+    }
+
+    fn cleanup_configfs(&self) {
+        if !self.owned.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.removed.swap(true, Ordering::Relaxed) || !self.fs.exists(&self.dir) {
+            return;
+        }
+        let _ = self.write_attr(&self.dir, "live", "0");
+        for (i, b) in self.banks.iter().enumerate() {
+            let bank = format!("bank{}", i);
+            let bank_dir = self.dir.join(bank);
+            if !self.fs.exists(&bank_dir) {
+                continue;
+            }
+            let lines = b.line_configs();
+            for (offset, _) in lines.iter().filter(|(_, cfg)| cfg.hog.is_some()) {
+                let line_dir = bank_dir.join(format!("line{}", offset));
+                let hog_dir = line_dir.join("hog");
+                let _ = self.fs.remove_dir(&hog_dir);
+                let _ = self.fs.remove_dir(&line_dir);
+            }
+            for (offset, _) in lines
+                .iter()
+                .filter(|(_, cfg)| cfg.hog.is_none() && cfg.name.is_some())
+            {
+                let line_dir = bank_dir.join(format!("line{}", offset));
+                let _ = self.fs.remove_dir(&line_dir);
+            }
+            let _ = self.fs.remove_dir(&bank_dir);
+        }
+        let _ = self.fs.remove_dir(&self.dir);
+        // the simulator is left behind if it remains busy, rather than
+        // blocking the drop indefinitely
+        wait_removed(self.fs.as_ref(), &self.dir);
+    }
+
+    // Create the configfs directories and attributes for the banks, spreading
+    // the banks across up to max_threads threads.
+    fn setup_configfs(&self, max_threads: usize) -> Result<()> {
+        let threads = max_threads.min(self.banks.len());
+        if threads <= 1 {
+            for i in 0..self.banks.len() {
+                self.setup_bank(i)?;
+            }
+            return Ok(());
+        }
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let worker = || -> Option<(usize, io::Error)> {
+            while !failed.load(Ordering::Relaxed) {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= self.banks.len() {
+                    break;
+                }
+                if let Err(e) = self.setup_bank(i) {
+                    failed.store(true, Ordering::Relaxed);
+                    return Some((i, e));
+                }
+            }
+            None
+        };
+        let errors: Vec<(usize, io::Error)> = thread::scope(|s| {
+            let workers: Vec<_> = (0..threads).map(|_| s.spawn(worker)).collect();
+            workers
+                .into_iter()
+                .filter_map(|w| w.join().expect("setup thread panicked"))
+                .collect()
+        });
+        // report the error for the first bank, as sequential setup would
+        match errors.into_iter().min_by_key(|(i, _)| *i) {
+            Some((_, e)) => Err(Error::IoError(e)),
+            None => Ok(()),
+        }
+    }
+
+    // The number of threads to use to set up the banks.
+    //
+    // Spawning threads costs more than it saves for small simulators, so they
+    // are set up sequentially.
+    fn setup_threads(&self) -> usize {
+        let ops: usize = self
+            .banks
+            .iter()
+            .map(|b| {
+                b.line_configs()
+                    .iter()
+                    .map(|(_, cfg)| {
+                        2 * cfg.name.is_some() as usize + 3 * cfg.hog.is_some() as usize
+                    })
+                    .sum::<usize>()
+                    + 3
+            })
+            .sum();
+        if ops < PARALLEL_SETUP_OPS {
+            return 1;
+        }
+        thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_SETUP_THREADS)
+    }
+
+    // Create the configfs directories and attributes for one bank.
+    fn setup_bank(&self, i: usize) -> io::Result<()> {
+        let write = |dir: &Path, attr: &str, data: &[u8]| self.fs.write(&dir.join(attr), data);
+        let b = &self.banks[i];
+        let bank_dir = self.dir.join(format!("bank{}", i));
+        self.fs.create_dir(&bank_dir)?;
+        write(&bank_dir, "label", b.label.as_bytes())?;
+        write(&bank_dir, "num_lines", b.num_lines.to_string().as_bytes())?;
+
+        // each line directory is created once, for both its name and hog,
+        // rather than probing for it
+        for (offset, cfg) in b.line_configs() {
+            if cfg.name.is_none() && cfg.hog.is_none() {
+                continue;
+            }
+            let line_dir = bank_dir.join(format!("line{}", offset));
+            self.fs.create_dir(&line_dir)?;
+            if let Some(name) = &cfg.name {
+                write(&line_dir, "name", name.as_bytes())?;
+            }
+            if let Some(hog) = &cfg.hog {
+                let hog_dir = line_dir.join("hog");
+                self.fs.create_dir(&hog_dir)?;
+                write(&hog_dir, "name", hog.consumer.as_bytes())?;
+                write(&hog_dir, "direction", hog.direction.as_str().as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_attrs(&mut self) -> Result<()> {
+        self.dev_name = self.read_attr(&self.dir, "dev_name")?;
+        for i in 0..self.banks.len() {
+            let bank_dir = self.dir.join(format!("bank{}", i));
+            let chip_name = self.read_attr(&bank_dir, "chip_name")?;
+            let mut sysfs_path = PathBuf::from("/sys/devices/platform");
+            sysfs_path.push(&self.dev_name);
+            sysfs_path.push(&chip_name);
+            let dev_path = self.resolve_dev_path(&chip_name, &sysfs_path)?;
+            let sysfs_dir = self.fs.open_dir(&sysfs_path)?;
+            self.chips.push(KernelChip {
+                chip_name,
+                dev_path,
+                sysfs_path,
+                sysfs_dir,
+            });
+        }
+        Ok(())
+    }
+
+    // The path to a chip in /dev.
+    //
+    // A symlink in place of the device would redirect users of the dev_path
+    // to some other chip, so is rejected, unless resolving symlinks and the
+    // target is the device of the chip.
+    fn resolve_dev_path(&self, chip_name: &str, sysfs_path: &Path) -> Result<PathBuf> {
+        let symlink = Path::new("/dev").join(chip_name);
+        let target = match self.fs.read_link(&symlink) {
+            Ok(target) => symlink.parent().unwrap().join(target),
+            Err(_) => return Ok(symlink),
+        };
+        if self.resolve_symlinks {
+            let dev = self.read_attr(sysfs_path, "dev")?;
+            if let Ok((major, minor)) = self.fs.device_number(&target) {
+                if dev == format!("{}:{}", major, minor) {
+                    return Ok(target);
+                }
+            }
+        }
+        Err(Error::DeviceMasked {
+            symlink,
+            chip: chip_name.into(),
+        })
+    }
+
+    // Read the configuration of the banks of an existing gpio-sim.
+    fn read_banks(&mut self) -> Result<()> {
+        let mut indices: Vec<usize> = self
+            .fs
+            .list_dir(&self.dir)?
+            .iter()
+            .filter_map(|n| n.strip_prefix("bank")?.parse().ok())
+            .collect();
+        indices.sort_unstable();
+        if indices.iter().enumerate().any(|(i, &n)| i != n) {
+            return Err(Error::UnexpectedValue(format!(
+                "banks of {} are not numbered sequentially",
+                self.dir.display()
+            )));
+        }
+        for i in indices {
+            let bank_dir = self.dir.join(format!("bank{}", i));
+            let num_lines = self.read_attr(&bank_dir, "num_lines")?;
+            let num_lines = num_lines
+                .parse()
+                .map_err(|_| Error::UnexpectedValue(num_lines))?;
+            let mut bank = Bank::new(num_lines, self.read_attr(&bank_dir, "label")?);
+            for entry in self.fs.list_dir(&bank_dir)? {
+                let offset = match entry.strip_prefix("line").and_then(|o| o.parse().ok()) {
+                    Some(offset) => offset,
+                    None => continue,
+                };
+                let line_dir = bank_dir.join(&entry);
+                let name = self.read_attr(&line_dir, "name").unwrap_or_default();
+                if !name.is_empty() {
+                    bank.name(offset, name);
+                }
+                let hog_dir = line_dir.join("hog");
+                if self.fs.exists(&hog_dir) {
+                    let direction = self.read_attr(&hog_dir, "direction")?;
+                    let direction = match direction.as_str() {
+                        "input" => Direction::Input,
+                        "output-high" => Direction::OutputHigh,
+                        "output-low" => Direction::OutputLow,
+                        _ => return Err(Error::UnexpectedValue(direction)),
+                    };
+                    bank.hog(offset, self.read_attr(&hog_dir, "name")?, direction);
+                }
+            }
+            self.banks.push(bank);
+        }
+        Ok(())
+    }
+
+    // Helper to write to simulator configuration files.
+    fn write_attr<D: AsRef<[u8]>>(&self, p: &Path, file: &str, data: D) -> Result<()> {
+        let path = p.join(file);
+        self.fs.write(&path, data.as_ref()).map_err(Error::IoError)
+    }
+
+    // Helper to read from simulator attribute files.
+    fn read_attr(&self, p: &Path, file: &str) -> Result<String> {
+        let path = p.join(file);
+        self.fs
+            .read_to_string(&path)
+            .map(|s| s.trim().to_string())
+            .map_err(Error::IoError)
+    }
+
+    fn get_attr(&self, bank: usize, offset: Offset, attr: &str) -> Result<String> {
+        let path = format!("sim_gpio{}/{}", offset, attr);
+        self.chips[bank]
+            .sysfs_dir
+            .read_to_string(&path)
+            .map(|s| s.trim().to_string())
+            .map_err(|e| self.sysfs_error(e))
+    }
+
+    // Return SimRemoved if a sysfs access failed as the sim has been removed.
+    fn sysfs_error(&self, e: io::Error) -> Error {
+        if self.is_alive() {
+            Error::IoError(e)
+        } else {
+            Error::SimRemoved
+        }
+    }
+}
+
+impl Device for KernelDevice {
+    fn dev_name(&self) -> &str {
+        &self.dev_name
+    }
+
+    fn chip_name(&self, bank: usize) -> &str {
+        &self.chips[bank].chip_name
+    }
+
+    fn dev_path(&self, bank: usize) -> PathBuf {
+        self.chips[bank].dev_path.clone()
+    }
+
+    fn sysfs_path(&self, bank: usize) -> Option<&Path> {
+        Some(&self.chips[bank].sysfs_path)
+    }
+
+    fn configfs_path(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+
+    fn aggregate(&self, lines: &[(&Chip, Offset)]) -> Result<Aggregator> {
+        Aggregator::create(self.fs.clone(), lines)
+    }
+
+    fn set_pull(&self, bank: usize, offset: Offset, pull: Level) -> Result<()> {
+        let path = format!("sim_gpio{}/pull", offset);
+        self.chips[bank]
+            .sysfs_dir
+            .write(&path, pull_value(pull).as_bytes())
+            .map_err(|e| self.sysfs_error(e))
+    }
+
+    fn set_pulls(&self, bank: usize, pulls: &[(Offset, Level)]) -> Result<()> {
+        let paths: Vec<String> = pulls
+            .iter()
+            .map(|(offset, _)| format!("sim_gpio{}/pull", offset))
+            .collect();
+        let writes: Vec<(&str, &[u8])> = paths
+            .iter()
+            .zip(pulls)
+            .map(|(path, &(_, pull))| (path.as_str(), pull_value(pull).as_bytes()))
+            .collect();
+        self.chips[bank]
+            .sysfs_dir
+            .write_batch(&writes)
+            .map_err(|e| self.sysfs_error(e))
+    }
+
+    fn get_pull(&self, bank: usize, offset: Offset) -> Result<Level> {
+        let pull = self.get_attr(bank, offset, "pull")?;
+        match pull.as_str() {
+            "pull-down" => Ok(Level::Low),
+            "pull-up" => Ok(Level::High),
+            _ => Err(Error::UnexpectedValue(pull)),
+        }
+    }
+
+    fn get_level(&self, bank: usize, offset: Offset) -> Result<Level> {
+        let val = self.get_attr(bank, offset, "value")?;
+        match val.as_str() {
+            "0" => Ok(Level::Low),
+            "1" => Ok(Level::High),
+            _ => Err(Error::UnexpectedValue(val)),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        !self.removed.load(Ordering::Relaxed)
+            && self
+                .read_attr(&self.dir, "live")
+                .map_or(false, |live| live == "1")
+    }
+
+    fn release(&self) {
+        self.owned.store(false, Ordering::Relaxed);
+    }
+
+    fn remove(&self) {
+        self.cleanup_configfs();
+    }
+}
+
+impl Drop for KernelDevice {
+    fn drop(&mut self) {
+        self.cleanup_configfs();
+    }
+}
+
+// The value of the pull attribute for a pull.
+fn pull_value(pull: Level) -> &'static str {
+    match pull {
+        Level::Low => "pull-down",
+        Level::High => "pull-up",
+    }
+}
+
+fn configfs_mountpoint() -> Option<PathBuf> {
+    if let Ok(f) = File::open("/proc/mounts") {
+        let r = BufReader::new(f);
+        for line in r.lines().map_while(|x| x.ok()) {
+            let words: Vec<&str> = line.split_ascii_whitespace().collect();
+            if words.len() >= 6 && words[2] == "configfs" {
+                return Some(PathBuf::from(words[1]));
+            }
+        }
+    }
+    None
+}
+
+// check if configfs is mounted, and if so where.
+fn find_configfs(fs: &dyn Fs) -> Result<PathBuf> {
+    find_module_configfs(fs, "gpio-sim")
+}
+
+// Find the configfs directory of a kernel module, loading the module if
+// necessary.
+pub(crate) fn find_module_configfs(fs: &dyn Fs, module: &str) -> Result<PathBuf> {
+    // Assume default location for starters
+    let path = Path::new("/sys/kernel/config").join(module);
+    if fs.exists(&path) {
+        return Ok(path);
+    }
+    // Perhaps the module is not loaded - so load it
+    load_module(module)?;
+    for _ in 0..10 {
+        if fs.exists(&path) {
+            return Ok(path);
+        }
+        // Loading the module should mount configfs, but maybe it isn't in the
+        // standard location, so check mounts...
+        if let Some(mut cfgfs) = configfs_mountpoint() {
+            cfgfs.push(module);
+            if fs.exists(&cfgfs) {
+                return Ok(cfgfs);
+            }
+        }
+        sleep(Duration::from_millis(100));
+    }
+    Err(Error::ConfigfsNotFound)
+}
+
+// Load a kernel module.
+pub(crate) fn load_module(module: &str) -> Result<()> {
+    modprobe(&[module])
+}
+
+// Run modprobe with the arguments.
+pub(crate) fn modprobe<S: AsRef<std::ffi::OsStr>>(args: &[S]) -> Result<()> {
+    let output = process::Command::new("modprobe")
+        .args(args)
+        .output()
+        .map_err(|e| Error::CommandError("modprobe".into(), Box::new(e)))?;
+    if !output.status.success() {
+        return Err(Error::ModuleLoadError(OsString::from_vec(output.stderr)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Direction, Memory};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::io;
+    use std::sync::Mutex;
+
+    // A filesystem that emulates the gpio-sim configfs and sysfs interfaces,
+    // and records the operations performed on it.
+    #[derive(Clone, Debug, Default)]
+    struct FakeFs {
+        state: Arc<Mutex<FakeState>>,
+    }
+
+    #[derive(Debug, Default)]
+    struct FakeState {
+        dirs: BTreeSet<PathBuf>,
+        files: BTreeMap<PathBuf, String>,
+        ops: Vec<String>,
+        fail: Option<PathBuf>,
+        links: BTreeMap<PathBuf, PathBuf>,
+        devices: BTreeMap<PathBuf, (u32, u32)>,
+    }
+
+    impl FakeFs {
+        fn new() -> Arc<FakeFs> {
+            let fs = FakeFs::default();
+            fs.state.lock().unwrap().dirs.insert(CONFIGFS_DIR.into());
+            Arc::new(fs)
+        }
+
+        fn fail_on(&self, path: &str) {
+            self.state.lock().unwrap().fail = Some(path.into());
+        }
+
+        fn ops(&self) -> Vec<String> {
+            self.state.lock().unwrap().ops.clone()
+        }
+
+        fn clear_ops(&self) {
+            self.state.lock().unwrap().ops.clear();
+        }
+
+        fn set_file(&self, path: &str, value: &str) {
+            let mut state = self.state.lock().unwrap();
+            state.files.insert(path.into(), value.into());
+        }
+    }
+
+    impl FakeState {
+        fn check(&self, path: &Path) -> io::Result<()> {
+            if self.fail.as_deref() == Some(path) {
+                return Err(io::Error::from_raw_os_error(22));
+            }
+            Ok(())
+        }
+
+        // Emulate the kernel creating the device when the sim goes live.
+        fn go_live(&mut self, dir: &Path) {
+            self.files.insert(dir.join("dev_name"), "gpio-sim.0".into());
+            let platform = PathBuf::from("/sys/devices/platform/gpio-sim.0");
+            let mut bank = 0;
+            while self.dirs.contains(&dir.join(format!("bank{}", bank))) {
+                let bank_dir = dir.join(format!("bank{}", bank));
+                let chip_name = format!("gpiochip{}", bank);
+                let num_lines: u32 = self.files[&bank_dir.join("num_lines")].parse().unwrap();
+                let chip_dir = platform.join(&chip_name);
+                for offset in 0..num_lines {
+                    let line_dir = chip_dir.join(format!("sim_gpio{}", offset));
+                    self.files.insert(line_dir.join("pull"), "pull-down".into());
+                    self.files.insert(line_dir.join("value"), "0".into());
+                }
+                self.files
+                    .insert(chip_dir.join("dev"), format!("254:{}", bank));
+                self.dirs.insert(chip_dir);
+                self.files.insert(bank_dir.join("chip_name"), chip_name);
+                bank += 1;
+            }
+        }
+    }
+
+    impl Fs for FakeFs {
+        fn create_dir(&self, path: &Path) -> io::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.ops.push(format!("mkdir {}", path.display()));
+            state.check(path)?;
+            if !state.dirs.insert(path.into()) {
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+            Ok(())
+        }
+
+        fn remove_dir(&self, path: &Path) -> io::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.ops.push(format!("rmdir {}", path.display()));
+            state.check(path)?;
+            if state.dirs.iter().any(|d| d.parent() == Some(path)) {
+                return Err(io::Error::from_raw_os_error(39));
+            }
+            state.files.retain(|f, _| f.parent() != Some(path));
+            if !state.dirs.remove(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            Ok(())
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            let state = self.state.lock().unwrap();
+            state.dirs.contains(path) || state.files.contains_key(path)
+        }
+
+        fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            let data = String::from_utf8_lossy(data).to_string();
+            state.ops.push(format!("write {} {}", path.display(), data));
+            state.check(path)?;
+            // sysfs attributes cannot be created
+            if path.starts_with("/sys/devices") && !state.files.contains_key(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            if path.file_name() == Some("live".as_ref()) && data == "1" {
+                state.go_live(path.parent().unwrap());
+            }
+            // emulate the gpio-aggregator driver
+            let agg_dir = Path::new("/sys/bus/platform/devices/gpio-aggregator.0");
+            if path.ends_with("gpio-aggregator/new_device") {
+                state.dirs.insert(agg_dir.into());
+                state.dirs.insert(agg_dir.join("gpiochip9"));
+            } else if path.ends_with("gpio-aggregator/delete_device") {
+                state.dirs.retain(|d| !d.starts_with(agg_dir));
+            }
+            state.files.insert(path.into(), data);
+            Ok(())
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            let mut state = self.state.lock().unwrap();
+            state.ops.push(format!("read {}", path.display()));
+            state.check(path)?;
+            match state.files.get(path) {
+                Some(data) => Ok(data.clone()),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+            let state = self.state.lock().unwrap();
+            if !state.dirs.contains(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let entries = state
+                .dirs
+                .iter()
+                .chain(state.files.keys())
+                .filter(|e| e.parent() == Some(path))
+                .map(|e| e.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            Ok(entries)
+        }
+
+        fn open_dir(&self, path: &Path) -> io::Result<Box<dyn FsDir>> {
+            if !self.exists(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            Ok(Box::new(FakeDir {
+                fs: self.clone(),
+                dir: path.into(),
+            }))
+        }
+
+        fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+            let state = self.state.lock().unwrap();
+            match state.links.get(path) {
+                Some(target) => Ok(target.clone()),
+                None => Err(io::ErrorKind::InvalidInput.into()),
+            }
+        }
+
+        fn device_number(&self, path: &Path) -> io::Result<(u32, u32)> {
+            let state = self.state.lock().unwrap();
+            let path = match state.links.get(path) {
+                Some(target) => Path::new("/dev").join(target),
+                None => path.into(),
+            };
+            match state.devices.get(&path) {
+                Some(&dev) => Ok(dev),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct FakeDir {
+        fs: FakeFs,
+        dir: PathBuf,
+    }
+
+    impl FsDir for FakeDir {
+        fn write(&self, path: &str, data: &[u8]) -> io::Result<()> {
+            Fs::write(&self.fs, &self.dir.join(path), data)
+        }
+
+        fn read_to_string(&self, path: &str) -> io::Result<String> {
+            Fs::read_to_string(&self.fs, &self.dir.join(path))
+        }
+    }
+
+    fn kernel(fs: &Arc<FakeFs>) -> Kernel {
+        Kernel::with_fs(fs.clone())
+    }
+
+    #[test]
+    fn live() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(Bank::new(4, "fruit").name(1, "banana").hog(
+                2,
+                "hogster",
+                Direction::OutputHigh,
+            ))
+            .live()
+            .unwrap();
+        assert_eq!(
+            fs.ops(),
+            [
+                "mkdir /sys/kernel/config/gpio-sim/sim",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/label fruit",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/num_lines 4",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0/line1",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/line1/name banana",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0/line2",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0/line2/hog",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/line2/hog/name hogster",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/line2/hog/direction output-high",
+                "write /sys/kernel/config/gpio-sim/sim/live 1",
+                "read /sys/kernel/config/gpio-sim/sim/dev_name",
+                "read /sys/kernel/config/gpio-sim/sim/bank0/chip_name",
+            ]
+        );
+        let c = &sim.chips()[0];
+        assert_eq!(c.dev_name, "gpio-sim.0");
+        assert_eq!(c.chip_name, "gpiochip0");
+        assert_eq!(c.dev_path(), Path::new("/dev/gpiochip0"));
+        assert_eq!(
+            c.sysfs_path(),
+            Some(Path::new("/sys/devices/platform/gpio-sim.0/gpiochip0"))
+        );
+        assert_eq!(
+            sim.configfs_path(),
+            Some(Path::new("/sys/kernel/config/gpio-sim/sim"))
+        );
+    }
+
+    #[test]
+    fn cleanup_order() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(
+                Bank::new(4, "fruit")
+                    .name(1, "banana")
+                    .hog(2, "hogster", Direction::Input),
+            )
+            .with_bank(&Bank::new(4, "vegetable"))
+            .live()
+            .unwrap();
+        fs.clear_ops();
+        drop(sim);
+        assert_eq!(
+            fs.ops(),
+            [
+                "write /sys/kernel/config/gpio-sim/sim/live 0",
+                "rmdir /sys/kernel/config/gpio-sim/sim/bank0/line2/hog",
+                "rmdir /sys/kernel/config/gpio-sim/sim/bank0/line2",
+                "rmdir /sys/kernel/config/gpio-sim/sim/bank0/line1",
+                "rmdir /sys/kernel/config/gpio-sim/sim/bank0",
+                "rmdir /sys/kernel/config/gpio-sim/sim/bank1",
+                "rmdir /sys/kernel/config/gpio-sim/sim",
+            ]
+        );
+        assert!(!fs.exists(Path::new("/sys/kernel/config/gpio-sim/sim")));
+    }
+
+    #[test]
+    fn cleanup_gives_up() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        // the drop must not block if the simulator cannot be removed
+        fs.fail_on("/sys/kernel/config/gpio-sim/sim");
+        drop(sim);
+        assert!(fs.exists(Path::new("/sys/kernel/config/gpio-sim/sim")));
+    }
+
+    #[test]
+    fn cleanup_aggregators_first() {
+        let fs = FakeFs::new();
+        {
+            let mut state = fs.state.lock().unwrap();
+            state
+                .dirs
+                .insert("/sys/bus/platform/drivers/gpio-aggregator".into());
+            state.dirs.insert("/sys/bus/platform/devices".into());
+        }
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        let agg = sim.aggregate(&[(&sim.chips()[0], 1)]).unwrap();
+        assert_eq!(agg.chip_name(), "gpiochip9");
+        drop(agg);
+
+        // chips of other simulators are rejected
+        let other = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        assert!(matches!(
+            sim.aggregate(&[(&other.chips()[0], 1)]),
+            Err(Error::InvalidConfig(_))
+        ));
+        // and aggregators require the Kernel backend
+        assert!(matches!(
+            other.aggregate(&[(&other.chips()[0], 1)]),
+            Err(Error::InvalidConfig(_))
+        ));
+
+        fs.clear_ops();
+        drop(sim);
+        let ops = fs.ops();
+        let delete = ops
+            .iter()
+            .position(|op| {
+                op == "write /sys/bus/platform/drivers/gpio-aggregator/delete_device gpio-aggregator.0"
+            })
+            .unwrap();
+        let offline = ops
+            .iter()
+            .position(|op| op == "write /sys/kernel/config/gpio-sim/sim/live 0")
+            .unwrap();
+        assert!(delete < offline);
+    }
+
+    #[test]
+    fn cleanup_on_failed_setup() {
+        let fs = FakeFs::new();
+        fs.fail_on("/sys/kernel/config/gpio-sim/sim/bank0/line1/name");
+        let res = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(Bank::new(4, "fruit").name(1, "banana"))
+            .live();
+        match res {
+            Err(Error::IoError(e)) => assert_eq!(e.raw_os_error(), Some(22)),
+            _ => panic!("unexpected result {:?}", res),
+        }
+        assert!(!fs.exists(Path::new("/sys/kernel/config/gpio-sim/sim")));
+    }
+
+    #[test]
+    fn masked_device() {
+        let fs = FakeFs::new();
+        fs.state.lock().unwrap().links.insert(
+            PathBuf::from("/dev/gpiochip1"),
+            PathBuf::from("/dev/gpiochip7"),
+        );
+        let res = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live();
+        match res {
+            Err(Error::DeviceMasked { symlink, chip }) => {
+                assert_eq!(symlink, Path::new("/dev/gpiochip1"));
+                assert_eq!(chip, "gpiochip1");
+            }
+            _ => panic!("unexpected result {:?}", res),
+        }
+        assert!(!fs.exists(Path::new("/sys/kernel/config/gpio-sim/sim")));
+
+        // a symlink to some other chip is rejected when resolving
+        fs.state
+            .lock()
+            .unwrap()
+            .devices
+            .insert(PathBuf::from("/dev/gpiochip7"), (254, 7));
+        let res = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs).resolve_symlinks(true))
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live();
+        assert!(matches!(res, Err(Error::DeviceMasked { .. })));
+
+        // a symlink to the chip is resolved
+        fs.state
+            .lock()
+            .unwrap()
+            .devices
+            .insert(PathBuf::from("/dev/gpiochip7"), (254, 1));
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs).resolve_symlinks(true))
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live()
+            .unwrap();
+        assert_eq!(sim.chips()[0].dev_path(), Path::new("/dev/gpiochip0"));
+        assert_eq!(sim.chips()[1].dev_path(), Path::new("/dev/gpiochip7"));
+    }
+
+    #[test]
+    fn parallel_setup() {
+        let banks: Vec<Bank> = (0..16)
+            .map(|i| {
+                let mut bank = Bank::new(8, format!("bank{}", i));
+                for offset in 0..8 {
+                    bank.name(offset, format!("line{}-{}", i, offset));
+                }
+                bank.hog(3, "hogster", Direction::OutputLow);
+                bank
+            })
+            .collect();
+        let setup = |fs: &Arc<FakeFs>| {
+            let dir = Path::new(CONFIGFS_DIR).join("sim");
+            fs.create_dir(&dir).unwrap();
+            let dev = KernelDevice {
+                fs: fs.clone(),
+                dir,
+                banks: banks.clone(),
+                dev_name: String::new(),
+                chips: Vec::new(),
+                removed: AtomicBool::new(false),
+                owned: AtomicBool::new(true),
+                resolve_symlinks: false,
+            };
+            // more threads than the test host may have cores
+            let res = dev.setup_configfs(4);
+            (res, fs.ops())
+        };
+        let fs = FakeFs::new();
+        let (res, mut ops) = setup(&fs);
+        res.unwrap();
+        ops.sort();
+        let mut planned: Vec<String> = plan("sim", &banks)
+            .into_iter()
+            .filter_map(|op| match op {
+                ConfigOp::CreateDir(p) => Some(format!("mkdir {}", p.display())),
+                ConfigOp::Write(p, v) if !p.ends_with("live") => {
+                    Some(format!("write {} {}", p.display(), v))
+                }
+                _ => None,
+            })
+            .collect();
+        planned.sort();
+        assert_eq!(ops, planned);
+
+        // the device is cleaned up as for sequential setup
+        let fs = FakeFs::new();
+        fs.fail_on("/sys/kernel/config/gpio-sim/sim/bank9/line3/name");
+        match setup(&fs).0 {
+            Err(Error::IoError(e)) => assert_eq!(e.raw_os_error(), Some(22)),
+            res => panic!("unexpected result {:?}", res),
+        }
+        assert!(!fs.exists(Path::new("/sys/kernel/config/gpio-sim/sim")));
+    }
+
+    #[test]
+    fn existing_name() {
+        let fs = FakeFs::new();
+        let mut builder = builder();
+        builder
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"));
+        let s1 = builder.live();
+        assert!(s1.is_ok());
+        let s2 = builder.live();
+        assert!(matches!(s2, Err(Error::SimulatorExists(n)) if n == "sim"));
+    }
+
+    #[test]
+    fn default_name() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        assert!(sim.name().contains(&format!("-p{}-", std::process::id())));
+        let dir = Path::new(CONFIGFS_DIR).join(sim.name());
+        assert!(fs.exists(&dir));
+    }
+
+    #[test]
+    fn logger() {
+        let fs = FakeFs::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let l = log.clone();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs).with_logger(move |op| l.lock().unwrap().push(op.to_string())))
+            .with_bank(Bank::new(4, "fruit").name(1, "banana"))
+            .live()
+            .unwrap();
+        assert_eq!(
+            log.lock().unwrap().as_slice(),
+            [
+                "mkdir /sys/kernel/config/gpio-sim/sim",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/label \"fruit\"",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/num_lines \"4\"",
+                "mkdir /sys/kernel/config/gpio-sim/sim/bank0/line1",
+                "write /sys/kernel/config/gpio-sim/sim/bank0/line1/name \"banana\"",
+                "write /sys/kernel/config/gpio-sim/sim/live \"1\"",
+                "read /sys/kernel/config/gpio-sim/sim/dev_name \"gpio-sim.0\"",
+                "read /sys/kernel/config/gpio-sim/sim/bank0/chip_name \"gpiochip0\"",
+            ]
+        );
+        log.lock().unwrap().clear();
+        let c = &sim.chips()[0];
+        c.pullup(2).unwrap();
+        assert!(c.get_pull(4).is_err());
+        assert_eq!(
+            log.lock().unwrap().as_slice(),
+            [
+                "write /sys/devices/platform/gpio-sim.0/gpiochip0/sim_gpio2/pull \"pull-up\"",
+                "read /sys/devices/platform/gpio-sim.0/gpiochip0/sim_gpio4/pull failed: entity not found",
+                // the failure prompts a check that the sim is still live
+                "read /sys/kernel/config/gpio-sim/sim/live \"1\"",
+            ]
+        );
+        log.lock().unwrap().clear();
+        drop(sim);
+        assert_eq!(
+            log.lock().unwrap()[0],
+            "write /sys/kernel/config/gpio-sim/sim/live \"0\""
+        );
+        assert_eq!(
+            log.lock().unwrap().last().unwrap(),
+            "rmdir /sys/kernel/config/gpio-sim/sim"
+        );
+    }
+
+    #[test]
+    fn plan_ops() {
+        let ops = plan(
+            "sim",
+            &[Bank::new(4, "fruit")
+                .name(1, "banana")
+                .hog(1, "hogster", Direction::OutputLow)
+                .clone()],
+        );
+        let dir = Path::new(CONFIGFS_DIR).join("sim");
+        let bank_dir = dir.join("bank0");
+        let line_dir = bank_dir.join("line1");
+        assert_eq!(
+            ops,
+            [
+                ConfigOp::CreateDir(dir.clone()),
+                ConfigOp::CreateDir(bank_dir.clone()),
+                ConfigOp::Write(bank_dir.join("label"), "fruit".into()),
+                ConfigOp::Write(bank_dir.join("num_lines"), "4".into()),
+                ConfigOp::CreateDir(line_dir.clone()),
+                ConfigOp::Write(line_dir.join("name"), "banana".into()),
+                ConfigOp::CreateDir(line_dir.join("hog")),
+                ConfigOp::Write(line_dir.join("hog/name"), "hogster".into()),
+                ConfigOp::Write(line_dir.join("hog/direction"), "output-low".into()),
+                ConfigOp::Write(dir.join("live"), "1".into()),
+            ]
+        );
+        assert_eq!(ops[0].to_string(), "mkdir /sys/kernel/config/gpio-sim/sim");
+        assert_eq!(
+            ops[2].to_string(),
+            "echo \"fruit\" > /sys/kernel/config/gpio-sim/sim/bank0/label"
+        );
+    }
+
+    #[test]
+    fn line_attrs() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(c.get_pull(1).unwrap(), Level::Low);
+        c.pullup(1).unwrap();
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+        assert_eq!(c.get_level(1).unwrap(), Level::Low);
+
+        let line_dir = "/sys/devices/platform/gpio-sim.0/gpiochip0/sim_gpio1";
+        fs.set_file(&format!("{}/value", line_dir), "1\n");
+        assert_eq!(c.get_level(1).unwrap(), Level::High);
+        fs.set_file(&format!("{}/value", line_dir), "high");
+        assert!(matches!(c.get_level(1), Err(Error::UnexpectedValue(v)) if v == "high"));
+        fs.set_file(&format!("{}/pull", line_dir), "pull-sideways");
+        assert!(matches!(c.get_pull(1), Err(Error::UnexpectedValue(v)) if v == "pull-sideways"));
+        assert!(matches!(c.get_pull(4), Err(Error::IoError(_))));
+    }
+
+    #[test]
+    fn removed_externally() {
+        let fs = FakeFs::new();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&Bank::new(4, "fruit"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert!(sim.is_alive());
+        c.pullup(1).unwrap();
+        // emulate another process taking the sim offline
+        {
+            let mut state = fs.state.lock().unwrap();
+            state
+                .files
+                .retain(|f, _| !f.starts_with("/sys/devices/platform/gpio-sim.0"));
+        }
+        fs.set_file("/sys/kernel/config/gpio-sim/sim/live", "0");
+        assert!(!sim.is_alive());
+        assert!(matches!(c.pullup(1), Err(Error::SimRemoved)));
+        assert!(matches!(c.get_level(1), Err(Error::SimRemoved)));
+    }
+
+    #[test]
+    fn attach() {
+        let fs = FakeFs::new();
+        let bank = Bank::new(4, "fruit")
+            .name(1, "banana")
+            .hog(2, "hogster", Direction::OutputHigh)
+            .hog(3, "piggly", Direction::Input)
+            .clone();
+        let sim = builder()
+            .with_name("sim")
+            .with_backend(kernel(&fs))
+            .with_bank(&bank)
+            .with_bank(&Bank::new(2, "veg"))
+            .live()
+            .unwrap();
+        let k = kernel(&fs);
+        assert_eq!(k.sims().unwrap(), ["sim"]);
+        assert!(k.attach("nosim").is_err());
+
+        let attached = k.attach("sim").unwrap();
+        assert_eq!(attached.name(), "sim");
+        assert_eq!(attached.chips().len(), 2);
+        let c = &attached.chips()[0];
+        assert_eq!(c.config(), &bank);
+        assert_eq!(c.chip_name, "gpiochip0");
+        assert_eq!(attached.chips()[1].config(), &Bank::new(2, "veg"));
+        c.pullup(1).unwrap();
+        assert_eq!(sim.chips()[0].get_pull(1).unwrap(), Level::High);
+
+        // dropping the attached sim leaves the sim in place
+        fs.clear_ops();
+        drop(attached);
+        assert!(fs.ops().is_empty());
+        assert!(sim.is_alive());
+
+        fs.set_file("/sys/kernel/config/gpio-sim/sim/live", "0");
+        assert!(k.attach("sim").is_err());
+    }
+}
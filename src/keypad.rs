@@ -0,0 +1,199 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Error, Level, Offset, Result};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// An emulator of a matrix keypad scanned by the consumer.
+///
+/// The consumer drives the row lines as outputs and reads the column lines as
+/// inputs.
+/// The columns are pulled high, and a column is pulled low while a row
+/// connected to it by a pressed key is driven low.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Keypad};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(8, "keys")).live()?;
+/// let keypad = Keypad::new(&sim.chips()[0], &[0, 1, 2, 3], &[4, 5, 6, 7])?;
+/// keypad.press(1, 2)?;
+///
+/// // ... consumer scans the keypad and finds the key at row 1, column 2 ...
+///
+/// keypad.release(1, 2)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Keypad {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct State {
+    rows: Vec<Offset>,
+
+    cols: Vec<Offset>,
+
+    /// The most recently sampled levels of the rows.
+    row_levels: Vec<Level>,
+
+    /// The pressed keys, as (row, column).
+    pressed: BTreeSet<(usize, usize)>,
+}
+
+impl State {
+    // Pull the columns to reflect the pressed keys and row levels.
+    fn update(&self, chip: &Chip) -> Result<()> {
+        for (c, &col) in self.cols.iter().enumerate() {
+            let connected = self
+                .pressed
+                .iter()
+                .any(|&(r, pc)| pc == c && self.row_levels[r] == Level::Low);
+            let pull = if connected { Level::Low } else { Level::High };
+            chip.set_pull(col, pull)?;
+        }
+        Ok(())
+    }
+}
+
+impl Keypad {
+    /// Start emulating a keypad with the row and column lines.
+    ///
+    /// Initially no keys are pressed.
+    pub fn new(chip: &Chip, rows: &[Offset], cols: &[Offset]) -> Result<Keypad> {
+        let row_levels = rows
+            .iter()
+            .map(|&r| chip.dev.get_level(chip.bank, r))
+            .collect::<Result<Vec<Level>>>()?;
+        let state = Arc::new(Mutex::new(State {
+            rows: rows.to_vec(),
+            cols: cols.to_vec(),
+            row_levels,
+            pressed: BTreeSet::new(),
+        }));
+        state.lock().unwrap().update(chip)?;
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, rows, move |chip, _, curr| {
+                let mut state = state.lock().unwrap();
+                state.row_levels = curr.to_vec();
+                _ = state.update(chip);
+            })?
+        };
+        Ok(Keypad {
+            chip: chip.share(),
+            state,
+            monitor,
+        })
+    }
+
+    /// Press the key at the row and column.
+    ///
+    /// The row and column are indices into the row and column lines.
+    pub fn press(&self, row: usize, col: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        check_key(&state, row, col)?;
+        state.pressed.insert((row, col));
+        state.update(&self.chip)
+    }
+
+    /// Release the key at the row and column.
+    pub fn release(&self, row: usize, col: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        check_key(&state, row, col)?;
+        state.pressed.remove(&(row, col));
+        state.update(&self.chip)
+    }
+
+    /// Release all the keys.
+    pub fn release_all(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.pressed.clear();
+        state.update(&self.chip)
+    }
+
+    /// The keys currently pressed, as (row, column).
+    pub fn pressed(&self) -> Vec<(usize, usize)> {
+        self.state.lock().unwrap().pressed.iter().copied().collect()
+    }
+
+    /// Wait until the current state of the rows has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+fn check_key(state: &State, row: usize, col: usize) -> Result<()> {
+    if row >= state.rows.len() || col >= state.cols.len() {
+        return Err(Error::InvalidConfig(format!(
+            "key ({}, {}) is outside the {}x{} keypad",
+            row,
+            col,
+            state.rows.len(),
+            state.cols.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn scan() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(7, "keys"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let rows = [0, 1, 2, 3];
+        let cols = [4, 5, 6];
+        for r in rows {
+            mem.drive(c, r, Some(Level::High)).unwrap();
+        }
+        let keypad = Keypad::new(c, &rows, &cols).unwrap();
+        // scan the rows, returning the keys found
+        let scan = || {
+            let mut keys = Vec::new();
+            for (r, &row) in rows.iter().enumerate() {
+                mem.drive(c, row, Some(Level::Low)).unwrap();
+                keypad.sync();
+                for (col, &offset) in cols.iter().enumerate() {
+                    if c.get_level(offset).unwrap() == Level::Low {
+                        keys.push((r, col));
+                    }
+                }
+                mem.drive(c, row, Some(Level::High)).unwrap();
+                keypad.sync();
+            }
+            keys
+        };
+        assert!(scan().is_empty());
+        keypad.press(1, 2).unwrap();
+        keypad.press(3, 0).unwrap();
+        assert_eq!(keypad.pressed(), [(1, 2), (3, 0)]);
+        assert_eq!(scan(), [(1, 2), (3, 0)]);
+        // columns are idle high between scans
+        assert!(cols.iter().all(|&o| c.get_level(o).unwrap() == Level::High));
+        keypad.release(1, 2).unwrap();
+        assert_eq!(scan(), [(3, 0)]);
+        keypad.release_all().unwrap();
+        assert!(scan().is_empty());
+        assert!(keypad.press(4, 0).is_err());
+        assert!(keypad.press(0, 3).is_err());
+    }
+}
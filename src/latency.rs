@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Measurement of the latency from setting a pull to the corresponding edge
+//! event being reported via the GPIO uAPI, e.g. to quantify the overhead of
+//! the simulator compared to real hardware.
+//!
+//! Each sample toggles the pull on a line requested with edge detection, using
+//! [`gpiocdev`], and measures both the time until the kernel timestamps the
+//! event, and the time until the event is read from userspace.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::latency::Bench;
+//! use gpiosim::Simpleton;
+//!
+//! let s = Simpleton::new(1);
+//! let report = Bench::new(s.chip(), 0).with_iterations(1000).run()?;
+//! println!("{}", report);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::timing::monotonic_now;
+use crate::{Chip, Error, Offset, Result, Summary};
+use gpiocdev::line::{EdgeDetection, EventClock};
+use gpiocdev::request::Request;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// A benchmark of the pull to edge event latency on a line.
+#[derive(Debug)]
+pub struct Bench<'a> {
+    chip: &'a Chip,
+    offset: Offset,
+
+    /// The number of samples to take.
+    iterations: usize,
+
+    /// How long to wait for each event before counting it as missed.
+    timeout: Duration,
+}
+
+impl<'a> Bench<'a> {
+    /// Create a benchmark for the line.
+    ///
+    /// By default the benchmark takes 100 samples, and waits up to 100ms for
+    /// each event.
+    pub fn new(chip: &'a Chip, offset: Offset) -> Bench<'a> {
+        Bench {
+            chip,
+            offset,
+            iterations: 100,
+            timeout: Duration::from_millis(100),
+        }
+    }
+
+    /// Set the number of samples to take.
+    pub fn with_iterations(&mut self, iterations: usize) -> &mut Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set how long to wait for each event before counting it as missed.
+    pub fn with_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the benchmark.
+    ///
+    /// The line is requested as an input for the duration of the benchmark,
+    /// and its pull is left as it was found.
+    pub fn run(&self) -> Result<Report> {
+        crate::bus::check_offset(self.chip, self.offset)?;
+        let req = Request::builder()
+            .on_chip(self.chip.dev_path())
+            .with_consumer("gpiosim-latency")
+            .with_line(self.offset)
+            .as_input()
+            .with_edge_detection(EdgeDetection::BothEdges)
+            .with_event_clock(EventClock::Monotonic)
+            .request()
+            .map_err(uapi)?;
+        let initial = self.chip.get_pull(self.offset)?;
+        let mut pull = initial;
+        let mut report = Report::default();
+        for _ in 0..self.iterations {
+            pull = pull.toggle();
+            let start = now_ns()?;
+            self.chip.set_pull(self.offset, pull)?;
+            if !req.wait_edge_event(self.timeout).map_err(uapi)? {
+                report.missed += 1;
+                continue;
+            }
+            let event = req.read_edge_event().map_err(uapi)?;
+            let read = now_ns()?;
+            report.event.push(Duration::from_nanos(
+                event.timestamp_ns.saturating_sub(start),
+            ));
+            report.read.push(Duration::from_nanos(read - start));
+        }
+        if pull != initial {
+            self.chip.set_pull(self.offset, initial)?;
+        }
+        Ok(report)
+    }
+}
+
+fn uapi(e: gpiocdev::Error) -> Error {
+    Error::IoError(io::Error::new(io::ErrorKind::Other, e))
+}
+
+fn now_ns() -> Result<u64> {
+    let ts = monotonic_now().ok_or_else(io::Error::last_os_error)?;
+    Ok(ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+}
+
+/// The latencies measured by a [`Bench`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    /// The latency from setting the pull to the event timestamp, for each
+    /// sample.
+    pub event: Vec<Duration>,
+
+    /// The latency from setting the pull to the event being read, for each
+    /// sample.
+    pub read: Vec<Duration>,
+
+    /// The number of events not received within the timeout.
+    pub missed: usize,
+}
+
+impl Report {
+    /// Summary statistics of the latency to the event timestamp.
+    pub fn event_summary(&self) -> Summary {
+        Summary::new(&self.event)
+    }
+
+    /// Summary statistics of the latency to the event being read.
+    pub fn read_summary(&self) -> Summary {
+        Summary::new(&self.read)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "samples {} missed {}", self.event.len(), self.missed)?;
+        writeln!(f, "event {}", self.event_summary())?;
+        write!(f, "read  {}", self.read_summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let us = Duration::from_micros;
+        assert_eq!(Summary::new(&[]), Summary::default());
+        let s = Summary::new(&(1..=100).rev().map(us).collect::<Vec<_>>());
+        assert_eq!(s.min, us(1));
+        assert_eq!(s.median, us(50));
+        assert_eq!(s.p99, us(99));
+        assert_eq!(s.max, us(100));
+        assert_eq!(s.mean, Duration::from_nanos(50_500));
+        let s = Summary::new(&[us(7)]);
+        assert_eq!(
+            (s.min, s.median, s.p99, s.max),
+            (us(7), us(7), us(7), us(7))
+        );
+
+        let report = Report {
+            event: vec![us(2), us(4)],
+            read: vec![us(5), us(7)],
+            missed: 1,
+        };
+        assert_eq!(report.read_summary().mean, us(6));
+        assert_eq!(
+            report.to_string(),
+            "samples 2 missed 1\n\
+            event min 2µs mean 3µs median 2µs p99 4µs max 4µs\n\
+            read  min 5µs mean 6µs median 5µs p99 7µs max 7µs"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Level, Offset, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The behavior of an LED over the observation window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LedState {
+    /// The LED is lit, and has not blinked within the window.
+    On,
+
+    /// The LED is unlit, and has not blinked within the window.
+    Off,
+
+    /// The LED has been repeatedly lit within the window.
+    Blinking {
+        /// The mean time between the LED being lit.
+        period: Duration,
+
+        /// The mean fraction of each period the LED is lit.
+        duty: f64,
+    },
+}
+
+/// A tracker for an LED driven by the consumer.
+///
+/// The tracker records when the LED is lit and unlit over an observation
+/// window, 2 seconds by default, and classifies the behavior of the LED over
+/// the window.
+/// The LED is active high by default.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Led, LedState};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(1, "status")).live()?;
+/// let led = Led::new(&sim.chips()[0], 0)?;
+///
+/// // ... consumer flags an error by blinking the LED twice ...
+///
+/// led.sync();
+/// assert_eq!(led.blinks(), 2);
+/// assert!(matches!(led.state(), LedState::Blinking { .. }));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Led {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The level of the line when the LED is lit.
+    active: Level,
+
+    /// The period over which the behavior is classified.
+    window: Duration,
+
+    /// The current level of the line.
+    level: Level,
+
+    /// The times of the edges within the window, and the level after each.
+    edges: VecDeque<(Instant, Level)>,
+}
+
+impl State {
+    fn prune(&mut self, now: Instant) {
+        if let Some(since) = now.checked_sub(self.window) {
+            while self.edges.front().map_or(false, |e| e.0 < since) {
+                self.edges.pop_front();
+            }
+        }
+    }
+
+    fn is_lit(&self) -> bool {
+        self.level == self.active
+    }
+
+    // The times the LED was lit within the window.
+    fn lit_times(&self) -> impl Iterator<Item = (usize, Instant)> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(move |(_, e)| e.1 == self.active)
+            .map(|(i, e)| (i, e.0))
+    }
+
+    fn classify(&self) -> LedState {
+        let lit: Vec<(usize, Instant)> = self.lit_times().collect();
+        if lit.len() < 2 {
+            return if self.is_lit() {
+                LedState::On
+            } else {
+                LedState::Off
+            };
+        }
+        let cycles = lit.len() - 1;
+        let span = lit[cycles].1 - lit[0].1;
+        let on: Duration = lit[..cycles]
+            .iter()
+            .map(|&(i, t)| self.edges[i + 1].0 - t)
+            .sum();
+        LedState::Blinking {
+            period: span / cycles as u32,
+            duty: on.as_secs_f64() / span.as_secs_f64(),
+        }
+    }
+}
+
+impl Led {
+    /// Start tracking the LED on the line.
+    pub fn new(chip: &Chip, offset: Offset) -> Result<Led> {
+        let level = chip.dev.get_level(chip.bank, offset)?;
+        let state = Arc::new(Mutex::new(State {
+            active: Level::High,
+            window: Duration::from_secs(2),
+            level,
+            edges: VecDeque::new(),
+        }));
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &[offset], move |_, _, curr| {
+                let now = Instant::now();
+                let mut state = state.lock().unwrap();
+                state.level = curr[0];
+                state.edges.push_back((now, curr[0]));
+                state.prune(now);
+            })?
+        };
+        Ok(Led { state, monitor })
+    }
+
+    /// Set the level of the line when the LED is lit.
+    pub fn with_active(&mut self, active: Level) -> &mut Self {
+        self.state.lock().unwrap().active = active;
+        self
+    }
+
+    /// Set the period over which the behavior of the LED is classified.
+    pub fn with_window(&mut self, window: Duration) -> &mut Self {
+        self.state.lock().unwrap().window = window;
+        self
+    }
+
+    /// Returns true if the LED is currently lit.
+    pub fn is_lit(&self) -> bool {
+        self.state.lock().unwrap().is_lit()
+    }
+
+    /// The number of times the LED has been lit within the window.
+    pub fn blinks(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.prune(Instant::now());
+        state.lit_times().count()
+    }
+
+    /// Classify the behavior of the LED over the window.
+    ///
+    /// The LED is blinking if it has been lit at least twice within the
+    /// window, with the period and duty cycle averaged over the complete
+    /// cycles.
+    pub fn state(&self) -> LedState {
+        let mut state = self.state.lock().unwrap();
+        state.prune(Instant::now());
+        state.classify()
+    }
+
+    /// Wait until the current state of the line has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    #[test]
+    fn classify() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut state = State {
+            active: Level::Low,
+            window: Duration::from_secs(1),
+            level: Level::High,
+            edges: VecDeque::new(),
+        };
+        assert_eq!(state.classify(), LedState::Off);
+        state.edges.push_back((ms(0), Level::Low));
+        state.level = Level::Low;
+        assert_eq!(state.classify(), LedState::On);
+        for t in [100, 400, 500, 800, 900] {
+            state.level = state.level.toggle();
+            state.edges.push_back((ms(t), state.level));
+        }
+        assert_eq!(
+            state.classify(),
+            LedState::Blinking {
+                period: Duration::from_millis(400),
+                duty: 0.25
+            }
+        );
+        state.prune(ms(1750));
+        assert_eq!(state.edges.len(), 2);
+        assert_eq!(state.classify(), LedState::Off);
+    }
+
+    #[test]
+    fn blink() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(1, "status"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let led = Led::new(c, 0).unwrap();
+        assert_eq!(led.state(), LedState::Off);
+        let set = |level| {
+            mem.drive(c, 0, Some(level)).unwrap();
+            led.sync();
+        };
+        set(Level::High);
+        assert!(led.is_lit());
+        assert_eq!(led.state(), LedState::On);
+        for _ in 0..2 {
+            thread::sleep(Duration::from_millis(10));
+            set(Level::Low);
+            thread::sleep(Duration::from_millis(10));
+            set(Level::High);
+        }
+        set(Level::Low);
+        assert_eq!(led.blinks(), 3);
+        match led.state() {
+            LedState::Blinking { period, duty } => {
+                assert!(period >= Duration::from_millis(20));
+                assert!(duty > 0.0 && duty < 1.0);
+            }
+            s => panic!("unexpected state {:?}", s),
+        }
+    }
+}
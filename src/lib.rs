@@ -22,8 +22,18 @@
 //! For simple tests that only require lines on a single chip, the [`Simpleton`]
 //! provides a simplified interface.
 //!
+//! For test suites that would otherwise create many identical simulators,
+//! a [`SimPool`] leases simulators to tests and reuses them.
+//!
+//! For loopback tests, a [`Wiring`] harness connects consumer outputs to
+//! inputs, possibly on different chips.
+//!
 //! Configuring a simulator involves *configfs*, and manipulating the chips once live
 //! involves *sysfs*, so root permissions are typically required to run a simulator.
+//! Tests that do not require the kernel side may instead use the [`Memory`] backend,
+//! which requires neither root permissions nor the gpio-sim module.
+//! Kernels prior to 5.19, which lack gpio-sim, fall back to the [`Mockup`] backend
+//! provided by the older gpio-mockup module.
 //!
 //! ## Example Usage
 //!
@@ -79,35 +89,196 @@
 //! [`Chip.set_pull`]: struct.Chip.html#method.set_pull
 //! [`Chip.get_level`]: struct.Chip.html#method.get_level
 
-use cap_std::fs::Dir;
+mod aggregator;
+#[cfg(feature = "asserts")]
+pub mod asserts;
+mod backend;
+mod bounce;
+mod bus;
+mod button;
+mod charlieplex;
+mod chunk;
+mod concurrency;
+mod describe;
+pub mod dht;
+mod dot;
+mod encoder;
+mod fds;
+mod fixture;
+mod fs;
+mod fuzz;
+mod guard;
+mod heartbeat;
+mod history;
+mod host_lock;
+pub mod i2c;
+pub mod ir;
+mod kernel;
+mod keypad;
+#[cfg(feature = "latency")]
+pub mod latency;
+mod led;
+mod limits;
+mod line;
+mod memory;
+#[cfg(feature = "prometheus")]
+pub mod metrics;
+mod mockup;
+mod monitor;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod onewire;
+mod play;
+mod pool;
+pub mod ps2;
+mod random;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod segment;
+mod sequence;
+mod shift;
+mod snapshot;
+mod soak;
+mod sonar;
+#[macro_use]
+mod span;
+pub mod spi;
+mod state;
+mod stepper;
+mod storm;
+#[cfg(feature = "strategies")]
+pub mod strategy;
+mod stress;
+mod summary;
+mod timing;
+pub mod trace;
+mod transaction;
+pub mod uart;
+#[cfg(feature = "io-uring")]
+mod uring;
+pub mod virtuser;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+mod wiring;
+pub use aggregator::Aggregator;
+pub use backend::{Backend, Device};
+pub use bounce::Bounce;
+pub use bus::{Bus, Latch};
+pub use button::Button;
+pub use charlieplex::Charlieplex;
+pub use chunk::ChunkedChip;
+pub use concurrency::{max_concurrent_sims, set_concurrent_sims_timeout, set_max_concurrent_sims};
+pub use encoder::Encoder;
+pub use fds::{fd_stats, set_fd_limit, FdStats};
+pub use fixture::{Fixture, FixtureBuilder, FixtureLine};
+pub use fs::{FsOp, FsOpKind};
+pub use fuzz::{FuzzEvent, Fuzzer};
+pub use guard::PullGuard;
+pub use heartbeat::Heartbeat;
+pub use history::PullRecord;
+pub use host_lock::host_lock_path;
+pub use kernel::{ConfigOp, Kernel};
+pub use keypad::Keypad;
+pub use led::{Led, LedState};
+pub use limits::KernelLimits;
+pub use line::{LineBuilder, LineConfig};
+pub use memory::Memory;
+pub use mockup::Mockup;
+pub use monitor::Edge;
+pub use play::Playback;
+pub use pool::{Lease, SimPool};
+pub use random::{Distribution, Jitter};
+pub use segment::SevenSegment;
+pub use sequence::{Coding, Sequence};
+pub use shift::{Hc165, Hc595};
+pub use snapshot::PullSnapshot;
+pub use soak::{Soak, SoakReport, SoakSample};
+pub use sonar::HcSr04;
+pub use state::{ChipState, SimState};
+pub use stepper::StepDir;
+pub use storm::{Storm, StormReport};
+pub use stress::{Stress, StressReport};
+pub use summary::Summary;
+pub use timing::{realtime_priority, request_realtime, set_realtime_priority};
+pub use transaction::Transaction;
+pub use wiring::{BusDriver, Logic, WiredAnd, Wiring};
+
+use aggregator::Aggregators;
+use bus::check_offset;
+use concurrency::SimPermit;
+use history::History;
+use host_lock::HostLock;
+
 use std::collections::HashMap;
 use std::env;
 use std::ffi::OsString;
-use std::fs::{self, File};
+use std::fmt;
 use std::hash::{BuildHasherDefault, Hasher};
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::os::unix::ffi::OsStringExt;
+use std::ops::{Deref, Not, Range, RangeInclusive};
 use std::path::{Path, PathBuf};
 use std::process;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::thread::sleep;
-use std::time::Duration;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// A live simulator of one or more chips.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug)]
 pub struct Sim {
+    /// The aggregators created over the lines of the simulator.
+    ///
+    /// These are removed before the device, as they hold lines of its chips.
+    aggregators: Aggregators,
+
     /// The name of the simulator in configfs and sysfs space.
     name: String,
 
     /// The details of the chips being simulated.
     chips: Vec<Chip>,
 
-    /// Path to the gpio-sim in configfs.
-    dir: PathBuf,
+    /// The live device provided by the backend.
+    dev: Arc<dyn Device>,
+
+    /// The reservation against the concurrent simulator limit, if the
+    /// simulator was taken live by the process.
+    _permit: Option<SimPermit>,
+
+    /// The host lock, if required by the builder.
+    _host_lock: Option<Arc<HostLock>>,
+
+    /// The layouts of the banks split across several chips.
+    layouts: Vec<chunk::Layout>,
 }
 
 impl Sim {
+    // Create the sim for the banks of a live device.
+    pub(crate) fn new(name: String, banks: &[Bank], dev: Arc<dyn Device>) -> Sim {
+        let chips = banks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| Chip {
+                cfg: b.clone(),
+                dev_path: dev.dev_path(i),
+                chip_name: dev.chip_name(i).into(),
+                dev_name: dev.dev_name().into(),
+                dev: dev.clone(),
+                bank: i,
+                stats: (0..b.num_lines).map(|_| Counters::default()).collect(),
+                history: Arc::default(),
+            })
+            .collect();
+        Sim {
+            aggregators: Aggregators::default(),
+            name,
+            chips,
+            dev,
+            _permit: None,
+            _host_lock: None,
+            layouts: Vec::new(),
+        }
+    }
+
     /// The details of the chips being simulated.
     pub fn chips(&self) -> &[Chip] {
         self.chips.as_slice()
@@ -118,91 +289,118 @@ impl Sim {
         &self.name
     }
 
-    fn live(&mut self) -> Result<()> {
-        self.setup_configfs()?;
-        write_attr(&self.dir, "live", "1")?;
-        self.read_attrs()
+    /// Find the chip simulating the bank with the label.
+    ///
+    /// If several banks share the label, the first is returned.
+    pub fn chip_by_label(&self, label: &str) -> Option<&Chip> {
+        self.chips.iter().find(|c| c.cfg.label == label)
     }
 
-    fn cleanup_configfs(&mut self) {
-        if !self.dir.exists() {
-            return;
+    /// Find the chips simulating the bank with the label, addressed by the
+    /// offsets within the bank.
+    ///
+    /// This provides access to banks split across several chips by
+    /// [`Builder::with_bank_chunking`], and to banks simulated by a single
+    /// chip, so callers need not know whether the bank was split.
+    ///
+    /// If several banks share the label, the first is returned.
+    pub fn chunked(&self, label: &str) -> Option<ChunkedChip> {
+        match self.layouts.iter().find(|l| l.label == label) {
+            Some(layout) => Some(ChunkedChip::new(layout, &self.chips)),
+            None => self.chip_by_label(label).map(ChunkedChip::single),
         }
-        let _ = write_attr(&self.dir, "live", "0");
-        for (i, c) in self.chips.iter().enumerate() {
-            let bank = format!("bank{}", i);
-            let bank_dir = self.dir.join(bank);
-            if !bank_dir.exists() {
-                continue;
-            }
-            for offset in c.cfg.hogs.keys() {
-                let line_dir = bank_dir.join(format!("line{}", offset));
-                let hog_dir = line_dir.join("hog");
-                let _ = fs::remove_dir(hog_dir);
-                let _ = fs::remove_dir(line_dir);
-            }
-            for offset in c.cfg.names.keys() {
-                let line_dir = bank_dir.join(format!("line{}", offset));
-                let _ = fs::remove_dir(line_dir);
+    }
+
+    /// Create a **gpio-aggregator** device presenting lines of the simulator
+    /// as a new gpiochip.
+    ///
+    /// The lines are identified by their chip and offset, and appear on the
+    /// aggregated chip in the order listed.
+    /// The lines are located by the label of their chip, so the labels must be
+    /// unique on the system, and must not contain whitespace.
+    ///
+    /// The device is removed before the simulator, or may be removed earlier
+    /// using [`Aggregator::remove`].
+    ///
+    /// The lines must be on chips of this simulator, and this requires the
+    /// [`Kernel`] backend, else [`Error::InvalidConfig`] is returned.
+    pub fn aggregate(&self, lines: &[(&Chip, Offset)]) -> Result<Arc<Aggregator>> {
+        for &(chip, _) in lines {
+            if self.chips.get(chip.bank) != Some(chip) {
+                return Err(Error::InvalidConfig(format!(
+                    "chip {} is not part of simulator {:?}",
+                    chip.chip_name, self.name
+                )));
             }
-            let _ = fs::remove_dir(bank_dir);
         }
-        let _ = fs::remove_dir(&self.dir);
-        while self.dir.exists() {}
+        let agg = Arc::new(self.dev.aggregate(lines)?);
+        self.aggregators.push(agg.clone());
+        Ok(agg)
     }
 
-    fn setup_configfs(&mut self) -> Result<()> {
-        for (i, c) in self.chips.iter().enumerate() {
-            let bank_dir = self.dir.join(format!("bank{}", i));
-            fs::create_dir(&bank_dir)?;
-            write_attr(&bank_dir, "label", c.cfg.label.as_bytes())?;
-            write_attr(&bank_dir, "num_lines", format!("{}", c.cfg.num_lines))?;
+    /// The chips simulating banks with labels starting with the prefix, in
+    /// bank order.
+    pub fn chips_by_label_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a Chip> {
+        self.chips
+            .iter()
+            .filter(move |c| c.cfg.label.starts_with(prefix))
+    }
 
-            for (offset, name) in &c.cfg.names {
-                let line_dir = bank_dir.join(format!("line{}", offset));
-                fs::create_dir(&line_dir)?;
-                write_attr(&line_dir, "name", name.as_bytes())?;
-            }
-            for (offset, hog) in &c.cfg.hogs {
-                let line_dir = bank_dir.join(format!("line{}", offset));
-                if !line_dir.exists() {
-                    fs::create_dir(&line_dir)?;
-                }
-                let hog_dir = line_dir.join("hog");
-                fs::create_dir(&hog_dir)?;
-                write_attr(&hog_dir, "name", hog.consumer.as_bytes())?;
-                write_attr(&hog_dir, "direction", hog.direction.as_str())?;
-            }
-        }
-        Ok(())
+    /// The path to the simulator in configfs, if provided by the backend.
+    ///
+    /// e.g. `/sys/kernel/config/gpio-sim/my_sim`
+    ///
+    /// The [`Memory`] backend has no configfs presence, so returns `None`.
+    pub fn configfs_path(&self) -> Option<&Path> {
+        self.dev.configfs_path()
     }
 
-    fn read_attrs(&mut self) -> Result<()> {
-        let dev_name = read_attr(&self.dir, "dev_name")?;
-        for (i, c) in self.chips.iter_mut().enumerate() {
-            let bank_dir = self.dir.join(format!("bank{}", i));
-            let chip_name = read_attr(&bank_dir, "chip_name")?;
-            c.dev_path = "/dev".into();
-            c.dev_path.push(&chip_name);
-            let mut sysfs_path = PathBuf::from("/sys/devices/platform");
-            sysfs_path.push(&dev_name);
-            sysfs_path.push(&chip_name);
-            c.sysfs_dir = Some(Dir::open_ambient_dir(
-                sysfs_path,
-                cap_std::ambient_authority(),
-            )?);
-            c.chip_name = chip_name;
-            c.dev_name.clone_from(&dev_name);
-        }
-        Ok(())
+    /// Returns true if the simulator is still live.
+    ///
+    /// Returns false if the simulator has been removed externally, e.g. by
+    /// another process or by the kernel module being unloaded, after which
+    /// chip operations return [`Error::SimRemoved`].
+    pub fn is_alive(&self) -> bool {
+        self.dev.is_alive()
+    }
+
+    /// Release the simulator, so it remains live after the `Sim` is dropped,
+    /// e.g. for use by other processes.
+    ///
+    /// A released kernel simulator must be removed by other means, such as
+    /// `examples/clean_sims.sh`.
+    pub fn detach(self) {
+        self.dev.release();
+    }
+
+    /// Capture the runtime state of the simulator.
+    pub fn state(&self) -> Result<SimState> {
+        SimState::capture(self)
+    }
+
+    /// Apply the pulls from a state to the simulator.
+    ///
+    /// The state must have the same number of chips, and lines on each chip,
+    /// as the simulator.
+    pub fn import_pulls(&self, state: &SimState) -> Result<()> {
+        state.import_pulls(self)
     }
 }
 
 impl Drop for Sim {
     fn drop(&mut self) {
-        self.cleanup_configfs();
+        // the aggregators hold lines of the chips, so must be removed first
+        self.aggregators.remove();
+        in_span!("remove", sim = self.name.as_str(); self.dev.remove());
+    }
+}
+
+impl PartialEq for Sim {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.chips == other.chips
     }
 }
+impl Eq for Sim {}
 
 /// A live simulated chip.
 #[derive(Debug)]
@@ -222,13 +420,20 @@ pub struct Chip {
     /// e.g. `gpio-sim.0`
     pub dev_name: String,
 
-    /// The path to the chip in /sys/device/platform.
-    ///
-    /// e.g. `/sys/devices/platform/gpio-sim.0`
-    sysfs_dir: Option<Dir>,
+    /// The live device containing the chip.
+    dev: Arc<dyn Device>,
+
+    /// The index of the chip within the device.
+    bank: usize,
 
     /// The configuration for the chip.
     cfg: Bank,
+
+    /// Counts of the operations performed on each line.
+    stats: Arc<[Counters]>,
+
+    /// The pulls applied to the lines, if recording.
+    history: Arc<History>,
 }
 
 impl Chip {
@@ -237,6 +442,22 @@ impl Chip {
         &self.cfg
     }
 
+    /// The offset, name and hog of every line of the chip, in offset order.
+    pub fn lines(&self) -> impl Iterator<Item = (Offset, Option<&str>, Option<&Hog>)> + '_ {
+        (0..self.cfg.num_lines).map(move |offset| {
+            let cfg = self.cfg.line_config(offset);
+            (offset, cfg.name.as_deref(), cfg.hog.as_ref())
+        })
+    }
+
+    /// The offset and name of the named lines of the chip, in offset order.
+    pub fn named_lines(&self) -> impl Iterator<Item = (Offset, &str)> + '_ {
+        self.cfg
+            .line_configs()
+            .into_iter()
+            .filter_map(|(offset, cfg)| Some((offset, cfg.name.as_deref()?)))
+    }
+
     /// The path to the chip in /dev
     ///
     /// e.g. `/dev/gpiopchip0`
@@ -244,18 +465,72 @@ impl Chip {
         &self.dev_path
     }
 
+    /// The path to the chip in sysfs, if provided by the backend.
+    ///
+    /// e.g. `/sys/devices/platform/gpio-sim.0/gpiochip0`
+    ///
+    /// The line attributes are in the `sim_gpio<offset>` subdirectories.
+    /// The [`Memory`] backend has no sysfs presence, so returns `None`.
+    pub fn sysfs_path(&self) -> Option<&Path> {
+        self.dev.sysfs_path(self.bank)
+    }
+
     /// Pull a line to simulate the line being externally driven.
     pub fn set_pull(&self, offset: Offset, pull: Level) -> Result<()> {
-        let value = match pull {
-            Level::Low => "pull-down",
-            Level::High => "pull-up",
-        };
-        let path = format!("sim_gpio{}/pull", offset);
-        self.sysfs_dir
-            .as_ref()
-            .unwrap()
-            .write(path, value)
-            .map_err(Error::IoError)
+        in_span!(
+            "set_pull",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            self.dev.set_pull(self.bank, offset, pull)
+        )?;
+        self.count(offset, |c| &c.pulls);
+        self.history.record(offset, pull);
+        Ok(())
+    }
+
+    /// Pull several lines, in order.
+    ///
+    /// All offsets are checked before any pull is applied.
+    /// Backends may apply the pulls as a batch, rather than individually, such
+    /// as the [`Kernel`] backend with the `io-uring` feature, which submits the
+    /// writes to sysfs with a single syscall.
+    /// Pulls applied before a failure remain applied.
+    pub fn set_pulls(&self, pulls: &[(Offset, Level)]) -> Result<()> {
+        for &(offset, _) in pulls {
+            check_offset(self, offset)?;
+        }
+        in_span!(
+            "set_pulls",
+            chip = self.chip_name.as_str(),
+            count = pulls.len();
+            self.dev.set_pulls(self.bank, pulls)
+        )?;
+        for &(offset, pull) in pulls {
+            self.count(offset, |c| &c.pulls);
+            self.history.record(offset, pull);
+        }
+        Ok(())
+    }
+
+    /// Temporarily pull a line, restoring the previous pull when the returned
+    /// guard is dropped.
+    pub fn pull_guard(&self, offset: Offset, pull: Level) -> Result<PullGuard<'_>> {
+        PullGuard::new(self, offset, pull)
+    }
+
+    /// The lines of the chip assigned the tag, in offset order.
+    pub fn lines_with_tag(&self, tag: &str) -> Vec<Offset> {
+        self.cfg.lines_with_tag(tag)
+    }
+
+    /// Capture the pulls of all the lines of the chip.
+    pub fn snapshot(&self) -> Result<PullSnapshot> {
+        PullSnapshot::capture(self)
+    }
+
+    /// Restore the pulls of all the lines of the chip from a snapshot.
+    pub fn restore(&self, snapshot: &PullSnapshot) -> Result<()> {
+        snapshot.restore(self)
     }
 
     /// Pull a line up to simulate the line being externally driven high.
@@ -270,43 +545,402 @@ impl Chip {
 
     /// Toggle the pull on a line.
     pub fn toggle(&self, offset: Offset) -> Result<Level> {
-        let value = match self.get_pull(offset)? {
-            Level::High => Level::Low,
-            Level::Low => Level::High,
-        };
-        self.set_pull(offset, value)?;
+        let value = in_span!(
+            "toggle",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            self.dev.get_pull(self.bank, offset).and_then(|pull| {
+                let value = pull.toggle();
+                self.dev.set_pull(self.bank, offset, value)?;
+                Ok(value)
+            })
+        )?;
+        self.count(offset, |c| &c.toggles);
+        self.history.record(offset, value);
         Ok(value)
     }
 
-    fn get_attr(&self, offset: Offset, attr: &str) -> Result<String> {
-        let path = format!("sim_gpio{}/{}", offset, attr);
-        self.sysfs_dir
-            .as_ref()
-            .unwrap()
-            .read_to_string(path)
-            .map(|s| s.trim().to_string())
-            .map_err(Error::IoError)
-    }
-
     /// Get the current state of the simulated external pull on a line.
     pub fn get_pull(&self, offset: Offset) -> Result<Level> {
-        let pull = self.get_attr(offset, "pull")?;
-        match pull.as_str() {
-            "pull-down" => Ok(Level::Low),
-            "pull-up" => Ok(Level::High),
-            _ => Err(Error::UnexpectedValue(pull)),
-        }
+        let pull = in_span!(
+            "get_pull",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            self.dev.get_pull(self.bank, offset)
+        )?;
+        self.count(offset, |c| &c.pull_reads);
+        Ok(pull)
     }
 
     /// Get the current output value for a simulated output line.
     pub fn get_level(&self, offset: Offset) -> Result<Level> {
-        let val = self.get_attr(offset, "value")?;
-        match val.as_str() {
-            "0" => Ok(Level::Low),
-            "1" => Ok(Level::High),
-            _ => Err(Error::UnexpectedValue(val)),
+        let level = in_span!(
+            "get_level",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            self.dev.get_level(self.bank, offset)
+        )?;
+        self.count(offset, |c| &c.level_reads);
+        Ok(level)
+    }
+
+    /// Pull a line, with contact bounce, to simulate a mechanical switch changing state.
+    ///
+    /// The line ends at the pull, after bouncing as per the profile.
+    pub fn set_pull_bouncy(&self, offset: Offset, pull: Level, bounce: &mut Bounce) -> Result<()> {
+        bounce.apply(self, offset, pull)
+    }
+
+    /// Pull a selection of lines to simulate the lines being externally driven.
+    ///
+    /// The lines are pulled in the order of the selection.
+    /// All offsets are checked before any pull is applied, as per [`set_pulls`].
+    ///
+    /// [`set_pulls`]: Chip::set_pulls
+    pub fn set_pull_lines<L: Into<Lines>>(&self, lines: L, pull: Level) -> Result<()> {
+        let pulls: Vec<(Offset, Level)> = lines
+            .into()
+            .offsets(&self.cfg)
+            .into_iter()
+            .map(|offset| (offset, pull))
+            .collect();
+        self.set_pulls(&pulls)
+    }
+
+    /// Get the current state of the simulated external pull on a selection of lines.
+    ///
+    /// Returns the offset and pull of each line, in the order of the selection.
+    pub fn get_pulls<L: Into<Lines>>(&self, lines: L) -> Result<Vec<(Offset, Level)>> {
+        lines
+            .into()
+            .offsets(&self.cfg)
+            .into_iter()
+            .map(|offset| Ok((offset, self.get_pull(offset)?)))
+            .collect()
+    }
+
+    /// Get the current level of a selection of lines.
+    ///
+    /// Returns the offset and level of each line, in the order of the selection.
+    pub fn get_levels<L: Into<Lines>>(&self, lines: L) -> Result<Vec<(Offset, Level)>> {
+        lines
+            .into()
+            .offsets(&self.cfg)
+            .into_iter()
+            .map(|offset| Ok((offset, self.get_level(offset)?)))
+            .collect()
+    }
+
+    /// Poll the level of a line until it satisfies the predicate.
+    ///
+    /// The level is read immediately, then at each interval until the
+    /// timeout, with a final read at the timeout, so a level reached just
+    /// before the timeout is not missed.
+    ///
+    /// Returns the time taken for the level to satisfy the predicate, or an
+    /// [`io::ErrorKind::TimedOut`] error if it does not within the timeout.
+    ///
+    /// ```no_run
+    /// # fn example(chip: &gpiosim::Chip) -> gpiosim::Result<()> {
+    /// use gpiosim::Level;
+    /// use std::time::Duration;
+    ///
+    /// let took = chip.poll_until(
+    ///     3,
+    ///     |level| level == Level::High,
+    ///     Duration::from_millis(1),
+    ///     Duration::from_millis(100),
+    /// )?;
+    /// println!("line 3 went high after {:?}", took);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`io::ErrorKind::TimedOut`]: std::io::ErrorKind::TimedOut
+    pub fn poll_until<F>(
+        &self,
+        offset: Offset,
+        mut pred: F,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Duration>
+    where
+        F: FnMut(Level) -> bool,
+    {
+        poll(|| Ok(pred(self.get_level(offset)?)), interval, timeout)
+    }
+
+    /// Poll the levels of a selection of lines until they satisfy the
+    /// predicate.
+    ///
+    /// The predicate is passed the offset and level of each line, in the
+    /// order of the selection, as per [`get_levels`].
+    /// The polling and result are as per [`poll_until`].
+    ///
+    /// [`get_levels`]: Chip::get_levels
+    /// [`poll_until`]: Chip::poll_until
+    pub fn poll_lines_until<L, F>(
+        &self,
+        lines: L,
+        mut pred: F,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Duration>
+    where
+        L: Into<Lines>,
+        F: FnMut(&[(Offset, Level)]) -> bool,
+    {
+        let offsets = lines.into().offsets(&self.cfg);
+        poll(
+            || {
+                let levels = offsets
+                    .iter()
+                    .map(|&offset| Ok((offset, self.get_level(offset)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(pred(&levels))
+            },
+            interval,
+            timeout,
+        )
+    }
+
+    /// Apply a sequence of pulls, in order and back-to-back.
+    ///
+    /// All offsets are checked before any pull is applied.
+    /// Use a [`Transaction`] to add delays between the pulls.
+    ///
+    /// This is equivalent to [`set_pulls`].
+    ///
+    /// [`set_pulls`]: Chip::set_pulls
+    pub fn apply(&self, pulls: &[(Offset, Level)]) -> Result<()> {
+        self.set_pulls(pulls)
+    }
+
+    /// Apply a sequence of timed transitions from a background thread.
+    ///
+    /// Each transition pulls a line after a delay from the previous
+    /// transition, or from the start for the first.
+    /// The delays are measured from the start of the sequence, so timing
+    /// errors do not accumulate.
+    ///
+    /// All offsets are checked before the playback starts.
+    /// The returned [`Playback`] can be waited on or cancelled, and cancels
+    /// the playback if dropped.
+    pub fn play(&self, transitions: &[(Offset, Level, Duration)]) -> Result<Playback> {
+        Playback::start(self, transitions)
+    }
+
+    /// Pull a group of lines to represent a binary value.
+    ///
+    /// The `order` determines whether the first offset holds the least or most
+    /// significant bit of the value.
+    /// Bits of the value beyond the width of the group are ignored.
+    ///
+    /// The group may contain at most 64 lines.
+    pub fn set_word(&self, offsets: &[Offset], value: u64, order: BitOrder) -> Result<()> {
+        check_word_width(offsets)?;
+        for (bit, offset) in order.bits(offsets) {
+            let pull = if value & (1 << bit) == 0 {
+                Level::Low
+            } else {
+                Level::High
+            };
+            self.set_pull(offset, pull)?;
+        }
+        Ok(())
+    }
+
+    /// Read the levels of a group of lines as a binary value.
+    ///
+    /// The `order` determines whether the first offset holds the least or most
+    /// significant bit of the value.
+    ///
+    /// The group may contain at most 64 lines.
+    pub fn get_word(&self, offsets: &[Offset], order: BitOrder) -> Result<u64> {
+        check_word_width(offsets)?;
+        let mut value = 0;
+        for (bit, offset) in order.bits(offsets) {
+            if self.get_level(offset)? == Level::High {
+                value |= 1 << bit;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Briefly toggle the pull on a line to simulate a glitch.
+    ///
+    /// The pull is restored once the width has elapsed, spinning rather than
+    /// sleeping so the glitch is as short as user space allows, e.g. to check
+    /// that a consumer debounce period filters glitches shorter than it.
+    ///
+    /// Returns the actual width of the glitch, which may be longer than
+    /// requested, particularly for widths of only a few microseconds.
+    pub fn glitch(&self, offset: Offset, width: Duration) -> Result<Duration> {
+        let pull = self.raw_pull(offset)?;
+        self.glitch_from(offset, pull, width)
+    }
+
+    /// Generate a train of glitches on a line.
+    ///
+    /// The glitches start at intervals of the period, and each is as per
+    /// [`glitch`].
+    ///
+    /// Returns the width of the longest glitch in the train.
+    ///
+    /// [`glitch`]: Chip::glitch
+    pub fn glitch_train(
+        &self,
+        offset: Offset,
+        width: Duration,
+        period: Duration,
+        count: usize,
+    ) -> Result<Duration> {
+        if period <= width {
+            return Err(Error::InvalidConfig(format!(
+                "glitch period {:?} must be longer than the width {:?}",
+                period, width
+            )));
+        }
+        let pull = self.raw_pull(offset)?;
+        let mut longest = Duration::ZERO;
+        let mut deadline = Instant::now();
+        for _ in 0..count {
+            timing::sleep_until(deadline);
+            longest = longest.max(self.glitch_from(offset, pull, width)?);
+            deadline += period;
+        }
+        Ok(longest)
+    }
+
+    // Read the pull on a line without counting the read.
+    fn raw_pull(&self, offset: Offset) -> Result<Level> {
+        in_span!(
+            "get_pull",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            self.dev.get_pull(self.bank, offset)
+        )
+    }
+
+    // Toggle the line from the pull and back again after the width.
+    fn glitch_from(&self, offset: Offset, pull: Level, width: Duration) -> Result<Duration> {
+        in_span!(
+            "glitch",
+            chip = self.chip_name.as_str(),
+            offset = offset;
+            {
+                self.dev.set_pull(self.bank, offset, pull.toggle())?;
+                let start = Instant::now();
+                self.count(offset, |c| &c.toggles);
+                self.history.record(offset, pull.toggle());
+                while start.elapsed() < width {
+                    std::hint::spin_loop();
+                }
+                self.dev.set_pull(self.bank, offset, pull)?;
+                let width = start.elapsed();
+                self.count(offset, |c| &c.toggles);
+                self.history.record(offset, pull);
+                Ok(width)
+            }
+        )
+    }
+
+    /// The counts of operations performed on each line of the chip via the
+    /// `Chip` API, indexed by offset.
+    ///
+    /// Only successful operations are counted.
+    pub fn stats(&self) -> Vec<LineStats> {
+        self.stats
+            .iter()
+            .map(|c| LineStats {
+                pulls: c.pulls.load(Ordering::Relaxed),
+                toggles: c.toggles.load(Ordering::Relaxed),
+                pull_reads: c.pull_reads.load(Ordering::Relaxed),
+                level_reads: c.level_reads.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Reset the counts of operations performed on the lines to zero.
+    pub fn reset_stats(&self) {
+        for c in self.stats.iter() {
+            c.pulls.store(0, Ordering::Relaxed);
+            c.toggles.store(0, Ordering::Relaxed);
+            c.pull_reads.store(0, Ordering::Relaxed);
+            c.level_reads.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Start or stop recording the pulls applied to the lines of the chip via
+    /// the `Chip` API.
+    ///
+    /// Starting recording clears any existing history, and timestamps are
+    /// relative to the start.
+    /// Stopping recording retains the history.
+    ///
+    /// The history includes pulls applied by generators and emulators using
+    /// the chip, so it can be dumped to show exactly what stimulus was
+    /// applied, and when, if a test fails.
+    pub fn record_pulls(&self, enable: bool) {
+        if enable {
+            self.history.start()
+        } else {
+            self.history.stop()
         }
     }
+
+    /// The pulls recorded since recording was last started, oldest first.
+    pub fn pull_history(&self) -> Vec<PullRecord> {
+        self.history.records()
+    }
+
+    /// Create another reference to the chip, sharing the live device and stats.
+    ///
+    /// Used by types, such as [`Bus`], that must operate on the chip without
+    /// borrowing it.
+    pub(crate) fn share(&self) -> Chip {
+        Chip {
+            dev_path: self.dev_path.clone(),
+            chip_name: self.chip_name.clone(),
+            dev_name: self.dev_name.clone(),
+            dev: self.dev.clone(),
+            bank: self.bank,
+            cfg: self.cfg.clone(),
+            stats: self.stats.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    fn count<F: Fn(&Counters) -> &AtomicU64>(&self, offset: Offset, counter: F) {
+        if let Some(c) = self.stats.get(offset as usize) {
+            counter(c).fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Counts of the operations performed on a line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LineStats {
+    /// The number of times the pull was set, including via [`Chip::pullup`]
+    /// and [`Chip::pulldown`].
+    pub pulls: u64,
+
+    /// The number of times the pull was toggled.
+    pub toggles: u64,
+
+    /// The number of times the pull was read.
+    pub pull_reads: u64,
+
+    /// The number of times the level was read.
+    pub level_reads: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    pulls: AtomicU64,
+    toggles: AtomicU64,
+    pull_reads: AtomicU64,
+    level_reads: AtomicU64,
 }
 impl PartialEq for Chip {
     fn eq(&self, other: &Self) -> bool {
@@ -315,6 +949,66 @@ impl PartialEq for Chip {
 }
 impl Eq for Chip {}
 
+/// A shareable handle to a live simulated chip.
+///
+/// The handle is `Clone`, `Send` and `Sync`, and is not borrowed from the
+/// [`Sim`], so it can be moved into other threads to drive lines of the chip
+/// concurrently.
+/// It provides the full [`Chip`] API, sharing the live device, including any
+/// open sysfs directory, stats and pull history with the chip.
+///
+/// The handle does not keep the simulator alive. Once the [`Sim`] is dropped
+/// operations on the handle return [`Error::SimRemoved`].
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::Bank;
+/// use std::thread;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "threads")).live()?;
+/// let workers: Vec<_> = (0..4)
+///     .map(|offset| {
+///         let chip = sim.chips()[0].handle();
+///         thread::spawn(move || chip.toggle(offset).is_ok())
+///     })
+///     .collect();
+/// for w in workers {
+///     assert!(w.join().unwrap());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ChipHandle(Chip);
+
+impl Chip {
+    /// Create a shareable handle to the chip.
+    pub fn handle(&self) -> ChipHandle {
+        ChipHandle(self.share())
+    }
+}
+
+impl Clone for ChipHandle {
+    fn clone(&self) -> Self {
+        ChipHandle(self.0.share())
+    }
+}
+
+impl Deref for ChipHandle {
+    type Target = Chip;
+
+    fn deref(&self) -> &Chip {
+        &self.0
+    }
+}
+
+impl PartialEq for ChipHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for ChipHandle {}
+
 /// Start building a GPIO simulator.
 pub fn builder() -> Builder {
     Builder::default()
@@ -336,14 +1030,27 @@ impl Simpleton {
     ///
     ///
     pub fn new(num_lines: u32) -> Simpleton {
-        Simpleton {
-            sim: builder()
-                .with_bank(&Bank::new(num_lines, "simpleton"))
-                .live()
-                .unwrap(),
+        Simpleton::builder(num_lines).live().unwrap()
+    }
+
+    /// Start building a customised single bank sim.
+    ///
+    /// For where a test requires a few named or hogged lines, but otherwise
+    /// the simplicity of the Simpleton.
+    pub fn builder(num_lines: u32) -> SimpletonBuilder {
+        SimpletonBuilder {
+            bank: Bank::new(num_lines, "simpleton"),
+            backend: None,
         }
     }
 
+    /// Convert the Simpleton into the underlying [`Sim`].
+    ///
+    /// For tests that outgrow the Simpleton interface.
+    pub fn into_sim(self) -> Sim {
+        self.sim
+    }
+
     /// Return the only chip simulated by the Simpleton.
     pub fn chip(&self) -> &Chip {
         &self.sim.chips[0]
@@ -388,6 +1095,104 @@ impl Simpleton {
     pub fn get_level(&self, offset: Offset) -> Result<Level> {
         self.sim.chips[0].get_level(offset)
     }
+
+    /// Pull a selection of lines to simulate the lines being externally driven.
+    ///
+    /// All offsets are checked before any pull is applied.
+    pub fn set_pull_lines<L: Into<Lines>>(&self, lines: L, pull: Level) -> Result<()> {
+        self.sim.chips[0].set_pull_lines(lines, pull)
+    }
+
+    /// Get the current state of the simulated external pull on a selection of lines.
+    pub fn get_pulls<L: Into<Lines>>(&self, lines: L) -> Result<Vec<(Offset, Level)>> {
+        self.sim.chips[0].get_pulls(lines)
+    }
+
+    /// Get the current level of a selection of lines.
+    pub fn get_levels<L: Into<Lines>>(&self, lines: L) -> Result<Vec<(Offset, Level)>> {
+        self.sim.chips[0].get_levels(lines)
+    }
+
+    /// Pull a group of lines to represent a binary value.
+    pub fn set_word(&self, offsets: &[Offset], value: u64, order: BitOrder) -> Result<()> {
+        self.sim.chips[0].set_word(offsets, value, order)
+    }
+
+    /// Read the levels of a group of lines as a binary value.
+    pub fn get_word(&self, offsets: &[Offset], order: BitOrder) -> Result<u64> {
+        self.sim.chips[0].get_word(offsets, order)
+    }
+
+    /// The counts of operations performed on each line, indexed by offset.
+    pub fn stats(&self) -> Vec<LineStats> {
+        self.sim.chips[0].stats()
+    }
+}
+
+/// A builder of [`Simpleton`]s.
+///
+/// Collects the configuration for the single bank, and then creates
+/// the Simpleton when taken live.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SimpletonBuilder {
+    /// The configuration of the bank to be simulated.
+    pub bank: Bank,
+
+    /// The backend providing the simulator.
+    backend: Option<SharedBackend>,
+}
+
+impl SimpletonBuilder {
+    /// Specify the backend providing the simulator.
+    ///
+    /// The default is the [`Kernel`] backend, or the [`Mockup`] backend on
+    /// kernels prior to 5.19.
+    pub fn with_backend<B: Backend + 'static>(&mut self, backend: B) -> &mut Self {
+        self.backend = Some(SharedBackend(Arc::new(backend)));
+        self
+    }
+
+    /// Set the label of the chip.
+    ///
+    /// The default label is `simpleton`.
+    pub fn label<N: Into<String>>(&mut self, label: N) -> &mut Self {
+        self.bank.label = label.into();
+        self
+    }
+
+    /// Assign a name to a line on the chip.
+    pub fn name<N: Into<String>>(&mut self, offset: Offset, name: N) -> &mut Self {
+        self.bank.name(offset, name);
+        self
+    }
+
+    /// Add a hog on a line on the chip.
+    ///
+    /// A "hog" simulates some other user holding the line.
+    pub fn hog<N: Into<String>>(
+        &mut self,
+        offset: Offset,
+        consumer: N,
+        direction: Direction,
+    ) -> &mut Self {
+        self.bank.hog(offset, consumer, direction);
+        self
+    }
+
+    /// Set the pull on a line on the chip when the Simpleton goes live.
+    pub fn pull(&mut self, offset: Offset, pull: Level) -> &mut Self {
+        self.bank.pull(offset, pull);
+        self
+    }
+
+    /// Take the Simpleton live.
+    pub fn live(&mut self) -> Result<Simpleton> {
+        let mut builder = builder();
+        builder.with_bank(&self.bank).backend = self.backend.clone();
+        Ok(Simpleton {
+            sim: builder.live()?,
+        })
+    }
 }
 
 /// A builder of simulators.
@@ -407,15 +1212,66 @@ pub struct Builder {
     ///
     /// Each bank becomes a chip when the simulator goes live.
     pub banks: Vec<Bank>,
-}
 
-impl Builder {
-    /// A convenience function to add a bank to the configuration.
+    /// The backend providing the simulator.
+    ///
+    /// If None when [`live`] is called then the [`Kernel`] backend is used,
+    /// or the [`Mockup`] backend on kernels prior to 5.19.
+    ///
+    /// [`live`]: Builder::live
+    backend: Option<SharedBackend>,
+
+    /// Set if the simulator holds the host lock while live.
+    host_lock: bool,
+
+    /// The maximum number of lines on a chip, if wider banks are to be split
+    /// across several chips.
+    chunk_lines: Option<u32>,
+}
+
+impl Builder {
+    /// A convenience function to specify the backend providing the simulator.
+    ///
+    /// The default is the [`Kernel`] backend, or the [`Mockup`] backend on
+    /// kernels prior to 5.19.
+    pub fn with_backend<B: Backend + 'static>(&mut self, backend: B) -> &mut Self {
+        self.backend = Some(SharedBackend(Arc::new(backend)));
+        self
+    }
+
+    /// A convenience function to add a bank to the configuration.
     pub fn with_bank(&mut self, bank: &Bank) -> &mut Self {
         self.banks.push(bank.clone());
         self
     }
 
+    /// A convenience function to add several banks to the configuration.
+    ///
+    /// e.g. `with_banks((0..20).map(|i| Bank::new(8, format!("chip{}", i))))`
+    pub fn with_banks<I: IntoIterator<Item = Bank>>(&mut self, banks: I) -> &mut Self {
+        self.banks.extend(banks);
+        self
+    }
+
+    /// A convenience function to add several identical banks to the
+    /// configuration.
+    ///
+    /// The banks are labelled with the prefix suffixed with their index, so
+    /// may be found using [`Sim::chips_by_label_prefix`].
+    ///
+    /// e.g. `with_chips(20, 8, "chip")` adds banks `chip0` to `chip19`, each
+    /// with 8 lines.
+    pub fn with_chips(
+        &mut self,
+        count: usize,
+        lines_per_chip: u32,
+        label_prefix: &str,
+    ) -> &mut Self {
+        self.with_banks(
+            (0..count).map(|i| Bank::new(lines_per_chip, format!("{}{}", label_prefix, i))),
+        )
+    }
+
     /// A convenience function to specify the name for the simulator.
     ///
     /// The name must be unique or going live will fail.
@@ -424,6 +1280,164 @@ impl Builder {
         self
     }
 
+    /// A convenience function to name the simulator after the current test.
+    ///
+    /// The name is generated by [`unique_name_for_test`] so any sims left behind
+    /// by a failing test can be traced back to that test.
+    pub fn with_test_name(&mut self) -> &mut Self {
+        self.name = Some(unique_name_for_test());
+        self
+    }
+
+    /// Hold an exclusive lock on the host while the simulator is live.
+    ///
+    /// Taking the simulator live waits until no other process holds the lock,
+    /// so test processes on the same machine can serialize their use of
+    /// gpio-sim, and so avoid interfering with each other via the kernel.
+    /// The lock is advisory, so only excludes processes that also request it.
+    ///
+    /// The lock is shared by all the simulators in the process that request
+    /// it, and is released when the last of them is dropped.
+    /// The lock file is located by [`host_lock_path`].
+    pub fn with_exclusive_host_lock(&mut self) -> &mut Self {
+        self.host_lock = true;
+        self
+    }
+
+    /// Split banks wider than max_lines across several chips when taken live.
+    ///
+    /// Each wider bank is simulated by chips of max_lines, as per
+    /// [`Bank::split`], and the lines of the bank remain addressable by their
+    /// offsets within the bank via [`Sim::chunked`].
+    /// This allows simulating banks that exceed the kernel limit on lines per
+    /// chip, [`KernelLimits::max_lines_per_chip`], such as port expanders with
+    /// thousands of logical lines.
+    ///
+    /// The chips are in [`Sim::chips`] in place of the bank, so subsequent chips
+    /// are shifted.
+    pub fn with_bank_chunking(&mut self, max_lines: u32) -> &mut Self {
+        self.chunk_lines = Some(max_lines);
+        self
+    }
+
+    /// Take the builder config live and return the created simulator as a [`Simpleton`].
+    ///
+    /// The config must contain exactly one bank.
+    pub fn simpleton(&mut self) -> Result<Simpleton> {
+        if self.banks.len() != 1 {
+            return Err(Error::InvalidConfig(format!(
+                "a Simpleton requires exactly one bank, found {}",
+                self.banks.len()
+            )));
+        }
+        Ok(Simpleton { sim: self.live()? })
+    }
+
+    /// Check that the configuration can be taken live.
+    ///
+    /// In addition to checking the configuration itself, the backend checks
+    /// it can simulate the banks, e.g. the [`Kernel`] checks them against the
+    /// [`KernelLimits`], so a configuration it would reject fails with a
+    /// specific error.
+    ///
+    /// This is performed by [`live`], so need only be called to check a
+    /// configuration in advance.
+    ///
+    /// [`live`]: Builder::live
+    pub fn validate(&self) -> Result<()> {
+        if let Some(name) = &self.name {
+            check_chars(name, || format!("simulator name {:?}", name))?;
+        }
+        self.banks.iter().try_for_each(check_offsets)?;
+        let (banks, _) = chunk::split_banks(&self.banks, self.chunk_lines);
+        self.validate_banks(&banks)
+    }
+
+    // Validate the banks as configured, so errors refer to their labels and
+    // offsets, then the split banks against the backend.
+    fn validate_banks(&self, split: &[Bank]) -> Result<()> {
+        for bank in &self.banks {
+            if bank.num_lines == 0 {
+                return Err(Error::InvalidConfig(format!(
+                    "bank {:?} has no lines",
+                    bank.label
+                )));
+            }
+            let chips = chunk::chunk_count(bank.num_lines, self.chunk_lines);
+            if chips > 1 {
+                // room for the ".<index>" suffix of the last chip
+                let suffix_len = format!(".{}", chips - 1).len();
+                if bank.label.len() + suffix_len > MAX_LABEL_LEN {
+                    return Err(Error::InvalidConfig(format!(
+                        "label {:?}, suffixed for the {} chips it is split across, \
+                        exceeds the {} byte limit on chip labels",
+                        bank.label, chips, MAX_LABEL_LEN
+                    )));
+                }
+            } else if bank.label.len() > MAX_LABEL_LEN {
+                return Err(Error::InvalidConfig(format!(
+                    "label {:?} exceeds the {} byte limit on chip labels",
+                    bank.label, MAX_LABEL_LEN
+                )));
+            }
+            check_chars(&bank.label, || format!("label {:?}", bank.label))?;
+            for (offset, cfg) in bank.line_configs() {
+                if let Some(name) = &cfg.name {
+                    if name.is_empty() {
+                        return Err(Error::InvalidConfig(format!(
+                            "line {} of bank {:?} has an empty name",
+                            offset, bank.label
+                        )));
+                    }
+                    check_chars(name, || {
+                        format!("name of line {} of bank {:?}", offset, bank.label)
+                    })?;
+                }
+                if let Some(hog) = &cfg.hog {
+                    check_chars(&hog.consumer, || {
+                        format!("hog consumer of line {} of bank {:?}", offset, bank.label)
+                    })?;
+                }
+            }
+        }
+        match &self.backend {
+            Some(b) => b.0.validate(split),
+            None => default_backend().validate(split),
+        }
+    }
+
+    /// Determine the configfs operations that [`live`] would perform, without
+    /// performing them.
+    ///
+    /// The operations are those of the [`Kernel`] backend, and are returned in
+    /// the order they would be performed.  Displaying the operations
+    /// produces the equivalent shell commands:
+    ///
+    /// ```
+    /// use gpiosim::Bank;
+    ///
+    /// for op in gpiosim::builder()
+    ///     .with_name("basic")
+    ///     .with_bank(Bank::new(8, "fruit").name(3, "banana"))
+    ///     .plan()
+    /// {
+    ///     println!("{}", op);
+    /// }
+    /// ```
+    ///
+    /// If no name has been provided for the builder then one is generated,
+    /// as per [`live`].
+    ///
+    /// [`live`]: Builder::live
+    pub fn plan(&self) -> Vec<ConfigOp> {
+        let name = match &self.name {
+            Some(n) => n.clone(),
+            None => default_name(),
+        };
+        let (banks, _) = chunk::split_banks(&self.banks, self.chunk_lines);
+        kernel::plan(&name, &banks)
+    }
+
     /// Take the builder config live and return the created simulator.
     ///
     /// If no name has been provided for the builder then one is generated
@@ -436,35 +1450,205 @@ impl Builder {
             Some(n) => n.clone(),
             None => default_name(),
         };
-        let sim_dir = find_configfs()?.join(&name);
-        if sim_dir.exists() {
-            return Err(Error::SimulatorExists(name));
+        check_chars(&name, || format!("simulator name {:?}", name))?;
+        self.banks.iter().try_for_each(check_offsets)?;
+        let (banks, layouts) = chunk::split_banks(&self.banks, self.chunk_lines);
+        self.validate_banks(&banks)?;
+        let permit = SimPermit::acquire()?;
+        let host_lock = if self.host_lock {
+            Some(HostLock::acquire()?)
+        } else {
+            None
+        };
+        let dev = in_span!(
+            "live",
+            sim = name.as_str();
+            match &self.backend {
+                Some(b) => b.0.live(&name, &banks),
+                None => default_backend().live(&name, &banks),
+            }
+        )?;
+        let mut sim = Sim::new(name, &banks, dev);
+        sim.layouts = layouts;
+        sim._permit = Some(permit);
+        sim._host_lock = host_lock;
+        // applied directly so the pulls are not counted in the stats
+        for c in &sim.chips {
+            for (offset, cfg) in c.cfg.line_configs() {
+                if let Some(pull) = cfg.pull {
+                    c.dev.set_pull(c.bank, offset, pull)?;
+                }
+            }
         }
-        fs::create_dir(&sim_dir)?;
+        Ok(sim)
+    }
+}
 
-        let mut sim = Sim {
-            name,
-            chips: Vec::new(),
-            dir: sim_dir,
+// Check that the lines configured in a bank are within the bank.
+//
+// This is checked prior to splitting, which ignores lines outside the bank.
+fn check_offsets(bank: &Bank) -> Result<()> {
+    let lines = bank.line_configs();
+    if let Some((offset, cfg)) = lines.iter().find(|(o, _)| *o >= bank.num_lines) {
+        let what = if cfg.name.is_some() {
+            "name"
+        } else if cfg.hog.is_some() {
+            "hog"
+        } else if cfg.pull.is_some() {
+            "pull"
+        } else {
+            "tag"
         };
-        for b in &self.banks {
-            sim.chips.push(Chip {
-                cfg: b.clone(),
-                dev_path: PathBuf::default(),
-                chip_name: String::default(),
-                dev_name: String::default(),
-                sysfs_dir: None,
-            })
-        }
-        sim.live()?;
+        return Err(Error::InvalidConfig(format!(
+            "{} on offset {} exceeds the {} lines of bank {:?}",
+            what, offset, bank.num_lines, bank.label
+        )));
+    }
+    Ok(())
+}
 
-        Ok(sim)
+// The backend used if none is specified.
+//
+// Kernels prior to 5.19 lack gpio-sim, so fall back to gpio-mockup.
+fn default_backend() -> Arc<dyn Backend> {
+    if Mockup::is_preferred() {
+        Arc::new(Mockup::default())
+    } else {
+        Arc::new(Kernel::default())
+    }
+}
+
+// A backend shared by builders.
+//
+// Backends are considered equal if they are the same instance.
+#[derive(Clone, Debug)]
+struct SharedBackend(Arc<dyn Backend>);
+
+impl PartialEq for SharedBackend {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
     }
 }
+impl Eq for SharedBackend {}
 
 /// The offset of a line on a chip.
 pub type Offset = u32;
 
+/// A selection of lines on a chip.
+///
+/// Explicit selections may be created from an offset, a slice, array or `Vec`
+/// of offsets, or a range of offsets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Lines {
+    /// All the lines on the chip, in offset order.
+    All,
+
+    /// The lines on the chip that have been assigned names, in offset order.
+    Named,
+
+    /// The lines on the chip that have been assigned the tag, in offset order.
+    Tagged(String),
+
+    /// The listed lines, in the listed order.
+    Offsets(Vec<Offset>),
+
+    /// The lines within the range.
+    Range(Range<Offset>),
+
+    /// The lines within the inclusive range.
+    RangeInclusive(RangeInclusive<Offset>),
+}
+
+impl Lines {
+    /// The offsets of the selected lines on a chip with the given configuration.
+    pub fn offsets(&self, bank: &Bank) -> Vec<Offset> {
+        match self {
+            Lines::All => (0..bank.num_lines).collect(),
+            Lines::Named => bank
+                .line_configs()
+                .into_iter()
+                .filter(|(_, cfg)| cfg.name.is_some())
+                .map(|(offset, _)| offset)
+                .collect(),
+            Lines::Tagged(tag) => bank.lines_with_tag(tag),
+            Lines::Offsets(offsets) => offsets.clone(),
+            Lines::Range(r) => r.clone().collect(),
+            Lines::RangeInclusive(r) => r.clone().collect(),
+        }
+    }
+}
+
+impl From<Offset> for Lines {
+    fn from(offset: Offset) -> Self {
+        Lines::Offsets(vec![offset])
+    }
+}
+
+impl From<&[Offset]> for Lines {
+    fn from(offsets: &[Offset]) -> Self {
+        Lines::Offsets(offsets.to_vec())
+    }
+}
+
+impl<const N: usize> From<[Offset; N]> for Lines {
+    fn from(offsets: [Offset; N]) -> Self {
+        Lines::Offsets(offsets.to_vec())
+    }
+}
+
+impl From<Vec<Offset>> for Lines {
+    fn from(offsets: Vec<Offset>) -> Self {
+        Lines::Offsets(offsets)
+    }
+}
+
+impl From<Range<Offset>> for Lines {
+    fn from(r: Range<Offset>) -> Self {
+        Lines::Range(r)
+    }
+}
+
+impl From<RangeInclusive<Offset>> for Lines {
+    fn from(r: RangeInclusive<Offset>) -> Self {
+        Lines::RangeInclusive(r)
+    }
+}
+
+/// The order of the bits of a word mapped onto a group of lines.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BitOrder {
+    /// The first line holds the least significant bit.
+    #[default]
+    LsbFirst,
+
+    /// The first line holds the most significant bit.
+    MsbFirst,
+}
+
+impl BitOrder {
+    /// Pair each offset with the bit of the word it holds.
+    pub(crate) fn bits(self, offsets: &[Offset]) -> impl Iterator<Item = (usize, Offset)> + '_ {
+        let width = offsets.len();
+        offsets.iter().enumerate().map(move |(idx, &offset)| {
+            let bit = match self {
+                BitOrder::LsbFirst => idx,
+                BitOrder::MsbFirst => width - 1 - idx,
+            };
+            (bit, offset)
+        })
+    }
+}
+
+pub(crate) fn check_word_width(offsets: &[Offset]) -> Result<()> {
+    if offsets.len() > 64 {
+        return Err(Error::InvalidConfig(format!(
+            "word of {} lines exceeds 64 bits",
+            offsets.len()
+        )));
+    }
+    Ok(())
+}
+
 /// A map from offset to T.
 pub type OffsetMap<T> = HashMap<Offset, T, BuildHasherDefault<OffsetHasher>>;
 
@@ -486,8 +1670,14 @@ impl Hasher for OffsetHasher {
     }
 }
 
+/// The maximum length of a chip label, as gpiolib truncates labels to fit a
+/// 32 byte buffer, including the terminating NUL.
+const MAX_LABEL_LEN: usize = 31;
+
 /// The configuration for a single simulated chip.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(default))]
 pub struct Bank {
     /// The number of lines simulated by this bank.
     pub num_lines: u32,
@@ -495,11 +1685,10 @@ pub struct Bank {
     /// The label of the chip.
     pub label: String,
 
-    /// Lines assigned a name.
-    pub names: OffsetMap<String>,
-
-    /// Lines that appear to be already in use by some other entity.
-    pub hogs: OffsetMap<Hog>,
+    /// The configuration of the lines with a name, hog, pull or tag.
+    ///
+    /// Lines with an empty configuration are removed.
+    lines: OffsetMap<LineConfig>,
 }
 
 impl Bank {
@@ -508,21 +1697,126 @@ impl Bank {
         Bank {
             num_lines,
             label: label.into(),
-            names: OffsetMap::default(),
-            hogs: OffsetMap::default(),
+            lines: OffsetMap::default(),
+        }
+    }
+
+    /// Construct a bank from a list of line names.
+    ///
+    /// The number of lines is the length of the list, and lines with
+    /// a `Some` name are assigned that name.
+    ///
+    /// e.g. `Bank::from_names("header", &[Some("A"), None, Some("B")])`
+    /// has three lines, with lines 0 and 2 named.
+    pub fn from_names<L: Into<String>, N: AsRef<str>>(label: L, names: &[Option<N>]) -> Bank {
+        let mut bank = Bank::new(names.len() as u32, label);
+        for (offset, name) in (0..).zip(names) {
+            if let Some(name) = name {
+                bank.name(offset, name.as_ref());
+            }
+        }
+        bank
+    }
+
+    /// Configure a line on the chip.
+    ///
+    /// The returned builder allows the name, hog, pull and tags of the line
+    /// to be chained, e.g. `bank.line(3).name("led").pull(Level::High)`.
+    pub fn line(&mut self, offset: Offset) -> LineBuilder<'_> {
+        LineBuilder::new(self, offset)
+    }
+
+    /// The name, hog, pull and tags of a line on the chip.
+    pub fn line_config(&self, offset: Offset) -> &LineConfig {
+        self.lines.get(&offset).unwrap_or(&line::EMPTY_LINE)
+    }
+
+    /// The configuration of each line on the chip with a name, hog, pull or
+    /// tag, in offset order.
+    pub fn line_configs(&self) -> Vec<(Offset, &LineConfig)> {
+        let mut lines: Vec<(Offset, &LineConfig)> = self
+            .lines
+            .iter()
+            .map(|(&offset, cfg)| (offset, cfg))
+            .collect();
+        lines.sort_unstable_by_key(|&(offset, _)| offset);
+        lines
+    }
+
+    /// The names of the named lines on the chip.
+    pub fn names(&self) -> OffsetMap<&str> {
+        self.lines
+            .iter()
+            .filter_map(|(&offset, cfg)| Some((offset, cfg.name.as_deref()?)))
+            .collect()
+    }
+
+    /// The hogs on the hogged lines on the chip.
+    pub fn hogs(&self) -> OffsetMap<&Hog> {
+        self.lines
+            .iter()
+            .filter_map(|(&offset, cfg)| Some((offset, cfg.hog.as_ref()?)))
+            .collect()
+    }
+
+    // Update the configuration of a line, removing it if left empty.
+    fn update_line<F: FnOnce(&mut LineConfig)>(&mut self, offset: Offset, f: F) -> &mut Self {
+        let cfg = self.lines.entry(offset).or_default();
+        f(cfg);
+        if cfg.is_empty() {
+            self.lines.remove(&offset);
         }
+        self
     }
 
     /// Assign a name to a line on the chip.
     pub fn name<N: Into<String>>(&mut self, offset: Offset, name: N) -> &mut Self {
-        self.names.insert(offset, name.into());
+        let name = name.into();
+        self.update_line(offset, |cfg| cfg.name = Some(name))
+    }
+
+    /// Assign names to consecutive lines on the chip, starting from the given offset.
+    ///
+    /// e.g. `names_from(4, &["RX", "TX", "CTS"])` names lines 4, 5 and 6.
+    pub fn names_from<N: AsRef<str>>(&mut self, start: Offset, names: &[N]) -> &mut Self {
+        self.names_from_iter(start, names.iter().map(|n| n.as_ref()))
+    }
+
+    /// Assign names from an iterator to consecutive lines on the chip, starting
+    /// from the given offset.
+    pub fn names_from_iter<I, N>(&mut self, start: Offset, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = N>,
+        N: Into<String>,
+    {
+        for (offset, name) in (start..).zip(names) {
+            self.name(offset, name);
+        }
+        self
+    }
+
+    /// Assign names to a range of lines on the chip.
+    ///
+    /// The name for each line is generated by the formatter, which is passed
+    /// the index of the line within the range, starting at 0.
+    ///
+    /// e.g. `name_range(8..16, |i| format!("D{}", i))` names lines 8 to 15
+    /// `D0` to `D7`.
+    pub fn name_range<R, F, N>(&mut self, offsets: R, formatter: F) -> &mut Self
+    where
+        R: IntoIterator<Item = Offset>,
+        F: Fn(usize) -> N,
+        N: Into<String>,
+    {
+        for (i, offset) in offsets.into_iter().enumerate() {
+            self.name(offset, formatter(i));
+        }
         self
     }
 
     /// Remove the name from a line.
     pub fn unname(&mut self, offset: Offset) -> &mut Self {
-        self.names.remove(&offset);
-        self
+        self.update_line(offset, |cfg| cfg.name = None)
     }
 
     /// Add a hog on a line on the chip.
@@ -536,20 +1830,155 @@ impl Bank {
         consumer: N,
         direction: Direction,
     ) -> &mut Self {
-        self.hogs.insert(
-            offset,
-            Hog {
-                direction,
-                consumer: consumer.into(),
-            },
-        );
+        let hog = Hog {
+            direction,
+            consumer: consumer.into(),
+        };
+        self.update_line(offset, |cfg| cfg.hog = Some(hog))
+    }
+
+    /// Add a hog on each of a range of lines on the chip.
+    ///
+    /// e.g. `hog_range(4..8, "bootloader", Direction::Input)`
+    pub fn hog_range<R, N>(&mut self, offsets: R, consumer: N, direction: Direction) -> &mut Self
+    where
+        R: IntoIterator<Item = Offset>,
+        N: Into<String>,
+    {
+        let consumer = consumer.into();
+        for offset in offsets {
+            self.hog(offset, consumer.clone(), direction);
+        }
+        self
+    }
+
+    /// Set the pull on a line when the simulator goes live.
+    ///
+    /// Lines are otherwise pulled low.
+    /// The pulls are applied before [`Builder::live`] returns, so are in place
+    /// before the consumer can read the lines.
+    pub fn pull(&mut self, offset: Offset, pull: Level) -> &mut Self {
+        self.update_line(offset, |cfg| cfg.pull = Some(pull))
+    }
+
+    /// Assign a tag to a line on the chip.
+    ///
+    /// A line may have several tags, e.g. `"i2c"` and `"debug"`, which allow
+    /// helpers to select functional groups of lines rather than hard-coding
+    /// their offsets.
+    pub fn tag<N: Into<String>>(&mut self, offset: Offset, tag: N) -> &mut Self {
+        let tag = tag.into();
+        self.update_line(offset, |cfg| {
+            if !cfg.tags.contains(&tag) {
+                cfg.tags.push(tag);
+            }
+        })
+    }
+
+    /// Assign a tag to each of a range of lines on the chip.
+    ///
+    /// e.g. `tag_range(4..6, "i2c")`
+    pub fn tag_range<R, N>(&mut self, offsets: R, tag: N) -> &mut Self
+    where
+        R: IntoIterator<Item = Offset>,
+        N: Into<String>,
+    {
+        let tag = tag.into();
+        for offset in offsets {
+            self.tag(offset, tag.clone());
+        }
         self
     }
 
+    /// Remove a tag from a line.
+    pub fn untag(&mut self, offset: Offset, tag: &str) -> &mut Self {
+        self.update_line(offset, |cfg| cfg.tags.retain(|t| t != tag))
+    }
+
+    /// The lines assigned the tag, in offset order.
+    pub fn lines_with_tag(&self, tag: &str) -> Vec<Offset> {
+        let mut offsets: Vec<Offset> = self
+            .lines
+            .iter()
+            .filter(|(_, cfg)| cfg.tags.iter().any(|t| t == tag))
+            .map(|(&offset, _)| offset)
+            .collect();
+        offsets.sort_unstable();
+        offsets
+    }
+
+    /// Remove the initial pull from a line.
+    pub fn unpull(&mut self, offset: Offset) -> &mut Self {
+        self.update_line(offset, |cfg| cfg.pull = None)
+    }
+
+    /// Add the lines of another bank to this bank, with the offsets of those
+    /// lines shifted by offset_shift.
+    ///
+    /// The names, hogs, pulls and tags of the other bank are added to this bank,
+    /// and the number of lines is increased to cover the shifted lines, if
+    /// necessary.
+    /// Where the lines overlap, the names, hogs and pulls of the other bank
+    /// overwrite those of this bank, and the tags of both are kept.
+    /// The label of this bank is unchanged.
+    ///
+    /// This allows a chip configuration to be composed of reusable blocks of lines.
+    ///
+    /// Returns [`Error::InvalidConfig`], leaving this bank unchanged, if a
+    /// shifted offset exceeds the range of [`Offset`].
+    pub fn extend(&mut self, other: &Bank, offset_shift: Offset) -> Result<&mut Self> {
+        let lines = other.line_configs();
+        let offsets = lines.iter().map(|(offset, _)| *offset);
+        for offset in offsets.chain(Some(other.num_lines)) {
+            if offset.checked_add(offset_shift).is_none() {
+                return Err(Error::InvalidConfig(format!(
+                    "offset {} of bank {:?} shifted by {} overflows",
+                    offset, other.label, offset_shift
+                )));
+            }
+        }
+        for (offset, cfg) in lines {
+            self.line(offset + offset_shift).merge(cfg);
+        }
+        self.num_lines = self.num_lines.max(other.num_lines + offset_shift);
+        Ok(self)
+    }
+
+    /// Split the bank into banks of at most max_lines.
+    ///
+    /// The names, hogs, pulls and tags are moved to the bank containing their
+    /// line, with the offset of the line within that bank, and the banks are
+    /// labelled with the label of this bank suffixed with their index, e.g.
+    /// `expander.0`, `expander.1`, etc.
+    ///
+    /// Names, hogs, pulls and tags on offsets beyond the lines of the bank
+    /// are dropped.
+    ///
+    /// A bank that does not exceed max_lines, or a max_lines of zero, returns
+    /// the bank unchanged.
+    pub fn split(&self, max_lines: u32) -> Vec<Bank> {
+        let count = chunk::chunk_count(self.num_lines, Some(max_lines));
+        if count == 1 {
+            return vec![self.clone()];
+        }
+        let mut banks: Vec<Bank> = (0..count)
+            .map(|i| {
+                let num_lines = max_lines.min(self.num_lines - i * max_lines);
+                Bank::new(num_lines, format!("{}.{}", self.label, i))
+            })
+            .collect();
+        for (offset, cfg) in self.line_configs() {
+            if offset < self.num_lines {
+                let chunk = (offset / max_lines) as usize;
+                banks[chunk].line(offset % max_lines).merge(cfg);
+            }
+        }
+        banks
+    }
+
     /// Unhog a line on the chip.
     pub fn unhog(&mut self, offset: Offset) -> &mut Self {
-        self.hogs.remove(&offset);
-        self
+        self.update_line(offset, |cfg| cfg.hog = None)
     }
 }
 
@@ -557,6 +1986,7 @@ impl Bank {
 ///
 /// A "hogged" line appears to be already requested by a consumer.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hog {
     /// The name of the consumer that appears to be using the line.
     pub consumer: String,
@@ -568,6 +1998,8 @@ pub struct Hog {
 
 /// The direction, and for outputs the pulled value, of a hogged line.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "kebab-case"))]
 pub enum Direction {
     /// Hogged line is requested as an input.
     Input,
@@ -589,8 +2021,33 @@ impl Direction {
     }
 }
 
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Direction {
+    type Err = Error;
+
+    /// Parse a direction from its configfs form, i.e. `input`, `output-low` or
+    /// `output-high`, ignoring case.
+    fn from_str(s: &str) -> Result<Direction> {
+        [
+            Direction::Input,
+            Direction::OutputLow,
+            Direction::OutputHigh,
+        ]
+        .into_iter()
+        .find(|d| d.as_str().eq_ignore_ascii_case(s))
+        .ok_or_else(|| Error::InvalidConfig(format!("invalid direction {:?}", s)))
+    }
+}
+
 /// The physical value of a line.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
 pub enum Level {
     /// The line is  physically high.
     High,
@@ -609,6 +2066,56 @@ impl Level {
     }
 }
 
+impl From<bool> for Level {
+    /// Converts `true` to [`Level::High`] and `false` to [`Level::Low`].
+    fn from(high: bool) -> Level {
+        if high {
+            Level::High
+        } else {
+            Level::Low
+        }
+    }
+}
+
+impl From<Level> for bool {
+    /// Converts [`Level::High`] to `true` and [`Level::Low`] to `false`.
+    fn from(level: Level) -> bool {
+        level == Level::High
+    }
+}
+
+impl Not for Level {
+    type Output = Level;
+
+    fn not(self) -> Level {
+        self.toggle()
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::High => "high",
+            Level::Low => "low",
+        })
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    /// Parse a level from `high` or `low`, ignoring case, or from `1` or `0`.
+    fn from_str(s: &str) -> Result<Level> {
+        if s == "1" || s.eq_ignore_ascii_case("high") {
+            Ok(Level::High)
+        } else if s == "0" || s.eq_ignore_ascii_case("low") {
+            Ok(Level::Low)
+        } else {
+            Err(Error::InvalidConfig(format!("invalid level {:?}", s)))
+        }
+    }
+}
+
 /// Create a unique, but predictable, name for the simulator.
 ///
 /// The name format is `<app>-p<pid>-<N>[-<instance>]`
@@ -633,18 +2140,52 @@ pub fn unique_name(app: &str, instance: Option<&str>) -> String {
     name
 }
 
-// Helper to write to simulator configuration files.
-fn write_attr<D: AsRef<[u8]>>(p: &Path, file: &str, data: D) -> Result<()> {
-    let path = p.join(file);
-    fs::write(path, data).map_err(Error::IoError)
+/// Create a unique name for the simulator that identifies the current test.
+///
+/// The name format is `<app>-p<pid>-<N>[-<thread>]`, as per [`unique_name`],
+/// where the app name is drawn from `argv[0]` of the executable and the
+/// instance is the name of the current thread, if it has one.
+///
+/// As `cargo test` names the thread running each test after the test, this
+/// identifies the test that created the simulator.
+pub fn unique_name_for_test() -> String {
+    let instance = thread::current().name().map(sanitize_name);
+    unique_name(&app_name(), instance.as_deref())
 }
 
-// Helper to read from simulator attribute files.
-fn read_attr(p: &Path, file: &str) -> Result<String> {
-    let path = p.join(file);
-    fs::read_to_string(path)
-        .map(|s| s.trim().to_string())
-        .map_err(Error::IoError)
+/// Replace the characters in a name that cannot be used in a simulator name,
+/// chip label, line name or hog consumer with `_`.
+///
+/// The characters are `/`, which is a path separator in configfs, and control
+/// characters, such as newline and NUL, which are stripped or truncate the
+/// value written to configfs.
+///
+/// Names containing those characters are otherwise rejected by
+/// [`Builder::validate`] and [`Builder::live`].
+///
+/// e.g. `sanitize_name("i2c/sda\n")` is `"i2c_sda_"`.
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if is_invalid_char(c) { '_' } else { c })
+        .collect()
+}
+
+fn is_invalid_char(c: char) -> bool {
+    c == '/' || c.is_control()
+}
+
+// Check that a name contains no characters that cannot be written to configfs.
+//
+// The what identifies the name in the error, and is only evaluated on failure.
+fn check_chars<F: FnOnce() -> String>(name: &str, what: F) -> Result<()> {
+    match name.chars().find(|&c| is_invalid_char(c)) {
+        Some(c) => Err(Error::InvalidConfig(format!(
+            "{} contains {:?}, see sanitize_name",
+            what(),
+            c
+        ))),
+        None => Ok(()),
+    }
 }
 
 fn app_name() -> String {
@@ -658,53 +2199,29 @@ fn app_name() -> String {
     "gpiosim".into()
 }
 
-fn default_name() -> String {
-    unique_name(&app_name(), None)
-}
-
-fn configfs_mountpoint() -> Option<PathBuf> {
-    if let Ok(f) = File::open("/proc/mounts") {
-        let r = BufReader::new(f);
-        for line in r.lines().map_while(|x| x.ok()) {
-            let words: Vec<&str> = line.split_ascii_whitespace().collect();
-            if words.len() >= 6 && words[2] == "configfs" {
-                return Some(PathBuf::from(words[1]));
-            }
+// Poll until the check is satisfied, returning the time taken.
+fn poll<F>(mut check: F, interval: Duration, timeout: Duration) -> Result<Duration>
+where
+    F: FnMut() -> Result<bool>,
+{
+    let start = Instant::now();
+    let deadline = start + timeout;
+    loop {
+        if check()? {
+            return Ok(start.elapsed());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Error::IoError(std::io::ErrorKind::TimedOut.into()));
         }
+        // sleep no further than the deadline so the final check is made
+        // at the timeout rather than up to an interval after it
+        thread::sleep(interval.min(deadline - now));
     }
-    None
 }
 
-// check if configfs is mounted, and if so where.
-fn find_configfs() -> Result<PathBuf> {
-    // Assume default location for starters
-    let path: PathBuf = "/sys/kernel/config/gpio-sim".into();
-    if path.exists() {
-        return Ok(path);
-    }
-    // Perhaps gpio-sim module is not loaded - so load it
-    let output = process::Command::new("modprobe")
-        .arg("gpio-sim")
-        .output()
-        .map_err(|e| Error::CommandError("modprobe".into(), Box::new(e)))?;
-    if !output.status.success() {
-        return Err(Error::ModuleLoadError(OsString::from_vec(output.stderr)));
-    }
-    for _ in 0..10 {
-        if path.exists() {
-            return Ok(path);
-        }
-        // Loading gpio-sim should mount configfs, but maybe it isn't in the
-        // standard location, so check mounts...
-        if let Some(mut cfgfs) = configfs_mountpoint() {
-            cfgfs.push("gpio-sim");
-            if path.exists() {
-                return Ok(cfgfs);
-            }
-        }
-        sleep(Duration::from_millis(100));
-    }
-    Err(Error::ConfigfsNotFound)
+fn default_name() -> String {
+    unique_name(&app_name(), None)
 }
 
 /// The result for [`gpiosim`] functions.
@@ -721,22 +2238,56 @@ pub enum Error {
     #[error("Could not find configsfs")]
     ConfigfsNotFound,
 
-    /// An error detected while loading the gpio-sim kernel module.
-    #[error("Could not load gpio-sim module: {0:?}")]
+    /// An error detected while loading a kernel module, such as gpio-sim.
+    #[error("Could not load kernel module: {0:?}")]
     ModuleLoadError(OsString),
 
     /// Attempt to take a simulator live with a name of an active simulator.
     #[error("Simulator with name {0:?} already exists")]
     SimulatorExists(String),
 
+    /// The configuration is not valid for the requested operation.
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     /// An unexpected value was read from a configfs or sysfs attribute file.
     #[error("Read unexpected attr value {0:?}")]
     UnexpectedValue(String),
 
+    /// The simulator has been removed, e.g. by another process or by the
+    /// kernel module being unloaded.
+    #[error("Simulator has been removed")]
+    SimRemoved,
+
+    /// Timed out waiting to take a simulator live, as the limit on
+    /// concurrent simulators has been reached.
+    #[error("Limit of {0} concurrent simulators reached")]
+    SimLimitReached(usize),
+
+    /// The configuration exceeds a limit of the kernel, such as the number of
+    /// lines on a chip.
+    #[error("Kernel limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    /// The path to a simulated chip in /dev is a symlink, so may refer to
+    /// some other chip.
+    #[error("Device {chip} is masked by symlink {symlink:?}")]
+    DeviceMasked {
+        /// The symlink in place of the device.
+        symlink: PathBuf,
+
+        /// The name of the simulated gpiochip.
+        chip: String,
+    },
+
     /// An IO error detected while accessing a configfs or sysfs attribute file
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 
+    /// An error reported by a remote server.
+    #[error("Remote error: {0}")]
+    RemoteError(String),
+
     /// An error detected while executing an external command.
     #[error("Command {0} returned error {1}")]
     CommandError(String, Box<dyn std::error::Error>),
@@ -760,27 +2311,79 @@ mod tests {
         assert!(name.ends_with("-test2"));
     }
 
+    #[test]
+    fn unique_name_for_test_thread() {
+        let name = unique_name_for_test();
+        assert!(name.contains("-p"));
+        if let Some(tname) = thread::current().name() {
+            assert!(name.ends_with(tname));
+        }
+    }
+
     #[test]
     fn bank_constructor_default() {
         let c = Bank::default();
         assert_eq!(c.num_lines, 0);
         assert!(c.label.is_empty());
-        assert_eq!(c.names.len(), 0);
-        assert_eq!(c.hogs.len(), 0);
+        assert_eq!(c.names().len(), 0);
+        assert_eq!(c.hogs().len(), 0);
+        assert!(c.line_configs().is_empty());
+    }
+
+    #[test]
+    fn bank_from_names() {
+        let c = Bank::from_names("header", &[Some("A"), None, Some("B"), None]);
+        assert_eq!(c.num_lines, 4);
+        assert_eq!(c.label, "header");
+        assert_eq!(c.names().len(), 2);
+        assert_eq!(c.names()[&0], "A");
+        assert_eq!(c.names()[&2], "B");
+        assert_eq!(c.hogs().len(), 0);
+
+        let c = Bank::from_names::<_, &str>("empty", &[]);
+        assert_eq!(c.num_lines, 0);
+        assert_eq!(c.names().len(), 0);
     }
 
     #[test]
     fn bank_name() {
         let mut c = Bank::default();
         c.name(3, "pinata");
-        assert_eq!(c.names.len(), 1);
-        assert_eq!(c.names[&3], "pinata");
+        assert_eq!(c.names().len(), 1);
+        assert_eq!(c.names()[&3], "pinata");
         c.name(3, "pineapple");
-        assert_eq!(c.names.len(), 1);
-        assert_eq!(c.names[&3], "pineapple");
+        assert_eq!(c.names().len(), 1);
+        assert_eq!(c.names()[&3], "pineapple");
         c.name(0, "nada");
-        assert_eq!(c.names.len(), 2);
-        assert_eq!(c.names[&0], "nada");
+        assert_eq!(c.names().len(), 2);
+        assert_eq!(c.names()[&0], "nada");
+    }
+
+    #[test]
+    fn bank_names_from() {
+        let mut c = Bank::default();
+        c.names_from(4, &["RX", "TX", "CTS"]);
+        assert_eq!(c.names().len(), 3);
+        assert_eq!(c.names()[&4], "RX");
+        assert_eq!(c.names()[&5], "TX");
+        assert_eq!(c.names()[&6], "CTS");
+        c.names_from(0, &[String::from("nada")]);
+        assert_eq!(c.names().len(), 4);
+        assert_eq!(c.names()[&0], "nada");
+        // overwrite
+        c.names_from(5, &["tx"]);
+        assert_eq!(c.names().len(), 4);
+        assert_eq!(c.names()[&5], "tx");
+    }
+
+    #[test]
+    fn bank_names_from_iter() {
+        let mut c = Bank::default();
+        c.names_from_iter(2, (0..3).map(|i| format!("D{}", i)));
+        assert_eq!(c.names().len(), 3);
+        assert_eq!(c.names()[&2], "D0");
+        assert_eq!(c.names()[&3], "D1");
+        assert_eq!(c.names()[&4], "D2");
     }
 
     #[test]
@@ -788,33 +2391,166 @@ mod tests {
         let mut c = Bank::default();
         c.name(3, "pinata");
         c.name(0, "nada");
-        assert_eq!(c.names.len(), 2);
+        assert_eq!(c.names().len(), 2);
         c.unname(3);
-        assert!(!c.names.contains_key(&3));
-        assert_eq!(c.names.len(), 1);
-        assert_eq!(c.names[&0], "nada");
+        assert!(!c.names().contains_key(&3));
+        assert_eq!(c.names().len(), 1);
+        assert_eq!(c.names()[&0], "nada");
     }
 
     #[test]
     fn bank_hog() {
         let mut c = Bank::default();
         c.hog(3, "pinata", Direction::Input);
-        assert_eq!(c.hogs.len(), 1);
+        assert_eq!(c.hogs().len(), 1);
         c.hog(2, "piggly", Direction::OutputLow);
-        assert_eq!(c.hogs.len(), 2);
+        assert_eq!(c.hogs().len(), 2);
         c.hog(1, "wiggly", Direction::OutputHigh);
-        assert_eq!(c.hogs.len(), 3);
-        assert_eq!(c.hogs[&3].consumer, "pinata");
-        assert_eq!(c.hogs[&2].consumer, "piggly");
-        assert_eq!(c.hogs[&1].consumer, "wiggly");
-        assert_eq!(c.hogs[&3].direction, Direction::Input);
-        assert_eq!(c.hogs[&2].direction, Direction::OutputLow);
-        assert_eq!(c.hogs[&1].direction, Direction::OutputHigh);
+        assert_eq!(c.hogs().len(), 3);
+        assert_eq!(c.hogs()[&3].consumer, "pinata");
+        assert_eq!(c.hogs()[&2].consumer, "piggly");
+        assert_eq!(c.hogs()[&1].consumer, "wiggly");
+        assert_eq!(c.hogs()[&3].direction, Direction::Input);
+        assert_eq!(c.hogs()[&2].direction, Direction::OutputLow);
+        assert_eq!(c.hogs()[&1].direction, Direction::OutputHigh);
         // overwrite
         c.hog(2, "wiggly", Direction::OutputHigh);
-        assert_eq!(c.hogs[&2].consumer, "wiggly");
-        assert_eq!(c.hogs[&2].direction, Direction::OutputHigh);
-        assert_eq!(c.hogs.len(), 3);
+        assert_eq!(c.hogs()[&2].consumer, "wiggly");
+        assert_eq!(c.hogs()[&2].direction, Direction::OutputHigh);
+        assert_eq!(c.hogs().len(), 3);
+    }
+
+    #[test]
+    fn bank_hog_range() {
+        let mut c = Bank::default();
+        c.hog_range(4..8, "bootloader", Input);
+        assert_eq!(c.hogs().len(), 4);
+        for offset in 4..8 {
+            assert_eq!(c.hogs()[&offset].consumer, "bootloader");
+            assert_eq!(c.hogs()[&offset].direction, Input);
+        }
+        c.hog_range(1..=2, "wiggly", OutputHigh);
+        assert_eq!(c.hogs().len(), 6);
+        assert_eq!(c.hogs()[&1].consumer, "wiggly");
+        assert_eq!(c.hogs()[&2].direction, OutputHigh);
+    }
+
+    #[test]
+    fn bank_name_range() {
+        let mut c = Bank::default();
+        c.name_range(8..12, |i| format!("D{}", i));
+        assert_eq!(c.names().len(), 4);
+        assert_eq!(c.names()[&8], "D0");
+        assert_eq!(c.names()[&11], "D3");
+        c.name_range([1, 3], |_| "odd");
+        assert_eq!(c.names().len(), 6);
+        assert_eq!(c.names()[&1], "odd");
+        assert_eq!(c.names()[&3], "odd");
+    }
+
+    #[test]
+    fn bank_extend() {
+        let mut uart = Bank::new(2, "uart");
+        uart.names_from(0, &["RX", "TX"])
+            .hog(1, "console", OutputHigh);
+        let mut c = Bank::new(4, "board");
+        c.name(1, "LED");
+        c.extend(&uart, 4).unwrap();
+        assert_eq!(c.label, "board");
+        assert_eq!(c.num_lines, 6);
+        assert_eq!(c.names().len(), 3);
+        assert_eq!(c.names()[&1], "LED");
+        assert_eq!(c.names()[&4], "RX");
+        assert_eq!(c.names()[&5], "TX");
+        assert_eq!(c.hogs().len(), 1);
+        assert_eq!(c.hogs()[&5].consumer, "console");
+
+        // within existing lines
+        c.extend(&uart, 0).unwrap();
+        assert_eq!(c.num_lines, 6);
+        assert_eq!(c.names().len(), 4);
+        assert_eq!(c.names()[&0], "RX");
+        assert_eq!(c.names()[&1], "TX");
+        assert_eq!(c.hogs().len(), 2);
+
+        // shifts that overflow are rejected, leaving the bank unchanged
+        let before = c.clone();
+        assert!(matches!(
+            c.extend(&uart, u32::MAX),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(c.extend(&Bank::new(1, "one"), u32::MAX - 1).is_ok());
+        assert_eq!(c.num_lines, u32::MAX);
+        assert!(c.extend(&Bank::new(1, "one"), u32::MAX).is_err());
+        let mut c = before;
+        assert!(c
+            .extend(Bank::new(1, "one").name(7, "beyond"), u32::MAX - 2)
+            .is_err());
+        assert_eq!(c.names().len(), 4);
+    }
+
+    #[test]
+    fn bank_pull() {
+        let mut c = Bank::new(4, "pulled");
+        c.pull(1, Level::High)
+            .pull(3, Level::High)
+            .pull(2, Level::Low);
+        assert_eq!(c.line_configs().len(), 3);
+        assert_eq!(c.line_config(1).pull, Some(Level::High));
+        c.unpull(2);
+        assert!(c.line_config(2).is_empty());
+        let mut big = Bank::new(2, "big");
+        big.extend(&c, 2).unwrap();
+        assert_eq!(big.line_configs().len(), 2);
+        assert_eq!(big.line_config(5).pull, Some(Level::High));
+
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&c)
+            .live()
+            .unwrap();
+        let chip = &sim.chips()[0];
+        assert_eq!(chip.get_level(1).unwrap(), Level::High);
+        assert_eq!(chip.get_level(2).unwrap(), Level::Low);
+        assert_eq!(chip.get_level(3).unwrap(), Level::High);
+        assert!(chip.stats().iter().all(|s| s.pulls == 0));
+
+        c.pull(4, Level::High);
+        let res = builder().with_backend(Memory::new()).with_bank(&c).live();
+        assert!(matches!(res, Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn bank_tag() {
+        let mut c = Bank::new(8, "tagged");
+        c.tag(3, "debug")
+            .tag_range(4..6, "i2c")
+            .tag(5, "debug")
+            .tag(5, "debug");
+        assert_eq!(c.line_config(5).tags, ["i2c", "debug"]);
+        assert_eq!(c.lines_with_tag("i2c"), [4, 5]);
+        assert_eq!(c.lines_with_tag("debug"), [3, 5]);
+        assert!(c.lines_with_tag("spi").is_empty());
+        assert_eq!(Lines::Tagged("debug".into()).offsets(&c), [3, 5]);
+        c.untag(3, "debug").untag(5, "i2c").untag(6, "i2c");
+        assert!(c.line_config(3).is_empty());
+        assert_eq!(c.lines_with_tag("debug"), [5]);
+        let mut big = Bank::new(2, "big");
+        big.tag(7, "i2c").extend(&c, 2).unwrap();
+        assert_eq!(big.lines_with_tag("i2c"), [6, 7]);
+        assert_eq!(big.line_config(7).tags, ["i2c", "debug"]);
+
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&c)
+            .live()
+            .unwrap();
+        let chip = &sim.chips()[0];
+        assert_eq!(chip.lines_with_tag("i2c"), [4]);
+        chip.set_pull_lines(Lines::Tagged("debug".into()), Level::High)
+            .unwrap();
+        assert_eq!(chip.get_pull(5).unwrap(), Level::High);
+        assert_eq!(chip.get_pull(4).unwrap(), Level::Low);
     }
 
     #[test]
@@ -823,14 +2559,310 @@ mod tests {
         c.hog(3, "pinata", Direction::Input);
         c.hog(2, "piggly", Direction::OutputLow);
         c.hog(1, "wiggly", Direction::OutputHigh);
-        assert_eq!(c.hogs.len(), 3);
+        assert_eq!(c.hogs().len(), 3);
         c.unhog(2);
-        assert_eq!(c.hogs.len(), 2);
-        assert!(!c.hogs.contains_key(&2));
-        assert_eq!(c.hogs[&3].consumer, "pinata");
-        assert_eq!(c.hogs[&1].consumer, "wiggly");
-        assert_eq!(c.hogs[&3].direction, Direction::Input);
-        assert_eq!(c.hogs[&1].direction, Direction::OutputHigh);
+        assert_eq!(c.hogs().len(), 2);
+        assert!(!c.hogs().contains_key(&2));
+        assert_eq!(c.hogs()[&3].consumer, "pinata");
+        assert_eq!(c.hogs()[&1].consumer, "wiggly");
+        assert_eq!(c.hogs()[&3].direction, Direction::Input);
+        assert_eq!(c.hogs()[&1].direction, Direction::OutputHigh);
+    }
+
+    #[test]
+    fn simpleton_builder() {
+        let mut builder = Simpleton::builder(12);
+        assert_eq!(builder.bank, Bank::new(12, "simpleton"));
+        builder
+            .label("uart-test")
+            .name(3, "RX")
+            .hog(7, "other", Input);
+        assert_eq!(builder.bank.num_lines, 12);
+        assert_eq!(builder.bank.label, "uart-test");
+        assert_eq!(builder.bank.names().len(), 1);
+        assert_eq!(builder.bank.names()[&3], "RX");
+        assert_eq!(builder.bank.hogs().len(), 1);
+        assert_eq!(builder.bank.hogs()[&7].consumer, "other");
+        assert_eq!(builder.bank.hogs()[&7].direction, Input);
+    }
+
+    #[test]
+    fn chip_stats() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(c.stats(), vec![LineStats::default(); 4]);
+        c.pullup(1).unwrap();
+        c.pulldown(1).unwrap();
+        c.set_pull(1, Level::High).unwrap();
+        c.toggle(2).unwrap();
+        c.get_pull(2).unwrap();
+        c.get_level(3).unwrap();
+        c.get_level(3).unwrap();
+        assert!(c.pullup(4).is_err());
+        let stats = c.stats();
+        assert_eq!(stats[0], LineStats::default());
+        assert_eq!(
+            stats[1],
+            LineStats {
+                pulls: 3,
+                ..LineStats::default()
+            }
+        );
+        assert_eq!(
+            stats[2],
+            LineStats {
+                toggles: 1,
+                pull_reads: 1,
+                ..LineStats::default()
+            }
+        );
+        assert_eq!(
+            stats[3],
+            LineStats {
+                level_reads: 2,
+                ..LineStats::default()
+            }
+        );
+        c.reset_stats();
+        assert_eq!(c.stats(), vec![LineStats::default(); 4]);
+    }
+
+    #[test]
+    fn chip_glitch() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "glitch"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        let width = c.glitch(1, Duration::from_micros(50)).unwrap();
+        assert!(width >= Duration::from_micros(50));
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+        assert_eq!(c.stats()[1].toggles, 2);
+
+        let start = Instant::now();
+        let width = c
+            .glitch_train(2, Duration::ZERO, Duration::from_millis(2), 5)
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(8));
+        assert!(width < Duration::from_millis(2));
+        assert_eq!(c.get_pull(2).unwrap(), Level::Low);
+        assert_eq!(c.stats()[2].toggles, 10);
+        assert!(c
+            .glitch_train(2, Duration::from_millis(1), Duration::from_millis(1), 1)
+            .is_err());
+        assert!(c.glitch(4, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn chip_pull_history() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "history"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(0).unwrap();
+        assert!(c.pull_history().is_empty());
+        c.record_pulls(true);
+        c.pullup(1).unwrap();
+        c.share().toggle(1).unwrap();
+        c.glitch(2, Duration::ZERO).unwrap();
+        assert!(c.pullup(4).is_err());
+        c.record_pulls(false);
+        c.pulldown(3).unwrap();
+        let history = c.pull_history();
+        let pulls: Vec<(Offset, Level)> = history.iter().map(|r| (r.offset, r.pull)).collect();
+        assert_eq!(
+            pulls,
+            [
+                (1, Level::High),
+                (1, Level::Low),
+                (2, Level::High),
+                (2, Level::Low)
+            ]
+        );
+        assert!(history.windows(2).all(|w| w[0].time <= w[1].time));
+        let r = PullRecord {
+            time: Duration::from_micros(15),
+            offset: 3,
+            pull: Level::High,
+        };
+        assert_eq!(r.to_string(), "15µs 3 high");
+        c.record_pulls(true);
+        assert!(c.pull_history().is_empty());
+    }
+
+    #[test]
+    fn lines_offsets() {
+        let mut bank = Bank::new(6, "lines");
+        bank.name(4, "four").name(1, "one");
+        assert_eq!(Lines::All.offsets(&bank), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(Lines::Named.offsets(&bank), [1, 4]);
+        assert_eq!(Lines::from(3).offsets(&bank), [3]);
+        assert_eq!(Lines::from([5, 2]).offsets(&bank), [5, 2]);
+        assert_eq!(Lines::from(&[2, 5][..]).offsets(&bank), [2, 5]);
+        assert_eq!(Lines::from(vec![1]).offsets(&bank), [1]);
+        assert_eq!(Lines::from(2..4).offsets(&bank), [2, 3]);
+        assert_eq!(Lines::from(2..=4).offsets(&bank), [2, 3, 4]);
+        assert_eq!(
+            Lines::from(7..=u32::MAX),
+            Lines::RangeInclusive(7..=u32::MAX)
+        );
+    }
+
+    #[test]
+    fn chip_line_config() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(
+                Bank::new(4, "left")
+                    .name(3, "three")
+                    .name(1, "one")
+                    .hog(1, "hogster", OutputHigh),
+            )
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let lines: Vec<_> = c.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], (0, None, None));
+        assert_eq!(lines[1].1, Some("one"));
+        assert_eq!(lines[1].2.unwrap().consumer, "hogster");
+        assert_eq!(lines[3], (3, Some("three"), None));
+        assert_eq!(
+            c.named_lines().collect::<Vec<_>>(),
+            [(1, "one"), (3, "three")]
+        );
+    }
+
+    #[test]
+    fn chip_handle() {
+        fn is_shareable<T: Clone + Send + Sync + 'static>() {}
+        is_shareable::<ChipHandle>();
+
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "handle"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let h = c.handle();
+        let workers: Vec<_> = (0..4)
+            .map(|offset| {
+                let h = h.clone();
+                thread::spawn(move || {
+                    for _ in 0..11 {
+                        h.toggle(offset).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for w in workers {
+            w.join().unwrap();
+        }
+        for offset in 0..4 {
+            assert_eq!(c.get_pull(offset).unwrap(), Level::High);
+            assert_eq!(c.stats()[offset as usize].toggles, 11);
+        }
+        assert_eq!(*h, *c);
+        drop(sim);
+        assert!(matches!(h.pullup(0), Err(Error::SimRemoved)));
+    }
+
+    #[test]
+    fn chip_lines() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(Bank::new(4, "left").name(2, "two"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.set_pull_lines(1..3, Level::High).unwrap();
+        assert_eq!(
+            c.get_pulls(Lines::All).unwrap(),
+            [
+                (0, Level::Low),
+                (1, Level::High),
+                (2, Level::High),
+                (3, Level::Low)
+            ]
+        );
+        c.set_pull_lines(Lines::Named, Level::Low).unwrap();
+        assert_eq!(
+            c.get_levels([2, 1]).unwrap(),
+            [(2, Level::Low), (1, Level::High)]
+        );
+        assert!(c.set_pull_lines([3, 4], Level::High).is_err());
+        // no line is pulled if any is out of range
+        assert_eq!(c.get_pull(3).unwrap(), Level::Low);
+        assert!(c.get_levels(2..5).is_err());
+    }
+
+    #[test]
+    fn chip_set_word() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.set_word(&[1, 2, 4, 7], 0x5, BitOrder::LsbFirst).unwrap();
+        assert_eq!(
+            c.get_pulls(Lines::All).unwrap(),
+            [
+                (0, Level::Low),
+                (1, Level::High),
+                (2, Level::Low),
+                (3, Level::Low),
+                (4, Level::High),
+                (5, Level::Low),
+                (6, Level::Low),
+                (7, Level::Low)
+            ]
+        );
+        c.set_word(&[1, 2, 4, 7], 0x15, BitOrder::MsbFirst).unwrap();
+        assert_eq!(
+            c.get_pulls([1, 2, 4, 7]).unwrap(),
+            [
+                (1, Level::Low),
+                (2, Level::High),
+                (4, Level::Low),
+                (7, Level::High)
+            ]
+        );
+        let wide: Vec<Offset> = (0..65).collect();
+        assert!(c.set_word(&wide, 0, BitOrder::LsbFirst).is_err());
+        assert!(c.set_word(&[3, 8], 0, BitOrder::LsbFirst).is_err());
+    }
+
+    #[test]
+    fn chip_get_word() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let bus: Vec<Offset> = (0..8).collect();
+        for offset in [0, 2, 5, 7] {
+            mem.drive(c, offset, Some(Level::High)).unwrap();
+        }
+        assert_eq!(c.get_word(&bus, BitOrder::LsbFirst).unwrap(), 0xa5);
+        assert_eq!(c.get_word(&bus, BitOrder::MsbFirst).unwrap(), 0xa5);
+        assert_eq!(c.get_word(&bus[..4], BitOrder::LsbFirst).unwrap(), 0x5);
+        assert_eq!(c.get_word(&bus[..4], BitOrder::MsbFirst).unwrap(), 0xa);
+        c.set_word(&bus[4..], 0x3, BitOrder::LsbFirst).unwrap();
+        mem.drive(c, 5, None).unwrap();
+        mem.drive(c, 7, None).unwrap();
+        assert_eq!(c.get_word(&bus, BitOrder::LsbFirst).unwrap(), 0x35);
+        assert!(c.get_word(&[7, 8], BitOrder::LsbFirst).is_err());
     }
 
     #[test]
@@ -851,11 +2883,161 @@ mod tests {
             );
         assert_eq!(builder.banks.len(), 2);
         assert_eq!(builder.banks[0].num_lines, 8);
-        assert_eq!(builder.banks[0].names.len(), 2);
-        assert_eq!(builder.banks[0].hogs.len(), 1);
+        assert_eq!(builder.banks[0].names().len(), 2);
+        assert_eq!(builder.banks[0].hogs().len(), 1);
         assert_eq!(builder.banks[1].num_lines, 42);
-        assert_eq!(builder.banks[1].names.len(), 1);
-        assert_eq!(builder.banks[1].hogs.len(), 2);
+        assert_eq!(builder.banks[1].names().len(), 1);
+        assert_eq!(builder.banks[1].hogs().len(), 2);
+    }
+
+    #[test]
+    fn builder_with_banks() {
+        let mut builder = builder();
+        builder
+            .with_bank(&Bank::new(4, "first"))
+            .with_banks((0..3).map(|i| Bank::new(8, format!("chip{}", i))));
+        assert_eq!(builder.banks.len(), 4);
+        assert_eq!(builder.banks[0].label, "first");
+        assert_eq!(builder.banks[1].label, "chip0");
+        assert_eq!(builder.banks[3].label, "chip2");
+        assert_eq!(builder.banks[3].num_lines, 8);
+        builder.with_banks(vec![Bank::new(1, "last")]);
+        assert_eq!(builder.banks.len(), 5);
+        assert_eq!(builder.banks[4].label, "last");
+    }
+
+    #[test]
+    fn builder_validate() {
+        let validate = |bank: &Bank| {
+            builder()
+                .with_backend(Memory::new())
+                .with_bank(bank)
+                .validate()
+                .map_err(|e| e.to_string())
+        };
+        assert!(validate(Bank::new(8, "a".repeat(31)).name(1, "led")).is_ok());
+        assert_eq!(
+            validate(&Bank::new(0, "empty")).unwrap_err(),
+            "Invalid configuration: bank \"empty\" has no lines"
+        );
+        assert_eq!(
+            validate(&Bank::new(8, "a".repeat(32))).unwrap_err(),
+            format!(
+                "Invalid configuration: label {:?} exceeds the 31 byte limit on chip labels",
+                "a".repeat(32)
+            )
+        );
+        let validate_chunked = |bank: &Bank| {
+            builder()
+                .with_backend(Memory::new())
+                .with_bank(bank)
+                .with_bank_chunking(4)
+                .validate()
+                .map_err(|e| e.to_string())
+        };
+        assert!(validate_chunked(&Bank::new(8, "a".repeat(29))).is_ok());
+        assert_eq!(
+            validate_chunked(&Bank::new(8, "a".repeat(30))).unwrap_err(),
+            format!(
+                "Invalid configuration: label {:?}, suffixed for the 2 chips it is \
+                split across, exceeds the 31 byte limit on chip labels",
+                "a".repeat(30)
+            )
+        );
+        assert!(validate_chunked(&Bank::new(4, "a".repeat(31))).is_ok());
+        assert_eq!(
+            validate_chunked(Bank::new(8, "left").name(6, "")).unwrap_err(),
+            "Invalid configuration: line 6 of bank \"left\" has an empty name"
+        );
+        assert_eq!(
+            validate(Bank::new(8, "left").name(5, "").name(3, "")).unwrap_err(),
+            "Invalid configuration: line 3 of bank \"left\" has an empty name"
+        );
+        assert!(builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(0, "empty"))
+            .live()
+            .is_err());
+    }
+
+    #[test]
+    fn builder_validate_chars() {
+        let validate = |bank: &Bank| {
+            builder()
+                .with_backend(Memory::new())
+                .with_bank(bank)
+                .validate()
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            validate(&Bank::new(8, "i2c/0")).unwrap_err(),
+            "Invalid configuration: label \"i2c/0\" contains '/', see sanitize_name"
+        );
+        assert_eq!(
+            validate(Bank::new(8, "left").name(4, "sda\n").name(6, "scl/")).unwrap_err(),
+            "Invalid configuration: name of line 4 of bank \"left\" contains '\\n', see sanitize_name"
+        );
+        assert_eq!(
+            validate(Bank::new(8, "left").hog(2, "boot\0", Direction::Input)).unwrap_err(),
+            "Invalid configuration: hog consumer of line 2 of bank \"left\" contains '\\0', see sanitize_name"
+        );
+        let res = builder()
+            .with_backend(Memory::new())
+            .with_name("sim/0")
+            .with_bank(&Bank::new(8, "left"))
+            .live();
+        assert!(matches!(res, Err(Error::InvalidConfig(_))));
+
+        let name = sanitize_name("i2c/sda\n\0");
+        assert_eq!(name, "i2c_sda__");
+        assert!(validate(Bank::new(8, &name).name(1, &name)).is_ok());
+        assert_eq!(sanitize_name("tests::led"), "tests::led");
+    }
+
+    #[test]
+    fn builder_with_chips() {
+        let mut builder = builder();
+        builder
+            .with_bank(&Bank::new(4, "first"))
+            .with_chips(20, 8, "chip");
+        assert_eq!(builder.banks.len(), 21);
+        assert_eq!(builder.banks[1], Bank::new(8, "chip0"));
+        assert_eq!(builder.banks[20], Bank::new(8, "chip19"));
+        builder.with_chips(0, 8, "none");
+        assert_eq!(builder.banks.len(), 21);
+    }
+
+    #[test]
+    fn sim_chip_by_label() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .with_banks((0..3).map(|i| Bank::new(2, format!("exp{}", i))))
+            .with_bank(&Bank::new(8, "right"))
+            .live()
+            .unwrap();
+        assert_eq!(sim.chip_by_label("right").unwrap().config().num_lines, 8);
+        assert_eq!(sim.chip_by_label("left"), Some(&sim.chips()[0]));
+        assert!(sim.chip_by_label("middle").is_none());
+        let labels: Vec<&str> = sim
+            .chips_by_label_prefix("exp")
+            .map(|c| c.config().label.as_str())
+            .collect();
+        assert_eq!(labels, ["exp0", "exp1", "exp2"]);
+        assert_eq!(sim.chips_by_label_prefix("").count(), 5);
+    }
+
+    #[test]
+    fn sim_detach() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let h = sim.chips()[0].handle();
+        sim.detach();
+        assert!(h.pullup(1).is_ok());
+        assert_eq!(h.get_level(1).unwrap(), Level::High);
     }
 
     #[test]
@@ -866,4 +3048,143 @@ mod tests {
         assert!(builder.name.is_some());
         assert_eq!(builder.name.unwrap(), "banana");
     }
+
+    #[test]
+    fn builder_simpleton_bank_count() {
+        let mut builder = builder();
+        let res = builder.simpleton();
+        assert!(matches!(res, Err(Error::InvalidConfig(_))));
+        builder
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(&Bank::new(8, "right"));
+        let res = builder.simpleton();
+        assert!(matches!(res, Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn builder_with_test_name() {
+        let mut builder = builder();
+        builder.with_test_name();
+        let name = builder.name.unwrap();
+        assert!(name.ends_with("builder_with_test_name"));
+    }
+
+    #[test]
+    fn set_pulls() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.record_pulls(true);
+        c.set_pulls(&[(1, Level::High), (3, Level::High), (1, Level::Low)])
+            .unwrap();
+        assert_eq!(c.get_pull(1).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(3).unwrap(), Level::High);
+        assert_eq!(c.stats()[1].pulls, 2);
+        assert_eq!(c.pull_history().len(), 3);
+
+        // no pulls are applied if any offset is invalid
+        assert!(c.set_pulls(&[(2, Level::High), (8, Level::High)]).is_err());
+        assert_eq!(c.get_pull(2).unwrap(), Level::Low);
+        assert_eq!(c.stats()[2].pulls, 0);
+    }
+
+    #[test]
+    fn poll_until() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let (interval, timeout) = (Duration::from_millis(1), Duration::from_millis(500));
+        let took = c
+            .poll_until(1, |l| l == Level::Low, interval, timeout)
+            .unwrap();
+        assert!(took < timeout);
+
+        let t = thread::spawn({
+            let c = c.share();
+            move || {
+                thread::sleep(Duration::from_millis(20));
+                c.pullup(1).unwrap();
+                c.pullup(2).unwrap();
+            }
+        });
+        let took = c
+            .poll_until(1, |l| l == Level::High, interval, timeout)
+            .unwrap();
+        assert!(took >= Duration::from_millis(20));
+        assert!(took < timeout);
+        t.join().unwrap();
+        c.poll_lines_until(
+            [1, 2],
+            |levels| levels == [(1, Level::High), (2, Level::High)],
+            interval,
+            timeout,
+        )
+        .unwrap();
+
+        // times out, after a final check at the timeout
+        let start = Instant::now();
+        let mut checks = 0;
+        let err = c
+            .poll_until(
+                3,
+                |l| {
+                    checks += 1;
+                    l == Level::High
+                },
+                Duration::from_secs(10),
+                Duration::from_millis(20),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::IoError(e) if e.kind() == std::io::ErrorKind::TimedOut));
+        assert_eq!(checks, 2);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(c.poll_until(8, |_| true, interval, timeout).is_err());
+    }
+
+    #[test]
+    fn level_conversions() {
+        assert_eq!(Level::from(true), Level::High);
+        assert_eq!(Level::from(false), Level::Low);
+        assert!(bool::from(Level::High));
+        assert!(!bool::from(Level::Low));
+        assert_eq!(!Level::High, Level::Low);
+        assert_eq!(!Level::Low, Level::High);
+        assert_eq!(Level::High.to_string(), "high");
+        assert_eq!(Level::Low.to_string(), "low");
+        for (s, level) in [
+            ("high", Level::High),
+            ("LOW", Level::Low),
+            ("1", Level::High),
+        ] {
+            assert_eq!(s.parse::<Level>().unwrap(), level);
+        }
+        assert!(matches!(
+            "on".parse::<Level>(),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn direction_conversions() {
+        for d in [
+            Direction::Input,
+            Direction::OutputLow,
+            Direction::OutputHigh,
+        ] {
+            assert_eq!(d.to_string().parse::<Direction>().unwrap(), d);
+        }
+        assert_eq!(Direction::OutputHigh.to_string(), "output-high");
+        assert_eq!("Input".parse::<Direction>().unwrap(), Direction::Input);
+        assert!(matches!(
+            "output".parse::<Direction>(),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
 }
@@ -0,0 +1,289 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::fs::Fs;
+use crate::{Bank, Error, Result};
+use std::ffi::CStr;
+use std::path::Path;
+
+/// The maximum number of lines on a gpiochip, as gpiolib stores the number of
+/// lines in a u16.
+const MAX_LINES_PER_CHIP: usize = u16::MAX as usize;
+
+/// The maximum number of gpiochips, being the number of character devices
+/// reserved by gpiolib.
+const MAX_CHIPS: usize = 256;
+
+/// The kernel limits on the gpiochips that may be simulated.
+///
+/// The limits are detected by [`Kernel::limits`], and checked by
+/// [`Builder::validate`] prior to taking a simulator live, so a configuration
+/// exceeding them fails with [`Error::LimitExceeded`] rather than being
+/// rejected opaquely by the kernel.
+///
+/// [`Builder::validate`]: crate::Builder::validate
+/// [`Kernel::limits`]: crate::Kernel::limits
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KernelLimits {
+    /// The maximum number of lines on a chip.
+    pub max_lines_per_chip: usize,
+
+    /// The maximum number of chips on the system.
+    pub max_chips: usize,
+
+    /// The number of chips currently on the system.
+    pub chips: usize,
+
+    /// The number of global GPIO numbers available for new chips, if limited.
+    ///
+    /// Kernels prior to 6.2 allocate each line a number from a global space of
+    /// `ARCH_NR_GPIOS`, which depends on the architecture and configuration,
+    /// e.g. 1024 on x86_64, and is exhausted by large simulators.
+    /// The size of the space is not exposed by the kernel, but numbers are
+    /// allocated downwards from the top of the space, so it is taken to end
+    /// at the highest number allocated to a chip.
+    /// This is `None` for later kernels, or if the numbers in use cannot be
+    /// determined as the kernel lacks the GPIO sysfs interface or has no
+    /// chips.
+    pub free_gpio_numbers: Option<usize>,
+}
+
+impl KernelLimits {
+    /// Detect the limits of the running kernel.
+    pub(crate) fn detect(fs: &dyn Fs) -> KernelLimits {
+        KernelLimits::detect_for(fs, &kernel_release())
+    }
+
+    fn detect_for(fs: &dyn Fs, release: &str) -> KernelLimits {
+        let chips = fs
+            .list_dir(Path::new("/sys/bus/gpio/devices"))
+            .map_or(0, |d| {
+                d.iter().filter(|n| n.starts_with("gpiochip")).count()
+            });
+        let free_gpio_numbers = if is_legacy_numbering(release) {
+            legacy_numbers(fs).map(|(top, used)| top.saturating_sub(used))
+        } else {
+            None
+        };
+        KernelLimits {
+            max_lines_per_chip: MAX_LINES_PER_CHIP,
+            max_chips: MAX_CHIPS,
+            chips,
+            free_gpio_numbers,
+        }
+    }
+
+    /// Check that the banks can be simulated within the limits.
+    pub fn check(&self, banks: &[Bank]) -> Result<()> {
+        for bank in banks {
+            if bank.num_lines as usize > self.max_lines_per_chip {
+                return Err(Error::LimitExceeded(format!(
+                    "bank {:?} has {} lines, but chips are limited to {}",
+                    bank.label, bank.num_lines, self.max_lines_per_chip
+                )));
+            }
+        }
+        let free = self.max_chips.saturating_sub(self.chips);
+        if banks.len() > free {
+            return Err(Error::LimitExceeded(format!(
+                "{} chips requested, but only {} of {} are available",
+                banks.len(),
+                free,
+                self.max_chips
+            )));
+        }
+        if let Some(free) = self.free_gpio_numbers {
+            let lines: usize = banks.iter().map(|b| b.num_lines as usize).sum();
+            if lines > free {
+                return Err(Error::LimitExceeded(format!(
+                    "{} lines requested, but only {} GPIO numbers are available",
+                    lines, free
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The release of the running kernel, e.g. "6.1.0-13-amd64".
+fn kernel_release() -> String {
+    // SAFETY: uts is a valid utsname to be written.
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return String::new();
+    }
+    // SAFETY: uname nul terminates the release.
+    unsafe { CStr::from_ptr(uts.release.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+// The major and minor version of the running kernel, if known.
+pub(crate) fn kernel_version() -> Option<(u32, u32)> {
+    parse_version(&kernel_release())
+}
+
+// The major and minor version from a kernel release.
+fn parse_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|p| p.parse::<u32>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor))) => Some((major, minor)),
+        _ => None,
+    }
+}
+
+// Returns true if the kernel release predates 6.2, so allocates GPIO numbers
+// from the legacy global space.
+fn is_legacy_numbering(release: &str) -> bool {
+    parse_version(release).map_or(false, |v| v < (6, 2))
+}
+
+// The top of the legacy GPIO number space, being the end of the highest range
+// allocated to a chip, and the number of numbers allocated to chips, as
+// reported by the GPIO sysfs interface.
+fn legacy_numbers(fs: &dyn Fs) -> Option<(usize, usize)> {
+    let class = Path::new("/sys/class/gpio");
+    let mut top = 0;
+    let mut used = 0;
+    for entry in fs.list_dir(class).ok()? {
+        if !entry.starts_with("gpiochip") {
+            continue;
+        }
+        let dir = class.join(&entry);
+        let attr = |name: &str| -> Option<usize> {
+            fs.read_to_string(&dir.join(name)).ok()?.trim().parse().ok()
+        };
+        let (base, ngpio) = (attr("base")?, attr("ngpio")?);
+        top = top.max(base + ngpio);
+        used += ngpio;
+    }
+    if top == 0 {
+        return None;
+    }
+    Some((top, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FsDir;
+    use std::collections::BTreeMap;
+    use std::io;
+    use std::path::PathBuf;
+
+    #[derive(Debug, Default)]
+    struct SysFs(BTreeMap<PathBuf, String>);
+
+    impl Fs for SysFs {
+        fn create_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn remove_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.keys().any(|p| p.starts_with(path))
+        }
+
+        fn write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.0
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+            if !self.exists(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let mut entries: Vec<String> = self
+                .0
+                .keys()
+                .filter_map(|p| p.strip_prefix(path).ok()?.iter().next())
+                .map(|n| n.to_string_lossy().into_owned())
+                .collect();
+            entries.dedup();
+            Ok(entries)
+        }
+
+        fn open_dir(&self, _path: &Path) -> io::Result<Box<dyn FsDir>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn detect() {
+        let mut fs = SysFs::default();
+        // as per x86_64, with a space of 1024
+        for (chip, base, ngpio) in [("gpiochip0", 0, 100), ("gpiochip964", 964, 60)] {
+            let dir = Path::new("/sys/class/gpio").join(chip);
+            fs.0.insert(dir.join("base"), format!("{}\n", base));
+            fs.0.insert(dir.join("ngpio"), format!("{}\n", ngpio));
+            let dir = Path::new("/sys/bus/gpio/devices").join(chip);
+            fs.0.insert(dir.join("uevent"), String::new());
+        }
+        let limits = KernelLimits::detect_for(&fs, "5.15.0-91-generic");
+        assert_eq!(limits.max_lines_per_chip, 65535);
+        assert_eq!(limits.max_chips, 256);
+        assert_eq!(limits.chips, 2);
+        assert_eq!(limits.free_gpio_numbers, Some(864));
+        assert!(limits.check(&[Bank::new(600, "wide")]).is_ok());
+        assert!(limits.check(&[Bank::new(900, "wider")]).is_err());
+
+        let limits = KernelLimits::detect_for(&fs, "6.2.0");
+        assert_eq!(limits.free_gpio_numbers, None);
+
+        let limits = KernelLimits::detect_for(&SysFs::default(), "6.1.0");
+        assert_eq!(limits.chips, 0);
+        assert_eq!(limits.free_gpio_numbers, None);
+
+        assert!(is_legacy_numbering("4.19.2"));
+        assert!(!is_legacy_numbering("6.10.1"));
+        assert!(!is_legacy_numbering(""));
+        assert_eq!(parse_version("5.10.0-28-arm64"), Some((5, 10)));
+        assert_eq!(parse_version("6"), None);
+    }
+
+    #[test]
+    fn check() {
+        let limits = KernelLimits {
+            max_lines_per_chip: 65535,
+            max_chips: 256,
+            chips: 250,
+            free_gpio_numbers: None,
+        };
+        assert!(limits.check(&[Bank::new(65535, "wide")]).is_ok());
+        let err = limits.check(&[Bank::new(65536, "wider")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Kernel limit exceeded: bank \"wider\" has 65536 lines, but chips are limited to 65535"
+        );
+        let banks: Vec<Bank> = (0..7).map(|i| Bank::new(8, format!("c{}", i))).collect();
+        assert!(limits.check(&banks[..6]).is_ok());
+        let err = limits.check(&banks).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Kernel limit exceeded: 7 chips requested, but only 6 of 256 are available"
+        );
+
+        let limits = KernelLimits {
+            free_gpio_numbers: Some(40),
+            ..limits
+        };
+        assert!(limits.check(&banks[..5]).is_ok());
+        let err = limits.check(&banks[..6]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Kernel limit exceeded: 48 lines requested, but only 40 GPIO numbers are available"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Bank, Direction, Hog, Level, Offset};
+
+/// The configuration of a single line of a [`Bank`].
+///
+/// The bank stores a config for each line with a name, hog, pull or tag,
+/// as returned by [`Bank::line_config`] and [`Bank::line_configs`].
+/// The config covers the settings of the line only, not those of the bank,
+/// such as the label and number of lines.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(default))]
+pub struct LineConfig {
+    /// The name of the line, if any.
+    pub name: Option<String>,
+
+    /// The hog on the line, if any.
+    pub hog: Option<Hog>,
+
+    /// The pull applied when the simulator goes live, if any.
+    pub pull: Option<Level>,
+
+    /// The tags assigned to the line.
+    pub tags: Vec<String>,
+}
+
+/// The config of a line with no configuration.
+pub(crate) static EMPTY_LINE: LineConfig = LineConfig {
+    name: None,
+    hog: None,
+    pull: None,
+    tags: Vec::new(),
+};
+
+impl LineConfig {
+    /// Returns true if the line has no configuration.
+    pub fn is_empty(&self) -> bool {
+        *self == LineConfig::default()
+    }
+}
+
+/// A builder for the configuration of a single line of a [`Bank`].
+///
+/// Returned by [`Bank::line`], allowing the settings for a line to be chained:
+///
+/// ```
+/// use gpiosim::{Bank, Direction, Level};
+///
+/// let mut bank = Bank::new(8, "left");
+/// bank.line(3).name("led").pull(Level::High).tag("status");
+/// bank.line(4).name("reset").hog("bootloader", Direction::OutputLow);
+/// let cfg = bank.line_config(3);
+/// assert_eq!(cfg.name.as_deref(), Some("led"));
+/// assert_eq!(cfg.pull, Some(Level::High));
+/// ```
+#[derive(Debug)]
+pub struct LineBuilder<'a> {
+    bank: &'a mut Bank,
+    offset: Offset,
+}
+
+impl<'a> LineBuilder<'a> {
+    pub(crate) fn new(bank: &'a mut Bank, offset: Offset) -> LineBuilder<'a> {
+        LineBuilder { bank, offset }
+    }
+
+    /// The offset of the line.
+    pub fn offset(&self) -> Offset {
+        self.offset
+    }
+
+    /// Assign a name to the line.
+    pub fn name<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.bank.name(self.offset, name);
+        self
+    }
+
+    /// Add a hog on the line.
+    pub fn hog<N: Into<String>>(&mut self, consumer: N, direction: Direction) -> &mut Self {
+        self.bank.hog(self.offset, consumer, direction);
+        self
+    }
+
+    /// Set the pull on the line when the simulator goes live.
+    pub fn pull(&mut self, pull: Level) -> &mut Self {
+        self.bank.pull(self.offset, pull);
+        self
+    }
+
+    /// Assign a tag to the line.
+    pub fn tag<N: Into<String>>(&mut self, tag: N) -> &mut Self {
+        self.bank.tag(self.offset, tag);
+        self
+    }
+
+    /// Apply a configuration to the line.
+    ///
+    /// The name, hog and pull of the config, if set, replace those of the
+    /// line, and the tags are added to those of the line.
+    pub fn merge(&mut self, cfg: &LineConfig) -> &mut Self {
+        if let Some(name) = &cfg.name {
+            self.name(name.clone());
+        }
+        if let Some(hog) = &cfg.hog {
+            self.hog(hog.consumer.clone(), hog.direction);
+        }
+        if let Some(pull) = cfg.pull {
+            self.pull(pull);
+        }
+        for tag in &cfg.tags {
+            self.tag(tag.clone());
+        }
+        self
+    }
+
+    /// The configuration of the line.
+    pub fn config(&self) -> &LineConfig {
+        self.bank.line_config(self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line() {
+        let mut bank = Bank::new(8, "left");
+        assert!(bank.line_config(2).is_empty());
+        let cfg = bank
+            .line(2)
+            .name("led")
+            .hog("blinker", Direction::OutputHigh)
+            .pull(Level::High)
+            .tag("status")
+            .tag("status")
+            .config()
+            .clone();
+        assert_eq!(
+            cfg,
+            LineConfig {
+                name: Some("led".into()),
+                hog: Some(Hog {
+                    consumer: "blinker".into(),
+                    direction: Direction::OutputHigh
+                }),
+                pull: Some(Level::High),
+                tags: vec!["status".into()],
+            }
+        );
+        assert_eq!(bank.line_config(2), &cfg);
+        assert_eq!(bank.names()[&2], "led");
+        assert_eq!(bank.hogs()[&2].consumer, "blinker");
+        assert!(bank.line_config(3).is_empty());
+
+        let lines = bank.line_configs();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0], (2, &cfg));
+
+        let mut other = Bank::new(4, "right");
+        other.line(1).name("old").tag("debug").pull(Level::Low);
+        other.line(1).merge(&cfg).merge(&LineConfig::default());
+        let merged = other.line_config(1).clone();
+        assert_eq!(merged.name.as_deref(), Some("led"));
+        assert_eq!(merged.hog, cfg.hog);
+        assert_eq!(merged.pull, Some(Level::High));
+        assert_eq!(merged.tags, ["debug", "status"]);
+
+        bank.unname(2).unpull(2);
+        let cfg = bank.line_config(2).clone();
+        assert_eq!(cfg.name, None);
+        assert_eq!(cfg.pull, None);
+        assert!(cfg.hog.is_some());
+    }
+}
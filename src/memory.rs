@@ -0,0 +1,325 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Backend, Bank, Chip, Device, Direction, Error, Level, Offset, Result};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A pure in-memory backend.
+///
+/// The simulators are not visible to the kernel, so there is no gpiochip to
+/// request lines from, but the simulator may be driven by tests that do not
+/// have root permissions or the gpio-sim module available.
+///
+/// The consumer side of a line, which would otherwise be driven through the
+/// GPIO uAPI, is emulated using [`drive`].
+///
+/// ```
+/// # use gpiosim::Result;
+/// # fn main() -> Result<()> {
+/// use gpiosim::{Bank, Level, Memory};
+///
+/// let mem = Memory::new();
+/// let sim = gpiosim::builder()
+///     .with_backend(mem.clone())
+///     .with_bank(&Bank::new(8, "left"))
+///     .live()?;
+/// let c = &sim.chips()[0];
+/// mem.drive(c, 3, Some(Level::High))?;
+/// assert_eq!(c.get_level(3)?, Level::High);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`drive`]: Memory::drive
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    /// The live simulators, keyed by name.
+    sims: Arc<Mutex<HashMap<String, Arc<MemoryDevice>>>>,
+}
+
+impl Memory {
+    /// Create an in-memory backend.
+    pub fn new() -> Memory {
+        Memory::default()
+    }
+
+    /// Drive a line as if it were an output requested by a consumer.
+    ///
+    /// A level of `None` releases the line, as if the consumer had released the
+    /// line or reconfigured it as an input, so the level follows the pull.
+    pub fn drive(&self, chip: &Chip, offset: Offset, level: Option<Level>) -> Result<()> {
+        let dev = self
+            .sims
+            .lock()
+            .unwrap()
+            .values()
+            .find(|d| d.dev_name == chip.dev_name)
+            .cloned()
+            .ok_or_else(|| Error::IoError(io::ErrorKind::NotFound.into()))?;
+        let mut lines = dev.lines(chip.bank, offset)?;
+        lines[offset as usize].driven = level;
+        Ok(())
+    }
+}
+
+impl Backend for Memory {
+    fn live(&self, name: &str, banks: &[Bank]) -> Result<Arc<dyn Device>> {
+        static DEV_COUNT: AtomicU32 = AtomicU32::new(0);
+        static CHIP_COUNT: AtomicU32 = AtomicU32::new(0);
+
+        let mut sims = self.sims.lock().unwrap();
+        if sims.contains_key(name) {
+            return Err(Error::SimulatorExists(name.into()));
+        }
+        let chips = banks
+            .iter()
+            .map(|b| {
+                let mut lines = vec![Line::default(); b.num_lines as usize];
+                for (offset, hog) in b.hogs() {
+                    if let Some(line) = lines.get_mut(offset as usize) {
+                        line.driven = match hog.direction {
+                            Direction::Input => None,
+                            Direction::OutputLow => Some(Level::Low),
+                            Direction::OutputHigh => Some(Level::High),
+                        };
+                    }
+                }
+                MemoryChip {
+                    chip_name: format!(
+                        "gpiochip-mem{}",
+                        CHIP_COUNT.fetch_add(1, Ordering::Relaxed)
+                    ),
+                    lines: Mutex::new(lines),
+                }
+            })
+            .collect();
+        let dev = Arc::new(MemoryDevice {
+            name: name.into(),
+            dev_name: format!("gpio-sim-mem.{}", DEV_COUNT.fetch_add(1, Ordering::Relaxed)),
+            chips,
+            sims: self.sims.clone(),
+            released: AtomicBool::new(false),
+        });
+        sims.insert(name.into(), dev.clone());
+        Ok(dev)
+    }
+}
+
+/// A live in-memory simulator.
+#[derive(Debug)]
+struct MemoryDevice {
+    /// The name of the simulator.
+    name: String,
+
+    /// The unique name of the device.
+    dev_name: String,
+
+    /// The simulated chips.
+    chips: Vec<MemoryChip>,
+
+    /// The registry of live simulators for the backend.
+    sims: Arc<Mutex<HashMap<String, Arc<MemoryDevice>>>>,
+
+    /// Set once released, so the simulator remains in the registry.
+    released: AtomicBool,
+}
+
+#[derive(Debug)]
+struct MemoryChip {
+    /// The unique name of the chip.
+    chip_name: String,
+
+    /// The state of each line on the chip.
+    lines: Mutex<Vec<Line>>,
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+    /// The simulated external pull.
+    pull: Level,
+
+    /// The level the line is being driven to, if it is an output.
+    driven: Option<Level>,
+}
+
+impl Default for Line {
+    fn default() -> Line {
+        Line {
+            pull: Level::Low,
+            driven: None,
+        }
+    }
+}
+
+impl MemoryDevice {
+    // Lock the lines of a chip, checking the offset is valid and the chip is still live.
+    fn lines(&self, bank: usize, offset: Offset) -> Result<MutexGuard<'_, Vec<Line>>> {
+        let lines = self.chips[bank].lines.lock().unwrap();
+        if !self.is_live() {
+            return Err(Error::SimRemoved);
+        }
+        if offset as usize >= lines.len() {
+            return Err(Error::IoError(io::ErrorKind::NotFound.into()));
+        }
+        Ok(lines)
+    }
+
+    fn is_live(&self) -> bool {
+        match self.sims.lock().unwrap().get(&self.name) {
+            Some(d) => std::ptr::eq(d.as_ref(), self),
+            None => false,
+        }
+    }
+}
+
+impl Device for MemoryDevice {
+    fn dev_name(&self) -> &str {
+        &self.dev_name
+    }
+
+    fn chip_name(&self, bank: usize) -> &str {
+        &self.chips[bank].chip_name
+    }
+
+    fn set_pull(&self, bank: usize, offset: Offset, pull: Level) -> Result<()> {
+        self.lines(bank, offset)?[offset as usize].pull = pull;
+        Ok(())
+    }
+
+    fn get_pull(&self, bank: usize, offset: Offset) -> Result<Level> {
+        Ok(self.lines(bank, offset)?[offset as usize].pull)
+    }
+
+    fn get_level(&self, bank: usize, offset: Offset) -> Result<Level> {
+        let line = &self.lines(bank, offset)?[offset as usize];
+        Ok(line.driven.unwrap_or(line.pull))
+    }
+
+    fn is_alive(&self) -> bool {
+        self.is_live()
+    }
+
+    fn release(&self) {
+        self.released.store(true, Ordering::Relaxed);
+    }
+
+    fn remove(&self) {
+        if self.is_live() && !self.released.load(Ordering::Relaxed) {
+            self.sims.lock().unwrap().remove(&self.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder;
+
+    #[test]
+    fn live() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_name("mem")
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live()
+            .unwrap();
+        assert_eq!(sim.name(), "mem");
+        let chips = sim.chips();
+        assert_eq!(chips.len(), 2);
+        assert_eq!(chips[0].dev_name, chips[1].dev_name);
+        assert_ne!(chips[0].chip_name, chips[1].chip_name);
+        assert_ne!(chips[0].dev_path(), chips[1].dev_path());
+        assert_eq!(chips[1].config().label, "right");
+        assert_eq!(chips[0].sysfs_path(), None);
+        assert_eq!(sim.configfs_path(), None);
+    }
+
+    #[test]
+    fn existing_name() {
+        let mem = Memory::new();
+        let mut builder = builder();
+        builder
+            .with_name("mem")
+            .with_backend(mem)
+            .with_bank(&Bank::new(8, "left"));
+        let s1 = builder.live().unwrap();
+        let s2 = builder.live();
+        assert!(matches!(s2, Err(Error::SimulatorExists(_))));
+        drop(s1);
+        assert!(builder.live().is_ok());
+    }
+
+    #[test]
+    fn pull() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(c.get_pull(5).unwrap(), Level::Low);
+        assert_eq!(c.get_level(5).unwrap(), Level::Low);
+        c.pullup(5).unwrap();
+        assert_eq!(c.get_pull(5).unwrap(), Level::High);
+        assert_eq!(c.get_level(5).unwrap(), Level::High);
+        assert_eq!(c.toggle(5).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(5).unwrap(), Level::Low);
+        assert!(c.pullup(8).is_err());
+        assert!(c.get_pull(8).is_err());
+    }
+
+    #[test]
+    fn drive() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(Bank::new(8, "left").hog(2, "hogster", Direction::OutputHigh))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(c.get_level(2).unwrap(), Level::High);
+        mem.drive(c, 3, Some(Level::High)).unwrap();
+        assert_eq!(c.get_level(3).unwrap(), Level::High);
+        assert_eq!(c.get_pull(3).unwrap(), Level::Low);
+        mem.drive(c, 3, None).unwrap();
+        assert_eq!(c.get_level(3).unwrap(), Level::Low);
+        assert!(mem.drive(c, 8, None).is_err());
+    }
+
+    #[test]
+    fn removed() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let dev = sim.chips()[0].dev.clone();
+        assert!(sim.is_alive());
+        drop(sim);
+        assert!(!dev.is_alive());
+        assert!(matches!(
+            dev.set_pull(0, 1, Level::High),
+            Err(Error::SimRemoved)
+        ));
+        assert!(mem.sims.lock().unwrap().is_empty());
+
+        // removed while the sim is still held
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.dev.remove();
+        assert!(!sim.is_alive());
+        assert!(matches!(c.pullup(1), Err(Error::SimRemoved)));
+        assert!(matches!(c.get_level(1), Err(Error::SimRemoved)));
+    }
+}
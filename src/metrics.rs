@@ -0,0 +1,372 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Prometheus metrics for long running simulations.
+//!
+//! A [`Metrics`] registry collects the line statistics and current state of
+//! registered chips, along with any gauges set by the test, and renders them in
+//! the Prometheus text exposition format, either on demand or via an HTTP
+//! endpoint provided by [`Metrics::serve`].
+//!
+//! The generators, such as [`Storm`], [`Sequence`] and [`Chip::play`], do not
+//! export their frequencies themselves, so those must be set by the test
+//! using [`Metrics::set_gauge`].
+//!
+//! [`Storm`]: crate::Storm
+//! [`Sequence`]: crate::Sequence
+
+use crate::{Chip, Counters, Device, Level, Sim};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A registry of metrics to be exported to Prometheus.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<Registry>>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    chips: Vec<ChipMetrics>,
+
+    /// Gauges keyed by name, then by rendered labels.
+    gauges: BTreeMap<String, Gauge>,
+}
+
+#[derive(Debug)]
+struct ChipMetrics {
+    chip_name: String,
+    label: String,
+    num_lines: u32,
+    dev: Arc<dyn Device>,
+    bank: usize,
+    stats: Arc<[Counters]>,
+}
+
+// The name, help, and value accessor of a line metric.
+type Metric<F> = (&'static str, &'static str, F);
+type CounterFn = fn(&Counters) -> u64;
+type GaugeFn = fn(&ChipMetrics, u32) -> Option<Level>;
+
+#[derive(Debug, Default)]
+struct Gauge {
+    help: String,
+    values: BTreeMap<String, f64>,
+}
+
+impl Metrics {
+    /// Create an empty registry.
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    /// Add the lines of a chip to the metrics.
+    ///
+    /// A chip with the same name as a registered chip replaces it, so
+    /// registering a chip again does not duplicate its lines, and a chip
+    /// reusing the name of one that has been removed takes its place.
+    ///
+    /// Chips that are no longer live are dropped from the registry, releasing
+    /// their simulators.
+    pub fn register(&self, chip: &Chip) {
+        let mut reg = self.inner.lock().unwrap();
+        reg.chips
+            .retain(|c| c.chip_name != chip.chip_name && c.dev.is_alive());
+        reg.chips.push(ChipMetrics {
+            chip_name: chip.chip_name.clone(),
+            label: chip.cfg.label.clone(),
+            num_lines: chip.cfg.num_lines,
+            dev: chip.dev.clone(),
+            bank: chip.bank,
+            stats: chip.stats.clone(),
+        });
+    }
+
+    /// Add the lines of all the chips of a simulator to the metrics.
+    pub fn register_sim(&self, sim: &Sim) {
+        for c in sim.chips() {
+            self.register(c);
+        }
+    }
+
+    /// Set the value of a gauge.
+    ///
+    /// Gauges provide metrics not tracked by the registry itself, such as the
+    /// frequency of a signal being generated by the test.
+    /// The name should be a valid Prometheus metric name.
+    pub fn set_gauge(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        let mut reg = self.inner.lock().unwrap();
+        let gauge = reg.gauges.entry(name.into()).or_default();
+        gauge.help = help.into();
+        gauge.values.insert(render_labels(labels), value);
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    ///
+    /// Chips that are no longer live are dropped from the registry, so their
+    /// lines are omitted.
+    pub fn render(&self) -> String {
+        let mut reg = self.inner.lock().unwrap();
+        reg.chips.retain(|c| c.dev.is_alive());
+        let mut out = String::new();
+        let counters: [Metric<CounterFn>; 4] = [
+            (
+                "gpiosim_line_pulls_total",
+                "Number of times the pull was set.",
+                |c| c.pulls.load(Ordering::Relaxed),
+            ),
+            (
+                "gpiosim_line_toggles_total",
+                "Number of times the pull was toggled.",
+                |c| c.toggles.load(Ordering::Relaxed),
+            ),
+            (
+                "gpiosim_line_pull_reads_total",
+                "Number of times the pull was read.",
+                |c| c.pull_reads.load(Ordering::Relaxed),
+            ),
+            (
+                "gpiosim_line_level_reads_total",
+                "Number of times the level was read.",
+                |c| c.level_reads.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, help, "counter");
+            for c in &reg.chips {
+                for (offset, stats) in c.stats.iter().enumerate() {
+                    let labels = c.labels(offset);
+                    let _ = writeln!(out, "{}{} {}", name, labels, value(stats));
+                }
+            }
+        }
+        let gauges: [Metric<GaugeFn>; 2] = [
+            (
+                "gpiosim_line_pull",
+                "The current pull on the line (1 for up).",
+                |c, o| c.dev.get_pull(c.bank, o).ok(),
+            ),
+            (
+                "gpiosim_line_level",
+                "The current level of the line (1 for high).",
+                |c, o| c.dev.get_level(c.bank, o).ok(),
+            ),
+        ];
+        for (name, help, value) in gauges {
+            header(&mut out, name, help, "gauge");
+            for c in &reg.chips {
+                for offset in 0..c.num_lines {
+                    if let Some(level) = value(c, offset) {
+                        let value = match level {
+                            Level::High => 1,
+                            Level::Low => 0,
+                        };
+                        let labels = c.labels(offset as usize);
+                        let _ = writeln!(out, "{}{} {}", name, labels, value);
+                    }
+                }
+            }
+        }
+        for (name, gauge) in &reg.gauges {
+            header(&mut out, name, &gauge.help, "gauge");
+            for (labels, value) in &gauge.values {
+                let _ = writeln!(out, "{}{} {}", name, labels, value);
+            }
+        }
+        out
+    }
+
+    /// Serve the metrics via HTTP on the given address.
+    ///
+    /// The metrics are served at any path, though Prometheus conventionally
+    /// scrapes `/metrics`.
+    /// The server runs on a background thread until the returned [`Server`] is dropped.
+    pub fn serve<A: ToSocketAddrs>(&self, addr: A) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let metrics = self.clone();
+        let done = stop.clone();
+        let thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if done.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let _ = metrics.respond(stream);
+                }
+            }
+        });
+        Ok(Server {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        // discard the request headers
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+        let body = self.render();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\n\
+            Content-Type: text/plain; version=0.0.4\r\n\
+            Content-Length: {}\r\n\
+            Connection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+}
+
+impl ChipMetrics {
+    fn labels(&self, offset: usize) -> String {
+        let offset = offset.to_string();
+        render_labels(&[
+            ("chip", &self.chip_name),
+            ("label", &self.label),
+            ("offset", &offset),
+        ])
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| {
+            let v = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", k, v)
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+/// An HTTP server exporting [`Metrics`].
+///
+/// The server is stopped when dropped.
+#[derive(Debug)]
+pub struct Server {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // unblock the accept
+        let _ = TcpStream::connect(self.addr);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::io::Read;
+
+    #[test]
+    fn render() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "le\"ft"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let metrics = Metrics::new();
+        metrics.register_sim(&sim);
+        metrics.register(c);
+        c.pullup(1).unwrap();
+        c.toggle(0).unwrap();
+        mem.drive(c, 0, Some(Level::Low)).unwrap();
+        metrics.set_gauge(
+            "gpiosim_generator_frequency_hz",
+            "The frequency of the generator.",
+            &[("generator", "clock")],
+            1000.0,
+        );
+        let out = metrics.render();
+        let labels = |offset| {
+            format!(
+                "{{chip=\"{}\",label=\"le\\\"ft\",offset=\"{}\"}}",
+                c.chip_name, offset
+            )
+        };
+        for line in [
+            "# TYPE gpiosim_line_pulls_total counter".to_string(),
+            format!("gpiosim_line_pulls_total{} 0", labels(0)),
+            format!("gpiosim_line_pulls_total{} 1", labels(1)),
+            format!("gpiosim_line_toggles_total{} 1", labels(0)),
+            "# TYPE gpiosim_line_level gauge".to_string(),
+            format!("gpiosim_line_pull{} 1", labels(0)),
+            format!("gpiosim_line_level{} 0", labels(0)),
+            format!("gpiosim_line_level{} 1", labels(1)),
+            "gpiosim_generator_frequency_hz{generator=\"clock\"} 1000".to_string(),
+        ] {
+            assert_eq!(
+                out.lines().filter(|&l| l == line).count(),
+                1,
+                "expected one {:?} in\n{}",
+                line,
+                out
+            );
+        }
+        drop(sim);
+        let out = metrics.render();
+        assert!(!out.contains("gpiosim_line_level{"));
+        assert!(!out.contains("gpiosim_line_pulls_total{"));
+        assert!(metrics.inner.lock().unwrap().chips.is_empty());
+    }
+
+    #[test]
+    fn serve() {
+        let metrics = Metrics::new();
+        metrics.set_gauge("answer", "The answer.", &[], 42.0);
+        let server = metrics.serve("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(resp.ends_with("# HELP answer The answer.\n# TYPE answer gauge\nanswer 42\n"));
+        drop(server);
+    }
+}
@@ -0,0 +1,463 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::fs::{Fs, HostFs};
+use crate::kernel::modprobe;
+use crate::{Backend, Bank, Device, Error, KernelLimits, Level, Offset, Result};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// The gpio-mockup chips in debugfs.
+const DEBUGFS_DIR: &str = "/sys/kernel/debug/gpio-mockup";
+
+/// The maximum number of chips the gpio-mockup module can create.
+const MAX_CHIPS: usize = 10;
+
+// Runs modprobe with the given arguments.
+type Modprobe = dyn Fn(&[String]) -> Result<()> + Send + Sync;
+
+/// The backend provided by the legacy Linux **gpio-mockup** kernel module.
+///
+/// This is intended for kernels prior to 5.19, which lack **gpio-sim**, and is
+/// the default backend on those kernels.
+///
+/// The chips are created by loading the module, and the lines manipulated via
+/// *debugfs*, which must be mounted.  As the module is loaded with the
+/// configuration of the chips, only one simulator may be live at a time, and
+/// the module is unloaded when the simulator is removed.
+///
+/// The module is more limited than gpio-sim:
+///  - at most 10 chips may be simulated
+///  - the chips are labelled by the module, e.g. `gpio-mockup-A`, so the
+///    [`Bank`] labels are only used to identify the chips within the simulator
+///  - line names and hogs are not supported
+///  - the pull is not reported by the module, so [`Chip::get_pull`] returns
+///    the last pull applied by the simulator
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Level, Mockup};
+///
+/// let sim = gpiosim::builder()
+///     .with_backend(Mockup::default())
+///     .with_bank(&Bank::new(8, "left"))
+///     .live()?;
+/// let c = &sim.chips()[0];
+/// c.pullup(3)?;
+/// assert_eq!(c.get_level(3)?, Level::High);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Chip::get_pull`]: crate::Chip::get_pull
+#[derive(Clone)]
+pub struct Mockup {
+    /// The filesystem containing debugfs.
+    fs: Arc<dyn Fs>,
+
+    /// Loads and unloads the module.
+    modprobe: Arc<Modprobe>,
+}
+
+impl Default for Mockup {
+    fn default() -> Mockup {
+        Mockup {
+            fs: Arc::new(HostFs),
+            modprobe: Arc::new(|args: &[String]| modprobe(args)),
+        }
+    }
+}
+
+impl fmt::Debug for Mockup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mockup").field("fs", &self.fs).finish()
+    }
+}
+
+impl Mockup {
+    #[cfg(test)]
+    pub(crate) fn with_fs<F>(fs: Arc<dyn Fs>, modprobe: F) -> Mockup
+    where
+        F: Fn(&[String]) -> Result<()> + Send + Sync + 'static,
+    {
+        Mockup {
+            fs,
+            modprobe: Arc::new(modprobe),
+        }
+    }
+
+    /// Returns true if the running kernel predates gpio-sim, so simulators
+    /// should be provided by gpio-mockup.
+    pub(crate) fn is_preferred() -> bool {
+        crate::limits::kernel_version().map_or(false, |v| v < (5, 19))
+    }
+
+    // The gpiochips in debugfs, in chip number order.
+    fn chips(&self) -> io::Result<Vec<String>> {
+        let mut chips: Vec<(u32, String)> = self
+            .fs
+            .list_dir(Path::new(DEBUGFS_DIR))?
+            .into_iter()
+            .filter_map(|n| Some((n.strip_prefix("gpiochip")?.parse().ok()?, n)))
+            .collect();
+        chips.sort_unstable();
+        Ok(chips.into_iter().map(|(_, n)| n).collect())
+    }
+}
+
+impl Backend for Mockup {
+    fn live(&self, _name: &str, banks: &[Bank]) -> Result<Arc<dyn Device>> {
+        self.validate(banks)?;
+        if self.fs.exists(Path::new(DEBUGFS_DIR)) {
+            return Err(Error::SimulatorExists("gpio-mockup".into()));
+        }
+        let ranges: Vec<String> = banks
+            .iter()
+            .map(|b| format!("-1,{}", b.num_lines))
+            .collect();
+        (self.modprobe)(&[
+            "gpio-mockup".into(),
+            format!("gpio_mockup_ranges={}", ranges.join(",")),
+        ])?;
+        // the debugfs entries are added as each chip is probed
+        let mut chips = Vec::new();
+        for _ in 0..10 {
+            chips = self.chips().unwrap_or_default();
+            if chips.len() == banks.len() {
+                break;
+            }
+            sleep(Duration::from_millis(100));
+        }
+        let found = chips.len() == banks.len();
+        let dev = MockupDevice {
+            fs: self.fs.clone(),
+            modprobe: self.modprobe.clone(),
+            chips,
+            pulls: Mutex::new(
+                banks
+                    .iter()
+                    .map(|b| vec![Level::Low; b.num_lines as usize])
+                    .collect(),
+            ),
+            removed: AtomicBool::new(false),
+            owned: AtomicBool::new(true),
+        };
+        if !found {
+            // dropping the device unloads the module
+            return Err(Error::UnexpectedValue(format!(
+                "gpio-mockup chips not found in {}, so debugfs may not be mounted",
+                DEBUGFS_DIR
+            )));
+        }
+        Ok(Arc::new(dev))
+    }
+
+    fn validate(&self, banks: &[Bank]) -> Result<()> {
+        if banks.len() > MAX_CHIPS {
+            return Err(Error::LimitExceeded(format!(
+                "{} chips requested, but gpio-mockup is limited to {}",
+                banks.len(),
+                MAX_CHIPS
+            )));
+        }
+        for bank in banks {
+            let lines = bank.line_configs();
+            if lines
+                .iter()
+                .any(|(_, cfg)| cfg.name.is_some() || cfg.hog.is_some())
+            {
+                return Err(Error::InvalidConfig(format!(
+                    "bank {:?} has line names or hogs, which gpio-mockup does not support",
+                    bank.label
+                )));
+            }
+        }
+        KernelLimits::detect(self.fs.as_ref()).check(banks)
+    }
+}
+
+/// A live gpio-mockup simulator.
+struct MockupDevice {
+    /// The filesystem containing debugfs.
+    fs: Arc<dyn Fs>,
+
+    /// Unloads the module.
+    modprobe: Arc<Modprobe>,
+
+    /// The names of the gpiochips, in bank order.
+    chips: Vec<String>,
+
+    /// The pull last applied to each line.
+    pulls: Mutex<Vec<Vec<Level>>>,
+
+    /// Set once the module has been unloaded.
+    removed: AtomicBool,
+
+    /// Set while the simulator is to be removed when dropped.
+    owned: AtomicBool,
+}
+
+impl fmt::Debug for MockupDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockupDevice")
+            .field("chips", &self.chips)
+            .field("removed", &self.removed)
+            .finish()
+    }
+}
+
+impl MockupDevice {
+    // The debugfs file for a line, checking the offset is valid.
+    fn line_path(&self, bank: usize, offset: Offset) -> Result<PathBuf> {
+        if offset as usize >= self.pulls.lock().unwrap()[bank].len() {
+            return Err(Error::IoError(io::ErrorKind::NotFound.into()));
+        }
+        Ok(Path::new(DEBUGFS_DIR)
+            .join(&self.chips[bank])
+            .join(offset.to_string()))
+    }
+
+    // Return SimRemoved if a debugfs access failed as the sim has been removed.
+    fn debugfs_error(&self, e: io::Error) -> Error {
+        if self.is_alive() {
+            Error::IoError(e)
+        } else {
+            Error::SimRemoved
+        }
+    }
+
+    fn unload(&self) {
+        if !self.removed.swap(true, Ordering::Relaxed) && self.owned.load(Ordering::Relaxed) {
+            let _ = (self.modprobe)(&["-r".into(), "gpio-mockup".into()]);
+        }
+    }
+}
+
+impl Device for MockupDevice {
+    fn dev_name(&self) -> &str {
+        "gpio-mockup"
+    }
+
+    fn chip_name(&self, bank: usize) -> &str {
+        &self.chips[bank]
+    }
+
+    fn set_pull(&self, bank: usize, offset: Offset, pull: Level) -> Result<()> {
+        let path = self.line_path(bank, offset)?;
+        let value = match pull {
+            Level::Low => "0",
+            Level::High => "1",
+        };
+        self.fs
+            .write(&path, value.as_bytes())
+            .map_err(|e| self.debugfs_error(e))?;
+        self.pulls.lock().unwrap()[bank][offset as usize] = pull;
+        Ok(())
+    }
+
+    fn get_pull(&self, bank: usize, offset: Offset) -> Result<Level> {
+        self.line_path(bank, offset)?;
+        if !self.is_alive() {
+            return Err(Error::SimRemoved);
+        }
+        Ok(self.pulls.lock().unwrap()[bank][offset as usize])
+    }
+
+    fn get_level(&self, bank: usize, offset: Offset) -> Result<Level> {
+        let path = self.line_path(bank, offset)?;
+        let val = self
+            .fs
+            .read_to_string(&path)
+            .map_err(|e| self.debugfs_error(e))?;
+        match val.trim() {
+            "0" => Ok(Level::Low),
+            "1" => Ok(Level::High),
+            _ => Err(Error::UnexpectedValue(val)),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        !self.removed.load(Ordering::Relaxed) && self.fs.exists(Path::new(DEBUGFS_DIR))
+    }
+
+    fn release(&self) {
+        self.owned.store(false, Ordering::Relaxed);
+    }
+
+    fn remove(&self) {
+        self.unload();
+    }
+}
+
+impl Drop for MockupDevice {
+    fn drop(&mut self) {
+        self.unload();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder;
+    use crate::fs::FsDir;
+    use std::collections::BTreeMap;
+
+    // A filesystem that emulates the gpio-mockup debugfs interface, with the
+    // chips created by the module load recorded in the fake modprobe.
+    #[derive(Debug, Default)]
+    struct FakeFs(Mutex<BTreeMap<PathBuf, String>>);
+
+    impl FakeFs {
+        fn modprobe(&self, args: &[String]) -> Result<()> {
+            let mut files = self.0.lock().unwrap();
+            if args[0] == "-r" {
+                files.clear();
+                return Ok(());
+            }
+            let ranges = args[1].strip_prefix("gpio_mockup_ranges=").unwrap();
+            let ranges: Vec<u32> = ranges.split(',').map(|r| r.parse().unwrap_or(0)).collect();
+            for (i, r) in ranges.chunks(2).enumerate() {
+                for offset in 0..r[1] {
+                    let path = Path::new(DEBUGFS_DIR)
+                        .join(format!("gpiochip{}", 3 + i))
+                        .join(offset.to_string());
+                    files.insert(path, "0\n".into());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Fs for FakeFs {
+        fn create_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn remove_dir(&self, _path: &Path) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.lock().unwrap().keys().any(|p| p.starts_with(path))
+        }
+
+        fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            match self.0.lock().unwrap().get_mut(path) {
+                Some(v) => {
+                    *v = format!("{}\n", String::from_utf8_lossy(data));
+                    Ok(())
+                }
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+            if !self.exists(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let mut entries: Vec<String> = self
+                .0
+                .lock()
+                .unwrap()
+                .keys()
+                .filter_map(|p| p.strip_prefix(path).ok()?.iter().next())
+                .map(|n| n.to_string_lossy().into_owned())
+                .collect();
+            entries.dedup();
+            Ok(entries)
+        }
+
+        fn open_dir(&self, _path: &Path) -> io::Result<Box<dyn FsDir>> {
+            unimplemented!()
+        }
+    }
+
+    fn mockup(fs: &Arc<FakeFs>) -> Mockup {
+        let modfs = fs.clone();
+        Mockup::with_fs(fs.clone(), move |args| modfs.modprobe(args))
+    }
+
+    #[test]
+    fn live() {
+        let fs = Arc::new(FakeFs::default());
+        let mut builder = builder();
+        builder
+            .with_backend(mockup(&fs))
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(Bank::new(12, "right").pull(5, Level::High));
+        let sim = builder.live().unwrap();
+        let chips = sim.chips();
+        assert_eq!(chips[0].dev_name, "gpio-mockup");
+        assert_eq!(chips[0].chip_name, "gpiochip3");
+        assert_eq!(chips[1].dev_path(), Path::new("/dev/gpiochip4"));
+        assert_eq!(chips[1].get_pull(5).unwrap(), Level::High);
+        assert_eq!(chips[1].get_level(5).unwrap(), Level::High);
+        chips[0].pullup(7).unwrap();
+        assert_eq!(chips[0].get_pull(7).unwrap(), Level::High);
+        assert_eq!(
+            fs.read_to_string(&Path::new(DEBUGFS_DIR).join("gpiochip3/7"))
+                .unwrap(),
+            "1\n"
+        );
+        assert!(chips[0].pullup(8).is_err());
+
+        // only one simulator at a time
+        assert!(matches!(builder.live(), Err(Error::SimulatorExists(_))));
+
+        drop(sim);
+        assert!(!fs.exists(Path::new(DEBUGFS_DIR)));
+        assert!(builder.live().is_ok());
+    }
+
+    #[test]
+    fn removed_externally() {
+        let fs = Arc::new(FakeFs::default());
+        let sim = builder()
+            .with_backend(mockup(&fs))
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        fs.modprobe(&["-r".into(), "gpio-mockup".into()]).unwrap();
+        let c = &sim.chips()[0];
+        assert!(matches!(c.pullup(1), Err(Error::SimRemoved)));
+        assert!(matches!(c.get_pull(1), Err(Error::SimRemoved)));
+        assert!(matches!(c.get_level(1), Err(Error::SimRemoved)));
+    }
+
+    #[test]
+    fn validate() {
+        let fs = Arc::new(FakeFs::default());
+        let mockup = mockup(&fs);
+        let banks: Vec<Bank> = (0..11).map(|i| Bank::new(4, format!("c{}", i))).collect();
+        assert!(mockup.validate(&banks[..10]).is_ok());
+        assert!(matches!(
+            mockup.validate(&banks),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            mockup.validate(&[Bank::new(4, "named").name(1, "one").clone()]),
+            Err(Error::InvalidConfig(_))
+        ));
+        let res = builder()
+            .with_backend(mockup)
+            .with_bank(Bank::new(4, "hogged").hog(2, "hogster", crate::Direction::OutputHigh))
+            .live();
+        assert!(matches!(res, Err(Error::InvalidConfig(_))));
+        assert!(!fs.exists(Path::new(DEBUGFS_DIR)));
+    }
+}
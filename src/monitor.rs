@@ -0,0 +1,228 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::timing;
+use crate::{Chip, Level, Offset, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// The period between samples of the monitored lines.
+pub(crate) const SAMPLE_PERIOD: Duration = Duration::from_micros(100);
+
+/// A transition of a line between levels.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Edge {
+    /// The line transitioned from low to high.
+    Rising,
+
+    /// The line transitioned from high to low.
+    Falling,
+
+    /// The line transitioned in either direction.
+    Both,
+}
+
+impl Edge {
+    /// Returns true if the transition from prev to curr is an edge of this kind.
+    pub fn matches(&self, prev: Level, curr: Level) -> bool {
+        match self {
+            Edge::Rising => prev == Level::Low && curr == Level::High,
+            Edge::Falling => prev == Level::High && curr == Level::Low,
+            Edge::Both => prev != curr,
+        }
+    }
+}
+
+/// A background thread that samples the levels of a set of lines and reports
+/// any changes, or that periodically polls the chip.
+///
+/// The gpio-sim does not report changes to line levels, so the monitor
+/// periodically samples the levels.
+/// Changes that are reverted within a sample period may be missed, so
+/// consumers being monitored must hold levels for at least a few periods.
+///
+/// The thread exits when the monitor is dropped, or if the lines can no
+/// longer be read, e.g. as the simulator has been removed.
+#[derive(Debug)]
+pub(crate) struct Monitor {
+    /// Requests the thread exit.
+    stop: Arc<AtomicBool>,
+
+    /// Tracks samples completed by the thread.
+    progress: Arc<Progress>,
+
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    /// The number of samples completed, and whether the thread has exited.
+    state: Mutex<(u64, bool)>,
+
+    /// Signalled when a sample is completed or the thread exits.
+    changed: Condvar,
+}
+
+impl Progress {
+    fn sampled(&self) {
+        self.state.lock().unwrap().0 += 1;
+        self.changed.notify_all();
+    }
+
+    fn exited(&self) {
+        self.state.lock().unwrap().1 = true;
+        self.changed.notify_all();
+    }
+}
+
+impl Monitor {
+    /// Start monitoring the lines.
+    ///
+    /// The initial levels are sampled before returning, and handler is called
+    /// with the previous and current levels, in the order of offsets, whenever
+    /// any of the levels change.
+    pub(crate) fn spawn<F>(chip: &Chip, offsets: &[Offset], mut handler: F) -> Result<Monitor>
+    where
+        F: FnMut(&Chip, &[Level], &[Level]) + Send + 'static,
+    {
+        let offsets = offsets.to_vec();
+        let mut prev = sample(chip, &offsets)?;
+        Monitor::poll(chip, move |chip| {
+            let curr = sample(chip, &offsets)?;
+            if curr != prev {
+                handler(chip, &prev, &curr);
+                prev = curr;
+            }
+            Ok(())
+        })
+    }
+
+    /// Start polling the chip.
+    ///
+    /// The handler is called once every sample period, and the thread exits
+    /// if the handler returns an error.
+    pub(crate) fn poll<F>(chip: &Chip, mut handler: F) -> Result<Monitor>
+    where
+        F: FnMut(&Chip) -> Result<()> + Send + 'static,
+    {
+        let chip = chip.share();
+        let stop = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(Progress::default());
+        let thread = {
+            let stop = stop.clone();
+            let progress = progress.clone();
+            thread::spawn(move || {
+                timing::apply_realtime();
+                while !stop.load(Ordering::Relaxed) {
+                    if handler(&chip).is_err() {
+                        break;
+                    }
+                    progress.sampled();
+                    thread::sleep(SAMPLE_PERIOD);
+                }
+                progress.exited();
+            })
+        };
+        Ok(Monitor {
+            stop,
+            progress,
+            thread: Some(thread),
+        })
+    }
+
+    /// Wait until the monitor has sampled, and handled, the current levels of
+    /// the lines.
+    ///
+    /// Returns immediately if the monitor thread has exited.
+    pub(crate) fn sync(&self) {
+        let mut state = self.progress.state.lock().unwrap();
+        // the sample in progress may predate the call, so wait for the next.
+        let target = state.0 + 2;
+        while state.0 < target && !state.1 {
+            state = self.progress.changed.wait(state).unwrap();
+        }
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.thread.take() {
+            _ = t.join();
+        }
+    }
+}
+
+// Read the levels without updating the chip stats.
+fn sample(chip: &Chip, offsets: &[Offset]) -> Result<Vec<Level>> {
+    offsets
+        .iter()
+        .map(|&offset| chip.dev.get_level(chip.bank, offset))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn edge_matches() {
+        use Level::*;
+        assert!(Edge::Rising.matches(Low, High));
+        assert!(!Edge::Rising.matches(High, Low));
+        assert!(!Edge::Rising.matches(High, High));
+        assert!(Edge::Falling.matches(High, Low));
+        assert!(!Edge::Falling.matches(Low, High));
+        assert!(!Edge::Falling.matches(Low, Low));
+        assert!(Edge::Both.matches(Low, High));
+        assert!(Edge::Both.matches(High, Low));
+        assert!(!Edge::Both.matches(Low, Low));
+    }
+
+    #[test]
+    fn changes() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "mon"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let m = {
+            let seen = seen.clone();
+            Monitor::spawn(c, &[1, 3], move |_, prev, curr| {
+                seen.lock().unwrap().push((prev.to_vec(), curr.to_vec()))
+            })
+            .unwrap()
+        };
+        m.sync();
+        assert!(seen.lock().unwrap().is_empty());
+        mem.drive(c, 3, Some(Level::High)).unwrap();
+        m.sync();
+        mem.drive(c, 2, Some(Level::High)).unwrap();
+        m.sync();
+        c.pullup(1).unwrap();
+        m.sync();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                (vec![Level::Low, Level::Low], vec![Level::Low, Level::High]),
+                (
+                    vec![Level::Low, Level::High],
+                    vec![Level::High, Level::High]
+                )
+            ]
+        );
+        // monitor reads are not counted
+        assert_eq!(c.stats()[3].level_reads, 0);
+        drop(sim);
+        // thread exits once the sim is removed
+        m.sync();
+        m.sync();
+    }
+}
@@ -0,0 +1,457 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A bridge between the lines of a simulator and an MQTT broker, e.g. to plug
+//! the simulator into existing home automation or HIL test infrastructure.
+//!
+//! The [`Bridge`] publishes the level of each line, initially and whenever it
+//! changes, to the retained topic `<prefix>/<label>/<offset>/level`, and
+//! applies pulls published to `<prefix>/<label>/<offset>/pull`.
+//! Levels and pulls are `high` or `low`, though pulls may also be `1` or `0`.
+//!
+//! The bridge implements the subset of MQTT v3.1.1 required, using QoS 0, so
+//! does not depend on an MQTT client library.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::mqtt::Bridge;
+//! use gpiosim::Bank;
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(8, "left")).live()?;
+//! let bridge = Bridge::connect(&sim, "broker:1883", "gpiosim")?;
+//! // ... `gpiosim/left/3/level` tracks the level of line 3 ...
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::monitor::Monitor;
+use crate::{Chip, Error, Level, Offset, Result, Sim};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The keep alive interval requested from the broker.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// The period between checks of the time since the last packet was sent.
+const IDLE_POLL: Duration = Duration::from_secs(5);
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xc0;
+const DISCONNECT: u8 = 0xe0;
+
+/// The flag marking a published message to be retained by the broker.
+const RETAIN: u8 = 0x01;
+
+/// The sending half of the connection to the broker.
+#[derive(Debug)]
+struct Link {
+    stream: TcpStream,
+
+    /// When a packet was last sent to the broker.
+    last_write: Instant,
+}
+
+impl Link {
+    fn send(&mut self, kind: u8, body: &[u8]) -> io::Result<()> {
+        write_packet(&mut self.stream, kind, body)?;
+        self.last_write = Instant::now();
+        Ok(())
+    }
+}
+
+type Writer = Arc<Mutex<Link>>;
+
+/// A bridge between the lines of a simulator and an MQTT broker.
+///
+/// The bridge disconnects from the broker when dropped.
+#[derive(Debug)]
+pub struct Bridge {
+    prefix: String,
+    writer: Writer,
+
+    /// Monitors of the levels of the chips, which publish the changes.
+    monitors: Vec<Monitor>,
+
+    /// The thread receiving pulls from the broker.
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Bridge {
+    /// Connect the lines of the simulator to the broker at the address, with
+    /// topics under the prefix.
+    ///
+    /// The client identifier is the name of the simulator.
+    pub fn connect<A: ToSocketAddrs>(sim: &Sim, broker: A, prefix: &str) -> Result<Bridge> {
+        let mut stream = TcpStream::connect(broker)?;
+        stream.set_nodelay(true)?;
+
+        let mut body = Vec::new();
+        put_str(&mut body, "MQTT");
+        // protocol level 4, clean session
+        body.extend([4, 0x02]);
+        body.extend((KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        put_str(&mut body, sim.name());
+        write_packet(&mut stream, CONNECT, &body)?;
+        let (kind, body) = read_packet(&mut stream)?;
+        if kind != CONNACK || body.len() != 2 || body[1] != 0 {
+            return Err(Error::RemoteError(format!(
+                "connection refused by broker: {:?}",
+                body
+            )));
+        }
+
+        let mut body = vec![0, 1];
+        put_str(&mut body, &format!("{}/+/+/pull", prefix));
+        body.push(0);
+        write_packet(&mut stream, SUBSCRIBE, &body)?;
+        let (kind, body) = read_packet(&mut stream)?;
+        if kind != SUBACK || body.last() == Some(&0x80) {
+            return Err(Error::RemoteError("subscription refused by broker".into()));
+        }
+
+        let writer = Arc::new(Mutex::new(Link {
+            stream: stream.try_clone()?,
+            last_write: Instant::now(),
+        }));
+        let monitors = sim
+            .chips()
+            .iter()
+            .map(|c| {
+                let offsets: Vec<_> = (0..c.cfg.num_lines).collect();
+                for &offset in &offsets {
+                    let level = c.dev.get_level(c.bank, offset)?;
+                    publish_level(&writer, prefix, c, offset, level)?;
+                }
+                let writer = writer.clone();
+                let prefix = prefix.to_string();
+                Monitor::spawn(c, &offsets, move |chip, prev, curr| {
+                    for (offset, level) in curr.iter().enumerate() {
+                        if prev[offset] != *level {
+                            // a lost connection is detected by the receiver
+                            let _ = publish_level(&writer, &prefix, chip, offset as u32, *level);
+                        }
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let chips: Vec<Chip> = sim.chips().iter().map(Chip::share).collect();
+        let thread = {
+            let writer = writer.clone();
+            let prefix = prefix.to_string();
+            stream.set_read_timeout(Some(IDLE_POLL))?;
+            thread::spawn(move || receive(stream, &writer, &prefix, &chips))
+        };
+        Ok(Bridge {
+            prefix: prefix.into(),
+            writer,
+            monitors,
+            thread: Some(thread),
+        })
+    }
+
+    /// The prefix of the topics.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Wait until the current levels of the lines have been sampled, and any
+    /// changes published.
+    pub fn sync(&self) {
+        for m in &self.monitors {
+            m.sync();
+        }
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        self.monitors.clear();
+        let mut link = self.writer.lock().unwrap();
+        let _ = link.send(DISCONNECT, &[]);
+        // unblock the receiver
+        let _ = link.stream.shutdown(Shutdown::Both);
+        drop(link);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+fn publish_level(
+    writer: &Writer,
+    prefix: &str,
+    chip: &Chip,
+    offset: Offset,
+    level: Level,
+) -> io::Result<()> {
+    let mut body = Vec::new();
+    put_str(
+        &mut body,
+        &format!("{}/{}/{}/level", prefix, chip.cfg.label, offset),
+    );
+    body.extend(level.to_string().bytes());
+    writer.lock().unwrap().send(PUBLISH | RETAIN, &body)
+}
+
+// Apply the pulls published by the broker until the connection is closed,
+// pinging the broker whenever nothing has been sent for half the keep alive
+// interval.
+//
+// Reads may time out mid-packet, so partial packets are buffered until
+// complete.
+fn receive(mut stream: TcpStream, writer: &Writer, prefix: &str, chips: &[Chip]) {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+    loop {
+        loop {
+            match take_packet(&mut buf) {
+                Ok(Some((kind, body))) if kind & 0xf0 == PUBLISH => {
+                    let _ = apply(&body, kind, prefix, chips);
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+        {
+            let mut link = writer.lock().unwrap();
+            if link.last_write.elapsed() >= KEEP_ALIVE / 2 && link.send(PINGREQ, &[]).is_err() {
+                return;
+            }
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) => return,
+            Ok(n) => buf.extend(&chunk[..n]),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock
+                        | io::ErrorKind::TimedOut
+                        | io::ErrorKind::Interrupted
+                ) => {}
+            Err(_) => return,
+        }
+    }
+}
+
+// Apply a pull published to a pull topic.
+fn apply(body: &[u8], kind: u8, prefix: &str, chips: &[Chip]) -> Option<()> {
+    let len = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    let topic = std::str::from_utf8(body.get(2..2 + len)?).ok()?;
+    // skip the packet identifier of QoS 1 and 2 messages
+    let start = if kind & 0x06 != 0 { 4 + len } else { 2 + len };
+    let pull: Level = std::str::from_utf8(body.get(start..)?).ok()?.parse().ok()?;
+    let mut parts = topic.strip_prefix(prefix)?.strip_prefix('/')?.split('/');
+    let label = parts.next()?;
+    let offset: Offset = parts.next()?.parse().ok()?;
+    if parts.next() != Some("pull") || parts.next().is_some() {
+        return None;
+    }
+    let chip = chips.iter().find(|c| c.cfg.label == label)?;
+    chip.set_pull(offset, pull).ok()
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend((s.len() as u16).to_be_bytes());
+    buf.extend(s.bytes());
+}
+
+fn write_packet<W: Write>(writer: &mut W, kind: u8, body: &[u8]) -> io::Result<()> {
+    let mut packet = vec![kind];
+    let mut len = body.len();
+    loop {
+        let mut b = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            b |= 0x80;
+        }
+        packet.push(b);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend(body);
+    writer.write_all(&packet)
+}
+
+// Remove the first packet from the buffer, if it has been completely read.
+fn take_packet(buf: &mut Vec<u8>) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let mut len = 0;
+    for (i, shift) in (0..28).step_by(7).enumerate() {
+        let b = match buf.get(1 + i) {
+            Some(b) => *b,
+            None => return Ok(None),
+        };
+        len |= ((b & 0x7f) as usize) << shift;
+        if b & 0x80 == 0 {
+            let start = 2 + i;
+            if buf.len() < start + len {
+                return Ok(None);
+            }
+            let kind = buf[0];
+            let body = buf[start..start + len].to_vec();
+            buf.drain(..start + len);
+            return Ok(Some((kind, body)));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "invalid remaining length",
+    ))
+}
+
+fn read_packet<R: Read>(reader: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut b = [0; 1];
+    reader.read_exact(&mut b)?;
+    let kind = b[0];
+    let mut len = 0;
+    for shift in (0..28).step_by(7) {
+        reader.read_exact(&mut b)?;
+        len |= ((b[0] & 0x7f) as usize) << shift;
+        if b[0] & 0x80 == 0 {
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body)?;
+            return Ok((kind, body));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "invalid remaining length",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::net::TcpListener;
+
+    // Read a published message, returning its topic and payload.
+    fn published(stream: &mut TcpStream) -> (String, String) {
+        let (kind, body) = read_packet(stream).unwrap();
+        assert_eq!(kind, PUBLISH | RETAIN);
+        let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+        (
+            String::from_utf8(body[2..2 + len].to_vec()).unwrap(),
+            String::from_utf8(body[2 + len..].to_vec()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn packets() {
+        let mut buf = Vec::new();
+        write_packet(&mut buf, PUBLISH, &[7; 200]).unwrap();
+        assert_eq!(buf[..3], [PUBLISH, 0xc8, 0x01]);
+        let (kind, body) = read_packet(&mut &buf[..]).unwrap();
+        assert_eq!(kind, PUBLISH);
+        assert_eq!(body, [7; 200]);
+        assert!(read_packet(&mut &[PUBLISH, 0xff, 0xff, 0xff, 0xff][..]).is_err());
+
+        // partial packets remain buffered until complete
+        let mut partial = buf[..100].to_vec();
+        assert!(take_packet(&mut partial).unwrap().is_none());
+        assert_eq!(partial.len(), 100);
+        partial.extend(&buf[100..]);
+        write_packet(&mut partial, PINGREQ, &[]).unwrap();
+        assert_eq!(
+            take_packet(&mut partial).unwrap(),
+            Some((PUBLISH, vec![7; 200]))
+        );
+        assert_eq!(take_packet(&mut partial).unwrap(), Some((PINGREQ, vec![])));
+        assert!(partial.is_empty());
+        assert!(take_packet(&mut vec![PUBLISH, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+
+    #[test]
+    fn bridge() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "left"))
+            .live()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let name = sim.name().to_string();
+        let broker = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let (kind, body) = read_packet(&mut stream).unwrap();
+            assert_eq!(kind, CONNECT);
+            assert_eq!(&body[..7], b"\0\x04MQTT\x04");
+            assert!(body.ends_with(name.as_bytes()));
+            write_packet(&mut stream, CONNACK, &[0, 0]).unwrap();
+            let (kind, body) = read_packet(&mut stream).unwrap();
+            assert_eq!(kind, SUBSCRIBE);
+            assert_eq!(&body[4..], b"sim/+/+/pull\0");
+            write_packet(&mut stream, SUBACK, &[0, 1, 0]).unwrap();
+            stream
+        });
+        let bridge = Bridge::connect(&sim, addr, "sim").unwrap();
+        assert_eq!(bridge.prefix(), "sim");
+        let mut stream = broker.join().unwrap();
+        assert_eq!(
+            published(&mut stream),
+            ("sim/left/0/level".into(), "low".into())
+        );
+        assert_eq!(
+            published(&mut stream),
+            ("sim/left/1/level".into(), "low".into())
+        );
+
+        let c = &sim.chips()[0];
+        mem.drive(c, 1, Some(Level::High)).unwrap();
+        bridge.sync();
+        assert_eq!(
+            published(&mut stream),
+            ("sim/left/1/level".into(), "high".into())
+        );
+
+        let mut body = Vec::new();
+        put_str(&mut body, "sim/left/0/pull");
+        body.extend(b"high");
+        write_packet(&mut stream, PUBLISH, &body).unwrap();
+        assert_eq!(
+            published(&mut stream),
+            ("sim/left/0/level".into(), "high".into())
+        );
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+
+        drop(bridge);
+        assert_eq!(read_packet(&mut stream).unwrap(), (DISCONNECT, vec![]));
+    }
+
+    #[test]
+    fn apply_pull() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "left"))
+            .live()
+            .unwrap();
+        let chips = sim.chips();
+        let msg = |topic: &str, payload: &str| {
+            let mut body = Vec::new();
+            put_str(&mut body, topic);
+            body.extend(payload.bytes());
+            body
+        };
+        assert!(apply(&msg("sim/left/1/pull", "1"), PUBLISH, "sim", chips).is_some());
+        assert_eq!(chips[0].get_pull(1).unwrap(), Level::High);
+        assert!(apply(&msg("sim/left/1/pull", "0"), PUBLISH, "sim", chips).is_some());
+        assert_eq!(chips[0].get_pull(1).unwrap(), Level::Low);
+        assert!(apply(&msg("sim/left/1/pull", "on"), PUBLISH, "sim", chips).is_none());
+        assert!(apply(&msg("sim/left/2/pull", "1"), PUBLISH, "sim", chips).is_none());
+        assert!(apply(&msg("sim/right/1/pull", "1"), PUBLISH, "sim", chips).is_none());
+        assert!(apply(&msg("sim/left/1/level", "1"), PUBLISH, "sim", chips).is_none());
+        assert!(apply(&msg("other/left/1/pull", "1"), PUBLISH, "sim", chips).is_none());
+        assert!(apply(&[0], PUBLISH, "sim", chips).is_none());
+    }
+}
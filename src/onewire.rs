@@ -0,0 +1,562 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of 1-Wire slave devices on simulated lines.
+//!
+//! The consumer acts as a bit-banged 1-Wire master, driving the line as an
+//! open-drain output, and the emulated slave answers by pulling the line low.
+//!
+//! The slave classifies the low pulses from the master by their width, so
+//! while the standard timings are the default, the line is sampled and
+//! consumers will typically need to use slower timings, matching a
+//! [`Timing::scaled`] slave.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::onewire::{Ds18b20, Timing};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(1, "w1")).live()?;
+//! let sensor = Ds18b20::new(&sim.chips()[0], 0, 0x1234, Timing::scaled(20))?;
+//! sensor.set_temperature(21.5);
+//!
+//! // ... consumer resets, skips ROM, converts and reads the scratchpad ...
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::timing::sleep_until;
+use crate::{Chip, Level, Offset, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The timing windows of the 1-Wire slave.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timing {
+    /// Low pulses at least this long are treated as a reset.
+    pub reset: Duration,
+
+    /// Low pulses at least this long, but shorter than a reset, are a write 0.
+    ///
+    /// Shorter pulses are a write 1, or a read slot.
+    pub write_zero: Duration,
+
+    /// The delay from the end of the reset to the start of the presence pulse.
+    pub presence_wait: Duration,
+
+    /// The width of the presence pulse.
+    pub presence: Duration,
+
+    /// The time the line is held low when responding to a read slot with a 0.
+    pub read_hold: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing {
+            reset: Duration::from_micros(240),
+            write_zero: Duration::from_micros(30),
+            presence_wait: Duration::from_micros(30),
+            presence: Duration::from_micros(120),
+            read_hold: Duration::from_micros(45),
+        }
+    }
+}
+
+impl Timing {
+    /// The standard timings slowed by the factor.
+    pub fn scaled(factor: u32) -> Timing {
+        let t = Timing::default();
+        Timing {
+            reset: t.reset * factor,
+            write_zero: t.write_zero * factor,
+            presence_wait: t.presence_wait * factor,
+            presence: t.presence * factor,
+            read_hold: t.read_hold * factor,
+        }
+    }
+}
+
+/// The ROM commands.
+const READ_ROM: u8 = 0x33;
+const MATCH_ROM: u8 = 0x55;
+const SKIP_ROM: u8 = 0xcc;
+const SEARCH_ROM: u8 = 0xf0;
+
+/// The function commands.
+const CONVERT_T: u8 = 0x44;
+const WRITE_SCRATCHPAD: u8 = 0x4e;
+const READ_SCRATCHPAD: u8 = 0xbe;
+
+/// The family code of the DS18B20.
+const DS18B20_FAMILY: u8 = 0x28;
+
+/// An emulated DS18B20 temperature sensor.
+///
+/// Supports the READ, MATCH, SKIP and SEARCH ROM commands, and the CONVERT T,
+/// READ SCRATCHPAD and WRITE SCRATCHPAD function commands.
+/// The EEPROM and alarm functions are not emulated.
+#[derive(Debug)]
+pub struct Ds18b20 {
+    engine: Arc<Mutex<Engine>>,
+
+    monitor: Monitor,
+}
+
+impl Ds18b20 {
+    /// Start emulating a sensor, with the 48-bit serial number, on the line.
+    ///
+    /// The line is pulled high, as per the 1-Wire bus pull-up.
+    pub fn new(chip: &Chip, offset: Offset, serial: u64, timing: Timing) -> Result<Ds18b20> {
+        chip.set_pull(offset, Level::High)?;
+        let engine = Arc::new(Mutex::new(Engine::new(serial, timing)));
+        let monitor = {
+            let engine = engine.clone();
+            Monitor::spawn(chip, &[offset], move |chip, _, curr| {
+                let now = Instant::now();
+                let response = match curr[0] {
+                    Level::Low => engine.lock().unwrap().falling(now),
+                    Level::High => engine.lock().unwrap().rising(now),
+                };
+                if let Some(r) = response {
+                    sleep_until(now + r.delay);
+                    _ = chip.set_pull(offset, Level::Low);
+                    sleep_until(now + r.delay + r.hold);
+                    _ = chip.set_pull(offset, Level::High);
+                }
+            })?
+        };
+        Ok(Ds18b20 { engine, monitor })
+    }
+
+    /// The 64-bit ROM code of the sensor, in order of transmission.
+    pub fn rom(&self) -> [u8; 8] {
+        self.engine.lock().unwrap().rom
+    }
+
+    /// The contents of the scratchpad.
+    pub fn scratchpad(&self) -> [u8; 9] {
+        self.engine.lock().unwrap().scratchpad
+    }
+
+    /// Set the temperature, in degrees Celsius, to be reported by the next
+    /// conversion.
+    ///
+    /// The temperature is reported with 12-bit resolution.
+    /// Until the first conversion the sensor reports the power-on value of 85.
+    pub fn set_temperature(&self, celsius: f32) {
+        self.engine.lock().unwrap().temperature = (celsius * 16.0).round() as i16;
+    }
+
+    /// Wait until the current state of the line has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+/// A pulse the slave should drive on the line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Response {
+    /// The delay before pulling the line low.
+    delay: Duration,
+
+    /// The time to hold the line low.
+    hold: Duration,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Phase {
+    /// Waiting for a reset.
+    Idle,
+
+    /// Receiving a ROM command.
+    Rom,
+
+    /// Receiving the ROM code to match.
+    MatchRom,
+
+    /// Participating in a search, at the bit and the number of slots started
+    /// for the bit.
+    Search { bit: u8, step: u8 },
+
+    /// Receiving a function command.
+    Function,
+
+    /// Receiving the TH, TL and configuration registers.
+    WriteScratchpad,
+
+    /// Transmitting the queued bits, then moving to the next phase.
+    Send { next: Next },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Next {
+    Idle,
+    Function,
+}
+
+/// The protocol state machine of the sensor, driven by the edges on the line.
+#[derive(Debug)]
+struct Engine {
+    timing: Timing,
+
+    rom: [u8; 8],
+
+    scratchpad: [u8; 9],
+
+    /// The temperature reported by the next conversion, in 1/16 degree units.
+    temperature: i16,
+
+    phase: Phase,
+
+    /// When the line last fell.
+    fall: Option<Instant>,
+
+    /// The bits received in the current phase, LSB first.
+    rx: u64,
+
+    /// The number of bits received in the current phase.
+    rx_bits: u8,
+
+    /// The bits queued for transmission.
+    tx: VecDeque<bool>,
+}
+
+impl Engine {
+    fn new(serial: u64, timing: Timing) -> Engine {
+        let mut rom = [0; 8];
+        rom[0] = DS18B20_FAMILY;
+        rom[1..7].copy_from_slice(&serial.to_le_bytes()[..6]);
+        rom[7] = crc8(&rom[..7]);
+        let mut scratchpad = [0x50, 0x05, 0x4b, 0x46, 0x7f, 0xff, 0x0c, 0x10, 0];
+        scratchpad[8] = crc8(&scratchpad[..8]);
+        Engine {
+            timing,
+            rom,
+            scratchpad,
+            temperature: 85 * 16,
+            phase: Phase::Idle,
+            fall: None,
+            rx: 0,
+            rx_bits: 0,
+            tx: VecDeque::new(),
+        }
+    }
+
+    // The master has pulled the line low, starting a slot.
+    fn falling(&mut self, now: Instant) -> Option<Response> {
+        self.fall = Some(now);
+        let bit = match self.phase {
+            Phase::Send { .. } => self.tx.pop_front().unwrap_or(true),
+            Phase::Search { bit, step } => {
+                self.phase = Phase::Search {
+                    bit,
+                    step: step.saturating_add(1),
+                };
+                // the bit, then its complement, then the master writes the direction
+                step == 2 || self.rom_bit(bit) != (step == 1)
+            }
+            _ => true,
+        };
+        if bit {
+            None
+        } else {
+            Some(Response {
+                delay: Duration::ZERO,
+                hold: self.timing.read_hold,
+            })
+        }
+    }
+
+    // The line has been released, ending a slot or reset.
+    fn rising(&mut self, now: Instant) -> Option<Response> {
+        let width = now - self.fall.take()?;
+        if width >= self.timing.reset {
+            self.tx.clear();
+            self.phase = self.receive(Phase::Rom);
+            return Some(Response {
+                delay: self.timing.presence_wait,
+                hold: self.timing.presence,
+            });
+        }
+        let bit = width < self.timing.write_zero;
+        match self.phase {
+            Phase::Rom | Phase::MatchRom | Phase::Function | Phase::WriteScratchpad => {
+                if bit {
+                    self.rx |= 1 << self.rx_bits;
+                }
+                self.rx_bits += 1;
+                self.received();
+            }
+            Phase::Send { next } if self.tx.is_empty() => {
+                self.phase = match next {
+                    Next::Idle => Phase::Idle,
+                    Next::Function => self.receive(Phase::Function),
+                };
+            }
+            Phase::Search { bit: idx, step: 3 } => {
+                // the sensor drops out if the master takes the other branch
+                self.phase = if bit != self.rom_bit(idx) || idx == 63 {
+                    Phase::Idle
+                } else {
+                    Phase::Search {
+                        bit: idx + 1,
+                        step: 0,
+                    }
+                };
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // Process the bits received so far in the phase.
+    fn received(&mut self) {
+        let needed = match self.phase {
+            Phase::MatchRom => 64,
+            Phase::WriteScratchpad => 24,
+            _ => 8,
+        };
+        if self.rx_bits < needed {
+            return;
+        }
+        let rx = self.rx;
+        self.phase = match self.phase {
+            Phase::Rom => match rx as u8 {
+                READ_ROM => {
+                    let rom = self.rom;
+                    self.send(&rom, Next::Function)
+                }
+                MATCH_ROM => self.receive(Phase::MatchRom),
+                SKIP_ROM => self.receive(Phase::Function),
+                SEARCH_ROM => Phase::Search { bit: 0, step: 0 },
+                _ => Phase::Idle,
+            },
+            Phase::MatchRom if rx == u64::from_le_bytes(self.rom) => self.receive(Phase::Function),
+            Phase::Function => match rx as u8 {
+                CONVERT_T => {
+                    self.scratchpad[..2].copy_from_slice(&self.temperature.to_le_bytes());
+                    self.scratchpad[8] = crc8(&self.scratchpad[..8]);
+                    Phase::Idle
+                }
+                READ_SCRATCHPAD => {
+                    let scratchpad = self.scratchpad;
+                    self.send(&scratchpad, Next::Idle)
+                }
+                WRITE_SCRATCHPAD => self.receive(Phase::WriteScratchpad),
+                _ => Phase::Idle,
+            },
+            Phase::WriteScratchpad => {
+                self.scratchpad[2..5].copy_from_slice(&rx.to_le_bytes()[..3]);
+                self.scratchpad[8] = crc8(&self.scratchpad[..8]);
+                Phase::Idle
+            }
+            _ => Phase::Idle,
+        };
+    }
+
+    // Prepare to receive the bits for the phase.
+    fn receive(&mut self, phase: Phase) -> Phase {
+        self.rx = 0;
+        self.rx_bits = 0;
+        phase
+    }
+
+    // Queue the bytes for transmission, LSB first.
+    fn send(&mut self, data: &[u8], next: Next) -> Phase {
+        self.tx = data
+            .iter()
+            .flat_map(|b| (0..8).map(move |bit| b & (1 << bit) != 0))
+            .collect();
+        Phase::Send { next }
+    }
+
+    fn rom_bit(&self, bit: u8) -> bool {
+        u64::from_le_bytes(self.rom) & (1 << bit) != 0
+    }
+}
+
+/// The Dallas/Maxim CRC8 used for ROM codes and scratchpads.
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0;
+    for &byte in data {
+        let mut b = byte;
+        for _ in 0..8 {
+            let mix = (crc ^ b) & 1;
+            crc >>= 1;
+            if mix != 0 {
+                crc ^= 0x8c;
+            }
+            b >>= 1;
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    // A master driving the engine with synthetic timestamps.
+    struct Master {
+        engine: Engine,
+        now: Instant,
+    }
+
+    impl Master {
+        fn new() -> Master {
+            Master {
+                engine: Engine::new(0x0000_c0ff_ee42, Timing::default()),
+                now: Instant::now(),
+            }
+        }
+
+        // Pulse the line low for the width, returning the slave's response.
+        fn pulse(&mut self, width: u64) -> Option<Response> {
+            let r = self.engine.falling(self.now);
+            self.now += Duration::from_micros(width);
+            let p = self.engine.rising(self.now);
+            self.now += Duration::from_micros(100);
+            r.or(p)
+        }
+
+        fn reset(&mut self) -> bool {
+            self.pulse(480).is_some()
+        }
+
+        fn write_bit(&mut self, bit: bool) {
+            self.pulse(if bit { 5 } else { 60 });
+        }
+
+        fn write(&mut self, byte: u8) {
+            for bit in 0..8 {
+                self.write_bit(byte & (1 << bit) != 0);
+            }
+        }
+
+        fn read_bit(&mut self) -> bool {
+            self.pulse(5).is_none()
+        }
+
+        fn read(&mut self) -> u8 {
+            (0..8).fold(0, |b, bit| b | (self.read_bit() as u8) << bit)
+        }
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc8(&[0x02, 0x1c, 0xb8, 0x01, 0x00, 0x00, 0x00]), 0xa2);
+    }
+
+    #[test]
+    fn read_rom() {
+        let mut m = Master::new();
+        assert!(m.reset());
+        m.write(READ_ROM);
+        let rom: Vec<u8> = (0..8).map(|_| m.read()).collect();
+        assert_eq!(rom, m.engine.rom);
+        assert_eq!(rom[..7], [0x28, 0x42, 0xee, 0xff, 0xc0, 0x00, 0x00]);
+        assert_eq!(rom[7], crc8(&rom[..7]));
+        assert_eq!(m.engine.phase, Phase::Function);
+        assert_eq!(m.engine.rx_bits, 0);
+    }
+
+    #[test]
+    fn convert_and_read() {
+        let mut m = Master::new();
+        m.engine.temperature = (-10.125_f32 * 16.0) as i16;
+        assert!(m.reset());
+        m.write(SKIP_ROM);
+        m.write(READ_SCRATCHPAD);
+        assert_eq!([m.read(), m.read()], [0x50, 0x05]);
+        // a reset terminates the read
+        assert!(m.reset());
+        m.write(SKIP_ROM);
+        m.write(CONVERT_T);
+        // conversion completes immediately
+        assert!(m.read_bit());
+        assert!(m.reset());
+        m.write(SKIP_ROM);
+        m.write(READ_SCRATCHPAD);
+        let sp: Vec<u8> = (0..9).map(|_| m.read()).collect();
+        assert_eq!(sp[..2], [0x5e, 0xff]);
+        assert_eq!(sp[8], crc8(&sp[..8]));
+    }
+
+    #[test]
+    fn write_scratchpad() {
+        let mut m = Master::new();
+        assert!(m.reset());
+        let rom = m.engine.rom;
+        m.write(MATCH_ROM);
+        for b in rom {
+            m.write(b);
+        }
+        m.write(WRITE_SCRATCHPAD);
+        for b in [0x12, 0x34, 0x1f] {
+            m.write(b);
+        }
+        assert_eq!(m.engine.scratchpad[2..5], [0x12, 0x34, 0x1f]);
+        assert_eq!(m.engine.scratchpad[8], crc8(&m.engine.scratchpad[..8]));
+
+        // a mismatched ROM deselects the sensor
+        assert!(m.reset());
+        m.write(MATCH_ROM);
+        for b in rom.iter().rev() {
+            m.write(*b);
+        }
+        assert_eq!(m.engine.phase, Phase::Idle);
+    }
+
+    #[test]
+    fn search() {
+        let mut m = Master::new();
+        assert!(m.reset());
+        m.write(SEARCH_ROM);
+        let mut rom = 0_u64;
+        for bit in 0..64 {
+            let value = m.read_bit();
+            let complement = m.read_bit();
+            assert_ne!(value, complement);
+            rom |= (value as u64) << bit;
+            m.write_bit(value);
+        }
+        assert_eq!(rom.to_le_bytes(), m.engine.rom);
+        assert_eq!(m.engine.phase, Phase::Idle);
+
+        // the sensor drops out if the master takes the other branch
+        assert!(m.reset());
+        m.write(SEARCH_ROM);
+        let value = m.read_bit();
+        m.read_bit();
+        m.write_bit(!value);
+        assert_eq!(m.engine.phase, Phase::Idle);
+    }
+
+    #[test]
+    fn presence() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(1, "w1"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let sensor = Ds18b20::new(c, 0, 1, Timing::scaled(10)).unwrap();
+        assert_eq!(sensor.rom()[..2], [0x28, 0x01]);
+        assert_eq!(sensor.scratchpad()[..2], [0x50, 0x05]);
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        mem.drive(c, 0, Some(Level::Low)).unwrap();
+        sensor.sync();
+        thread::sleep(Duration::from_millis(5));
+        mem.drive(c, 0, None).unwrap();
+        sensor.sync();
+        // presence pulse pulled the line low, then released it.
+        assert_eq!(c.stats()[0].pulls, 3);
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+    }
+}
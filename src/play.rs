@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::{timing, Chip, Error, Level, Offset, Result};
+use std::io;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// A sequence of timed transitions being applied from a background thread,
+/// as started by [`Chip::play`].
+///
+/// Dropping the playback cancels it.
+#[derive(Debug)]
+pub struct Playback {
+    /// Requests the thread stop.
+    stop: Arc<Stop>,
+
+    /// Returns the number of transitions applied.
+    thread: Option<JoinHandle<io::Result<usize>>>,
+}
+
+#[derive(Debug, Default)]
+struct Stop {
+    requested: Mutex<bool>,
+
+    changed: Condvar,
+}
+
+impl Stop {
+    // Wait until the deadline, returning false if a stop is requested.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let mut requested = self.requested.lock().unwrap();
+        loop {
+            if *requested {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            requested = self
+                .changed
+                .wait_timeout(requested, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+}
+
+impl Playback {
+    pub(crate) fn start(
+        chip: &Chip,
+        transitions: &[(Offset, Level, Duration)],
+    ) -> Result<Playback> {
+        for &(offset, _, _) in transitions {
+            check_offset(chip, offset)?;
+        }
+        let chip = chip.share();
+        let transitions = transitions.to_vec();
+        let stop = Arc::new(Stop::default());
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                timing::apply_realtime();
+                let mut deadline = Instant::now();
+                let mut n = 0;
+                while n < transitions.len() {
+                    deadline += transitions[n].2;
+                    if !stop.wait_until(deadline) {
+                        return Ok(n);
+                    }
+                    // transitions without a delay are applied as a batch
+                    let batch = 1 + transitions[n + 1..]
+                        .iter()
+                        .take_while(|t| t.2.is_zero())
+                        .count();
+                    let pulls: Vec<(Offset, Level)> = transitions[n..n + batch]
+                        .iter()
+                        .map(|&(offset, pull, _)| (offset, pull))
+                        .collect();
+                    if let Err(e) = chip.set_pulls(&pulls) {
+                        return Err(match e {
+                            Error::IoError(e) => e,
+                            e => io::Error::new(io::ErrorKind::Other, e.to_string()),
+                        });
+                    }
+                    n += batch;
+                }
+                Ok(n)
+            })
+        };
+        Ok(Playback {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns true once all the transitions have been applied, or the
+    /// playback has failed.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().map_or(true, |t| t.is_finished())
+    }
+
+    /// Wait for all the transitions to be applied.
+    ///
+    /// Returns the number of transitions applied.
+    pub fn wait(mut self) -> Result<usize> {
+        self.join()
+    }
+
+    /// Stop the playback, leaving any remaining transitions unapplied.
+    ///
+    /// Returns the number of transitions applied.
+    pub fn cancel(mut self) -> Result<usize> {
+        self.request_stop();
+        self.join()
+    }
+
+    fn request_stop(&self) {
+        *self.stop.requested.lock().unwrap() = true;
+        self.stop.changed.notify_all();
+    }
+
+    fn join(&mut self) -> Result<usize> {
+        match self.thread.take() {
+            Some(t) => Ok(t.join().expect("playback thread panicked")?),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        self.request_stop();
+        _ = self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn play() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "play"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let p = c
+            .play(&[
+                (0, Level::High, ms(0)),
+                (2, Level::High, ms(5)),
+                (0, Level::Low, ms(5)),
+            ])
+            .unwrap();
+        assert_eq!(p.wait().unwrap(), 3);
+        assert!(start.elapsed() >= ms(10));
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(2).unwrap(), Level::High);
+
+        let p = c
+            .play(&[(1, Level::High, ms(0)), (1, Level::Low, ms(10_000))])
+            .unwrap();
+        while c.get_pull(1).unwrap() != Level::High {
+            thread::sleep(ms(1));
+        }
+        assert!(!p.is_finished());
+        let start = Instant::now();
+        assert_eq!(p.cancel().unwrap(), 1);
+        assert!(start.elapsed() < ms(1000));
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+
+        assert!(c.play(&[(4, Level::High, ms(0))]).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Builder, PullSnapshot, Result, Sim};
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// A pool of identically configured live simulators that may be leased to tests.
+///
+/// Creating and removing a simulator is relatively slow, so suites with many
+/// tests can instead lease a simulator from a pool, and return it when done.
+///
+/// When a [`Lease`] is dropped the pulls on all lines of its simulator are
+/// restored to their state when leased, which is their initial state, before
+/// the simulator is returned to the pool.
+///
+/// Note that any simulators held by a pool that is never dropped, such as a pool
+/// in a static, are not removed when the process exits.
+/// `examples/clean_sims.sh` can be used to remove them.
+#[derive(Clone, Debug)]
+pub struct SimPool {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// The simulators available for lease.
+    idle: Mutex<Vec<Sim>>,
+
+    /// Signalled when a simulator is returned to the pool.
+    returned: Condvar,
+
+    /// The number of simulators in the pool.
+    size: usize,
+}
+
+impl SimPool {
+    /// Create a pool of size simulators, each configured by the builder.
+    ///
+    /// If the builder has a name then the simulators are named `<name>-<N>`,
+    /// where N is the index of the simulator in the pool, else each is given a
+    /// generated name.
+    pub fn new(builder: &Builder, size: usize) -> Result<SimPool> {
+        let mut idle = Vec::with_capacity(size);
+        let mut builder = builder.clone();
+        let name = builder.name.clone();
+        for i in 0..size {
+            if let Some(name) = &name {
+                builder.with_name(format!("{}-{}", name, i));
+            }
+            idle.push(builder.live()?);
+        }
+        Ok(SimPool {
+            inner: Arc::new(Inner {
+                idle: Mutex::new(idle),
+                returned: Condvar::new(),
+                size,
+            }),
+        })
+    }
+
+    /// The number of simulators in the pool.
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// The number of simulators currently available for lease.
+    pub fn available(&self) -> usize {
+        self.inner.idle.lock().unwrap().len()
+    }
+
+    /// Lease a simulator from the pool.
+    ///
+    /// Blocks until a simulator is available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is empty, as it would block forever.
+    pub fn lease(&self) -> Lease {
+        assert!(self.inner.size > 0, "lease from empty SimPool");
+        let mut idle = self.inner.idle.lock().unwrap();
+        loop {
+            if let Some(sim) = idle.pop() {
+                return self.leased(sim);
+            }
+            idle = self.inner.returned.wait(idle).unwrap();
+        }
+    }
+
+    /// Lease a simulator from the pool, if one is available.
+    pub fn try_lease(&self) -> Option<Lease> {
+        self.inner
+            .idle
+            .lock()
+            .unwrap()
+            .pop()
+            .map(|sim| self.leased(sim))
+    }
+
+    fn leased(&self, sim: Sim) -> Lease {
+        let snapshots = sim.chips().iter().map(|c| c.snapshot().ok()).collect();
+        Lease {
+            pool: self.inner.clone(),
+            sim: Some(sim),
+            snapshots,
+        }
+    }
+}
+
+/// A simulator leased from a [`SimPool`].
+///
+/// The simulator is reset and returned to the pool when the lease is dropped.
+#[derive(Debug)]
+pub struct Lease {
+    pool: Arc<Inner>,
+    sim: Option<Sim>,
+
+    /// The pulls of each chip when leased.
+    snapshots: Vec<Option<PullSnapshot>>,
+}
+
+impl Deref for Lease {
+    type Target = Sim;
+
+    fn deref(&self) -> &Sim {
+        self.sim.as_ref().unwrap()
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        if let Some(sim) = self.sim.take() {
+            for (c, snapshot) in sim.chips().iter().zip(&self.snapshots) {
+                if let Some(snapshot) = snapshot {
+                    let _ = c.restore(snapshot);
+                }
+            }
+            self.pool.idle.lock().unwrap().push(sim);
+            self.pool.returned.notify_one();
+        }
+    }
+}
@@ -0,0 +1,534 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of PS/2 keyboards and mice on simulated lines.
+//!
+//! The consumer acts as a bit-banged PS/2 host, reading the clock and data
+//! lines, and driving them as open-drain outputs to inhibit the device or to
+//! send it commands.
+//! The emulated device generates the clock, and drives both lines, via their
+//! pulls.
+//!
+//! While the standard clock rate is the default, the lines are sampled, so
+//! consumers will typically need to tolerate slower clocks, as generated by a
+//! [`Timing::scaled`] device.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::ps2::{Device, Model, Timing};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(2, "ps2")).live()?;
+//! let kbd = Device::new(&sim.chips()[0], 0, 1, Model::Keyboard, Timing::scaled(10))?;
+//!
+//! // press and release the A key
+//! kbd.press_key(&[0x1c])?;
+//! kbd.release_key(&[0x1c])?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::{Monitor, SAMPLE_PERIOD};
+use crate::timing::sleep_until;
+use crate::{Chip, Error, Level, Offset, Result};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The kind of PS/2 device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Model {
+    /// A keyboard, using scan code set 2.
+    #[default]
+    Keyboard,
+
+    /// A standard mouse.
+    Mouse,
+}
+
+/// The timing of the PS/2 device clock.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timing {
+    /// The time the clock is held at each level.
+    pub half_period: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing {
+            half_period: Duration::from_micros(40),
+        }
+    }
+}
+
+impl Timing {
+    /// The standard timing slowed by the factor.
+    pub fn scaled(factor: u32) -> Timing {
+        Timing {
+            half_period: Timing::default().half_period * factor,
+        }
+    }
+}
+
+/// The time the device waits for the host to stop inhibiting communication.
+const INHIBIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The prefix of a key release in scan code set 2.
+const BREAK: u8 = 0xf0;
+
+/// The host commands.
+const RESET: u8 = 0xff;
+const RESEND: u8 = 0xfe;
+const IDENTIFY: u8 = 0xf2;
+const ECHO: u8 = 0xee;
+const SET_LEDS: u8 = 0xed;
+const SET_TYPEMATIC: u8 = 0xf3;
+const SET_SCAN_CODE_SET: u8 = 0xf0;
+const SET_RESOLUTION: u8 = 0xe8;
+
+/// The device responses.
+const ACK: u8 = 0xfa;
+const SELF_TEST_PASSED: u8 = 0xaa;
+
+/// An emulated PS/2 device.
+///
+/// Bytes are sent to the host once it releases the clock and data lines.
+/// Host requests to send a command are serviced immediately, or between bytes
+/// when the device is sending.
+/// The device does not abort a byte if the host inhibits it mid-transmission.
+///
+/// Commands received from the host are acknowledged, and the reset, identify,
+/// echo and resend commands are answered as per the model.
+/// Command arguments, such as the state of the keyboard LEDs, are
+/// acknowledged and recorded, but are otherwise ignored.
+#[derive(Debug)]
+pub struct Device {
+    port: Arc<Mutex<Port>>,
+
+    monitor: Monitor,
+}
+
+impl Device {
+    /// Start emulating a device on the clock and data lines.
+    ///
+    /// Both lines are pulled high, as per the PS/2 bus pull-ups.
+    pub fn new(
+        chip: &Chip,
+        clock: Offset,
+        data: Offset,
+        model: Model,
+        timing: Timing,
+    ) -> Result<Device> {
+        chip.set_pull(clock, Level::High)?;
+        chip.set_pull(data, Level::High)?;
+        let port = Arc::new(Mutex::new(Port {
+            chip: chip.share(),
+            clock,
+            data,
+            model,
+            timing,
+            data_pull: Level::High,
+            commands: Vec::new(),
+            pending: None,
+            leds: 0,
+            last: ACK,
+        }));
+        let monitor = {
+            let port = port.clone();
+            Monitor::poll(chip, move |_| port.lock().unwrap().poll())?
+        };
+        Ok(Device { port, monitor })
+    }
+
+    /// Send the bytes to the host.
+    ///
+    /// Returns an error if the host inhibits communication for more than a
+    /// second.
+    pub fn send(&self, bytes: &[u8]) -> Result<()> {
+        for &byte in bytes {
+            self.port.lock().unwrap().write(byte)?;
+        }
+        Ok(())
+    }
+
+    /// Send the make code for a key press.
+    pub fn press_key(&self, scancode: &[u8]) -> Result<()> {
+        self.send(scancode)
+    }
+
+    /// Send the break code for a key release.
+    ///
+    /// The break code is the make code with the release prefix, 0xf0,
+    /// inserted before the final byte, as per scan code set 2.
+    pub fn release_key(&self, scancode: &[u8]) -> Result<()> {
+        if let Some((last, prefix)) = scancode.split_last() {
+            let mut code = prefix.to_vec();
+            code.push(BREAK);
+            code.push(*last);
+            self.send(&code)?;
+        }
+        Ok(())
+    }
+
+    /// The bytes received from the host, including command arguments.
+    pub fn commands(&self) -> Vec<u8> {
+        self.port.lock().unwrap().commands.clone()
+    }
+
+    /// The state of the keyboard LEDs most recently set by the host.
+    pub fn leds(&self) -> u8 {
+        self.port.lock().unwrap().leds
+    }
+
+    /// Wait until the current state of the lines has been sampled, and any
+    /// command from the host serviced.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[derive(Debug)]
+struct Port {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    clock: Offset,
+
+    data: Offset,
+
+    model: Model,
+
+    timing: Timing,
+
+    /// The current pull on the data line.
+    data_pull: Level,
+
+    /// The bytes received from the host.
+    commands: Vec<u8>,
+
+    /// The command awaiting an argument.
+    pending: Option<u8>,
+
+    /// The state of the keyboard LEDs.
+    leds: u8,
+
+    /// The last byte sent, for resending.
+    last: u8,
+}
+
+impl Port {
+    fn level(&self, offset: Offset) -> Result<Level> {
+        self.chip.dev.get_level(self.chip.bank, offset)
+    }
+
+    fn set_clock(&self, level: Level) -> Result<()> {
+        self.chip.set_pull(self.clock, level)
+    }
+
+    fn set_data(&mut self, level: Level) -> Result<()> {
+        self.data_pull = level;
+        self.chip.set_pull(self.data, level)
+    }
+
+    // Returns true if the host is requesting to send, by holding data low
+    // after releasing the clock.
+    fn request_to_send(&self) -> Result<bool> {
+        Ok(self.data_pull == Level::High
+            && self.level(self.clock)? == Level::High
+            && self.level(self.data)? == Level::Low)
+    }
+
+    fn idle(&self) -> Result<bool> {
+        Ok(self.level(self.clock)? == Level::High && self.level(self.data)? == Level::High)
+    }
+
+    // Service any request to send from the host.
+    fn poll(&mut self) -> Result<()> {
+        if self.request_to_send()? {
+            self.receive()?;
+        }
+        Ok(())
+    }
+
+    // Write a byte to the host once it stops inhibiting communication,
+    // servicing any requests to send in the meantime.
+    fn write(&mut self, byte: u8) -> Result<()> {
+        let deadline = Instant::now() + INHIBIT_TIMEOUT;
+        loop {
+            if self.request_to_send()? {
+                self.receive()?;
+                continue;
+            }
+            if self.idle()? {
+                return self.transmit(byte);
+            }
+            if Instant::now() > deadline {
+                return Err(Error::IoError(io::ErrorKind::TimedOut.into()));
+            }
+            thread::sleep(SAMPLE_PERIOD);
+        }
+    }
+
+    // Clock a byte out to the host.
+    fn transmit(&mut self, byte: u8) -> Result<()> {
+        self.last = byte;
+        let half = self.timing.half_period;
+        let start = Instant::now();
+        for (n, bit) in frame(byte).into_iter().enumerate() {
+            let t = start + half * 2 * n as u32;
+            sleep_until(t);
+            self.set_data(bit)?;
+            sleep_until(t + half);
+            self.set_clock(Level::Low)?;
+            sleep_until(t + half * 2);
+            self.set_clock(Level::High)?;
+        }
+        self.set_data(Level::High)?;
+        sleep_until(start + half * 23);
+        Ok(())
+    }
+
+    // Clock a byte in from the host, acknowledge it, and respond.
+    fn receive(&mut self) -> Result<()> {
+        let half = self.timing.half_period;
+        let start = Instant::now() + half;
+        let mut bits = Vec::with_capacity(10);
+        for n in 0..10 {
+            let t = start + half * 2 * n;
+            sleep_until(t);
+            self.set_clock(Level::Low)?;
+            sleep_until(t + half);
+            self.set_clock(Level::High)?;
+            sleep_until(t + half * 2);
+            bits.push(self.level(self.data)?);
+        }
+        let t = start + half * 20;
+        self.set_data(Level::Low)?;
+        self.set_clock(Level::Low)?;
+        sleep_until(t + half);
+        self.set_clock(Level::High)?;
+        sleep_until(t + half * 2);
+        self.set_data(Level::High)?;
+        // allow the host to see the line released before responding
+        sleep_until(t + half * 4);
+
+        let byte = bits[..8]
+            .iter()
+            .enumerate()
+            .filter(|(_, &l)| l == Level::High)
+            .fold(0_u8, |b, (n, _)| b | 1 << n);
+        let responses = if frame(byte)[9..] == bits[8..] {
+            self.commands.push(byte);
+            self.process(byte)
+        } else {
+            vec![RESEND]
+        };
+        for r in responses {
+            self.write(r)?;
+        }
+        Ok(())
+    }
+
+    // Update the device state for a byte from the host, returning the
+    // response.
+    fn process(&mut self, byte: u8) -> Vec<u8> {
+        if let Some(cmd) = self.pending.take() {
+            if cmd == SET_LEDS {
+                self.leds = byte;
+            }
+            return vec![ACK];
+        }
+        match (self.model, byte) {
+            (Model::Keyboard, RESET) => {
+                self.leds = 0;
+                vec![ACK, SELF_TEST_PASSED]
+            }
+            (Model::Mouse, RESET) => vec![ACK, SELF_TEST_PASSED, 0x00],
+            (_, RESEND) => vec![self.last],
+            (Model::Keyboard, IDENTIFY) => vec![ACK, 0xab, 0x83],
+            (Model::Mouse, IDENTIFY) => vec![ACK, 0x00],
+            (Model::Keyboard, ECHO) => vec![ECHO],
+            (Model::Keyboard, SET_LEDS | SET_TYPEMATIC | SET_SCAN_CODE_SET)
+            | (Model::Mouse, SET_TYPEMATIC | SET_RESOLUTION) => {
+                self.pending = Some(byte);
+                vec![ACK]
+            }
+            _ => vec![ACK],
+        }
+    }
+}
+
+fn level(high: bool) -> Level {
+    if high {
+        Level::High
+    } else {
+        Level::Low
+    }
+}
+
+// The levels of the start, data, odd parity and stop bits of a byte.
+fn frame(byte: u8) -> Vec<Level> {
+    let mut bits = vec![Level::Low];
+    bits.extend((0..8).map(|n| level(byte & (1 << n) != 0)));
+    bits.push(level(byte.count_ones() % 2 == 0));
+    bits.push(Level::High);
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    const CLOCK: Offset = 0;
+    const DATA: Offset = 1;
+
+    // A bit-banged host, polling the lines.
+    struct Host<'a> {
+        mem: &'a Memory,
+        chip: &'a Chip,
+    }
+
+    impl Host<'_> {
+        fn wait(&self, offset: Offset, level: Level) {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while self.chip.get_level(offset).unwrap() != level {
+                assert!(Instant::now() < deadline, "timeout waiting for line");
+                thread::sleep(Duration::from_micros(50));
+            }
+        }
+
+        fn read(&self) -> u8 {
+            let mut bits = Vec::new();
+            for _ in 0..11 {
+                self.wait(CLOCK, Level::Low);
+                bits.push(self.chip.get_level(DATA).unwrap());
+                self.wait(CLOCK, Level::High);
+            }
+            assert_eq!(bits[0], Level::Low);
+            let byte = (0..8)
+                .filter(|&n| bits[n + 1] == Level::High)
+                .fold(0_u8, |b, n| b | 1 << n);
+            assert_eq!(frame(byte), bits);
+            byte
+        }
+
+        fn write(&self, byte: u8) -> bool {
+            self.write_frame(&frame(byte))
+        }
+
+        fn write_frame(&self, bits: &[Level]) -> bool {
+            self.mem.drive(self.chip, CLOCK, Some(Level::Low)).unwrap();
+            thread::sleep(Duration::from_millis(1));
+            self.mem.drive(self.chip, DATA, Some(Level::Low)).unwrap();
+            self.mem.drive(self.chip, CLOCK, None).unwrap();
+            for &bit in &bits[1..] {
+                self.wait(CLOCK, Level::Low);
+                let level = if bit == Level::Low { Some(bit) } else { None };
+                self.mem.drive(self.chip, DATA, level).unwrap();
+                self.wait(CLOCK, Level::High);
+            }
+            self.wait(CLOCK, Level::Low);
+            let ack = self.chip.get_level(DATA).unwrap() == Level::Low;
+            self.wait(CLOCK, Level::High);
+            self.wait(DATA, Level::High);
+            ack
+        }
+    }
+
+    #[test]
+    fn frame_bits() {
+        use Level::*;
+        assert_eq!(
+            frame(0x1c),
+            [Low, Low, Low, High, High, High, Low, Low, Low, Low, High]
+        );
+        assert_eq!(frame(0x00)[9], High);
+        assert_eq!(frame(0x01)[9], Low);
+    }
+
+    #[test]
+    fn scancodes() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "ps2"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let kbd = Device::new(c, CLOCK, DATA, Model::Keyboard, Timing::scaled(250)).unwrap();
+        let host = Host { mem: &mem, chip: c };
+        let received = thread::scope(|s| {
+            let reader = s.spawn(|| (0..3).map(|_| host.read()).collect::<Vec<u8>>());
+            kbd.press_key(&[0x1c]).unwrap();
+            kbd.release_key(&[0x1c]).unwrap();
+            reader.join().unwrap()
+        });
+        assert_eq!(received, [0x1c, 0xf0, 0x1c]);
+        assert_eq!(c.get_level(CLOCK).unwrap(), Level::High);
+        assert_eq!(c.get_level(DATA).unwrap(), Level::High);
+    }
+
+    #[test]
+    fn commands() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "ps2"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let kbd = Device::new(c, CLOCK, DATA, Model::Keyboard, Timing::scaled(250)).unwrap();
+        let host = Host { mem: &mem, chip: c };
+        assert!(host.write(RESET));
+        assert_eq!(host.read(), ACK);
+        assert_eq!(host.read(), SELF_TEST_PASSED);
+        assert!(host.write(SET_LEDS));
+        assert_eq!(host.read(), ACK);
+        assert!(host.write(0x05));
+        assert_eq!(host.read(), ACK);
+        kbd.sync();
+        assert_eq!(kbd.leds(), 0x05);
+        // bad parity is answered with a resend request
+        let mut bits = frame(ECHO);
+        bits[9] = bits[9].toggle();
+        assert!(host.write_frame(&bits));
+        assert_eq!(host.read(), RESEND);
+        kbd.sync();
+        assert_eq!(kbd.commands(), [RESET, SET_LEDS, 0x05]);
+    }
+
+    #[test]
+    fn process() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "ps2"))
+            .live()
+            .unwrap();
+        let mouse = Device::new(
+            &sim.chips()[0],
+            CLOCK,
+            DATA,
+            Model::Mouse,
+            Timing::default(),
+        )
+        .unwrap();
+        let mut port = mouse.port.lock().unwrap();
+        assert_eq!(port.process(RESET), [ACK, SELF_TEST_PASSED, 0x00]);
+        assert_eq!(port.process(IDENTIFY), [ACK, 0x00]);
+        assert_eq!(port.process(SET_RESOLUTION), [ACK]);
+        assert_eq!(port.pending, Some(SET_RESOLUTION));
+        assert_eq!(port.process(0x03), [ACK]);
+        assert_eq!(port.pending, None);
+        assert_eq!(port.process(0xf4), [ACK]);
+        port.model = Model::Keyboard;
+        assert_eq!(port.process(IDENTIFY), [ACK, 0xab, 0x83]);
+        assert_eq!(port.process(ECHO), [ECHO]);
+        port.last = 0x1c;
+        assert_eq!(port.process(RESEND), [0x1c]);
+        assert_eq!(port.process(SET_LEDS), [ACK]);
+        assert_eq!(port.process(0x07), [ACK]);
+        assert_eq!(port.leds, 0x07);
+    }
+}
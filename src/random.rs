@@ -0,0 +1,203 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A distribution of durations from which random delays are drawn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Distribution {
+    /// Always the same duration.
+    Fixed(Duration),
+
+    /// Uniformly distributed between min and max, inclusive.
+    Uniform {
+        /// The shortest duration.
+        min: Duration,
+
+        /// The longest duration.
+        max: Duration,
+    },
+}
+
+/// Random jitter applied to the timing of the edges generated by a
+/// generator, e.g. [`uart::Transmitter`].
+///
+/// Each edge is displaced from its ideal time by a duration drawn from the
+/// distribution, randomly either early or late.
+/// The displacement is relative to the ideal time, so jitter does not
+/// accumulate over a long train of edges, though edges are never reordered,
+/// so an edge displaced earlier than a preceding late edge immediately
+/// follows it.
+///
+/// The jitter is fully reproducible from the seed.
+///
+/// [`uart::Transmitter`]: crate::uart::Transmitter
+#[derive(Debug)]
+pub struct Jitter {
+    /// The distribution of the displacements.
+    dist: Distribution,
+
+    seed: u64,
+
+    rng: Mutex<Rng>,
+}
+
+impl Jitter {
+    /// Create jitter with displacements drawn from the distribution, seeded
+    /// with the seed.
+    pub fn new(dist: Distribution, seed: u64) -> Jitter {
+        Jitter {
+            dist,
+            seed,
+            rng: Mutex::new(Rng::new(seed)),
+        }
+    }
+
+    /// The distribution of the displacements.
+    pub fn distribution(&self) -> Distribution {
+        self.dist
+    }
+
+    /// The seed of the jitter.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Displace the ideal time of an edge.
+    pub(crate) fn apply(&self, ideal: Instant) -> Instant {
+        let mut rng = self.rng.lock().unwrap();
+        let offset = rng.duration(&self.dist);
+        if rng.next_u64() & 1 != 0 {
+            ideal + offset
+        } else {
+            ideal.checked_sub(offset).unwrap_or(ideal)
+        }
+    }
+}
+
+impl Clone for Jitter {
+    /// The clone restarts the jitter sequence from the seed.
+    fn clone(&self) -> Self {
+        Jitter::new(self.dist, self.seed)
+    }
+}
+
+impl Default for Jitter {
+    /// No jitter.
+    fn default() -> Self {
+        Jitter::new(Distribution::Fixed(Duration::ZERO), 0)
+    }
+}
+
+/// A small, fast and reproducible pseudo-random number generator.
+///
+/// This is the SplitMix64 generator, which is more than adequate for
+/// generating stimulus, and avoids a dependency on an external crate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value uniformly distributed in the range [0, n].
+    pub(crate) fn up_to(&mut self, n: u64) -> u64 {
+        match n.checked_add(1) {
+            Some(m) => self.next_u64() % m,
+            None => self.next_u64(),
+        }
+    }
+
+    /// A duration drawn from the distribution.
+    pub(crate) fn duration(&mut self, dist: &Distribution) -> Duration {
+        match *dist {
+            Distribution::Fixed(d) => d,
+            Distribution::Uniform { min, max } => {
+                if max <= min {
+                    return min;
+                }
+                let span = (max - min).as_nanos().min(u64::MAX as u128) as u64;
+                min + Duration::from_nanos(self.up_to(span))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut r1 = Rng::new(42);
+        let mut r2 = Rng::new(42);
+        let mut r3 = Rng::new(43);
+        let s1: Vec<u64> = (0..8).map(|_| r1.next_u64()).collect();
+        let s2: Vec<u64> = (0..8).map(|_| r2.next_u64()).collect();
+        let s3: Vec<u64> = (0..8).map(|_| r3.next_u64()).collect();
+        assert_eq!(s1, s2);
+        assert_ne!(s1, s3);
+        // reference value for SplitMix64 seeded with 0
+        assert_eq!(Rng::new(0).next_u64(), 0xe220_a839_7b1d_cdaf);
+    }
+
+    #[test]
+    fn durations() {
+        let mut r = Rng::new(1);
+        let d = Duration::from_millis(3);
+        assert_eq!(r.duration(&Distribution::Fixed(d)), d);
+        let dist = Distribution::Uniform {
+            min: Duration::from_micros(10),
+            max: Duration::from_micros(20),
+        };
+        for _ in 0..100 {
+            let d = r.duration(&dist);
+            assert!(d >= Duration::from_micros(10));
+            assert!(d <= Duration::from_micros(20));
+        }
+        let dist = Distribution::Uniform {
+            min: Duration::from_micros(10),
+            max: Duration::from_micros(5),
+        };
+        assert_eq!(r.duration(&dist), Duration::from_micros(10));
+        let mut counts = [0; 4];
+        for _ in 0..400 {
+            counts[r.up_to(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 50));
+    }
+    #[test]
+    fn jitter() {
+        let now = Instant::now() + Duration::from_secs(1);
+        let none = Jitter::default();
+        assert_eq!(none.apply(now), now);
+        let dist = Distribution::Uniform {
+            min: Duration::ZERO,
+            max: Duration::from_micros(100),
+        };
+        let j1 = Jitter::new(dist, 7);
+        let j2 = j1.clone();
+        let s1: Vec<Instant> = (0..100).map(|_| j1.apply(now)).collect();
+        let s2: Vec<Instant> = (0..100).map(|_| j2.apply(now)).collect();
+        assert_eq!(s1, s2);
+        assert_eq!(j1.seed(), 7);
+        assert_eq!(j1.distribution(), dist);
+        let max = Duration::from_micros(100);
+        assert!(s1.iter().all(|&t| t <= now + max && t >= now - max));
+        assert!(s1.iter().any(|&t| t < now));
+        assert!(s1.iter().any(|&t| t > now));
+    }
+}
@@ -0,0 +1,538 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Control of a simulator from another host, or process.
+//!
+//! A [`Server`] exposes the chips of a simulator via a TCP or unix socket, and
+//! a [`RemoteChip`] drives a chip on a server with the same pull and level
+//! API as a [`Chip`], so tests running on the development host can drive a
+//! simulator living on the target device.
+//!
+//! The protocol is line delimited JSON, so the server may also be driven by
+//! other clients, e.g. from Python.
+//! Each request is an object identifying the operation, the chip, by label,
+//! and the line:
+//!
+//! ```text
+//! {"op": "set_pull", "chip": "left", "offset": 3, "pull": "high"}
+//! {"op": "get_pull", "chip": "left", "offset": 3}
+//! {"op": "get_level", "chip": "left", "offset": 3}
+//! {"op": "toggle", "chip": "left", "offset": 3}
+//! {"op": "info", "chip": "left"}
+//! ```
+//!
+//! and is answered with either a `{"result": ...}` or an `{"error": "..."}`.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::remote::{RemoteChip, Server};
+//! use gpiosim::{Bank, Level};
+//!
+//! // on the target
+//! let sim = gpiosim::builder().with_bank(&Bank::new(8, "left")).live()?;
+//! let server = Server::bind(&sim, "0.0.0.0:9797")?;
+//!
+//! // on the host
+//! let c = RemoteChip::connect("target:9797", "left")?;
+//! c.pullup(3)?;
+//! assert_eq!(c.get_pull(3)?, Level::High);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Chip`]: crate::Chip
+
+use crate::{Chip, Error, Level, Offset, Result, Sim};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often idle connections check if the server has been stopped.
+const POLL_PERIOD: Duration = Duration::from_millis(100);
+
+/// The longest request accepted from a client, including the newline.
+const MAX_REQUEST: usize = 1 << 16;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    Info {
+        chip: String,
+    },
+    SetPull {
+        chip: String,
+        offset: Offset,
+        pull: Level,
+    },
+    GetPull {
+        chip: String,
+        offset: Offset,
+    },
+    GetLevel {
+        chip: String,
+        offset: Offset,
+    },
+    Toggle {
+        chip: String,
+        offset: Offset,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Response {
+    Result(Value),
+    Error(String),
+}
+
+// Apply a request to the chips, returning the response.
+pub(crate) fn handle(chips: &[Chip], request: &str) -> String {
+    let response = match execute(chips, request) {
+        Ok(v) => Response::Result(v),
+        Err(e) => Response::Error(e.to_string()),
+    };
+    // serialization of the response cannot fail
+    serde_json::to_string(&response).unwrap()
+}
+
+fn execute(chips: &[Chip], request: &str) -> Result<Value> {
+    let request: Request = serde_json::from_str(request)
+        .map_err(|e| Error::InvalidConfig(format!("invalid request: {}", e)))?;
+    let find = |label: &str| {
+        chips
+            .iter()
+            .find(|c| c.cfg.label == label)
+            .ok_or_else(|| Error::InvalidConfig(format!("unknown chip {:?}", label)))
+    };
+    let level = |l: Level| serde_json::to_value(l).unwrap();
+    Ok(match request {
+        Request::Info { chip } => {
+            let c = find(&chip)?;
+            serde_json::json!({ "label": c.cfg.label, "num_lines": c.cfg.num_lines })
+        }
+        Request::SetPull { chip, offset, pull } => {
+            find(&chip)?.set_pull(offset, pull)?;
+            Value::Null
+        }
+        Request::GetPull { chip, offset } => level(find(&chip)?.get_pull(offset)?),
+        Request::GetLevel { chip, offset } => level(find(&chip)?.get_level(offset)?),
+        Request::Toggle { chip, offset } => level(find(&chip)?.toggle(offset)?),
+    })
+}
+
+// The operations common to TCP and unix streams.
+trait Stream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Stream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl Stream for UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        UnixStream::try_clone(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[derive(Debug)]
+enum Addr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+/// A server exposing the chips of a simulator to [`RemoteChip`]s.
+///
+/// Each connection is served by its own thread.
+/// The server, and any open connections, are stopped when dropped.
+/// The chips remain available to clients even if the simulator itself is
+/// dropped, though requests fail once the simulator is removed.
+#[derive(Debug)]
+pub struct Server {
+    addr: Addr,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /// Serve the chips of the simulator via TCP on the given address.
+    pub fn bind<A: ToSocketAddrs>(sim: &Sim, addr: A) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        Ok(Server::spawn(sim, Addr::Tcp(addr), move |stop, chips| {
+            accept(listener.incoming(), stop, chips)
+        }))
+    }
+
+    /// Serve the chips of the simulator via a unix socket at the given path.
+    ///
+    /// The socket is removed when the server is dropped.
+    pub fn bind_unix<P: AsRef<Path>>(sim: &Sim, path: P) -> io::Result<Server> {
+        let path = path.as_ref().to_path_buf();
+        let listener = UnixListener::bind(&path)?;
+        Ok(Server::spawn(sim, Addr::Unix(path), move |stop, chips| {
+            accept(listener.incoming(), stop, chips)
+        }))
+    }
+
+    fn spawn<F>(sim: &Sim, addr: Addr, accept: F) -> Server
+    where
+        F: FnOnce(Arc<AtomicBool>, Arc<Vec<Chip>>) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let chips = Arc::new(sim.chips().iter().map(Chip::share).collect());
+        let done = stop.clone();
+        let thread = thread::spawn(move || accept(done, chips));
+        Server {
+            addr,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// The address the server is listening on, if it is a TCP server.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self.addr {
+            Addr::Tcp(addr) => Some(addr),
+            Addr::Unix(_) => None,
+        }
+    }
+
+    /// The path of the socket the server is listening on, if it is a unix
+    /// socket server.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.addr {
+            Addr::Tcp(_) => None,
+            Addr::Unix(path) => Some(path),
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // unblock the accept
+        match &self.addr {
+            Addr::Tcp(addr) => {
+                let _ = TcpStream::connect(addr);
+            }
+            Addr::Unix(path) => {
+                let _ = UnixStream::connect(path);
+            }
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        if let Addr::Unix(path) = &self.addr {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn accept<S, I>(incoming: I, stop: Arc<AtomicBool>, chips: Arc<Vec<Chip>>)
+where
+    S: Stream,
+    I: Iterator<Item = io::Result<S>>,
+{
+    let mut sessions: Vec<thread::JoinHandle<io::Result<()>>> = Vec::new();
+    for stream in incoming {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            let stop = stop.clone();
+            let chips = chips.clone();
+            sessions.retain(|s| !s.is_finished());
+            sessions.push(thread::spawn(move || serve(stream, &stop, &chips)));
+        }
+    }
+    // the sessions stop within a poll period
+    for s in sessions {
+        let _ = s.join();
+    }
+}
+
+// Serve the requests on the connection until it is closed or the server is
+// stopped.
+fn serve<S: Stream>(mut stream: S, stop: &AtomicBool, chips: &[Chip]) -> io::Result<()> {
+    stream.set_read_timeout(Some(POLL_PERIOD))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while !stop.load(Ordering::Relaxed) {
+        // a partial line read before a timeout remains in the line
+        let limit = (MAX_REQUEST - line.len()) as u64;
+        match (&mut reader).take(limit).read_line(&mut line) {
+            Ok(0) if line.len() < MAX_REQUEST => break,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    writeln!(stream, "{}", handle(chips, &line))?;
+                    line.clear();
+                } else if line.len() >= MAX_REQUEST {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "request too long",
+                    ));
+                }
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+struct Connection {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+}
+
+/// A chip on a remote [`Server`].
+///
+/// Requests are sent over a single connection, so are serialized.
+pub struct RemoteChip {
+    label: String,
+    num_lines: u32,
+    conn: Mutex<Connection>,
+}
+
+impl std::fmt::Debug for RemoteChip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteChip")
+            .field("label", &self.label)
+            .field("num_lines", &self.num_lines)
+            .finish()
+    }
+}
+
+impl RemoteChip {
+    /// Connect to the chip with the label on the TCP server at the address.
+    pub fn connect<A: ToSocketAddrs>(addr: A, label: &str) -> Result<RemoteChip> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        RemoteChip::open(Box::new(stream.try_clone()?), Box::new(stream), label)
+    }
+
+    /// Connect to the chip with the label on the unix socket server at the
+    /// path.
+    pub fn connect_unix<P: AsRef<Path>>(path: P, label: &str) -> Result<RemoteChip> {
+        let stream = UnixStream::connect(path)?;
+        RemoteChip::open(Box::new(stream.try_clone()?), Box::new(stream), label)
+    }
+
+    fn open(
+        reader: Box<dyn Read + Send>,
+        writer: Box<dyn Write + Send>,
+        label: &str,
+    ) -> Result<RemoteChip> {
+        let mut chip = RemoteChip {
+            label: label.into(),
+            num_lines: 0,
+            conn: Mutex::new(Connection {
+                reader: BufReader::new(reader),
+                writer,
+            }),
+        };
+        let info = chip.request(&Request::Info { chip: label.into() })?;
+        chip.num_lines = info["num_lines"]
+            .as_u64()
+            .ok_or_else(|| Error::UnexpectedValue(info.to_string()))?
+            as u32;
+        Ok(chip)
+    }
+
+    fn request(&self, request: &Request) -> Result<Value> {
+        let mut conn = self.conn.lock().unwrap();
+        let mut msg = serde_json::to_string(request).unwrap();
+        msg.push('\n');
+        conn.writer.write_all(msg.as_bytes())?;
+        conn.writer.flush()?;
+        let mut line = String::new();
+        if conn.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        match serde_json::from_str(&line) {
+            Ok(Response::Result(v)) => Ok(v),
+            Ok(Response::Error(e)) => Err(Error::RemoteError(e)),
+            Err(_) => Err(Error::UnexpectedValue(line)),
+        }
+    }
+
+    fn level(&self, request: &Request) -> Result<Level> {
+        let v = self.request(request)?;
+        serde_json::from_value(v.clone()).map_err(|_| Error::UnexpectedValue(v.to_string()))
+    }
+
+    /// The label of the chip.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The number of lines on the chip.
+    pub fn num_lines(&self) -> u32 {
+        self.num_lines
+    }
+
+    /// Set the pull on a line.
+    pub fn set_pull(&self, offset: Offset, pull: Level) -> Result<()> {
+        self.request(&Request::SetPull {
+            chip: self.label.clone(),
+            offset,
+            pull,
+        })?;
+        Ok(())
+    }
+
+    /// Pull a line up.
+    pub fn pullup(&self, offset: Offset) -> Result<()> {
+        self.set_pull(offset, Level::High)
+    }
+
+    /// Pull a line down.
+    pub fn pulldown(&self, offset: Offset) -> Result<()> {
+        self.set_pull(offset, Level::Low)
+    }
+
+    /// Toggle the pull on a line, returning the new pull.
+    pub fn toggle(&self, offset: Offset) -> Result<Level> {
+        self.level(&Request::Toggle {
+            chip: self.label.clone(),
+            offset,
+        })
+    }
+
+    /// Get the pull on a line.
+    pub fn get_pull(&self, offset: Offset) -> Result<Level> {
+        self.level(&Request::GetPull {
+            chip: self.label.clone(),
+            offset,
+        })
+    }
+
+    /// Get the level of a line.
+    pub fn get_level(&self, offset: Offset) -> Result<Level> {
+        self.level(&Request::GetLevel {
+            chip: self.label.clone(),
+            offset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn tcp() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(2, "right"))
+            .live()
+            .unwrap();
+        let server = Server::bind(&sim, "127.0.0.1:0").unwrap();
+        assert!(server.path().is_none());
+        let addr = server.local_addr().unwrap();
+        let c = RemoteChip::connect(addr, "right").unwrap();
+        assert_eq!(c.label(), "right");
+        assert_eq!(c.num_lines(), 2);
+        c.pullup(1).unwrap();
+        assert_eq!(sim.chips()[1].get_pull(1).unwrap(), Level::High);
+        assert_eq!(c.get_level(1).unwrap(), Level::High);
+        assert_eq!(c.toggle(1).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(1).unwrap(), Level::Low);
+        mem.drive(&sim.chips()[1], 0, Some(Level::High)).unwrap();
+        assert_eq!(c.get_level(0).unwrap(), Level::High);
+        assert!(matches!(c.pullup(2), Err(Error::RemoteError(_))));
+        assert!(matches!(
+            RemoteChip::connect(addr, "middle"),
+            Err(Error::RemoteError(_))
+        ));
+        drop(server);
+        assert!(c.pullup(0).is_err());
+    }
+
+    #[test]
+    fn unix() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("{}.sock", sim.name()));
+        let server = Server::bind_unix(&sim, &path).unwrap();
+        assert_eq!(server.path(), Some(path.as_path()));
+        assert!(server.local_addr().is_none());
+        let c = RemoteChip::connect_unix(&path, "left").unwrap();
+        c.pullup(3).unwrap();
+        assert_eq!(sim.chips()[0].get_level(3).unwrap(), Level::High);
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn request_too_long() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let server = Server::bind(&sim, "127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        // the server may close the connection before the whole request is sent
+        let _ = stream.write_all(&[b' '; MAX_REQUEST + 1]);
+        let mut buf = [0; 1];
+        assert!(!matches!(stream.read(&mut buf), Ok(n) if n > 0));
+    }
+
+    #[test]
+    fn protocol() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let chips = sim.chips();
+        assert_eq!(
+            handle(
+                chips,
+                r#"{"op": "set_pull", "chip": "left", "offset": 3, "pull": "high"}"#
+            ),
+            r#"{"result":null}"#
+        );
+        assert_eq!(
+            handle(chips, r#"{"op": "get_level", "chip": "left", "offset": 3}"#),
+            r#"{"result":"high"}"#
+        );
+        assert_eq!(
+            handle(chips, r#"{"op": "info", "chip": "left"}"#),
+            r#"{"result":{"label":"left","num_lines":4}}"#
+        );
+        assert!(handle(chips, r#"{"op": "frobnicate"}"#).starts_with(r#"{"error":"#));
+    }
+}
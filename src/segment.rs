@@ -0,0 +1,264 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Error, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+
+/// The segment patterns of the characters, with segment a as bit 0 through
+/// segment g as bit 6.
+const CHARS: [(u8, char); 22] = [
+    (0x3f, '0'),
+    (0x06, '1'),
+    (0x5b, '2'),
+    (0x4f, '3'),
+    (0x66, '4'),
+    (0x6d, '5'),
+    (0x7d, '6'),
+    (0x07, '7'),
+    (0x7f, '8'),
+    (0x6f, '9'),
+    (0x77, 'A'),
+    (0x7c, 'b'),
+    (0x39, 'C'),
+    (0x5e, 'd'),
+    (0x79, 'E'),
+    (0x71, 'F'),
+    (0x76, 'H'),
+    (0x38, 'L'),
+    (0x73, 'P'),
+    (0x3e, 'U'),
+    (0x40, '-'),
+    (0x00, ' '),
+];
+
+/// The bit of the decimal point in a segment pattern.
+const DP: u8 = 0x80;
+
+/// A monitor of a seven-segment display driven by the consumer.
+///
+/// The consumer drives the segment lines, a through g and optionally the
+/// decimal point, and, for multiplexed displays, the digit select lines.
+/// The monitor records the segments lit while each digit is selected.
+///
+/// By default the segments and digit selects are active high.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, SevenSegment};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(10, "display")).live()?;
+/// let display = SevenSegment::new(&sim.chips()[0], &[0, 1, 2, 3, 4, 5, 6], &[8, 9])?;
+///
+/// // ... consumer scans "42" onto the display ...
+///
+/// display.sync();
+/// assert_eq!(display.text(), "42");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SevenSegment {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The number of segment lines.
+    segments: usize,
+
+    /// The level of a segment line when lit.
+    segment_active: Level,
+
+    /// The level of a digit select line when selected.
+    digit_active: Level,
+
+    /// The segments most recently lit for each digit.
+    patterns: Vec<Option<u8>>,
+}
+
+impl State {
+    fn update(&mut self, levels: &[Level]) {
+        let (segments, digits) = levels.split_at(self.segments);
+        let pattern = segments
+            .iter()
+            .enumerate()
+            .filter(|(_, &l)| l == self.segment_active)
+            .fold(0, |p, (n, _)| p | 1 << n);
+        if digits.is_empty() {
+            self.patterns[0] = Some(pattern);
+            return;
+        }
+        for (p, &d) in self.patterns.iter_mut().zip(digits) {
+            if d == self.digit_active {
+                *p = Some(pattern);
+            }
+        }
+    }
+}
+
+impl SevenSegment {
+    /// Start monitoring a display with the segment and digit select lines.
+    ///
+    /// The segments are in order a through g, optionally followed by the
+    /// decimal point.
+    /// The digits are in order from left to right, and may be empty for a
+    /// single, statically driven, digit.
+    pub fn new(chip: &Chip, segments: &[Offset], digits: &[Offset]) -> Result<SevenSegment> {
+        if segments.len() != 7 && segments.len() != 8 {
+            return Err(Error::InvalidConfig(format!(
+                "seven-segment display requires 7 or 8 segments, not {}",
+                segments.len()
+            )));
+        }
+        let state = Arc::new(Mutex::new(State {
+            segments: segments.len(),
+            segment_active: Level::High,
+            digit_active: Level::High,
+            patterns: vec![None; digits.len().max(1)],
+        }));
+        let mut offsets = segments.to_vec();
+        offsets.extend_from_slice(digits);
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &offsets, move |_, _, curr| {
+                state.lock().unwrap().update(curr)
+            })?
+        };
+        Ok(SevenSegment { state, monitor })
+    }
+
+    /// Set the levels of the segment and digit select lines when active.
+    ///
+    /// e.g. a multiplexed common cathode display typically has active high
+    /// segments and active low digit selects.
+    pub fn with_active(&mut self, segment: Level, digit: Level) -> &mut Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.segment_active = segment;
+            state.digit_active = digit;
+        }
+        self
+    }
+
+    /// The segments most recently lit for each digit, with segment a as bit 0
+    /// through to the decimal point as bit 7.
+    ///
+    /// Digits that have never been selected are `None`.
+    pub fn patterns(&self) -> Vec<Option<u8>> {
+        self.state.lock().unwrap().patterns.clone()
+    }
+
+    /// The text most recently shown on the display.
+    ///
+    /// Digits that have never been selected are shown as spaces, patterns that
+    /// are not recognised as characters as '?', and lit decimal points as '.'
+    /// following the digit.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for p in self.patterns() {
+            let p = p.unwrap_or(0);
+            text.push(SevenSegment::decode(p).unwrap_or('?'));
+            if p & DP != 0 {
+                text.push('.');
+            }
+        }
+        text
+    }
+
+    /// The character displayed by the segment pattern, ignoring the decimal
+    /// point.
+    pub fn decode(pattern: u8) -> Option<char> {
+        CHARS
+            .iter()
+            .find(|(p, _)| *p == pattern & !DP)
+            .map(|(_, c)| *c)
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn decode() {
+        assert_eq!(SevenSegment::decode(0x3f), Some('0'));
+        assert_eq!(SevenSegment::decode(0x5b | DP), Some('2'));
+        assert_eq!(SevenSegment::decode(0x71), Some('F'));
+        assert_eq!(SevenSegment::decode(0x01), None);
+    }
+
+    #[test]
+    fn multiplexed() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(10, "display"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let segments = [0, 1, 2, 3, 4, 5, 6, 7];
+        for d in [8, 9] {
+            mem.drive(c, d, Some(Level::High)).unwrap();
+        }
+        let mut display = SevenSegment::new(c, &segments, &[8, 9]).unwrap();
+        display.with_active(Level::High, Level::Low);
+        display.sync();
+        assert_eq!(display.patterns(), [None, None]);
+        assert_eq!(display.text(), "  ");
+        let light = |pattern: u8| {
+            for (n, &s) in segments.iter().enumerate() {
+                let level = if pattern & (1 << n) != 0 {
+                    Level::High
+                } else {
+                    Level::Low
+                };
+                mem.drive(c, s, Some(level)).unwrap();
+            }
+            display.sync();
+        };
+        let show = |digit, pattern: u8| {
+            light(pattern);
+            mem.drive(c, digit, Some(Level::Low)).unwrap();
+            display.sync();
+            mem.drive(c, digit, Some(Level::High)).unwrap();
+            display.sync();
+        };
+        show(8, 0x66);
+        show(9, 0x5b | DP);
+        assert_eq!(display.patterns(), [Some(0x66), Some(0xdb)]);
+        assert_eq!(display.text(), "42.");
+        // segment changes while no digit is selected are ignored
+        light(0x01);
+        assert_eq!(display.text(), "42.");
+        show(8, 0x01);
+        assert_eq!(display.text(), "?2.");
+    }
+
+    #[test]
+    fn single() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(7, "display"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let display = SevenSegment::new(c, &[0, 1, 2, 3, 4, 5, 6], &[]).unwrap();
+        for s in [1, 2] {
+            mem.drive(c, s, Some(Level::High)).unwrap();
+        }
+        display.sync();
+        assert_eq!(display.text(), "1");
+        assert!(SevenSegment::new(c, &[0, 1, 2], &[]).is_err());
+    }
+}
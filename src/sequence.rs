@@ -0,0 +1,270 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::timing::sleep_until;
+use crate::{check_word_width, BitOrder, Chip, Error, Jitter, Level, Offset, Result};
+use std::time::{Duration, Instant};
+
+/// The encoding of the position of a [`Sequence`] onto its lines.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Coding {
+    /// The lines hold the position as a binary number, so several lines may
+    /// change on each step.
+    #[default]
+    Binary,
+
+    /// The lines hold the position as a reflected binary Gray code, so exactly
+    /// one line changes on each step.
+    Gray,
+}
+
+impl Coding {
+    /// The word representing the position.
+    pub fn encode(&self, position: u64) -> u64 {
+        match self {
+            Coding::Binary => position,
+            Coding::Gray => position ^ (position >> 1),
+        }
+    }
+
+    /// The position represented by the word.
+    pub fn decode(&self, word: u64) -> u64 {
+        match self {
+            Coding::Binary => word,
+            Coding::Gray => {
+                let mut position = word;
+                let mut shift = 1;
+                while shift < 64 {
+                    position ^= position >> shift;
+                    shift <<= 1;
+                }
+                position
+            }
+        }
+    }
+}
+
+/// A generator that walks a group of lines through a counting or Gray code
+/// sequence.
+///
+/// The position wraps at the width of the group, as per an absolute encoder
+/// completing a revolution.
+/// Only the lines that change are pulled on each step, in the order of the
+/// group.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Coding, Sequence};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "abs")).live()?;
+/// let mut seq = Sequence::new(&sim.chips()[0], &[0, 1, 2, 3], Coding::Gray)?;
+/// seq.with_period(Duration::from_millis(2));
+/// seq.step_up(20)?;
+/// assert_eq!(seq.position(), 4);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sequence {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    offsets: Vec<Offset>,
+
+    order: BitOrder,
+
+    coding: Coding,
+
+    /// The time between successive steps.
+    period: Duration,
+
+    /// The jitter applied to the steps.
+    jitter: Jitter,
+
+    /// The current position within the sequence.
+    position: u64,
+}
+
+impl Sequence {
+    /// Create a sequence on the group of lines, and pull the lines to
+    /// position 0.
+    ///
+    /// The group may contain at most 64 lines.
+    /// By default the first line holds the least significant bit, and the
+    /// period between steps is 1ms.
+    pub fn new(chip: &Chip, offsets: &[Offset], coding: Coding) -> Result<Sequence> {
+        check_word_width(offsets)?;
+        if offsets.is_empty() {
+            return Err(Error::InvalidConfig(
+                "sequence requires at least one line".into(),
+            ));
+        }
+        for &offset in offsets {
+            check_offset(chip, offset)?;
+        }
+        let seq = Sequence {
+            chip: chip.share(),
+            offsets: offsets.to_vec(),
+            order: BitOrder::default(),
+            coding,
+            period: Duration::from_millis(1),
+            jitter: Jitter::default(),
+            position: 0,
+        };
+        seq.pull(None)?;
+        Ok(seq)
+    }
+
+    /// Set the order of the bits on the lines, and pull the lines to match.
+    pub fn with_order(&mut self, order: BitOrder) -> Result<&mut Self> {
+        self.order = order;
+        self.pull(None)?;
+        Ok(self)
+    }
+
+    /// Set the time between successive steps.
+    pub fn with_period(&mut self, period: Duration) -> &mut Self {
+        self.period = period;
+        self
+    }
+
+    /// Set the jitter applied to the steps.
+    ///
+    /// There is no jitter by default.
+    pub fn with_jitter(&mut self, jitter: Jitter) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The current position within the sequence.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The word currently encoded on the lines.
+    pub fn word(&self) -> u64 {
+        self.coding.encode(self.position)
+    }
+
+    /// The number of positions before the sequence wraps.
+    ///
+    /// Zero for a 64 line group, which does not wrap within a u64.
+    pub fn positions(&self) -> u64 {
+        match self.offsets.len() {
+            64 => 0,
+            w => 1 << w,
+        }
+    }
+
+    /// Move directly to a position, wrapped to the width of the group.
+    pub fn set_position(&mut self, position: u64) -> Result<()> {
+        let prev = self.word();
+        self.position = position & self.mask();
+        self.pull(Some(prev))
+    }
+
+    /// Step forward through the sequence.
+    ///
+    /// The first step is immediate, and successive steps are separated by the
+    /// period, with the call returning a period after the final step.
+    pub fn step_up(&mut self, steps: u64) -> Result<()> {
+        self.step(steps, 1)
+    }
+
+    /// Step backward through the sequence.
+    pub fn step_down(&mut self, steps: u64) -> Result<()> {
+        self.step(steps, self.mask())
+    }
+
+    // Step through the sequence, adding delta to the position on each step.
+    fn step(&mut self, steps: u64, delta: u64) -> Result<()> {
+        let start = Instant::now();
+        for n in 0..steps {
+            if n != 0 {
+                sleep_until(self.jitter.apply(start + self.period * n as u32));
+            }
+            let prev = self.word();
+            self.position = self.position.wrapping_add(delta) & self.mask();
+            self.pull(Some(prev))?;
+        }
+        if steps != 0 {
+            sleep_until(start + self.period * steps as u32);
+        }
+        Ok(())
+    }
+
+    fn mask(&self) -> u64 {
+        self.positions().wrapping_sub(1)
+    }
+
+    // Pull the lines that differ from the previous word, or all lines if none.
+    fn pull(&self, prev: Option<u64>) -> Result<()> {
+        let word = self.word();
+        for (bit, offset) in self.order.bits(&self.offsets) {
+            let mask = 1 << bit;
+            if prev.map_or(true, |p| (p ^ word) & mask != 0) {
+                let pull = if word & mask == 0 {
+                    Level::Low
+                } else {
+                    Level::High
+                };
+                self.chip.set_pull(offset, pull)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn coding() {
+        let gray: Vec<u64> = (0..8).map(|n| Coding::Gray.encode(n)).collect();
+        assert_eq!(gray, [0, 1, 3, 2, 6, 7, 5, 4]);
+        for n in [0, 1, 5, 1234, u64::MAX] {
+            assert_eq!(Coding::Gray.decode(Coding::Gray.encode(n)), n);
+            assert_eq!(Coding::Binary.decode(Coding::Binary.encode(n)), n);
+        }
+    }
+
+    #[test]
+    fn step() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "seq"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let offsets = [0, 1, 2];
+        let mut seq = Sequence::new(c, &offsets, Coding::Gray).unwrap();
+        seq.with_period(Duration::from_micros(100));
+        assert_eq!(seq.positions(), 8);
+        c.reset_stats();
+        seq.step_up(10).unwrap();
+        assert_eq!(seq.position(), 2);
+        assert_eq!(c.get_word(&offsets, BitOrder::LsbFirst).unwrap(), 3);
+        // one line changes per step
+        let pulls: u64 = c.stats().iter().map(|s| s.pulls).sum();
+        assert_eq!(pulls, 10);
+        seq.step_down(3).unwrap();
+        assert_eq!(seq.position(), 7);
+        assert_eq!(seq.word(), 4);
+        assert_eq!(c.get_word(&offsets, BitOrder::LsbFirst).unwrap(), 4);
+
+        let mut seq = Sequence::new(c, &offsets, Coding::Binary).unwrap();
+        seq.with_order(BitOrder::MsbFirst).unwrap();
+        seq.set_position(6).unwrap();
+        assert_eq!(c.get_word(&offsets, BitOrder::MsbFirst).unwrap(), 6);
+        seq.set_position(9).unwrap();
+        assert_eq!(seq.position(), 1);
+
+        assert!(Sequence::new(c, &[], Coding::Binary).is_err());
+        assert!(Sequence::new(c, &[3, 4], Coding::Binary).is_err());
+    }
+}
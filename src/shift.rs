@@ -0,0 +1,364 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Edge, Error, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+
+/// An emulator of a serial-in, parallel-out shift register, such as the 74HC595,
+/// driven by the consumer.
+///
+/// The consumer drives the serial data, shift clock and storage (latch) clock
+/// lines.
+/// The data is shifted in on each rising edge of the shift clock, with the
+/// first bit shifted ending up as the most significant bit of the register,
+/// and the shift register is copied to the storage register on each rising
+/// edge of the latch clock.
+///
+/// Daisy chained registers may be emulated as a single wider register.
+///
+/// The output enable and master reset inputs are not emulated.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Hc595};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(3, "595")).live()?;
+/// let sr = Hc595::new(&sim.chips()[0], 0, 1, 2, 8)?;
+///
+/// // ... consumer shifts out 0xa5 and latches it ...
+///
+/// sr.sync();
+/// assert_eq!(sr.outputs(), 0xa5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Hc595 {
+    state: Arc<Mutex<Hc595State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug, Default)]
+struct Hc595State {
+    /// The contents of the shift register.
+    shift: u64,
+
+    /// The contents of the storage register.
+    outputs: u64,
+
+    /// The values latched into the storage register, oldest first.
+    history: Vec<u64>,
+}
+
+impl Hc595 {
+    /// Start emulating a shift register of the given width, in bits, on the
+    /// data, clock and latch lines.
+    ///
+    /// The width may be at most 64.
+    pub fn new(
+        chip: &Chip,
+        data: Offset,
+        clock: Offset,
+        latch: Offset,
+        width: u32,
+    ) -> Result<Hc595> {
+        let mask = width_mask(width)?;
+        let state = Arc::new(Mutex::new(Hc595State::default()));
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &[data, clock, latch], move |_, prev, curr| {
+                let mut state = state.lock().unwrap();
+                if Edge::Rising.matches(prev[1], curr[1]) {
+                    state.shift = (state.shift << 1 | (curr[0] == Level::High) as u64) & mask;
+                }
+                if Edge::Rising.matches(prev[2], curr[2]) {
+                    state.outputs = state.shift;
+                    let outputs = state.outputs;
+                    state.history.push(outputs);
+                }
+            })?
+        };
+        Ok(Hc595 { state, monitor })
+    }
+
+    /// The contents of the storage register, as presented on the parallel outputs.
+    pub fn outputs(&self) -> u64 {
+        self.state.lock().unwrap().outputs
+    }
+
+    /// The contents of the shift register, which have not necessarily been latched.
+    pub fn shift_register(&self) -> u64 {
+        self.state.lock().unwrap().shift
+    }
+
+    /// The values latched into the storage register so far, oldest first.
+    pub fn history(&self) -> Vec<u64> {
+        self.state.lock().unwrap().history.clone()
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+/// An emulator of a parallel-in, serial-out shift register, such as the 74HC165,
+/// read by the consumer.
+///
+/// The test sets the parallel inputs, and the consumer drives the clock and
+/// load lines to read them.
+/// While the load line is low the parallel inputs are loaded into the register.
+/// While the load line is high the register is shifted towards the most
+/// significant bit on each rising edge of the clock, shifting in zeros.
+/// The most significant bit of the register is presented on the serial output
+/// line via its pull.
+///
+/// Daisy chained registers may be emulated as a single wider register.
+///
+/// The clock inhibit input is not emulated.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Hc165};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(3, "165")).live()?;
+/// let sr = Hc165::new(&sim.chips()[0], 0, 1, 2, 8)?;
+/// sr.set_inputs(0xa5)?;
+///
+/// // ... consumer loads and shifts in the inputs ...
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Hc165 {
+    /// The chip containing the serial output.
+    chip: Chip,
+
+    /// The serial output line.
+    output: Offset,
+
+    state: Arc<Mutex<Hc165State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug)]
+struct Hc165State {
+    /// The level of the parallel inputs.
+    inputs: u64,
+
+    /// The contents of the shift register.
+    shift: u64,
+
+    /// True while the load line is low.
+    loading: bool,
+
+    /// The mask of bits within the register.
+    mask: u64,
+
+    /// The most significant bit of the register.
+    msb: u64,
+}
+
+impl Hc165State {
+    // The level of the serial output.
+    fn output(&self) -> Level {
+        if self.shift & self.msb == 0 {
+            Level::Low
+        } else {
+            Level::High
+        }
+    }
+}
+
+impl Hc165 {
+    /// Start emulating a shift register of the given width, in bits, with the
+    /// serial output, clock and load lines.
+    ///
+    /// The parallel inputs are initially all low.
+    ///
+    /// The width may be at most 64.
+    pub fn new(
+        chip: &Chip,
+        output: Offset,
+        clock: Offset,
+        load: Offset,
+        width: u32,
+    ) -> Result<Hc165> {
+        let mask = width_mask(width)?;
+        chip.set_pull(output, Level::Low)?;
+        let state = Arc::new(Mutex::new(Hc165State {
+            inputs: 0,
+            shift: 0,
+            loading: chip.dev.get_level(chip.bank, load)? == Level::Low,
+            mask,
+            msb: 1 << (width - 1),
+        }));
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &[clock, load], move |chip, prev, curr| {
+                let mut state = state.lock().unwrap();
+                state.loading = curr[1] == Level::Low;
+                if state.loading {
+                    state.shift = state.inputs;
+                } else if Edge::Rising.matches(prev[0], curr[0]) {
+                    state.shift = (state.shift << 1) & state.mask;
+                } else {
+                    return;
+                }
+                _ = chip.set_pull(output, state.output());
+            })?
+        };
+        Ok(Hc165 {
+            chip: chip.share(),
+            output,
+            state,
+            monitor,
+        })
+    }
+
+    /// Set the level of the parallel inputs.
+    ///
+    /// If the consumer is holding the load line low then the inputs are loaded
+    /// into the register immediately.
+    pub fn set_inputs(&self, inputs: u64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.inputs = inputs & state.mask;
+        if state.loading {
+            state.shift = state.inputs;
+            self.chip.set_pull(self.output, state.output())?;
+        }
+        Ok(())
+    }
+
+    /// The level of the parallel inputs.
+    pub fn inputs(&self) -> u64 {
+        self.state.lock().unwrap().inputs
+    }
+
+    /// The contents of the shift register.
+    pub fn shift_register(&self) -> u64 {
+        self.state.lock().unwrap().shift
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+fn width_mask(width: u32) -> Result<u64> {
+    match width {
+        1..=63 => Ok((1 << width) - 1),
+        64 => Ok(u64::MAX),
+        _ => Err(Error::InvalidConfig(format!(
+            "register width {} must be between 1 and 64",
+            width
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    fn pulse(mem: &Memory, c: &Chip, offset: Offset, sync: &dyn Fn()) {
+        mem.drive(c, offset, Some(Level::High)).unwrap();
+        sync();
+        mem.drive(c, offset, Some(Level::Low)).unwrap();
+        sync();
+    }
+
+    #[test]
+    fn widths() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "shift"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert!(Hc595::new(c, 0, 1, 2, 0).is_err());
+        assert!(Hc595::new(c, 0, 1, 2, 65).is_err());
+        assert!(Hc595::new(c, 0, 1, 2, 64).is_ok());
+        assert!(Hc595::new(c, 0, 1, 5, 8).is_err());
+    }
+
+    #[test]
+    fn hc165() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "shift"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        // load starts high, i.e. shifting.
+        mem.drive(c, 2, Some(Level::High)).unwrap();
+        let sr = Hc165::new(c, 0, 1, 2, 8).unwrap();
+        let sync = || sr.sync();
+        sr.set_inputs(0x1a5).unwrap();
+        assert_eq!(sr.inputs(), 0xa5);
+        assert_eq!(sr.shift_register(), 0);
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+        mem.drive(c, 2, Some(Level::Low)).unwrap();
+        sync();
+        assert_eq!(sr.shift_register(), 0xa5);
+        assert_eq!(c.get_pull(0).unwrap(), Level::High);
+        // inputs follow while loading
+        sr.set_inputs(0x5a).unwrap();
+        assert_eq!(sr.shift_register(), 0x5a);
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+        mem.drive(c, 2, Some(Level::High)).unwrap();
+        sync();
+        let mut value = 0;
+        for _ in 0..8 {
+            value = value << 1 | (c.get_pull(0).unwrap() == Level::High) as u64;
+            pulse(&mem, c, 1, &sync);
+        }
+        assert_eq!(value, 0x5a);
+        assert_eq!(sr.shift_register(), 0);
+        // inputs are not loaded while shifting
+        sr.set_inputs(0xff).unwrap();
+        assert_eq!(sr.shift_register(), 0);
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+    }
+
+    #[test]
+    fn hc595() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "shift"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let sr = Hc595::new(c, 0, 1, 2, 8).unwrap();
+        let sync = || sr.sync();
+        let shift_out = |value: u16, bits: u32| {
+            for bit in (0..bits).rev() {
+                let level = if value & (1 << bit) == 0 {
+                    Level::Low
+                } else {
+                    Level::High
+                };
+                mem.drive(c, 0, Some(level)).unwrap();
+                pulse(&mem, c, 1, &sync);
+            }
+        };
+        shift_out(0xa5, 8);
+        assert_eq!(sr.shift_register(), 0xa5);
+        assert_eq!(sr.outputs(), 0);
+        pulse(&mem, c, 2, &sync);
+        assert_eq!(sr.outputs(), 0xa5);
+        // bits beyond the width are shifted out
+        shift_out(0x13c, 12);
+        assert_eq!(sr.outputs(), 0xa5);
+        pulse(&mem, c, 2, &sync);
+        assert_eq!(sr.outputs(), 0x3c);
+        assert_eq!(sr.history(), [0xa5, 0x3c]);
+    }
+}
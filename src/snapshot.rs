@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Chip, Error, Level, Offset, Result};
+
+/// The pulls of all the lines of a chip, captured by [`Chip::snapshot`].
+///
+/// The snapshot can be reapplied to the chip, or any chip with the same
+/// number of lines, with [`Chip::restore`].
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::Bank;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(8, "snap")).live()?;
+/// let c = &sim.chips()[0];
+/// let clean = c.snapshot()?;
+/// for case in 0..4 {
+///     // ... run a test case that pulls lines ...
+///     c.restore(&clean)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PullSnapshot {
+    /// The pulls, indexed by offset.
+    pulls: Vec<Level>,
+}
+
+impl PullSnapshot {
+    pub(crate) fn capture(chip: &Chip) -> Result<PullSnapshot> {
+        let pulls = (0..chip.cfg.num_lines)
+            .map(|offset| chip.dev.get_pull(chip.bank, offset))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PullSnapshot { pulls })
+    }
+
+    /// The pull on a line, if it is in the snapshot.
+    pub fn get(&self, offset: Offset) -> Option<Level> {
+        self.pulls.get(offset as usize).copied()
+    }
+
+    /// The pulls, indexed by offset.
+    pub fn pulls(&self) -> &[Level] {
+        &self.pulls
+    }
+
+    // Only the lines with a different pull are pulled, so restoring an
+    // unchanged chip is cheap.
+    pub(crate) fn restore(&self, chip: &Chip) -> Result<()> {
+        if self.pulls.len() != chip.cfg.num_lines as usize {
+            return Err(Error::InvalidConfig(format!(
+                "snapshot of {} lines does not match chip with {} lines",
+                self.pulls.len(),
+                chip.cfg.num_lines
+            )));
+        }
+        for (offset, &pull) in self.pulls.iter().enumerate() {
+            let offset = offset as Offset;
+            if chip.dev.get_pull(chip.bank, offset)? != pull {
+                chip.set_pull(offset, pull)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Level>> for PullSnapshot {
+    fn from(pulls: Vec<Level>) -> Self {
+        PullSnapshot { pulls }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder, Bank, Level, Memory};
+
+    #[test]
+    fn restore() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "snap"))
+            .with_bank(&Bank::new(3, "other"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        let snap = c.snapshot().unwrap();
+        assert_eq!(
+            snap.pulls(),
+            [Level::Low, Level::High, Level::Low, Level::Low]
+        );
+        assert_eq!(snap.get(1), Some(Level::High));
+        assert_eq!(snap.get(4), None);
+        c.pulldown(1).unwrap();
+        c.pullup(3).unwrap();
+        c.reset_stats();
+        c.restore(&snap).unwrap();
+        assert_eq!(c.snapshot().unwrap(), snap);
+        let stats = c.stats();
+        assert_eq!(stats.iter().map(|s| s.pulls).sum::<u64>(), 2);
+        assert!(sim.chips()[1].restore(&snap).is_err());
+    }
+}
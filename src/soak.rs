@@ -0,0 +1,324 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Builder, Fuzzer, Level, Lines};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// A long running soak test that checks for leaked resources.
+///
+/// Each cycle takes a simulator, configured as per the builder, live,
+/// toggles the pulls on all its lines, runs a [`Fuzzer`] on each chip, and
+/// then drops the simulator.
+/// The file descriptors and resident memory of the process are sampled
+/// periodically, and any configfs entries not removed with the simulator are
+/// recorded, to detect leaks before deploying in overnight CI jobs.
+///
+/// ```no_run
+/// use gpiosim::{Bank, Soak};
+/// use std::time::Duration;
+///
+/// let mut builder = gpiosim::builder();
+/// builder.with_bank(&Bank::new(32, "soak"));
+/// let report = Soak::new(&builder)
+///     .with_cycles(usize::MAX)
+///     .with_duration(Duration::from_secs(8 * 3600))
+///     .run();
+/// println!("{}", report);
+/// assert!(report.is_clean());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Soak {
+    /// The template for the simulators.
+    builder: Builder,
+
+    /// The maximum number of cycles.
+    cycles: usize,
+
+    /// The maximum duration of the test.
+    duration: Option<Duration>,
+
+    /// The number of fuzzer events per chip per cycle.
+    events: usize,
+
+    /// The minimum interval between samples of the resources.
+    interval: Duration,
+}
+
+/// A sample of the resources used by the process.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SoakSample {
+    /// The number of cycles completed when the sample was taken.
+    pub cycle: usize,
+
+    /// The time since the test started.
+    pub elapsed: Duration,
+
+    /// The number of open file descriptors.
+    pub fds: usize,
+
+    /// The resident memory, in kB.
+    pub rss_kb: u64,
+}
+
+impl SoakSample {
+    // Sample the resources of the process, which are zero if unavailable.
+    fn take(cycle: usize, elapsed: Duration) -> SoakSample {
+        let fds = fs::read_dir("/proc/self/fd").map_or(0, |d| d.count());
+        let rss_kb = fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|s| {
+                s.lines()
+                    .find_map(|l| l.strip_prefix("VmRSS:"))
+                    .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+            })
+            .unwrap_or(0);
+        SoakSample {
+            cycle,
+            elapsed,
+            fds,
+            rss_kb,
+        }
+    }
+}
+
+/// The results of a [`Soak`] test.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SoakReport {
+    /// The number of cycles completed.
+    pub cycles: usize,
+
+    /// The errors for the cycles that failed.
+    pub failures: Vec<String>,
+
+    /// The configfs entries that remained after their simulator was dropped.
+    pub leaked: Vec<PathBuf>,
+
+    /// The resources used by the process, before the first cycle, then
+    /// periodically after cycles, and after the final cycle.
+    pub samples: Vec<SoakSample>,
+}
+
+impl SoakReport {
+    /// The growth in open file descriptors over the test.
+    pub fn fd_growth(&self) -> isize {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(first), Some(last)) => last.fds as isize - first.fds as isize,
+            _ => 0,
+        }
+    }
+
+    /// The growth in resident memory, in kB, over the test.
+    pub fn rss_growth_kb(&self) -> i64 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(first), Some(last)) => last.rss_kb as i64 - first.rss_kb as i64,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if no cycles failed, and no configfs entries or file
+    /// descriptors were leaked.
+    ///
+    /// Memory growth is not considered, as it is subject to allocator noise,
+    /// so should be checked against a threshold appropriate to the test.
+    pub fn is_clean(&self) -> bool {
+        self.failures.is_empty() && self.leaked.is_empty() && self.fd_growth() <= 0
+    }
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elapsed = self.samples.last().map(|s| s.elapsed).unwrap_or_default();
+        writeln!(
+            f,
+            "cycles {} failures {} leaked {} elapsed {:?}",
+            self.cycles,
+            self.failures.len(),
+            self.leaked.len(),
+            elapsed
+        )?;
+        let fds = self.samples.iter().map(|s| s.fds).max().unwrap_or(0);
+        writeln!(f, "fds growth {} max {}", self.fd_growth(), fds)?;
+        let rss = self.samples.iter().map(|s| s.rss_kb).max().unwrap_or(0);
+        write!(f, "rss growth {}kB max {}kB", self.rss_growth_kb(), rss)?;
+        for e in &self.failures {
+            write!(f, "\nfailed: {}", e)?;
+        }
+        for p in &self.leaked {
+            write!(f, "\nleaked: {}", p.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl Soak {
+    /// Create a soak test of simulators configured as per the builder.
+    ///
+    /// Any name set in the builder is ignored, as each simulator is given a
+    /// unique name.
+    /// By default the test runs 100 cycles, with 100 fuzzer events per chip
+    /// per cycle, and samples the resources at most once a second.
+    pub fn new(builder: &Builder) -> Soak {
+        let mut builder = builder.clone();
+        builder.name = None;
+        Soak {
+            builder,
+            cycles: 100,
+            duration: None,
+            events: 100,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the maximum number of cycles.
+    pub fn with_cycles(&mut self, cycles: usize) -> &mut Self {
+        self.cycles = cycles;
+        self
+    }
+
+    /// Set the maximum duration of the test.
+    ///
+    /// The test ends after the cycle in progress when the duration expires.
+    pub fn with_duration(&mut self, duration: Duration) -> &mut Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Set the number of fuzzer events per chip per cycle.
+    pub fn with_events(&mut self, events: usize) -> &mut Self {
+        self.events = events;
+        self
+    }
+
+    /// Set the minimum interval between samples of the resources.
+    ///
+    /// Samples are only taken between cycles, so each interval is extended to
+    /// the end of the cycle in progress.
+    pub fn with_sample_interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Run the test, returning once the cycles or duration are exhausted.
+    pub fn run(&self) -> SoakReport {
+        let start = Instant::now();
+        let mut report = SoakReport {
+            samples: vec![SoakSample::take(0, Duration::ZERO)],
+            ..Default::default()
+        };
+        let mut builder = self.builder.clone();
+        let mut sampled = Duration::ZERO;
+        while report.cycles < self.cycles {
+            if let Some(d) = self.duration {
+                if start.elapsed() >= d {
+                    break;
+                }
+            }
+            report.cycles += 1;
+            if let Err(e) = self.cycle(&mut builder, report.cycles, &mut report.leaked) {
+                report
+                    .failures
+                    .push(format!("cycle {}: {}", report.cycles, e));
+            }
+            let elapsed = start.elapsed();
+            if elapsed - sampled >= self.interval {
+                report
+                    .samples
+                    .push(SoakSample::take(report.cycles, elapsed));
+                sampled = elapsed;
+            }
+        }
+        if report.samples.last().map(|s| s.cycle) != Some(report.cycles) {
+            report
+                .samples
+                .push(SoakSample::take(report.cycles, start.elapsed()));
+        }
+        report
+    }
+
+    fn cycle(
+        &self,
+        builder: &mut Builder,
+        cycle: usize,
+        leaked: &mut Vec<PathBuf>,
+    ) -> crate::Result<()> {
+        let sim = builder.live()?;
+        let path = sim.configfs_path().map(|p| p.to_path_buf());
+        let res = sim.chips().iter().try_for_each(|c| {
+            c.set_pull_lines(Lines::All, Level::High)?;
+            c.set_pull_lines(Lines::All, Level::Low)?;
+            Fuzzer::new(c, Lines::All, cycle as u64)?
+                .with_delay(crate::Distribution::Fixed(Duration::ZERO))
+                .run(self.events)
+                .map(|_| ())
+        });
+        drop(sim);
+        if let Some(path) = path {
+            if path.exists() {
+                leaked.push(path);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn run() {
+        let mut builder = builder();
+        builder
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(&Bank::new(4, "right"));
+        let report = Soak::new(&builder)
+            .with_cycles(10)
+            .with_events(20)
+            .with_sample_interval(Duration::ZERO)
+            .run();
+        assert_eq!(report.cycles, 10);
+        assert_eq!(report.samples.len(), 11);
+        assert_eq!(report.samples[10].cycle, 10);
+        assert!(report.samples.iter().all(|s| s.fds > 0 && s.rss_kb > 0));
+        assert!(report.failures.is_empty());
+        assert!(report.leaked.is_empty());
+        assert!(report
+            .to_string()
+            .starts_with("cycles 10 failures 0 leaked 0 "));
+
+        let report = Soak::new(&builder).with_cycles(3).run();
+        assert_eq!(report.cycles, 3);
+        assert_eq!(report.samples.len(), 2);
+        assert_eq!(report.samples[1].cycle, 3);
+
+        let report = Soak::new(&builder).with_duration(Duration::ZERO).run();
+        assert_eq!(report.cycles, 0);
+        assert_eq!(report.samples.len(), 1);
+
+        let report = SoakReport {
+            cycles: 1,
+            samples: vec![
+                SoakSample {
+                    fds: 4,
+                    rss_kb: 100,
+                    ..Default::default()
+                },
+                SoakSample {
+                    fds: 5,
+                    rss_kb: 90,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(report.fd_growth(), 1);
+        assert_eq!(report.rss_growth_kb(), -10);
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::timing::sleep_until;
+use crate::{Chip, Edge, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The speed of sound used to convert distances to echo times, in m/s.
+const SPEED_OF_SOUND: f64 = 343.0;
+
+/// The delay from the end of the trigger pulse to the start of the echo pulse.
+const ECHO_DELAY: Duration = Duration::from_micros(250);
+
+/// The width of the echo pulse when no object is detected.
+const NO_ECHO: Duration = Duration::from_millis(38);
+
+/// An emulator of an HC-SR04 ultrasonic distance sensor.
+///
+/// The consumer triggers a measurement by pulsing the trigger line high, as
+/// an output, and the emulator responds with a high pulse on the echo line,
+/// via its pull, with a width corresponding to the simulated distance.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, HcSr04};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(2, "sonar")).live()?;
+/// let sonar = HcSr04::new(&sim.chips()[0], 0, 1)?;
+/// sonar.set_distance(Some(0.5));
+///
+/// // ... consumer triggers and measures an echo of about 2.9ms ...
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HcSr04 {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The distance to the simulated object, in metres.
+    distance: Option<f64>,
+
+    /// The number of measurements triggered.
+    pings: usize,
+}
+
+impl HcSr04 {
+    /// Start emulating a sensor on the trigger and echo lines.
+    ///
+    /// Initially no object is detected.
+    pub fn new(chip: &Chip, trigger: Offset, echo: Offset) -> Result<HcSr04> {
+        chip.set_pull(echo, Level::Low)?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &[trigger], move |chip, prev, curr| {
+                if !Edge::Falling.matches(prev[0], curr[0]) {
+                    return;
+                }
+                let width = {
+                    let mut state = state.lock().unwrap();
+                    state.pings += 1;
+                    HcSr04::echo_width(state.distance)
+                };
+                let start = Instant::now() + ECHO_DELAY;
+                sleep_until(start);
+                _ = chip.set_pull(echo, Level::High);
+                sleep_until(start + width);
+                _ = chip.set_pull(echo, Level::Low);
+            })?
+        };
+        Ok(HcSr04 { state, monitor })
+    }
+
+    /// Set the distance to the simulated object, in metres.
+    ///
+    /// A distance of `None` simulates no object being detected.
+    pub fn set_distance(&self, distance: Option<f64>) {
+        self.state.lock().unwrap().distance = distance;
+    }
+
+    /// The width of the echo pulse for an object at the distance, in metres.
+    pub fn echo_width(distance: Option<f64>) -> Duration {
+        match distance {
+            Some(d) => Duration::from_secs_f64(2.0 * d.max(0.0) / SPEED_OF_SOUND),
+            None => NO_ECHO,
+        }
+    }
+
+    /// The number of measurements triggered.
+    pub fn pings(&self) -> usize {
+        self.state.lock().unwrap().pings
+    }
+
+    /// Wait until the current state of the trigger has been sampled, and any
+    /// echo completed.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn echo_width() {
+        assert_eq!(HcSr04::echo_width(None), NO_ECHO);
+        assert_eq!(HcSr04::echo_width(Some(0.0)), Duration::ZERO);
+        assert_eq!(HcSr04::echo_width(Some(-1.0)), Duration::ZERO);
+        assert_eq!(
+            HcSr04::echo_width(Some(3.43)).as_micros(),
+            Duration::from_millis(20).as_micros()
+        );
+    }
+
+    #[test]
+    fn ping() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "sonar"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let sonar = HcSr04::new(c, 0, 1).unwrap();
+        sonar.set_distance(Some(0.343));
+        let trigger = || {
+            mem.drive(c, 0, Some(Level::High)).unwrap();
+            sonar.sync();
+            mem.drive(c, 0, Some(Level::Low)).unwrap();
+            let start = Instant::now();
+            sonar.sync();
+            start.elapsed()
+        };
+        let elapsed = trigger();
+        assert!(elapsed >= ECHO_DELAY + Duration::from_millis(2));
+        assert_eq!(sonar.pings(), 1);
+        // the initial pull, then the echo pulse
+        assert_eq!(c.stats()[1].pulls, 3);
+        assert_eq!(c.get_pull(1).unwrap(), Level::Low);
+        sonar.set_distance(None);
+        assert!(trigger() >= NO_ECHO);
+        assert_eq!(sonar.pings(), 2);
+    }
+}
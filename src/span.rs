@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Spans covering the simulator lifecycle and chip operations.
+//!
+//! With the `spans` feature, simulator setup and teardown, and the operations
+//! on chip lines, are performed within `tracing` spans at the debug level, so
+//! their timing is visible to any subscriber already collecting traces.
+//!
+//! The spans are:
+//!  - `live` and `remove`, with the `sim` name,
+//!  - `set_pull`, `get_pull`, `get_level`, `toggle` and `glitch`, with the
+//!    `chip` name and line `offset`,
+//!  - `set_pulls`, with the `chip` name and the `count` of pulls.
+
+// Evaluate the expression within a debug span, if spans are enabled.
+macro_rules! in_span {
+    ($name:literal $(, $field:ident = $value:expr)*; $e:expr) => {{
+        #[cfg(feature = "spans")]
+        let res = tracing::debug_span!($name $(, $field = $value)*).in_scope(|| $e);
+        #[cfg(not(feature = "spans"))]
+        let res = $e;
+        res
+    }};
+}
+
+#[cfg(all(test, feature = "spans"))]
+mod tests {
+    use crate::{builder, Bank, Memory};
+    use std::fmt::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        spans: Mutex<Vec<String>>,
+    }
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            write!(self.0, " {}={}", field.name(), value).unwrap();
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(span.metadata().name().to_string());
+            span.record(&mut fields);
+            self.spans.lock().unwrap().push(fields.0);
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn lifecycle() {
+        let rec = Arc::new(Recorder::default());
+        let chip = tracing::subscriber::with_default(rec.clone(), || {
+            let sim = builder()
+                .with_name("span_lifecycle")
+                .with_backend(Memory::new())
+                .with_bank(&Bank::new(4, "left"))
+                .live()
+                .unwrap();
+            let c = &sim.chips()[0];
+            c.pullup(1).unwrap();
+            c.get_level(1).unwrap();
+            c.toggle(2).unwrap();
+            c.glitch(3, Duration::ZERO).unwrap();
+            c.chip_name.clone()
+        });
+        assert_eq!(
+            *rec.spans.lock().unwrap(),
+            [
+                "live sim=span_lifecycle".to_string(),
+                format!("set_pull chip={} offset=1", chip),
+                format!("get_level chip={} offset=1", chip),
+                format!("toggle chip={} offset=2", chip),
+                format!("get_pull chip={} offset=3", chip),
+                format!("glitch chip={} offset=3", chip),
+                "remove sim=span_lifecycle".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of SPI slave devices on simulated lines.
+//!
+//! The consumer acts as a bit-banged SPI master, driving the SCLK, MOSI and CS
+//! lines as outputs, and the emulated slave answers on MISO via its pull.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::spi::{Mode, Pins, Slave};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(4, "spi")).live()?;
+//! let pins = Pins {
+//!     sclk: 0,
+//!     mosi: 1,
+//!     miso: 2,
+//!     cs: 3,
+//! };
+//! // respond to each byte with its complement
+//! let slave = Slave::new(&sim.chips()[0], pins, Mode::Mode0, |rx: &[u8]| {
+//!     rx.last().map(|b| !b).unwrap_or(0)
+//! })?;
+//!
+//! // ... consumer transfers 0x9f 0x00 ...
+//!
+//! slave.sync();
+//! assert_eq!(slave.transactions(), [[0x9f, 0x00]]);
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::{Chip, Edge, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+
+/// The handler of transactions for an emulated SPI slave.
+pub trait Handler: Send {
+    /// The next byte to transmit on MISO.
+    ///
+    /// Called when the slave is selected, with no bytes received, and after
+    /// each byte is received, with all the bytes received in the transaction
+    /// so far.
+    fn next(&mut self, received: &[u8]) -> u8;
+
+    /// Called when the slave is deselected, with all the bytes received in the
+    /// transaction.
+    fn complete(&mut self, _received: &[u8]) {}
+}
+
+impl<F: FnMut(&[u8]) -> u8 + Send> Handler for F {
+    fn next(&mut self, received: &[u8]) -> u8 {
+        self(received)
+    }
+}
+
+/// The SPI mode, defined by the clock polarity (CPOL) and phase (CPHA).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Mode {
+    /// The clock idles low and data is sampled on the rising edge.
+    #[default]
+    Mode0,
+
+    /// The clock idles low and data is sampled on the falling edge.
+    Mode1,
+
+    /// The clock idles high and data is sampled on the falling edge.
+    Mode2,
+
+    /// The clock idles high and data is sampled on the rising edge.
+    Mode3,
+}
+
+impl Mode {
+    /// The level of the clock while idle.
+    fn cpol(&self) -> Level {
+        match self {
+            Mode::Mode0 | Mode::Mode1 => Level::Low,
+            Mode::Mode2 | Mode::Mode3 => Level::High,
+        }
+    }
+
+    /// True if data is sampled on the trailing edge of the clock.
+    fn cpha(&self) -> bool {
+        matches!(self, Mode::Mode1 | Mode::Mode3)
+    }
+}
+
+/// The lines used by an SPI slave.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pins {
+    /// The clock, driven by the master.
+    pub sclk: Offset,
+
+    /// Data from master to slave.
+    pub mosi: Offset,
+
+    /// Data from slave to master, pulled by the slave.
+    pub miso: Offset,
+
+    /// The active low chip select, driven by the master.
+    pub cs: Offset,
+}
+
+/// An emulated SPI slave device.
+///
+/// Data is transferred most significant bit first.
+///
+/// The slave samples the lines, so the master must hold each level for at
+/// least a few sample periods.
+#[derive(Debug)]
+pub struct Slave {
+    /// The transactions completed so far.
+    transactions: Arc<Mutex<Vec<Vec<u8>>>>,
+
+    monitor: Monitor,
+}
+
+impl Slave {
+    /// Start emulating a slave on the lines.
+    pub fn new<H: Handler + 'static>(
+        chip: &Chip,
+        pins: Pins,
+        mode: Mode,
+        handler: H,
+    ) -> Result<Slave> {
+        chip.set_pull(pins.miso, Level::Low)?;
+        let transactions = Arc::new(Mutex::new(Vec::new()));
+        let mut state = State {
+            mode,
+            handler: Box::new(handler),
+            selected: false,
+            bits: 0,
+            rx: 0,
+            tx: 0,
+            received: Vec::new(),
+            transactions: transactions.clone(),
+        };
+        let monitor = Monitor::spawn(
+            chip,
+            &[pins.sclk, pins.mosi, pins.cs],
+            move |chip, prev, curr| {
+                if let Some(level) = state.update(prev, curr) {
+                    _ = chip.set_pull(pins.miso, level);
+                }
+            },
+        )?;
+        Ok(Slave {
+            transactions,
+            monitor,
+        })
+    }
+
+    /// The bytes received in each transaction completed so far, oldest first.
+    pub fn transactions(&self) -> Vec<Vec<u8>> {
+        self.transactions.lock().unwrap().clone()
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+struct State {
+    mode: Mode,
+
+    handler: Box<dyn Handler>,
+
+    /// True while CS is asserted.
+    selected: bool,
+
+    /// The number of bits of the current byte sampled.
+    bits: u8,
+
+    /// The byte being received.
+    rx: u8,
+
+    /// The byte being transmitted.
+    tx: u8,
+
+    /// The bytes received in the current transaction.
+    received: Vec<u8>,
+
+    transactions: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl State {
+    // Update the state given the change in the SCLK, MOSI and CS levels.
+    //
+    // Returns the level MISO should be pulled to, if that changes.
+    fn update(&mut self, prev: &[Level], curr: &[Level]) -> Option<Level> {
+        let (sclk, mosi, cs) = (curr[0], curr[1], curr[2]);
+        if prev[2] != cs {
+            self.selected = cs == Level::Low;
+            if self.selected {
+                self.bits = 0;
+                self.rx = 0;
+                self.received.clear();
+                self.tx = self.handler.next(&self.received);
+                if !self.mode.cpha() {
+                    return Some(self.bit());
+                }
+            } else {
+                let received = std::mem::take(&mut self.received);
+                self.handler.complete(&received);
+                self.transactions.lock().unwrap().push(received);
+                return Some(Level::Low);
+            }
+            return None;
+        }
+        if !self.selected || prev[0] == sclk {
+            return None;
+        }
+        let leading = if self.mode.cpol() == Level::Low {
+            Edge::Rising.matches(prev[0], sclk)
+        } else {
+            Edge::Falling.matches(prev[0], sclk)
+        };
+        if leading == self.mode.cpha() {
+            // shift out the next bit
+            return Some(self.bit());
+        }
+        self.rx = self.rx << 1 | (mosi == Level::High) as u8;
+        self.bits += 1;
+        if self.bits == 8 {
+            self.received.push(self.rx);
+            self.bits = 0;
+            self.rx = 0;
+            self.tx = self.handler.next(&self.received);
+        }
+        None
+    }
+
+    // The level of the current bit to be transmitted.
+    fn bit(&self) -> Level {
+        if self.tx & (0x80 >> self.bits) == 0 {
+            Level::Low
+        } else {
+            Level::High
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    const PINS: Pins = Pins {
+        sclk: 0,
+        mosi: 1,
+        miso: 2,
+        cs: 3,
+    };
+
+    // Perform a transaction as a bit-banged master, returning the bytes read.
+    fn transfer(mem: &Memory, c: &Chip, slave: &Slave, mode: Mode, tx: &[u8]) -> Vec<u8> {
+        let set = |offset, level| {
+            mem.drive(c, offset, Some(level)).unwrap();
+            slave.sync();
+        };
+        let idle = mode.cpol();
+        set(PINS.sclk, idle);
+        set(PINS.cs, Level::Low);
+        let mut rx = Vec::new();
+        for byte in tx {
+            let mut value = 0;
+            for bit in (0..8).rev() {
+                let level = if byte & (1 << bit) == 0 {
+                    Level::Low
+                } else {
+                    Level::High
+                };
+                if mode.cpha() {
+                    set(PINS.sclk, idle.toggle());
+                    set(PINS.mosi, level);
+                    value = value << 1 | (c.get_level(PINS.miso).unwrap() == Level::High) as u8;
+                    set(PINS.sclk, idle);
+                } else {
+                    set(PINS.mosi, level);
+                    value = value << 1 | (c.get_level(PINS.miso).unwrap() == Level::High) as u8;
+                    set(PINS.sclk, idle.toggle());
+                    set(PINS.sclk, idle);
+                }
+            }
+            rx.push(value);
+        }
+        set(PINS.cs, Level::High);
+        rx
+    }
+
+    #[test]
+    fn modes() {
+        for mode in [Mode::Mode0, Mode::Mode1, Mode::Mode2, Mode::Mode3] {
+            let mem = Memory::new();
+            let sim = builder()
+                .with_backend(mem.clone())
+                .with_bank(&Bank::new(4, "spi"))
+                .live()
+                .unwrap();
+            let c = &sim.chips()[0];
+            mem.drive(c, PINS.cs, Some(Level::High)).unwrap();
+            // respond with a header, then the complement of each byte received
+            let slave = Slave::new(c, PINS, mode, |rx: &[u8]| {
+                rx.last().map(|b| !b).unwrap_or(0x5a)
+            })
+            .unwrap();
+            assert_eq!(
+                transfer(&mem, c, &slave, mode, &[0x9f, 0x0f, 0x00]),
+                [0x5a, 0x60, 0xf0],
+                "{:?}",
+                mode
+            );
+            assert_eq!(transfer(&mem, c, &slave, mode, &[0x81]), [0x5a]);
+            assert_eq!(slave.transactions(), [vec![0x9f, 0x0f, 0x00], vec![0x81]]);
+        }
+    }
+}
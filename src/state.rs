@@ -0,0 +1,247 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Bank, Builder, Chip, Error, Level, PullSnapshot, Result, Sim};
+
+/// The runtime state of a simulator, captured by [`Sim::state`].
+///
+/// With the `json` feature the state can be exported to and imported from
+/// JSON, e.g. to attach the exact state of the simulator to a bug report, or
+/// to hand it to another process, which can apply the pulls to its own
+/// simulator with [`Sim::import_pulls`], or re-create the simulator with
+/// [`SimState::builder`].
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::Bank;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(8, "state")).live()?;
+/// // ... the test fails ...
+/// # #[cfg(feature = "json")]
+/// eprintln!("sim state: {}", sim.state()?.to_json());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimState {
+    /// The name of the simulator.
+    pub name: String,
+
+    /// The state of each chip, in bank order.
+    pub chips: Vec<ChipState>,
+}
+
+/// The runtime state of a simulated chip.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChipState {
+    /// The label of the chip.
+    pub label: String,
+
+    /// The configuration of the chip.
+    ///
+    /// Defaults to a bank with only the label and number of lines if absent
+    /// from imported JSON.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub config: Bank,
+
+    /// The simulated external pull on each line, indexed by offset.
+    pub pulls: Vec<Level>,
+
+    /// The level of each line, indexed by offset.
+    ///
+    /// For lines requested as outputs this is the level driven by the consumer.
+    pub levels: Vec<Level>,
+}
+
+impl ChipState {
+    pub(crate) fn capture(chip: &Chip) -> Result<ChipState> {
+        let levels = (0..chip.cfg.num_lines)
+            .map(|offset| chip.dev.get_level(chip.bank, offset))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ChipState {
+            label: chip.cfg.label.clone(),
+            config: chip.cfg.clone(),
+            pulls: PullSnapshot::capture(chip)?.pulls().to_vec(),
+            levels,
+        })
+    }
+}
+
+impl SimState {
+    pub(crate) fn capture(sim: &Sim) -> Result<SimState> {
+        Ok(SimState {
+            name: sim.name().into(),
+            chips: sim
+                .chips()
+                .iter()
+                .map(ChipState::capture)
+                .collect::<Result<Vec<_>>>()?,
+        })
+    }
+
+    /// A builder to re-create the simulator, with the same name,
+    /// configuration and pulls.
+    ///
+    /// The name can be changed with [`Builder::with_name`] before the
+    /// simulator is taken live.
+    pub fn builder(&self) -> Builder {
+        let mut builder = Builder::default();
+        builder.with_name(self.name.as_str());
+        for chip in &self.chips {
+            let mut bank = if chip.config.num_lines == 0 {
+                Bank::new(chip.pulls.len() as u32, chip.label.as_str())
+            } else {
+                chip.config.clone()
+            };
+            for (offset, &pull) in chip.pulls.iter().enumerate() {
+                if pull == Level::High {
+                    bank.pull(offset as u32, pull);
+                } else {
+                    bank.unpull(offset as u32);
+                }
+            }
+            builder.with_bank(&bank);
+        }
+        builder
+    }
+
+    // The levels cannot be imported as they are driven by the consumer.
+    pub(crate) fn import_pulls(&self, sim: &Sim) -> Result<()> {
+        if self.chips.len() != sim.chips().len() {
+            return Err(Error::InvalidConfig(format!(
+                "state of {} chips does not match sim with {} chips",
+                self.chips.len(),
+                sim.chips().len()
+            )));
+        }
+        for (state, chip) in self.chips.iter().zip(sim.chips()) {
+            chip.restore(&PullSnapshot::from(state.pulls.clone()))?;
+        }
+        Ok(())
+    }
+
+    /// Export the state as JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // serialization of the state cannot fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Import a state from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<SimState> {
+        serde_json::from_str(json)
+            .map_err(|e| Error::InvalidConfig(format!("invalid sim state: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Direction, Memory};
+
+    #[test]
+    fn capture() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(3, "left"))
+            .with_bank(&Bank::new(2, "right"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        c.pullup(1).unwrap();
+        mem.drive(c, 2, Some(Level::High)).unwrap();
+        let state = sim.state().unwrap();
+        assert_eq!(state.name, sim.name());
+        assert_eq!(state.chips.len(), 2);
+        assert_eq!(state.chips[0].label, "left");
+        assert_eq!(state.chips[0].pulls, [Level::Low, Level::High, Level::Low]);
+        assert_eq!(
+            state.chips[0].levels,
+            [Level::Low, Level::High, Level::High]
+        );
+        assert_eq!(state.chips[1].pulls, [Level::Low, Level::Low]);
+
+        let other = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(3, "left"))
+            .with_bank(&Bank::new(2, "right"))
+            .live()
+            .unwrap();
+        other.import_pulls(&state).unwrap();
+        assert_eq!(other.chips()[0].get_pull(1).unwrap(), Level::High);
+        assert_eq!(other.chips()[0].get_level(2).unwrap(), Level::Low);
+
+        let mut bad = state.clone();
+        bad.chips.pop();
+        assert!(other.import_pulls(&bad).is_err());
+        bad.chips.push(state.chips[0].clone());
+        assert!(other.import_pulls(&bad).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json() {
+        let state = SimState {
+            name: "sim".into(),
+            chips: vec![ChipState {
+                label: "chip".into(),
+                config: Bank::new(2, "chip"),
+                pulls: vec![Level::High, Level::Low],
+                levels: vec![Level::Low, Level::Low],
+            }],
+        };
+        let json = state.to_json();
+        assert!(json.contains("\"high\""));
+        assert_eq!(SimState::from_json(&json).unwrap(), state);
+        assert!(SimState::from_json("{\"name\": 1}").is_err());
+    }
+
+    #[test]
+    fn recreate() {
+        let mut bank = Bank::new(4, "left");
+        bank.name(1, "led")
+            .hog(2, "hogger", Direction::OutputHigh)
+            .pull(3, Level::High);
+        let state = builder()
+            .with_backend(Memory::new())
+            .with_bank(&bank)
+            .live()
+            .unwrap()
+            .state()
+            .unwrap();
+        assert_eq!(state.chips[0].config, bank);
+
+        let sim = state
+            .builder()
+            .with_name("recreated")
+            .with_backend(Memory::new())
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(sim.name(), "recreated");
+        assert_eq!(c.config().names(), bank.names());
+        assert_eq!(c.config().hogs(), bank.hogs());
+        assert_eq!(c.get_pull(3).unwrap(), Level::High);
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+
+        // a state without a config
+        let state = SimState {
+            name: "bare".into(),
+            chips: vec![ChipState {
+                label: "chip".into(),
+                pulls: vec![Level::Low, Level::High],
+                ..Default::default()
+            }],
+        };
+        let sim = state.builder().with_backend(Memory::new()).live().unwrap();
+        let c = &sim.chips()[0];
+        assert_eq!(c.config().num_lines, 2);
+        assert_eq!(c.config().label, "chip");
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+    }
+}
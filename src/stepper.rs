@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::monitor::Monitor;
+use crate::{Chip, Edge, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A monitor of the STEP and DIR outputs a consumer uses to drive a stepper
+/// motor driver.
+///
+/// Each rising edge on STEP is a step, forward if DIR is high and in reverse
+/// if DIR is low.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, StepDir};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(2, "motor")).live()?;
+/// let motor = StepDir::new(&sim.chips()[0], 0, 1)?;
+///
+/// // ... consumer moves the motor forward 200 steps ...
+///
+/// motor.sync();
+/// assert_eq!(motor.position(), 200);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StepDir {
+    state: Arc<Mutex<State>>,
+
+    monitor: Monitor,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The number of steps forward.
+    forward: u64,
+
+    /// The number of steps in reverse.
+    reverse: u64,
+
+    /// The time of the most recent step.
+    last: Option<Instant>,
+
+    /// The interval between the two most recent steps.
+    interval: Option<Duration>,
+}
+
+impl StepDir {
+    /// Start monitoring the STEP and DIR lines.
+    pub fn new(chip: &Chip, step: Offset, dir: Offset) -> Result<StepDir> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let monitor = {
+            let state = state.clone();
+            Monitor::spawn(chip, &[step, dir], move |_, prev, curr| {
+                if !Edge::Rising.matches(prev[0], curr[0]) {
+                    return;
+                }
+                let now = Instant::now();
+                let mut state = state.lock().unwrap();
+                if curr[1] == Level::High {
+                    state.forward += 1;
+                } else {
+                    state.reverse += 1;
+                }
+                state.interval = state.last.map(|t| now - t);
+                state.last = Some(now);
+            })?
+        };
+        Ok(StepDir { state, monitor })
+    }
+
+    /// The number of steps forward, less the number in reverse.
+    pub fn position(&self) -> i64 {
+        let state = self.state.lock().unwrap();
+        state.forward as i64 - state.reverse as i64
+    }
+
+    /// The number of steps forward.
+    pub fn forward_steps(&self) -> u64 {
+        self.state.lock().unwrap().forward
+    }
+
+    /// The number of steps in reverse.
+    pub fn reverse_steps(&self) -> u64 {
+        self.state.lock().unwrap().reverse
+    }
+
+    /// The step rate, in steps per second, measured from the interval between
+    /// the two most recent steps.
+    pub fn rate(&self) -> Option<f64> {
+        self.state
+            .lock()
+            .unwrap()
+            .interval
+            .filter(|i| !i.is_zero())
+            .map(|i| 1.0 / i.as_secs_f64())
+    }
+
+    /// Clear the step counts and rate.
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = State::default();
+    }
+
+    /// Wait until the current state of the lines has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    #[test]
+    fn steps() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(2, "motor"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let motor = StepDir::new(c, 0, 1).unwrap();
+        let set = |offset, level| {
+            mem.drive(c, offset, Some(level)).unwrap();
+            motor.sync();
+        };
+        let step = || {
+            set(0, Level::High);
+            set(0, Level::Low);
+        };
+        set(1, Level::High);
+        for _ in 0..5 {
+            step();
+        }
+        assert!(motor.rate().is_some());
+        set(1, Level::Low);
+        step();
+        thread::sleep(Duration::from_millis(10));
+        step();
+        assert_eq!(motor.forward_steps(), 5);
+        assert_eq!(motor.reverse_steps(), 2);
+        assert_eq!(motor.position(), 3);
+        assert!(motor.rate().unwrap() < 100.0);
+        motor.reset();
+        assert_eq!(motor.position(), 0);
+        assert!(motor.rate().is_none());
+        // direction changes alone are not steps
+        set(1, Level::High);
+        assert_eq!(motor.position(), 0);
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::{Chip, Error, Lines, Offset, Result};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A stress generator that toggles lines as fast as possible.
+///
+/// The storm toggles the pulls on its lines, from one or more threads, for
+/// its duration, to generate edge events faster than consumers may be able
+/// to read them, e.g. to test event buffer overflow handling.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Storm};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "storm")).live()?;
+/// let mut storm = Storm::new(&sim.chips()[0], 0..4)?;
+/// storm.with_threads(2).with_duration(Duration::from_millis(100));
+/// let report = storm.run();
+/// println!("{} transitions at {:.0}/s", report.total(), report.rate());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Storm {
+    /// The chip containing the lines.
+    chip: Chip,
+
+    offsets: Vec<Offset>,
+
+    /// The number of threads toggling the lines.
+    threads: usize,
+
+    /// How long the storm lasts.
+    duration: Duration,
+}
+
+/// The transitions applied by a [`Storm`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StormReport {
+    /// The number of transitions applied to each line, in the order of the
+    /// storm lines.
+    pub transitions: Vec<(Offset, u64)>,
+
+    /// How long the storm lasted.
+    pub elapsed: Duration,
+}
+
+impl StormReport {
+    /// The total number of transitions applied.
+    pub fn total(&self) -> u64 {
+        self.transitions.iter().map(|(_, n)| n).sum()
+    }
+
+    /// The mean number of transitions applied per second.
+    pub fn rate(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.total() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Storm {
+    /// Create a storm on a selection of lines.
+    ///
+    /// By default the storm is a single thread that lasts for 1 second.
+    pub fn new<L: Into<Lines>>(chip: &Chip, lines: L) -> Result<Storm> {
+        let offsets = lines.into().offsets(&chip.cfg);
+        if offsets.is_empty() {
+            return Err(Error::InvalidConfig(
+                "storm requires at least one line".into(),
+            ));
+        }
+        for &offset in &offsets {
+            check_offset(chip, offset)?;
+        }
+        Ok(Storm {
+            chip: chip.share(),
+            offsets,
+            threads: 1,
+            duration: Duration::from_secs(1),
+        })
+    }
+
+    /// Set the number of threads toggling the lines.
+    ///
+    /// The lines are shared between the threads, so the number of threads is
+    /// limited to the number of lines.
+    pub fn with_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.clamp(1, self.offsets.len());
+        self
+    }
+
+    /// Set how long the storm lasts.
+    pub fn with_duration(&mut self, duration: Duration) -> &mut Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Run the storm, returning once it has passed.
+    ///
+    /// Each line is left at the opposite pull to the one it started with if it
+    /// was toggled an odd number of times.
+    ///
+    /// A thread stops toggling its lines early if a pull cannot be set, e.g.
+    /// if the simulator has been removed, so the report only includes the
+    /// transitions actually applied.
+    pub fn run(&self) -> StormReport {
+        let start = Instant::now();
+        let deadline = start + self.duration;
+        let workers: Vec<_> = (0..self.threads)
+            .map(|t| {
+                let chip = self.chip.share();
+                let offsets: Vec<Offset> = self
+                    .offsets
+                    .iter()
+                    .skip(t)
+                    .step_by(self.threads)
+                    .copied()
+                    .collect();
+                thread::spawn(move || toggle(&chip, &offsets, deadline))
+            })
+            .collect();
+        let mut counts = vec![0; self.offsets.len()];
+        for (t, w) in workers.into_iter().enumerate() {
+            let thread_counts = w.join().expect("storm thread panicked");
+            for (n, count) in thread_counts.into_iter().enumerate() {
+                counts[t + n * self.threads] = count;
+            }
+        }
+        StormReport {
+            transitions: self.offsets.iter().copied().zip(counts).collect(),
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+// Toggle the lines in turn until the deadline, returning the number of
+// transitions applied to each.
+fn toggle(chip: &Chip, offsets: &[Offset], deadline: Instant) -> Vec<u64> {
+    let mut counts = vec![0; offsets.len()];
+    let mut pulls = match offsets
+        .iter()
+        .map(|&offset| chip.dev.get_pull(chip.bank, offset))
+        .collect::<Result<Vec<_>>>()
+    {
+        Ok(pulls) => pulls,
+        Err(_) => return counts,
+    };
+    while Instant::now() < deadline {
+        for (n, &offset) in offsets.iter().enumerate() {
+            pulls[n] = pulls[n].toggle();
+            if chip.set_pull(offset, pulls[n]).is_err() {
+                return counts;
+            }
+            counts[n] += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn run() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "storm"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut storm = Storm::new(c, [0, 2, 3]).unwrap();
+        storm
+            .with_threads(5)
+            .with_duration(Duration::from_millis(20));
+        assert_eq!(storm.threads, 3);
+        storm.with_threads(2);
+        let report = storm.run();
+        assert!(report.elapsed >= Duration::from_millis(20));
+        assert_eq!(
+            report.transitions.iter().map(|t| t.0).collect::<Vec<_>>(),
+            [0, 2, 3]
+        );
+        let stats = c.stats();
+        for &(offset, count) in &report.transitions {
+            assert!(count > 0);
+            assert_eq!(stats[offset as usize].pulls, count);
+        }
+        assert_eq!(stats[1].pulls, 0);
+        assert_eq!(
+            report.total(),
+            report.transitions.iter().map(|t| t.1).sum::<u64>()
+        );
+        assert!(report.rate() > 0.0);
+
+        assert!(Storm::new(c, Vec::new()).is_err());
+        assert!(Storm::new(c, 3..5).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Generation of arbitrary valid simulator configurations for property-based
+//! testing.
+//!
+//! [`bank`], [`builder`] and [`pulls`] are proptest strategies, so failing
+//! cases are shrunk by proptest towards fewer lines, banks and pulls, with
+//! fewer named and hogged lines.
+//!
+//! ```no_run
+//! use gpiosim::strategy;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn consumer_handles_any_sim(mut builder in strategy::builder(4, 64)) {
+//!         let sim = builder.live().unwrap();
+//!         // ... exercise the consumer against the sim ...
+//!     }
+//! }
+//! ```
+//!
+//! For use without proptest, a [`Gen`] generates the same kinds of values,
+//! with the output fully determined by its seed, and [`check`] runs a
+//! property over many generated cases, reporting the seed of any failing case
+//! so it can be reproduced.
+//!
+//! ```no_run
+//! use gpiosim::strategy::check;
+//!
+//! check(100, 42, |g| {
+//!     let sim = g.builder().live().map_err(|e| e.to_string())?;
+//!     // ... exercise the consumer against the sim ...
+//!     Ok(())
+//! });
+//! ```
+use crate::random::Rng;
+use crate::{Bank, Builder, Direction, Level, Offset};
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+
+/// A strategy for banks with at least one and up to `max_lines` lines.
+///
+/// A random selection of the lines are named, with unique names, or hogged,
+/// as per [`Gen::bank`].
+pub fn bank(max_lines: u32) -> impl Strategy<Value = Bank> {
+    (1..=max_lines.max(1)).prop_flat_map(|num_lines| {
+        let lines = num_lines as usize;
+        (
+            0..1000u32,
+            vec(prop::bool::weighted(1.0 / 3.0), lines),
+            vec(prop::option::weighted(1.0 / 8.0, direction()), lines),
+        )
+            .prop_map(move |(label, named, hogs)| {
+                let mut bank = Bank::new(num_lines, format!("bank{}", label));
+                for (offset, (named, hog)) in (0..num_lines).zip(named.into_iter().zip(hogs)) {
+                    if named {
+                        bank.name(offset, format!("line{}", offset));
+                    }
+                    if let Some(direction) = hog {
+                        bank.hog(offset, format!("hog{}", offset), direction);
+                    }
+                }
+                bank
+            })
+    })
+}
+
+/// A strategy for builders with at least one and up to `max_banks` banks,
+/// each as per [`bank`].
+///
+/// The builder has no name or backend, as per [`Gen::builder`].
+pub fn builder(max_banks: usize, max_lines: u32) -> impl Strategy<Value = Builder> {
+    vec(bank(max_lines), 1..=max_banks.max(1)).prop_map(|banks| {
+        let mut builder = Builder::default();
+        builder.with_banks(banks);
+        builder
+    })
+}
+
+/// A strategy for sequences of pulls on the lines of the bank, with the
+/// length of the sequence in the range.
+pub fn pulls(
+    bank: &Bank,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<(Offset, Level)>> {
+    vec(
+        (
+            0..bank.num_lines.max(1),
+            any::<bool>().prop_map(Level::from),
+        ),
+        len,
+    )
+}
+
+fn direction() -> impl Strategy<Value = Direction> {
+    prop_oneof![
+        Just(Direction::Input),
+        Just(Direction::OutputLow),
+        Just(Direction::OutputHigh),
+    ]
+}
+
+/// A generator of arbitrary valid configurations.
+#[derive(Clone, Debug)]
+pub struct Gen {
+    rng: Rng,
+
+    /// The maximum number of lines in a bank.
+    max_lines: u32,
+
+    /// The maximum number of banks in a builder.
+    max_banks: usize,
+}
+
+impl Gen {
+    /// Create a generator seeded with the seed.
+    ///
+    /// By default banks have up to 64 lines, and builders up to 4 banks.
+    pub fn new(seed: u64) -> Gen {
+        Gen {
+            rng: Rng::new(seed),
+            max_lines: 64,
+            max_banks: 4,
+        }
+    }
+
+    /// Set the maximum number of lines in a generated bank.
+    pub fn with_max_lines(&mut self, max_lines: u32) -> &mut Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// Set the maximum number of banks in a generated builder.
+    pub fn with_max_banks(&mut self, max_banks: usize) -> &mut Self {
+        self.max_banks = max_banks.max(1);
+        self
+    }
+
+    /// A value uniformly distributed in the range [min, max].
+    fn between(&mut self, min: u64, max: u64) -> u64 {
+        min + self.rng.up_to(max - min)
+    }
+
+    /// Returns true with a probability of 1 in n.
+    fn one_in(&mut self, n: u64) -> bool {
+        self.rng.up_to(n - 1) == 0
+    }
+
+    /// Generate a bank.
+    ///
+    /// The bank has at least one line, and a random selection of its lines are
+    /// named, with unique names, or hogged.
+    pub fn bank(&mut self) -> Bank {
+        let num_lines = self.between(1, self.max_lines as u64) as u32;
+        let label = format!("bank{}", self.rng.up_to(999));
+        let mut bank = Bank::new(num_lines, label);
+        for offset in 0..num_lines {
+            if self.one_in(3) {
+                bank.name(offset, format!("line{}", offset));
+            }
+            if self.one_in(8) {
+                let direction = match self.rng.up_to(2) {
+                    0 => Direction::Input,
+                    1 => Direction::OutputLow,
+                    _ => Direction::OutputHigh,
+                };
+                bank.hog(offset, format!("hog{}", offset), direction);
+            }
+        }
+        bank
+    }
+
+    /// Generate a builder with at least one bank.
+    ///
+    /// The builder has no name or backend, so will use a unique name and the
+    /// kernel backend unless those are subsequently set.
+    pub fn builder(&mut self) -> Builder {
+        let banks = self.between(1, self.max_banks as u64) as usize;
+        let mut builder = Builder::default();
+        builder.with_banks((0..banks).map(|_| self.bank()).collect::<Vec<_>>());
+        builder
+    }
+
+    /// Generate a sequence of pulls on the lines of the bank.
+    pub fn pulls(&mut self, bank: &Bank, len: usize) -> Vec<(Offset, Level)> {
+        (0..len)
+            .map(|_| {
+                let offset = self.rng.up_to(bank.num_lines.max(1) as u64 - 1) as Offset;
+                let pull = if self.one_in(2) {
+                    Level::High
+                } else {
+                    Level::Low
+                };
+                (offset, pull)
+            })
+            .collect()
+    }
+}
+
+/// Check a property holds for a number of generated cases.
+///
+/// Each case is passed a generator with its own seed, derived from the seed.
+///
+/// # Panics
+///
+/// Panics if the property fails for any case, reporting the error and the
+/// seed of the failing case, which can be passed to [`Gen::new`] to reproduce
+/// it.
+pub fn check<F>(cases: usize, seed: u64, mut property: F)
+where
+    F: FnMut(&mut Gen) -> std::result::Result<(), String>,
+{
+    let mut seeds = Rng::new(seed);
+    for case in 0..cases {
+        let case_seed = seeds.next_u64();
+        if let Err(e) = property(&mut Gen::new(case_seed)) {
+            panic!(
+                "property failed for case {} with seed {:#x}: {}",
+                case, case_seed, e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Memory;
+    use proptest::test_runner::{TestError, TestRunner};
+
+    proptest! {
+        #[test]
+        fn strategies(
+            (bank, pulls) in bank(16).prop_flat_map(|b| {
+                let pulls = pulls(&b, 0..20);
+                (Just(b), pulls)
+            }),
+            mut builder in builder(3, 16),
+        ) {
+            prop_assert!(bank.num_lines >= 1 && bank.num_lines <= 16);
+            prop_assert!(bank.line_configs().iter().all(|&(o, _)| o < bank.num_lines));
+            prop_assert!(pulls.iter().all(|&(o, _)| o < bank.num_lines));
+            let sim = builder.with_backend(Memory::new()).live().unwrap();
+            prop_assert!(!sim.chips().is_empty() && sim.chips().len() <= 3);
+        }
+    }
+
+    #[test]
+    fn shrinks() {
+        let mut runner = TestRunner::deterministic();
+        match runner.run(&bank(64), |b| {
+            prop_assert!(b.num_lines < 10);
+            Ok(())
+        }) {
+            Err(TestError::Fail(_, b)) => {
+                assert_eq!(b.num_lines, 10);
+                assert!(b.line_configs().is_empty());
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        check(50, 1, |g| {
+            let bank = g.bank();
+            if bank.num_lines == 0 || bank.num_lines > 64 {
+                return Err(format!("bad num_lines {}", bank.num_lines));
+            }
+            if bank
+                .line_configs()
+                .iter()
+                .any(|&(o, _)| o >= bank.num_lines)
+            {
+                return Err("offset out of range".into());
+            }
+            let pulls = g.pulls(&bank, 20);
+            if pulls.iter().any(|&(o, _)| o >= bank.num_lines) {
+                return Err("pull out of range".into());
+            }
+            let sim = g
+                .with_max_banks(3)
+                .builder()
+                .with_backend(Memory::new())
+                .live()
+                .map_err(|e| e.to_string())?;
+            if sim.chips().is_empty() || sim.chips().len() > 3 {
+                return Err(format!("bad chip count {}", sim.chips().len()));
+            }
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn reproducible() {
+        let mut g1 = Gen::new(7);
+        let mut g2 = Gen::new(7);
+        g1.with_max_lines(8);
+        g2.with_max_lines(8);
+        let b1 = g1.bank();
+        assert_eq!(b1, g2.bank());
+        assert!(b1.num_lines <= 8);
+        assert_eq!(g1.pulls(&b1, 10), g2.pulls(&b1, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "with seed 0x")]
+    fn failing() {
+        check(10, 3, |g| {
+            if g.bank().num_lines > 50 {
+                Err("too many lines".into())
+            } else {
+                Ok(())
+            }
+        });
+    }
+}
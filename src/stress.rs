@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{Builder, Summary};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A stress test that repeatedly creates and removes simulators.
+///
+/// Each iteration takes a simulator, configured as per the builder, live and
+/// then drops it, from one or more threads, recording how long each step
+/// takes and any failures, e.g. to reproduce sporadic configfs errors under
+/// heavy parallel test load.
+///
+/// ```no_run
+/// use gpiosim::{Bank, Stress};
+///
+/// let mut builder = gpiosim::builder();
+/// builder.with_banks((0..4).map(|i| Bank::new(32, format!("bank{}", i))));
+/// let report = Stress::new(&builder).with_threads(8).with_iterations(1000).run();
+/// println!("{}", report);
+/// assert!(report.failures.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Stress {
+    /// The template for the simulators.
+    builder: Builder,
+
+    /// The number of threads creating simulators.
+    threads: usize,
+
+    /// The total number of simulators created.
+    iterations: usize,
+}
+
+/// The results of a [`Stress`] test.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StressReport {
+    /// The number of iterations attempted.
+    pub iterations: usize,
+
+    /// The time taken to take each simulator live, for the iterations that
+    /// succeeded.
+    pub create: Vec<Duration>,
+
+    /// The time taken to remove each simulator.
+    pub teardown: Vec<Duration>,
+
+    /// The errors for the iterations that failed.
+    ///
+    /// This includes simulators that were not fully removed, which are
+    /// otherwise not reported as teardown errors are ignored.
+    pub failures: Vec<String>,
+
+    /// How long the test took.
+    pub elapsed: Duration,
+}
+
+impl StressReport {
+    /// Summary statistics of the times taken to take the simulators live.
+    pub fn create_summary(&self) -> Summary {
+        Summary::new(&self.create)
+    }
+
+    /// Summary statistics of the times taken to remove the simulators.
+    pub fn teardown_summary(&self) -> Summary {
+        Summary::new(&self.teardown)
+    }
+
+    fn merge(&mut self, other: StressReport) {
+        self.iterations += other.iterations;
+        self.create.extend(other.create);
+        self.teardown.extend(other.teardown);
+        self.failures.extend(other.failures);
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "iterations {} failures {} elapsed {:?}",
+            self.iterations,
+            self.failures.len(),
+            self.elapsed
+        )?;
+        writeln!(f, "create   {}", self.create_summary())?;
+        write!(f, "teardown {}", self.teardown_summary())?;
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for e in &self.failures {
+            *counts.entry(e).or_default() += 1;
+        }
+        for (e, count) in counts {
+            write!(f, "\n{:>8} x {}", count, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl Stress {
+    /// Create a stress test of simulators configured as per the builder.
+    ///
+    /// Any name set in the builder is ignored, as each simulator is given a
+    /// unique name.
+    /// By default the test runs 100 iterations on a single thread.
+    pub fn new(builder: &Builder) -> Stress {
+        let mut builder = builder.clone();
+        builder.name = None;
+        Stress {
+            builder,
+            threads: 1,
+            iterations: 100,
+        }
+    }
+
+    /// Set the number of threads creating simulators concurrently.
+    pub fn with_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Set the total number of simulators created.
+    pub fn with_iterations(&mut self, iterations: usize) -> &mut Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Run the test, returning once all the iterations are complete.
+    pub fn run(&self) -> StressReport {
+        let start = Instant::now();
+        let workers: Vec<_> = (0..self.threads)
+            .map(|t| {
+                // spread the remainder over the first threads
+                let iterations = self.iterations / self.threads
+                    + usize::from(t < self.iterations % self.threads);
+                let builder = self.builder.clone();
+                thread::spawn(move || churn(builder, iterations))
+            })
+            .collect();
+        let mut report = StressReport::default();
+        for w in workers {
+            report.merge(w.join().expect("stress thread panicked"));
+        }
+        report.elapsed = start.elapsed();
+        report
+    }
+}
+
+// Create and remove simulators for the given number of iterations.
+fn churn(mut builder: Builder, iterations: usize) -> StressReport {
+    let mut report = StressReport {
+        iterations,
+        ..Default::default()
+    };
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let sim = match builder.live() {
+            Ok(sim) => sim,
+            Err(e) => {
+                report.failures.push(format!("create: {}", e));
+                continue;
+            }
+        };
+        report.create.push(start.elapsed());
+        let path = sim.configfs_path().map(|p| p.to_path_buf());
+        let start = Instant::now();
+        drop(sim);
+        report.teardown.push(start.elapsed());
+        if let Some(path) = path {
+            if path.exists() {
+                report
+                    .failures
+                    .push(format!("teardown: {} remains", path.display()));
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn run() {
+        let mem = Memory::new();
+        let mut builder = builder();
+        builder
+            .with_name("ignored")
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "left"))
+            .with_bank(&Bank::new(4, "right"));
+        let report = Stress::new(&builder)
+            .with_threads(3)
+            .with_iterations(20)
+            .run();
+        assert_eq!(report.iterations, 20);
+        assert_eq!(report.create.len(), 20);
+        assert_eq!(report.teardown.len(), 20);
+        assert!(report.failures.is_empty());
+        assert!(report.create_summary().max >= report.create_summary().min);
+        assert!(report.to_string().starts_with("iterations 20 failures 0 "));
+
+        let report = StressReport {
+            iterations: 3,
+            failures: vec!["create: busy".into(), "create: busy".into()],
+            ..Default::default()
+        };
+        assert!(report.to_string().ends_with("\n       2 x create: busy"));
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fmt;
+use std::time::Duration;
+
+/// Summary statistics of a set of durations, such as latencies.
+///
+/// All the statistics are zero for an empty set.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    /// The shortest duration.
+    pub min: Duration,
+
+    /// The mean duration.
+    pub mean: Duration,
+
+    /// The median duration.
+    pub median: Duration,
+
+    /// The 99th percentile duration.
+    pub p99: Duration,
+
+    /// The longest duration.
+    pub max: Duration,
+}
+
+impl Summary {
+    /// Summarise the durations.
+    pub fn new(durations: &[Duration]) -> Summary {
+        if durations.is_empty() {
+            return Summary::default();
+        }
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+        // nearest rank
+        let rank = |p: usize| sorted[((p * sorted.len() + 99) / 100).max(1) - 1];
+        Summary {
+            min: sorted[0],
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            median: rank(50),
+            p99: rank(99),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:?} mean {:?} median {:?} p99 {:?} max {:?}",
+            self.min, self.mean, self.median, self.p99, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let us = Duration::from_micros;
+        assert_eq!(Summary::new(&[]), Summary::default());
+        let s = Summary::new(&(1..=100).rev().map(us).collect::<Vec<_>>());
+        assert_eq!(s.min, us(1));
+        assert_eq!(s.median, us(50));
+        assert_eq!(s.p99, us(99));
+        assert_eq!(s.max, us(100));
+        assert_eq!(s.mean, Duration::from_nanos(50_500));
+        let s = Summary::new(&[us(7)]);
+        assert_eq!(
+            (s.min, s.median, s.p99, s.max),
+            (us(7), us(7), us(7), us(7))
+        );
+        assert_eq!(s.to_string(), "min 7µs mean 7µs median 7µs p99 7µs max 7µs");
+    }
+}
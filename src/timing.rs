@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const NANOS_PER_SEC: libc::c_long = 1_000_000_000;
+
+/// An Instant and the monotonic clock time read back to back with it,
+/// captured on the first sleep, so deadlines are converted to the monotonic
+/// clock with a fixed offset.
+static EPOCH: Mutex<Option<(Instant, libc::timespec)>> = Mutex::new(None);
+
+/// The SCHED_FIFO priority requested for emulator threads, or 0 for none.
+static REALTIME: AtomicU8 = AtomicU8::new(0);
+
+/// Set the real-time priority requested by emulator threads.
+///
+/// Emulator threads subsequently started by the crate, such as those
+/// sampling lines for protocol emulators, attempt to switch to the SCHED_FIFO
+/// scheduling policy at the priority, clamped to the range 1 to 99, to tighten
+/// their timing on loaded machines.
+/// Threads that are not permitted to switch, e.g. as the process lacks
+/// CAP_SYS_NICE, continue with their normal scheduling.
+///
+/// Passing `None`, the default, leaves the scheduling of subsequent threads
+/// unchanged.
+///
+/// Generators that run on the calling thread, such as transmitters, are not
+/// affected, but the calling thread can be switched using [`request_realtime`].
+pub fn set_realtime_priority(priority: Option<u8>) {
+    REALTIME.store(priority.map_or(0, |p| p.clamp(1, 99)), Ordering::Relaxed);
+}
+
+/// The real-time priority requested by emulator threads, if any.
+pub fn realtime_priority() -> Option<u8> {
+    match REALTIME.load(Ordering::Relaxed) {
+        0 => None,
+        p => Some(p),
+    }
+}
+
+/// Attempt to switch the calling thread to the SCHED_FIFO scheduling policy
+/// at the priority, clamped to the range 1 to 99.
+///
+/// Returns false, and leaves the scheduling of the thread unchanged, if the
+/// thread is not permitted to switch.
+pub fn request_realtime(priority: u8) -> bool {
+    let param = libc::sched_param {
+        sched_priority: priority.clamp(1, 99) as libc::c_int,
+    };
+    // SAFETY: param is a valid sched_param, and the thread is the caller.
+    unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) == 0 }
+}
+
+/// Switch the calling emulator thread to the requested real-time priority,
+/// if any, falling back to normal scheduling if not permitted.
+pub(crate) fn apply_realtime() {
+    if let Some(priority) = realtime_priority() {
+        _ = request_realtime(priority);
+    }
+}
+
+/// Sleep until the deadline.
+///
+/// Sleeping to absolute deadlines, rather than for periods, prevents errors in
+/// successive sleeps from accumulating.
+///
+/// The sleep targets the deadline on the monotonic clock, so, unlike a relative
+/// sleep, it is not lengthened if the thread is preempted before starting to
+/// sleep, or if the sleep is interrupted.
+/// The deadline is converted using the fixed offset between Instant and the
+/// monotonic clock, so the conversion is not skewed by preemption between
+/// reading the two clocks.
+pub(crate) fn sleep_until(deadline: Instant) {
+    // the epoch precedes now, so also the deadline
+    let epoch = epoch();
+    let now = Instant::now();
+    if deadline <= now {
+        return;
+    }
+    let target = match epoch {
+        Some((instant, ts)) => add(ts, deadline - instant),
+        None => {
+            std::thread::sleep(deadline - now);
+            return;
+        }
+    };
+    loop {
+        // SAFETY: target is a valid timespec and the remain pointer may be null
+        // for an absolute sleep.
+        let res = unsafe {
+            libc::clock_nanosleep(
+                libc::CLOCK_MONOTONIC,
+                libc::TIMER_ABSTIME,
+                &target,
+                std::ptr::null_mut(),
+            )
+        };
+        if res != libc::EINTR {
+            return;
+        }
+    }
+}
+
+// The epoch relating Instant to the monotonic clock, capturing it if
+// necessary.
+fn epoch() -> Option<(Instant, libc::timespec)> {
+    let mut epoch = EPOCH.lock().unwrap();
+    if epoch.is_none() {
+        let ts = monotonic_now()?;
+        *epoch = Some((Instant::now(), ts));
+    }
+    *epoch
+}
+
+// The current time on the monotonic clock, which is the clock underlying
+// Instant.
+pub(crate) fn monotonic_now() -> Option<libc::timespec> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: ts is a valid timespec to be written.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) } != 0 {
+        return None;
+    }
+    Some(ts)
+}
+
+fn add(ts: libc::timespec, d: Duration) -> libc::timespec {
+    // both terms are less than a second, so the sum fits in a c_long
+    let nsec = ts.tv_nsec + d.subsec_nanos() as libc::c_long;
+    libc::timespec {
+        tv_sec: ts.tv_sec + (d.as_secs() + (nsec / NANOS_PER_SEC) as u64) as libc::time_t,
+        tv_nsec: nsec % NANOS_PER_SEC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_carry() {
+        let ts = libc::timespec {
+            tv_sec: 5,
+            tv_nsec: 900_000_000,
+        };
+        let t = add(ts, Duration::from_millis(1200));
+        assert_eq!(t.tv_sec, 7);
+        assert_eq!(t.tv_nsec, 100_000_000);
+    }
+
+    #[test]
+    fn epoch_fixed() {
+        let first = epoch().unwrap().0;
+        sleep_until(Instant::now() + Duration::from_micros(100));
+        assert_eq!(epoch().unwrap().0, first);
+    }
+
+    #[test]
+    fn train() {
+        // a train of short sleeps does not accumulate error
+        let start = Instant::now();
+        let period = Duration::from_micros(200);
+        for n in 1..=50 {
+            sleep_until(start + period * n);
+            assert!(Instant::now() >= start + period * n);
+        }
+        sleep_until(start);
+        assert!(start.elapsed() < period * 50 + Duration::from_millis(50));
+    }
+
+    #[test]
+    fn realtime() {
+        assert_eq!(realtime_priority(), None);
+        // whether permitted or not, the request must not fail the thread
+        std::thread::spawn(|| request_realtime(200)).join().unwrap();
+    }
+}
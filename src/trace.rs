@@ -0,0 +1,487 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Capture of output transitions and golden trace comparison.
+//!
+//! A [`Capture`] records the transitions the consumer drives on a selection
+//! of output lines as a [`Trace`].
+//!
+//! Traces can be saved to, and loaded from, a simple text format, and
+//! compared with tolerances on timing, so the waveform produced by a driver
+//! can be checked against a golden reference.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::trace::Capture;
+//! use std::time::Duration;
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(4, "spi")).live()?;
+//! let capture = Capture::new(&sim.chips()[0], 0..4)?;
+//!
+//! // ... consumer drives a waveform on the lines ...
+//!
+//! capture.sync();
+//! capture
+//!     .trace()
+//!     .assert_golden("tests/golden/spi.trace", Duration::from_micros(500));
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::{Chip, Level, Lines, Offset, Result};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The environment variable that, if set, causes [`Trace::assert_golden`] to
+/// overwrite the golden file with the actual trace.
+pub const BLESS_VAR: &str = "GPIOSIM_BLESS";
+
+/// A transition of a line within a [`Trace`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Transition {
+    /// The time of the transition, relative to the first transition in the
+    /// trace.
+    pub time: Duration,
+
+    /// The line that transitioned.
+    pub offset: Offset,
+
+    /// The level of the line after the transition.
+    pub level: Level,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} {}", self.time, self.offset, self.level)
+    }
+}
+
+/// The transitions on a selection of lines.
+///
+/// The text format has one entry per line, with the initial level of each
+/// line as `initial <offset> <level>`, followed by the transitions, in order,
+/// as `<nanoseconds> <offset> <level>`, where level is `high` or `low`.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trace {
+    /// The level of each line when the capture started.
+    pub initial: Vec<(Offset, Level)>,
+
+    /// The transitions, oldest first.
+    pub transitions: Vec<Transition>,
+}
+
+impl Trace {
+    /// Load a trace from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Trace> {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    /// Save the trace to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+
+    /// Compare the actual trace against this, the expected, trace.
+    ///
+    /// The transitions on each line are compared in turn, and must match in
+    /// level and be within the tolerance of the expected time.
+    pub fn compare(
+        &self,
+        actual: &Trace,
+        tolerance: Duration,
+    ) -> std::result::Result<(), Mismatch> {
+        let mut differences = Vec::new();
+        for &(offset, level) in &self.initial {
+            let got = actual.initial.iter().find(|i| i.0 == offset).map(|i| i.1);
+            if got != Some(level) {
+                differences.push((
+                    Duration::ZERO,
+                    Difference::Initial {
+                        offset,
+                        expected: Some(level),
+                        actual: got,
+                    },
+                ));
+            }
+        }
+        for &(offset, level) in &actual.initial {
+            if !self.initial.iter().any(|i| i.0 == offset) {
+                differences.push((
+                    Duration::ZERO,
+                    Difference::Initial {
+                        offset,
+                        expected: None,
+                        actual: Some(level),
+                    },
+                ));
+            }
+        }
+        let mut offsets: Vec<Offset> = self
+            .transitions
+            .iter()
+            .chain(actual.transitions.iter())
+            .map(|t| t.offset)
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        for offset in offsets {
+            let expected = self.transitions.iter().filter(|t| t.offset == offset);
+            let mut actual = actual.transitions.iter().filter(|t| t.offset == offset);
+            for &e in expected {
+                match actual.next() {
+                    Some(&a) => {
+                        let error = if a.time > e.time {
+                            a.time - e.time
+                        } else {
+                            e.time - a.time
+                        };
+                        if a.level != e.level || error > tolerance {
+                            differences.push((
+                                e.time,
+                                Difference::Changed {
+                                    expected: e,
+                                    actual: a,
+                                },
+                            ));
+                        }
+                    }
+                    None => differences.push((e.time, Difference::Missing(e))),
+                }
+            }
+            for &a in actual {
+                differences.push((a.time, Difference::Unexpected(a)));
+            }
+        }
+        if differences.is_empty() {
+            return Ok(());
+        }
+        differences.sort_by_key(|d| d.0);
+        Err(Mismatch {
+            differences: differences.into_iter().map(|d| d.1).collect(),
+        })
+    }
+
+    /// Assert that the trace matches the golden trace in the file.
+    ///
+    /// If the file does not exist, or the [`BLESS_VAR`] environment variable
+    /// is set, the trace is saved as the golden trace instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics, listing the differences, if the trace does not match, or if
+    /// the file cannot be read or written.
+    pub fn assert_golden<P: AsRef<Path>>(&self, path: P, tolerance: Duration) {
+        let path = path.as_ref();
+        if env::var_os(BLESS_VAR).is_some() || !path.exists() {
+            if let Err(e) = self.save(path) {
+                panic!("failed to save golden trace {:?}: {}", path, e);
+            }
+            return;
+        }
+        let golden = match Trace::load(path) {
+            Ok(golden) => golden,
+            Err(e) => panic!("failed to load golden trace {:?}: {}", path, e),
+        };
+        if let Err(m) = golden.compare(self, tolerance) {
+            panic!("trace does not match golden trace {:?}:\n{}", path, m);
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(offset, level) in &self.initial {
+            writeln!(f, "initial {} {}", offset, level)?;
+        }
+        for t in &self.transitions {
+            writeln!(f, "{} {} {}", t.time.as_nanos(), t.offset, t.level)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Trace {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut trace = Trace::default();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid trace line {}: {:?}", n + 1, line),
+                )
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(invalid());
+            }
+            let offset = fields[1].parse().map_err(|_| invalid())?;
+            let level = match fields[2] {
+                "high" => Level::High,
+                "low" => Level::Low,
+                _ => return Err(invalid()),
+            };
+            if fields[0] == "initial" {
+                trace.initial.push((offset, level));
+            } else {
+                let nanos: u64 = fields[0].parse().map_err(|_| invalid())?;
+                trace.transitions.push(Transition {
+                    time: Duration::from_nanos(nanos),
+                    offset,
+                    level,
+                });
+            }
+        }
+        Ok(trace)
+    }
+}
+
+/// A difference between an expected and actual [`Trace`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Difference {
+    /// The initial level of a line differs, or the line is only in one of
+    /// the traces.
+    Initial {
+        /// The line.
+        offset: Offset,
+
+        /// The expected initial level.
+        expected: Option<Level>,
+
+        /// The actual initial level.
+        actual: Option<Level>,
+    },
+
+    /// A transition differs in level or time.
+    Changed {
+        /// The expected transition.
+        expected: Transition,
+
+        /// The actual transition.
+        actual: Transition,
+    },
+
+    /// An expected transition is missing.
+    Missing(Transition),
+
+    /// An actual transition was not expected.
+    Unexpected(Transition),
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = |l: Option<Level>| l.map_or("absent".to_string(), |l| l.to_string());
+        match self {
+            Difference::Initial {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "initial {}: expected {}, got {}",
+                offset,
+                level(*expected),
+                level(*actual)
+            ),
+            Difference::Changed { expected, actual } => {
+                write!(f, "- {}\n+ {}", expected, actual)
+            }
+            Difference::Missing(t) => write!(f, "- {}", t),
+            Difference::Unexpected(t) => write!(f, "+ {}", t),
+        }
+    }
+}
+
+/// The differences between an expected and actual [`Trace`], in order of
+/// time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mismatch {
+    /// The differences.
+    pub differences: Vec<Difference>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.differences {
+            writeln!(f, "{}", d)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// A recorder of the transitions the consumer drives on a selection of lines.
+#[derive(Debug)]
+pub struct Capture {
+    trace: Arc<Mutex<Trace>>,
+
+    monitor: Monitor,
+}
+
+impl Capture {
+    /// Start capturing the transitions on a selection of lines.
+    pub fn new<L: Into<Lines>>(chip: &Chip, lines: L) -> Result<Capture> {
+        let offsets = lines.into().offsets(&chip.cfg);
+        let initial = offsets
+            .iter()
+            .map(|&offset| Ok((offset, chip.dev.get_level(chip.bank, offset)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let trace = Arc::new(Mutex::new(Trace {
+            initial,
+            transitions: Vec::new(),
+        }));
+        let monitor = {
+            let trace = trace.clone();
+            let lines = offsets.clone();
+            let mut first = None;
+            Monitor::spawn(chip, &lines, move |_, prev, curr| {
+                let now = Instant::now();
+                let time = now - *first.get_or_insert(now);
+                let mut trace = trace.lock().unwrap();
+                for (i, &offset) in offsets.iter().enumerate() {
+                    if prev[i] != curr[i] {
+                        trace.transitions.push(Transition {
+                            time,
+                            offset,
+                            level: curr[i],
+                        });
+                    }
+                }
+            })?
+        };
+        Ok(Capture { trace, monitor })
+    }
+
+    /// The trace captured so far.
+    pub fn trace(&self) -> Trace {
+        self.trace.lock().unwrap().clone()
+    }
+
+    /// Wait until the current levels of the lines have been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    fn transition(ms: u64, offset: Offset, level: Level) -> Transition {
+        Transition {
+            time: Duration::from_millis(ms),
+            offset,
+            level,
+        }
+    }
+
+    #[test]
+    fn compare() {
+        let expected = Trace {
+            initial: vec![(0, Level::Low), (1, Level::Low)],
+            transitions: vec![
+                transition(0, 0, Level::High),
+                transition(10, 1, Level::High),
+                transition(20, 0, Level::Low),
+            ],
+        };
+        let tol = Duration::from_millis(2);
+        let mut actual = expected.clone();
+        actual.transitions[1].time = Duration::from_millis(11);
+        assert_eq!(expected.compare(&actual, tol), Ok(()));
+
+        actual.transitions[1].time = Duration::from_millis(13);
+        actual.transitions.pop();
+        actual.transitions.push(transition(30, 1, Level::Low));
+        actual.initial[1].1 = Level::High;
+        let m = expected.compare(&actual, tol).unwrap_err();
+        assert_eq!(
+            m.differences,
+            [
+                Difference::Initial {
+                    offset: 1,
+                    expected: Some(Level::Low),
+                    actual: Some(Level::High)
+                },
+                Difference::Changed {
+                    expected: transition(10, 1, Level::High),
+                    actual: transition(13, 1, Level::High)
+                },
+                Difference::Missing(transition(20, 0, Level::Low)),
+                Difference::Unexpected(transition(30, 1, Level::Low)),
+            ]
+        );
+        assert_eq!(
+            m.to_string(),
+            "initial 1: expected low, got high\n\
+             - 10ms 1 high\n\
+             + 13ms 1 high\n\
+             - 20ms 0 low\n\
+             + 30ms 1 low\n"
+        );
+    }
+
+    #[test]
+    fn text() {
+        let trace = Trace {
+            initial: vec![(2, Level::High)],
+            transitions: vec![transition(0, 2, Level::Low), transition(1, 2, Level::High)],
+        };
+        let text = trace.to_string();
+        assert_eq!(text, "initial 2 high\n0 2 low\n1000000 2 high\n");
+        assert_eq!(text.parse::<Trace>().unwrap(), trace);
+        assert_eq!(
+            format!("# golden\n\n{}", text).parse::<Trace>().unwrap(),
+            trace
+        );
+        assert!("12 2 middle".parse::<Trace>().is_err());
+        assert!("initial 2".parse::<Trace>().is_err());
+
+        let path = env::temp_dir().join(format!("gpiosim-trace-{}", std::process::id()));
+        trace.assert_golden(&path, Duration::ZERO);
+        assert_eq!(Trace::load(&path).unwrap(), trace);
+        trace.assert_golden(&path, Duration::ZERO);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn capture() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "trace"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let capture = Capture::new(c, [1, 3]).unwrap();
+        mem.drive(c, 1, Some(Level::High)).unwrap();
+        capture.sync();
+        thread::sleep(Duration::from_millis(10));
+        mem.drive(c, 3, Some(Level::High)).unwrap();
+        mem.drive(c, 0, Some(Level::High)).unwrap();
+        capture.sync();
+        let trace = capture.trace();
+        assert_eq!(trace.initial, [(1, Level::Low), (3, Level::Low)]);
+        assert_eq!(trace.transitions.len(), 2);
+        assert_eq!(trace.transitions[0], transition(0, 1, Level::High));
+        assert_eq!(trace.transitions[1].offset, 3);
+        assert!(trace.transitions[1].time >= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::timing::sleep_until;
+use crate::{Chip, Level, Offset, Result};
+use std::time::{Duration, Instant};
+
+/// An ordered group of pulls, with optional delays between them, applied to
+/// a chip as a unit.
+///
+/// The pulls are applied in the order they were added, with each delay
+/// measured from the start of the transaction, so the timing of later pulls
+/// is not affected by the time taken to apply earlier ones.
+/// Pulls without an intervening delay are applied back-to-back.
+///
+/// All offsets are checked before any pull is applied, so a transaction with
+/// an invalid offset has no effect.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Transaction};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "bus")).live()?;
+/// // place data, then strobe
+/// Transaction::new()
+///     .pullup(0)
+///     .pulldown(1)
+///     .delay(Duration::from_micros(100))
+///     .pulldown(3)
+///     .delay(Duration::from_micros(100))
+///     .pullup(3)
+///     .apply(&sim.chips()[0])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Transaction {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Step {
+    Pull(Offset, Level),
+    Delay(Duration),
+}
+
+impl Transaction {
+    /// Start building an empty transaction.
+    pub fn new() -> Transaction {
+        Transaction::default()
+    }
+
+    /// Add a pull on a line.
+    pub fn pull(&mut self, offset: Offset, pull: Level) -> &mut Self {
+        self.steps.push(Step::Pull(offset, pull));
+        self
+    }
+
+    /// Add a pull up on a line.
+    pub fn pullup(&mut self, offset: Offset) -> &mut Self {
+        self.pull(offset, Level::High)
+    }
+
+    /// Add a pull down on a line.
+    pub fn pulldown(&mut self, offset: Offset) -> &mut Self {
+        self.pull(offset, Level::Low)
+    }
+
+    /// Add a delay before any subsequent pulls.
+    pub fn delay(&mut self, delay: Duration) -> &mut Self {
+        self.steps.push(Step::Delay(delay));
+        self
+    }
+
+    /// The number of pulls in the transaction.
+    pub fn len(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|s| matches!(s, Step::Pull(..)))
+            .count()
+    }
+
+    /// Returns true if the transaction contains no pulls.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total of the delays in the transaction.
+    pub fn duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .map(|s| match s {
+                Step::Delay(d) => *d,
+                Step::Pull(..) => Duration::ZERO,
+            })
+            .sum()
+    }
+
+    /// Apply the transaction to the chip, returning once the final step is
+    /// complete.
+    pub fn apply(&self, chip: &Chip) -> Result<()> {
+        for step in &self.steps {
+            if let Step::Pull(offset, _) = step {
+                check_offset(chip, *offset)?;
+            }
+        }
+        let mut deadline = Instant::now();
+        for step in &self.steps {
+            match *step {
+                Step::Pull(offset, pull) => {
+                    sleep_until(deadline);
+                    chip.set_pull(offset, pull)?;
+                }
+                Step::Delay(d) => deadline += d,
+            }
+        }
+        sleep_until(deadline);
+        Ok(())
+    }
+}
+
+impl From<&[(Offset, Level)]> for Transaction {
+    fn from(pulls: &[(Offset, Level)]) -> Self {
+        Transaction {
+            steps: pulls.iter().map(|&(o, l)| Step::Pull(o, l)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn apply() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "txn"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut txn = Transaction::new();
+        txn.pullup(0)
+            .pull(1, Level::High)
+            .delay(Duration::from_millis(5))
+            .pulldown(0)
+            .delay(Duration::from_millis(5));
+        assert_eq!(txn.len(), 3);
+        assert_eq!(txn.duration(), Duration::from_millis(10));
+        let start = Instant::now();
+        txn.apply(c).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(c.get_pull(0).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+        assert_eq!(c.stats()[0].pulls, 2);
+
+        // invalid offsets are detected before any pull is applied
+        c.reset_stats();
+        let bad = Transaction::from(&[(2, Level::High), (4, Level::High)][..]);
+        assert!(bad.apply(c).is_err());
+        assert_eq!(c.stats()[2].pulls, 0);
+        assert!(Transaction::new().is_empty());
+    }
+}
@@ -0,0 +1,514 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Emulation of asynchronous serial (UART) peers on simulated lines.
+//!
+//! A [`Transmitter`] sends bytes to a consumer software UART receiver by
+//! pulling a line.
+//!
+//! A [`Receiver`] decodes the frames sent by a consumer software UART
+//! transmitter on a line.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::Bank;
+//! use gpiosim::uart::{Config, Receiver, Transmitter};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(2, "uart")).live()?;
+//! let config = Config {
+//!     baud: 1200,
+//!     ..Default::default()
+//! };
+//! let tx = Transmitter::new(&sim.chips()[0], 0, config)?;
+//! let rx = Receiver::new(&sim.chips()[0], 1, config)?;
+//! tx.send(b"hello")?;
+//!
+//! // ... consumer echoes the bytes back ...
+//!
+//! rx.sync();
+//! assert_eq!(rx.received(), b"hello");
+//! # Ok(())
+//! # }
+//! ```
+use crate::monitor::Monitor;
+use crate::timing::sleep_until;
+use crate::{Chip, Error, Jitter, Level, Offset, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The parity bit included in each frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Parity {
+    /// No parity bit.
+    #[default]
+    None,
+
+    /// The parity bit makes the number of high bits even.
+    Even,
+
+    /// The parity bit makes the number of high bits odd.
+    Odd,
+}
+
+/// The format and rate of the serial frames.
+///
+/// The default is 9600 baud, 8 data bits, no parity and 1 stop bit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// The number of bits per second.
+    pub baud: u32,
+
+    /// The number of data bits in each frame, from 5 to 8.
+    pub data_bits: u8,
+
+    /// The parity bit in each frame.
+    pub parity: Parity,
+
+    /// The number of stop bits in each frame, 1 or 2.
+    pub stop_bits: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            baud: 9600,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+        }
+    }
+}
+
+impl Config {
+    /// The duration of each bit.
+    pub fn bit_period(&self) -> Duration {
+        Duration::from_nanos(1_000_000_000 / self.baud as u64)
+    }
+
+    /// The levels of the bits in the frame for a byte, in order of transmission.
+    fn frame(&self, byte: u8) -> Vec<Level> {
+        let mut bits = vec![Level::Low];
+        let mut ones = 0;
+        for bit in 0..self.data_bits {
+            if byte & (1 << bit) == 0 {
+                bits.push(Level::Low);
+            } else {
+                bits.push(Level::High);
+                ones += 1;
+            }
+        }
+        match self.parity {
+            Parity::None => {}
+            Parity::Even => bits.push(level(ones % 2 == 1)),
+            Parity::Odd => bits.push(level(ones % 2 == 0)),
+        }
+        for _ in 0..self.stop_bits {
+            bits.push(Level::High);
+        }
+        bits
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.baud == 0 {
+            return Err(Error::InvalidConfig("baud must be non-zero".into()));
+        }
+        if !(5..=8).contains(&self.data_bits) {
+            return Err(Error::InvalidConfig(format!(
+                "data bits {} must be between 5 and 8",
+                self.data_bits
+            )));
+        }
+        if !(1..=2).contains(&self.stop_bits) {
+            return Err(Error::InvalidConfig(format!(
+                "stop bits {} must be 1 or 2",
+                self.stop_bits
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn level(high: bool) -> Level {
+    if high {
+        Level::High
+    } else {
+        Level::Low
+    }
+}
+
+/// Transmits serial frames to the consumer by pulling a line.
+///
+/// The line idles high.
+#[derive(Debug)]
+pub struct Transmitter {
+    /// The chip containing the line.
+    chip: Chip,
+
+    /// The line to transmit on.
+    offset: Offset,
+
+    config: Config,
+
+    /// The jitter applied to the bit edges.
+    jitter: Jitter,
+}
+
+impl Transmitter {
+    /// Create a transmitter on the line, pulling the line high.
+    pub fn new(chip: &Chip, offset: Offset, config: Config) -> Result<Transmitter> {
+        config.validate()?;
+        chip.set_pull(offset, Level::High)?;
+        Ok(Transmitter {
+            chip: chip.share(),
+            offset,
+            config,
+            jitter: Jitter::default(),
+        })
+    }
+
+    /// Set the jitter applied to the bit edges.
+    ///
+    /// There is no jitter by default.
+    pub fn with_jitter(&mut self, jitter: Jitter) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The format and rate of the frames.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Transmit the bytes, returning once the final stop bit is complete.
+    ///
+    /// Bits beyond the configured number of data bits are ignored.
+    pub fn send(&self, data: &[u8]) -> Result<()> {
+        let period = self.config.bit_period();
+        let start = Instant::now();
+        let mut n = 0;
+        for &byte in data {
+            for bit in self.config.frame(byte) {
+                sleep_until(self.jitter.apply(start + period * n));
+                self.chip.set_pull(self.offset, bit)?;
+                n += 1;
+            }
+        }
+        sleep_until(start + period * n);
+        Ok(())
+    }
+}
+
+/// Decodes serial frames transmitted by the consumer on a line.
+///
+/// The line is sampled, and the frames decoded from the timing of the
+/// transitions, so the baud rate must be low enough for each bit to span
+/// several sample periods.
+///
+/// Frames with an invalid start, parity or stop bit are discarded and
+/// counted as errors.
+#[derive(Debug)]
+pub struct Receiver {
+    decoder: Arc<Mutex<Decoder>>,
+
+    monitor: Monitor,
+}
+
+impl Receiver {
+    /// Start decoding frames on the line.
+    pub fn new(chip: &Chip, offset: Offset, config: Config) -> Result<Receiver> {
+        config.validate()?;
+        let level = chip.dev.get_level(chip.bank, offset)?;
+        let decoder = Arc::new(Mutex::new(Decoder::new(config, level)));
+        let monitor = {
+            let decoder = decoder.clone();
+            Monitor::spawn(chip, &[offset], move |_, _, curr| {
+                let now = Instant::now();
+                let mut decoder = decoder.lock().unwrap();
+                decoder.push(now, curr[0]);
+                decoder.decode(now);
+            })?
+        };
+        Ok(Receiver { decoder, monitor })
+    }
+
+    /// The bytes received so far, oldest first.
+    pub fn received(&self) -> Vec<u8> {
+        let mut decoder = self.decoder.lock().unwrap();
+        decoder.decode(Instant::now());
+        decoder.received.clone()
+    }
+
+    /// Remove and return the bytes received so far, oldest first.
+    pub fn take(&self) -> Vec<u8> {
+        let mut decoder = self.decoder.lock().unwrap();
+        decoder.decode(Instant::now());
+        std::mem::take(&mut decoder.received)
+    }
+
+    /// The number of invalid frames received.
+    pub fn errors(&self) -> usize {
+        let mut decoder = self.decoder.lock().unwrap();
+        decoder.decode(Instant::now());
+        decoder.errors
+    }
+
+    /// Wait until the current state of the line has been sampled.
+    pub fn sync(&self) {
+        self.monitor.sync()
+    }
+}
+
+#[derive(Debug)]
+struct Decoder {
+    config: Config,
+
+    /// The level of the line prior to the first of the edges.
+    level: Level,
+
+    /// The transitions of the line not yet decoded.
+    edges: Vec<(Instant, Level)>,
+
+    /// The bytes decoded.
+    received: Vec<u8>,
+
+    /// The number of invalid frames decoded.
+    errors: usize,
+}
+
+impl Decoder {
+    fn new(config: Config, level: Level) -> Decoder {
+        Decoder {
+            config,
+            level,
+            edges: Vec::new(),
+            received: Vec::new(),
+            errors: 0,
+        }
+    }
+
+    fn push(&mut self, time: Instant, level: Level) {
+        self.edges.push((time, level));
+    }
+
+    // Decode all the frames that are complete at the time now.
+    fn decode(&mut self, now: Instant) {
+        let period = self.config.bit_period();
+        let cfg = self.config;
+        let num_bits = cfg.frame(0).len() as u32;
+        loop {
+            // find the start bit
+            let start = match self.edges.iter().position(|e| e.1 == Level::Low) {
+                Some(idx) => idx,
+                None => {
+                    self.discard(self.edges.len());
+                    return;
+                }
+            };
+            self.discard(start);
+            let t0 = self.edges[0].0;
+            // sample the middle of each bit
+            let sample = |n: u32| t0 + period * n + period / 2;
+            if now < sample(num_bits - 1) {
+                return;
+            }
+            let bits: Vec<Level> = (0..num_bits).map(|n| self.level_at(sample(n))).collect();
+            let mut byte = 0;
+            for (idx, bit) in bits[1..=cfg.data_bits as usize].iter().enumerate() {
+                if *bit == Level::High {
+                    byte |= 1 << idx;
+                }
+            }
+            if bits == cfg.frame(byte) {
+                self.received.push(byte);
+            } else {
+                self.errors += 1;
+            }
+            let end = sample(num_bits - 1);
+            let consumed = self.edges.iter().take_while(|e| e.0 <= end).count();
+            self.discard(consumed);
+        }
+    }
+
+    // The level of the line at time t.
+    fn level_at(&self, t: Instant) -> Level {
+        self.edges
+            .iter()
+            .take_while(|e| e.0 <= t)
+            .last()
+            .map(|e| e.1)
+            .unwrap_or(self.level)
+    }
+
+    // Drop the first n edges.
+    fn discard(&mut self, n: usize) {
+        if n > 0 {
+            self.level = self.edges[n - 1].1;
+            self.edges.drain(..n);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Distribution, Memory};
+    use Level::*;
+
+    #[test]
+    fn frame() {
+        let cfg = Config::default();
+        assert_eq!(
+            cfg.frame(0x35),
+            [Low, High, Low, High, Low, High, High, Low, Low, High]
+        );
+        let cfg = Config {
+            data_bits: 7,
+            parity: Parity::Even,
+            stop_bits: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            cfg.frame(0xb5),
+            [Low, High, Low, High, Low, High, High, Low, Low, High, High]
+        );
+        let cfg = Config {
+            data_bits: 5,
+            parity: Parity::Odd,
+            ..Default::default()
+        };
+        assert_eq!(
+            cfg.frame(0x03),
+            [Low, High, High, Low, Low, Low, High, High]
+        );
+    }
+
+    #[test]
+    fn config() {
+        assert_eq!(Config::default().bit_period(), Duration::from_nanos(104166));
+        assert!(Config::default().validate().is_ok());
+        for cfg in [
+            Config {
+                baud: 0,
+                ..Default::default()
+            },
+            Config {
+                data_bits: 9,
+                ..Default::default()
+            },
+            Config {
+                stop_bits: 3,
+                ..Default::default()
+            },
+        ] {
+            assert!(cfg.validate().is_err());
+        }
+    }
+
+    // Generate the edges for the frames, as transmitted from time t0.
+    fn edges(cfg: &Config, t0: Instant, data: &[u8]) -> Vec<(Instant, Level)> {
+        let mut edges = Vec::new();
+        let mut level = High;
+        let bits = data.iter().flat_map(|b| cfg.frame(*b));
+        for (n, bit) in bits.enumerate() {
+            if bit != level {
+                edges.push((t0 + cfg.bit_period() * n as u32, bit));
+                level = bit;
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn decode() {
+        let cfg = Config {
+            parity: Parity::Odd,
+            ..Default::default()
+        };
+        let period = cfg.bit_period();
+        let t0 = Instant::now();
+        let mut d = Decoder::new(cfg, High);
+        for e in edges(&cfg, t0, &[0x00, 0xa5, 0xff]) {
+            d.push(e.0, e.1);
+        }
+        // the first frame is incomplete until the middle of its stop bit
+        d.decode(t0 + period * 10);
+        assert!(d.received.is_empty());
+        d.decode(t0 + period * 22);
+        assert_eq!(d.received, [0x00, 0xa5]);
+        d.decode(t0 + period * 40);
+        assert_eq!(d.received, [0x00, 0xa5, 0xff]);
+        assert_eq!(d.errors, 0);
+        assert!(d.edges.is_empty());
+        assert_eq!(d.level, High);
+
+        // bad parity
+        let t0 = t0 + period * 40;
+        // parity bit should be low
+        for e in edges(&Config::default(), t0, &[0x01]) {
+            d.push(e.0, e.1);
+        }
+        d.decode(t0 + period * 20);
+        assert_eq!(d.received, [0x00, 0xa5, 0xff]);
+        assert_eq!(d.errors, 1);
+        assert!(d.edges.is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "uart"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let config = Config {
+            baud: 100,
+            ..Default::default()
+        };
+        let tx = Transmitter::new(c, 1, config).unwrap();
+        let rx = Receiver::new(c, 1, config).unwrap();
+        tx.send(b"hi").unwrap();
+        rx.sync();
+        assert_eq!(rx.received(), b"hi");
+        assert_eq!(rx.errors(), 0);
+        assert_eq!(rx.take(), b"hi");
+        assert!(rx.received().is_empty());
+
+        // 10% jitter
+        let mut tx = tx;
+        tx.with_jitter(Jitter::new(
+            Distribution::Uniform {
+                min: Duration::ZERO,
+                max: Duration::from_millis(1),
+            },
+            3,
+        ));
+        tx.send(b"ok").unwrap();
+        rx.sync();
+        assert_eq!(rx.take(), b"ok");
+        assert_eq!(rx.errors(), 0);
+    }
+
+    #[test]
+    fn send() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(2, "uart"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let config = Config {
+            baud: 10000,
+            ..Default::default()
+        };
+        let tx = Transmitter::new(c, 1, config).unwrap();
+        assert_eq!(c.get_pull(1).unwrap(), High);
+        let start = Instant::now();
+        tx.send(&[0x55, 0xff]).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(2));
+        assert_eq!(c.get_pull(1).unwrap(), High);
+        // the idle level, then each bit of the two frames
+        assert_eq!(c.stats()[1].pulls, 21);
+    }
+}
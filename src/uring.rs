@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use io_uring::{opcode, squeue, types, IoUring};
+use std::io;
+use std::os::unix::io::RawFd;
+
+/// The number of entries in the submission queue.
+const ENTRIES: u32 = 64;
+
+// From linux/io_uring.h
+const IORING_ENTER_GETEVENTS: u32 = 1;
+
+/// A minimal io_uring, used to submit batches of writes with a single
+/// syscall.
+pub(crate) struct Ring {
+    ring: IoUring,
+
+    /// Set if submission failed, so entries may remain queued in the ring.
+    broken: bool,
+}
+
+impl std::fmt::Debug for Ring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ring")
+            .field("entries", &self.ring.params().sq_entries())
+            .field("broken", &self.broken)
+            .finish()
+    }
+}
+
+impl Ring {
+    /// Create a ring, failing if io_uring is not supported or is disabled.
+    pub(crate) fn new() -> io::Result<Ring> {
+        Ok(Ring {
+            ring: IoUring::new(ENTRIES)?,
+            broken: false,
+        })
+    }
+
+    /// Write each buffer to the start of its file, in order.
+    ///
+    /// The writes are linked, so a failed write cancels those following it
+    /// in the same submission, and the error of the first failed write is
+    /// returned.
+    ///
+    /// If the writes cannot be submitted then the ring is broken, and all
+    /// subsequent calls fail.
+    pub(crate) fn write_all(&mut self, writes: &[(RawFd, &[u8])]) -> io::Result<()> {
+        if self.broken {
+            return Err(io::Error::new(io::ErrorKind::Other, "io_uring is broken"));
+        }
+        for chunk in writes.chunks(self.ring.params().sq_entries() as usize) {
+            self.submit(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if a submission failed, so the ring cannot be reused.
+    pub(crate) fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    fn submit(&mut self, writes: &[(RawFd, &[u8])]) -> io::Result<()> {
+        {
+            let mut sq = self.ring.submission();
+            for (i, &(fd, data)) in writes.iter().enumerate() {
+                let mut entry = opcode::Write::new(types::Fd(fd), data.as_ptr(), data.len() as u32)
+                    .offset(0)
+                    .build()
+                    .user_data(i as u64);
+                if i + 1 < writes.len() {
+                    entry = entry.flags(squeue::Flags::IO_LINK);
+                }
+                // SAFETY: the buffers outlive the entries, as all submitted
+                // entries are completed before returning.
+                if unsafe { sq.push(&entry) }.is_err() {
+                    // the ring is empty between submissions, so this is
+                    // unreachable, but the queued entries are never submitted
+                    self.broken = true;
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "io_uring submission queue is full",
+                    ));
+                }
+            }
+        }
+        let mut results = vec![None; writes.len()];
+        let mut submitted = 0;
+        let mut completed = 0;
+        while completed < writes.len() {
+            match self.ring.submit_and_wait(writes.len() - completed) {
+                Ok(n) => submitted += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    // the unsubmitted entries remain in the ring, so it
+                    // cannot be reused, and the submitted entries refer to
+                    // the buffers, so must complete before returning
+                    self.broken = true;
+                    self.drain(&mut results, submitted, completed);
+                    return Err(e);
+                }
+            }
+            completed += self.reap(&mut results);
+        }
+        for (&(_, data), res) in writes.iter().zip(results) {
+            match res {
+                Some(res) if res < 0 => return Err(io::Error::from_raw_os_error(-res)),
+                Some(res) if (res as usize) < data.len() => {
+                    return Err(io::ErrorKind::WriteZero.into())
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Wait for the submitted entries to complete, without submitting any
+    // more.
+    fn drain(&mut self, results: &mut [Option<i32>], submitted: usize, mut completed: usize) {
+        while completed < submitted {
+            // SAFETY: waits for completions, with no signal mask.
+            let res = unsafe {
+                self.ring.submitter().enter::<libc::sigset_t>(
+                    0,
+                    (submitted - completed) as u32,
+                    IORING_ENTER_GETEVENTS,
+                    None,
+                )
+            };
+            if matches!(&res, Err(e) if e.kind() != io::ErrorKind::Interrupted) {
+                // completions are still posted to the ring, so poll for them
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            completed += self.reap(results);
+        }
+    }
+
+    // Collect the available completions, returning the number collected.
+    fn reap(&mut self, results: &mut [Option<i32>]) -> usize {
+        let mut count = 0;
+        for cqe in self.ring.completion() {
+            if let Some(r) = results.get_mut(cqe.user_data() as usize) {
+                *r = Some(cqe.result());
+            }
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File, OpenOptions};
+    use std::os::unix::io::AsRawFd;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gpiosim-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_all() {
+        let mut ring = match Ring::new() {
+            Ok(ring) => ring,
+            // io_uring is disabled or unsupported on the test host
+            Err(_) => return,
+        };
+        let dir = temp_dir("uring");
+        let files: Vec<File> = (0..100)
+            .map(|i| {
+                OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(dir.join(i.to_string()))
+                    .unwrap()
+            })
+            .collect();
+        let data: Vec<String> = (0..100).map(|i| format!("value {}", i)).collect();
+        let writes: Vec<(RawFd, &[u8])> = files
+            .iter()
+            .zip(&data)
+            .map(|(f, d)| (f.as_raw_fd(), d.as_bytes()))
+            .collect();
+        // more writes than entries, so several submissions
+        ring.write_all(&writes).unwrap();
+        for (i, d) in data.iter().enumerate() {
+            assert_eq!(&fs::read_to_string(dir.join(i.to_string())).unwrap(), d);
+        }
+
+        // a failed write cancels the following writes
+        let ro = File::open(dir.join("0")).unwrap();
+        let err = ring
+            .write_all(&[(ro.as_raw_fd(), b"fail"), (writes[1].0, b"cancelled")])
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+        assert_eq!(fs::read_to_string(dir.join("1")).unwrap(), "value 1");
+        assert!(!ring.is_broken());
+
+        // a broken ring does not submit
+        ring.broken = true;
+        assert!(ring.write_all(&[(writes[1].0, b"unwritten")]).is_err());
+        assert_eq!(fs::read_to_string(dir.join("1")).unwrap(), "value 1");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
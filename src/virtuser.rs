@@ -0,0 +1,731 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Simulation of the consumer side of GPIO lines using the **gpio-virtuser**
+//! kernel module, available since Linux 6.12.
+//!
+//! A virtual consumer requests lines from real or simulated chips, as
+//! configured via configfs in the same style as gpio-sim, and the direction
+//! and value of the requested lines are then controlled via debugfs, so code
+//! that monitors lines requested by kernel drivers can be tested.
+//!
+//! The lines are grouped into lookups, each identified by a connection ID,
+//! as per the lookups of a kernel driver, and each line within a lookup is
+//! identified by its index in the lookup.
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::virtuser::{self, Direction, Line};
+//! use gpiosim::{Bank, Level};
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(8, "left")).live()?;
+//! let chip = &sim.chips()[0];
+//! let consumer = virtuser::builder()
+//!     .with_lookup("reset", &[Line::from_chip(chip, 3)])
+//!     .live()?;
+//! consumer.set_direction("reset", 0, Direction::Output)?;
+//! consumer.set_value("reset", 0, Level::High)?;
+//! assert_eq!(chip.get_level(3)?, Level::High);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::fs::{Fs, HostFs};
+use crate::kernel::find_module_configfs;
+use crate::{Chip, Error, Level, Offset, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The location of the gpio-virtuser in debugfs.
+const DEBUGFS_DIR: &str = "/sys/kernel/debug/gpio-virtuser";
+
+/// Start building a virtual consumer.
+pub fn builder() -> Builder {
+    Builder::default()
+}
+
+/// The drive of a requested line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Drive {
+    /// Drive the line both high and low.
+    #[default]
+    PushPull,
+
+    /// Drive the line low, and leave it floating when high.
+    OpenDrain,
+
+    /// Drive the line high, and leave it floating when low.
+    OpenSource,
+}
+
+impl Drive {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Drive::PushPull => "push-pull",
+            Drive::OpenDrain => "open-drain",
+            Drive::OpenSource => "open-source",
+        }
+    }
+}
+
+/// The bias of a requested line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Bias {
+    /// Leave the bias as is.
+    #[default]
+    AsIs,
+
+    /// Pull the line up.
+    PullUp,
+
+    /// Pull the line down.
+    PullDown,
+
+    /// Disable any bias.
+    Disabled,
+}
+
+impl Bias {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Bias::AsIs => "as-is",
+            Bias::PullUp => "pull-up",
+            Bias::PullDown => "pull-down",
+            Bias::Disabled => "pull-disabled",
+        }
+    }
+}
+
+/// The direction of a requested line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// The line is an input.
+    Input,
+
+    /// The line is an output.
+    Output,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Input => "input",
+            Direction::Output => "output",
+        }
+    }
+}
+
+/// The configuration of a line requested by a virtual consumer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Line {
+    /// The label of the chip containing the line, or the name of the line.
+    pub key: String,
+
+    /// The offset of the line on the chip, or `None` if the key is the name
+    /// of the line.
+    pub offset: Option<Offset>,
+
+    /// The drive of the line.
+    pub drive: Drive,
+
+    /// The bias of the line.
+    pub bias: Bias,
+
+    /// Set if the line is active low.
+    pub active_low: bool,
+
+    /// Set if the line may lose its state when the consumer is suspended, or
+    /// the line is released.
+    pub transitory: bool,
+}
+
+impl Line {
+    /// A line identified by the label of its chip and its offset on that chip.
+    pub fn on_chip<L: Into<String>>(label: L, offset: Offset) -> Line {
+        Line {
+            key: label.into(),
+            offset: Some(offset),
+            drive: Drive::default(),
+            bias: Bias::default(),
+            active_low: false,
+            transitory: false,
+        }
+    }
+
+    /// A line on a simulated chip.
+    pub fn from_chip(chip: &Chip, offset: Offset) -> Line {
+        Line::on_chip(chip.config().label.as_str(), offset)
+    }
+
+    /// A line identified by its name.
+    pub fn named<N: Into<String>>(name: N) -> Line {
+        Line {
+            offset: None,
+            ..Line::on_chip(name, 0)
+        }
+    }
+
+    /// Set the drive of the line.
+    pub fn with_drive(mut self, drive: Drive) -> Line {
+        self.drive = drive;
+        self
+    }
+
+    /// Set the bias of the line.
+    pub fn with_bias(mut self, bias: Bias) -> Line {
+        self.bias = bias;
+        self
+    }
+
+    /// Set the line to be active low.
+    pub fn as_active_low(mut self) -> Line {
+        self.active_low = true;
+        self
+    }
+
+    /// Set the line to be transitory.
+    pub fn as_transitory(mut self) -> Line {
+        self.transitory = true;
+        self
+    }
+}
+
+/// A builder of virtual consumers.
+///
+/// Collects the lookups for the consumer, and then creates the consumer when
+/// taken live.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    /// The name for the consumer in the configfs space.
+    ///
+    /// If None when [`live`] is called then a unique name is generated.
+    ///
+    /// [`live`]: Builder::live
+    pub name: Option<String>,
+
+    /// The lookups of the consumer, identified by their connection ID.
+    pub lookups: Vec<(String, Vec<Line>)>,
+
+    /// The filesystem containing configfs and debugfs.
+    fs: Arc<dyn Fs>,
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder {
+            name: None,
+            lookups: Vec::new(),
+            fs: Arc::new(HostFs),
+        }
+    }
+}
+
+impl Builder {
+    #[cfg(test)]
+    fn with_fs(&mut self, fs: Arc<dyn Fs>) -> &mut Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Specify the name for the consumer.
+    ///
+    /// The name must be unique or going live will fail.
+    pub fn with_name<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Add a lookup of lines to be requested by the consumer.
+    ///
+    /// The connection ID identifies the lookup, and the lines are requested
+    /// together.
+    pub fn with_lookup<C: Into<String>>(&mut self, con_id: C, lines: &[Line]) -> &mut Self {
+        self.lookups.push((con_id.into(), lines.to_vec()));
+        self
+    }
+
+    /// Take the builder config live and return the created consumer.
+    ///
+    /// The lines are requested when the consumer goes live, so the chips
+    /// containing them must already exist.
+    pub fn live(&mut self) -> Result<Consumer> {
+        if self.lookups.is_empty() {
+            return Err(Error::InvalidConfig("consumer has no lookups".into()));
+        }
+        for (con_id, lines) in &self.lookups {
+            if con_id.is_empty() || con_id.contains(['/', ':']) {
+                return Err(Error::InvalidConfig(format!(
+                    "invalid connection ID {:?}",
+                    con_id
+                )));
+            }
+            if lines.is_empty() {
+                return Err(Error::InvalidConfig(format!(
+                    "lookup {:?} has no lines",
+                    con_id
+                )));
+            }
+        }
+        let name = match &self.name {
+            Some(n) => n.clone(),
+            None => crate::default_name(),
+        };
+        let dir = find_module_configfs(self.fs.as_ref(), "gpio-virtuser")?.join(&name);
+        if self.fs.exists(&dir) {
+            return Err(Error::SimulatorExists(name));
+        }
+        self.fs.create_dir(&dir)?;
+        let mut consumer = Consumer {
+            fs: self.fs.clone(),
+            name,
+            dir,
+            lookups: self.lookups.clone(),
+            dev_name: String::new(),
+        };
+        consumer.live()?;
+        Ok(consumer)
+    }
+}
+
+/// A live virtual consumer.
+///
+/// The consumer is removed, releasing its lines, when dropped.
+#[derive(Debug)]
+pub struct Consumer {
+    /// The filesystem containing configfs and debugfs.
+    fs: Arc<dyn Fs>,
+
+    /// The name of the consumer in configfs.
+    name: String,
+
+    /// Path to the consumer in configfs.
+    dir: PathBuf,
+
+    /// The lookups of the consumer.
+    lookups: Vec<(String, Vec<Line>)>,
+
+    /// The name of the device in debugfs.
+    dev_name: String,
+}
+
+impl Consumer {
+    fn live(&mut self) -> Result<()> {
+        for (con_id, lines) in &self.lookups {
+            let lookup_dir = self.dir.join(con_id);
+            self.fs.create_dir(&lookup_dir)?;
+            for (idx, line) in lines.iter().enumerate() {
+                let line_dir = lookup_dir.join(format!("line{}", idx));
+                self.fs.create_dir(&line_dir)?;
+                let offset = line.offset.map_or(-1, i64::from);
+                self.write_attr(&line_dir, "key", &line.key)?;
+                self.write_attr(&line_dir, "offset", &offset.to_string())?;
+                self.write_attr(&line_dir, "drive", line.drive.as_str())?;
+                self.write_attr(&line_dir, "pull", line.bias.as_str())?;
+                self.write_attr(&line_dir, "active_low", bool_attr(line.active_low))?;
+                self.write_attr(&line_dir, "transitory", bool_attr(line.transitory))?;
+            }
+        }
+        self.write_attr(&self.dir, "live", "1")?;
+        self.dev_name = self.read_attr(&self.dir.join("dev_name"))?;
+        Ok(())
+    }
+
+    /// The name of the consumer in configfs.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the device, e.g. `gpio-virtuser.0`.
+    pub fn dev_name(&self) -> &str {
+        &self.dev_name
+    }
+
+    /// The path to the consumer in configfs.
+    ///
+    /// e.g. `/sys/kernel/config/gpio-virtuser/my_consumer`
+    pub fn configfs_path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path to a requested line in debugfs.
+    ///
+    /// e.g. `/sys/kernel/debug/gpio-virtuser/gpio-virtuser.0/gpiod:reset:0`
+    pub fn debugfs_path(&self, con_id: &str, idx: usize) -> PathBuf {
+        Path::new(DEBUGFS_DIR)
+            .join(&self.dev_name)
+            .join(format!("gpiod:{}:{}", con_id, idx))
+    }
+
+    /// The lines requested by the consumer, by connection ID.
+    pub fn lookups(&self) -> &[(String, Vec<Line>)] {
+        &self.lookups
+    }
+
+    /// Get the direction of a requested line.
+    pub fn get_direction(&self, con_id: &str, idx: usize) -> Result<Direction> {
+        let dir = self.read_line_attr(con_id, idx, "direction")?;
+        match dir.as_str() {
+            "input" => Ok(Direction::Input),
+            "output" => Ok(Direction::Output),
+            _ => Err(Error::UnexpectedValue(dir)),
+        }
+    }
+
+    /// Set the direction of a requested line.
+    pub fn set_direction(&self, con_id: &str, idx: usize, direction: Direction) -> Result<()> {
+        self.write_line_attr(con_id, idx, "direction", direction.as_str())
+    }
+
+    /// Get the logical value of a requested line.
+    pub fn get_value(&self, con_id: &str, idx: usize) -> Result<Level> {
+        let val = self.read_line_attr(con_id, idx, "value")?;
+        match val.as_str() {
+            "0" => Ok(Level::Low),
+            "1" => Ok(Level::High),
+            _ => Err(Error::UnexpectedValue(val)),
+        }
+    }
+
+    /// Set the logical value of a requested output line.
+    pub fn set_value(&self, con_id: &str, idx: usize, value: Level) -> Result<()> {
+        let value = match value {
+            Level::Low => "0",
+            Level::High => "1",
+        };
+        self.write_line_attr(con_id, idx, "value", value)
+    }
+
+    /// The consumer label of a requested line.
+    pub fn consumer(&self, con_id: &str, idx: usize) -> Result<String> {
+        self.read_line_attr(con_id, idx, "consumer")
+    }
+
+    /// The number of interrupts received on a requested line.
+    pub fn interrupts(&self, con_id: &str, idx: usize) -> Result<u64> {
+        let count = self.read_line_attr(con_id, idx, "interrupts")?;
+        count.parse().map_err(|_| Error::UnexpectedValue(count))
+    }
+
+    fn check_line(&self, con_id: &str, idx: usize) -> Result<()> {
+        match self.lookups.iter().find(|(c, _)| c == con_id) {
+            Some((_, lines)) if idx < lines.len() => Ok(()),
+            Some((_, lines)) => Err(Error::InvalidConfig(format!(
+                "index {} is out of range for lookup {:?} with {} lines",
+                idx,
+                con_id,
+                lines.len()
+            ))),
+            None => Err(Error::InvalidConfig(format!(
+                "no lookup with connection ID {:?}",
+                con_id
+            ))),
+        }
+    }
+
+    fn read_line_attr(&self, con_id: &str, idx: usize, attr: &str) -> Result<String> {
+        self.check_line(con_id, idx)?;
+        self.read_attr(&self.debugfs_path(con_id, idx).join(attr))
+    }
+
+    fn write_line_attr(&self, con_id: &str, idx: usize, attr: &str, value: &str) -> Result<()> {
+        self.check_line(con_id, idx)?;
+        self.write_attr(&self.debugfs_path(con_id, idx), attr, value)
+    }
+
+    // Helper to write to configuration and debugfs files.
+    fn write_attr(&self, p: &Path, file: &str, data: &str) -> Result<()> {
+        self.fs
+            .write(&p.join(file), data.as_bytes())
+            .map_err(Error::IoError)
+    }
+
+    // Helper to read from attribute files.
+    fn read_attr(&self, path: &Path) -> Result<String> {
+        self.fs
+            .read_to_string(path)
+            .map(|s| s.trim().to_string())
+            .map_err(Error::IoError)
+    }
+
+    fn cleanup_configfs(&self) {
+        if !self.fs.exists(&self.dir) {
+            return;
+        }
+        let _ = self.write_attr(&self.dir, "live", "0");
+        for (con_id, lines) in &self.lookups {
+            let lookup_dir = self.dir.join(con_id);
+            if !self.fs.exists(&lookup_dir) {
+                continue;
+            }
+            for idx in 0..lines.len() {
+                let _ = self.fs.remove_dir(&lookup_dir.join(format!("line{}", idx)));
+            }
+            let _ = self.fs.remove_dir(&lookup_dir);
+        }
+        let _ = self.fs.remove_dir(&self.dir);
+    }
+}
+
+impl Drop for Consumer {
+    fn drop(&mut self) {
+        self.cleanup_configfs();
+    }
+}
+
+fn bool_attr(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FsDir;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::io;
+    use std::sync::Mutex;
+
+    const CONFIGFS_DIR: &str = "/sys/kernel/config/gpio-virtuser";
+
+    // A filesystem that emulates the gpio-virtuser configfs and debugfs
+    // interfaces, and records the operations performed on it.
+    #[derive(Debug, Default)]
+    struct FakeFs(Mutex<FakeState>);
+
+    #[derive(Debug, Default)]
+    struct FakeState {
+        dirs: BTreeSet<PathBuf>,
+        files: BTreeMap<PathBuf, String>,
+        ops: Vec<String>,
+    }
+
+    impl FakeFs {
+        fn new() -> Arc<FakeFs> {
+            let fs = FakeFs::default();
+            fs.0.lock().unwrap().dirs.insert(CONFIGFS_DIR.into());
+            Arc::new(fs)
+        }
+
+        fn ops(&self) -> Vec<String> {
+            self.0.lock().unwrap().ops.clone()
+        }
+    }
+
+    impl FakeState {
+        // Emulate the kernel creating the device when the consumer goes live.
+        fn go_live(&mut self, dir: &Path) {
+            self.files
+                .insert(dir.join("dev_name"), "gpio-virtuser.0\n".into());
+            let debugfs = Path::new(DEBUGFS_DIR).join("gpio-virtuser.0");
+            let lines: Vec<PathBuf> = self
+                .dirs
+                .iter()
+                .filter(|d| d.parent().and_then(Path::parent) == Some(dir))
+                .cloned()
+                .collect();
+            for line in lines {
+                let con_id = line.parent().unwrap().file_name().unwrap();
+                let idx = line.file_name().unwrap().to_str().unwrap();
+                let line_dir = debugfs.join(format!(
+                    "gpiod:{}:{}",
+                    con_id.to_str().unwrap(),
+                    idx.trim_start_matches("line")
+                ));
+                self.files
+                    .insert(line_dir.join("direction"), "input\n".into());
+                self.files.insert(line_dir.join("value"), "0\n".into());
+                self.files
+                    .insert(line_dir.join("consumer"), "gpio-virtuser.0\n".into());
+                self.files.insert(line_dir.join("interrupts"), "0\n".into());
+            }
+        }
+    }
+
+    impl Fs for FakeFs {
+        fn create_dir(&self, path: &Path) -> io::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.ops.push(format!("mkdir {}", path.display()));
+            if !state.dirs.insert(path.into()) {
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+            Ok(())
+        }
+
+        fn remove_dir(&self, path: &Path) -> io::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.ops.push(format!("rmdir {}", path.display()));
+            state.files.retain(|f, _| f.parent() != Some(path));
+            if !state.dirs.remove(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            Ok(())
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            let state = self.0.lock().unwrap();
+            state.dirs.contains(path) || state.files.contains_key(path)
+        }
+
+        fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            let data = String::from_utf8_lossy(data).to_string();
+            state.ops.push(format!("write {} {}", path.display(), data));
+            if path.starts_with(DEBUGFS_DIR) {
+                if !state.files.contains_key(path) {
+                    return Err(io::ErrorKind::NotFound.into());
+                }
+                let dir = path.parent().unwrap().join("direction");
+                if path.ends_with("value") && state.files[&dir] != "output" {
+                    return Err(io::ErrorKind::PermissionDenied.into());
+                }
+            }
+            if path.file_name() == Some("live".as_ref()) && data == "1" {
+                state.go_live(path.parent().unwrap());
+            }
+            state.files.insert(path.into(), data);
+            Ok(())
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            let state = self.0.lock().unwrap();
+            match state.files.get(path) {
+                Some(data) => Ok(data.clone()),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn list_dir(&self, _path: &Path) -> io::Result<Vec<String>> {
+            unimplemented!()
+        }
+
+        fn open_dir(&self, _path: &Path) -> io::Result<Box<dyn FsDir>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn live() {
+        let fs = FakeFs::new();
+        let consumer = builder()
+            .with_fs(fs.clone())
+            .with_name("vu")
+            .with_lookup(
+                "reset",
+                &[Line::on_chip("left", 3)
+                    .with_drive(Drive::OpenDrain)
+                    .as_active_low()],
+            )
+            .with_lookup(
+                "leds",
+                &[
+                    Line::named("led0").with_bias(Bias::PullUp),
+                    Line::on_chip("left", 5).as_transitory(),
+                ],
+            )
+            .live()
+            .unwrap();
+        assert_eq!(consumer.name(), "vu");
+        assert_eq!(consumer.dev_name(), "gpio-virtuser.0");
+        assert_eq!(
+            consumer.configfs_path(),
+            Path::new("/sys/kernel/config/gpio-virtuser/vu")
+        );
+        assert_eq!(
+            consumer.debugfs_path("leds", 1),
+            Path::new("/sys/kernel/debug/gpio-virtuser/gpio-virtuser.0/gpiod:leds:1")
+        );
+        let dir = "/sys/kernel/config/gpio-virtuser/vu";
+        let line = |con_id: &str, idx: usize, attrs: [&str; 6]| {
+            let line_dir = format!("{}/{}/line{}", dir, con_id, idx);
+            let mut ops = vec![format!("mkdir {}", line_dir)];
+            for (attr, value) in ["key", "offset", "drive", "pull", "active_low", "transitory"]
+                .iter()
+                .zip(attrs)
+            {
+                ops.push(format!("write {}/{} {}", line_dir, attr, value));
+            }
+            ops
+        };
+        let mut expected = vec![format!("mkdir {}", dir), format!("mkdir {}/reset", dir)];
+        expected.extend(line(
+            "reset",
+            0,
+            ["left", "3", "open-drain", "as-is", "1", "0"],
+        ));
+        expected.push(format!("mkdir {}/leds", dir));
+        expected.extend(line(
+            "leds",
+            0,
+            ["led0", "-1", "push-pull", "pull-up", "0", "0"],
+        ));
+        expected.extend(line(
+            "leds",
+            1,
+            ["left", "5", "push-pull", "as-is", "0", "1"],
+        ));
+        expected.push(format!("write {}/live 1", dir));
+        assert_eq!(fs.ops(), expected);
+
+        assert_eq!(consumer.get_direction("leds", 1).unwrap(), Direction::Input);
+        assert!(consumer.set_value("leds", 1, Level::High).is_err());
+        consumer
+            .set_direction("leds", 1, Direction::Output)
+            .unwrap();
+        assert_eq!(
+            consumer.get_direction("leds", 1).unwrap(),
+            Direction::Output
+        );
+        consumer.set_value("leds", 1, Level::High).unwrap();
+        assert_eq!(consumer.get_value("leds", 1).unwrap(), Level::High);
+        assert_eq!(consumer.get_value("leds", 0).unwrap(), Level::Low);
+        assert_eq!(consumer.consumer("reset", 0).unwrap(), "gpio-virtuser.0");
+        assert_eq!(consumer.interrupts("reset", 0).unwrap(), 0);
+        assert!(consumer.get_value("leds", 2).is_err());
+        assert!(consumer.get_value("missing", 0).is_err());
+
+        let ops = fs.ops().len();
+        drop(consumer);
+        let ops = fs.ops().split_off(ops);
+        assert_eq!(
+            ops,
+            vec![
+                format!("write {}/live 0", dir),
+                format!("rmdir {}/reset/line0", dir),
+                format!("rmdir {}/reset", dir),
+                format!("rmdir {}/leds/line0", dir),
+                format!("rmdir {}/leds/line1", dir),
+                format!("rmdir {}/leds", dir),
+                format!("rmdir {}", dir),
+            ]
+        );
+        assert!(!fs.exists(Path::new(dir)));
+    }
+
+    #[test]
+    fn invalid() {
+        let fs = FakeFs::new();
+        let mut builder = builder();
+        builder.with_fs(fs.clone());
+        assert!(builder.live().is_err());
+        builder.with_lookup("reset", &[]);
+        assert!(builder.live().is_err());
+        let mut builder = super::builder();
+        builder
+            .with_fs(fs.clone())
+            .with_lookup("re:set", &[Line::on_chip("left", 3)]);
+        assert!(builder.live().is_err());
+        assert!(fs.ops().is_empty());
+
+        let mut builder = super::builder();
+        builder
+            .with_fs(fs.clone())
+            .with_name("vu")
+            .with_lookup("reset", &[Line::on_chip("left", 3)]);
+        let _consumer = builder.live().unwrap();
+        assert!(matches!(builder.live(), Err(Error::SimulatorExists(_))));
+    }
+}
@@ -0,0 +1,466 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A WebSocket endpoint for control of a simulator, e.g. from browser based
+//! dashboards and JavaScript test frameworks.
+//!
+//! Each text message from the client is a request, using the same JSON
+//! requests as the [`remote`] protocol, and is answered with a
+//! `{"result": ...}` or `{"error": "..."}` message.
+//!
+//! The server also sends an event message to all clients whenever the level
+//! of any line changes, e.g. as the consumer drives an output:
+//!
+//! ```text
+//! {"event": "level", "chip": "left", "offset": 3, "level": "high"}
+//! ```
+//!
+//! ```no_run
+//! # fn example() -> gpiosim::Result<()> {
+//! use gpiosim::websocket::Server;
+//! use gpiosim::Bank;
+//!
+//! let sim = gpiosim::builder().with_bank(&Bank::new(8, "left")).live()?;
+//! let server = Server::bind(&sim, "127.0.0.1:9798")?;
+//! // ... connect from the browser to ws://127.0.0.1:9798/ ...
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`remote`]: crate::remote
+
+use crate::monitor::Monitor;
+use crate::remote::handle;
+use crate::{Chip, Level, Result, Sim};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The GUID appended to the client key to form the accept key.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The longest message accepted from a client.
+const MAX_MESSAGE: usize = 1 << 16;
+
+/// How long a write to a client may block before the client is dropped, and
+/// how long a client has to complete the handshake.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xa;
+
+type Client = Arc<Mutex<TcpStream>>;
+
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+/// A WebSocket server exposing the chips of a simulator.
+///
+/// Each connection is served by its own thread.
+/// The server, and any open connections, are stopped when dropped.
+#[derive(Debug)]
+pub struct Server {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    clients: Clients,
+
+    /// Monitors of the levels of the chips, which broadcast the events.
+    monitors: Vec<Monitor>,
+
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /// Serve the chips of the simulator via WebSocket on the given address.
+    ///
+    /// The endpoint is available at any path.
+    pub fn bind<A: ToSocketAddrs>(sim: &Sim, addr: A) -> Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let clients = Clients::default();
+        let monitors = sim
+            .chips()
+            .iter()
+            .map(|c| {
+                let clients = clients.clone();
+                let offsets: Vec<_> = (0..c.cfg.num_lines).collect();
+                Monitor::spawn(c, &offsets, move |chip, prev, curr| {
+                    for (offset, level) in curr.iter().enumerate() {
+                        if prev[offset] != *level {
+                            broadcast(&clients, &event(chip, offset as u32, *level));
+                        }
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let chips: Arc<Vec<Chip>> = Arc::new(sim.chips().iter().map(Chip::share).collect());
+        let thread = {
+            let stop = stop.clone();
+            let clients = clients.clone();
+            thread::spawn(move || accept(listener, &stop, chips, clients))
+        };
+        Ok(Server {
+            addr,
+            stop,
+            clients,
+            monitors,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The number of connected clients.
+    pub fn clients(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Wait until the current levels of the lines have been sampled, and any
+    /// changes sent to the clients.
+    pub fn sync(&self) {
+        for m in &self.monitors {
+            m.sync();
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.monitors.clear();
+        self.stop.store(true, Ordering::Relaxed);
+        // unblock the accept
+        let _ = TcpStream::connect(self.addr);
+        // unblock the sessions
+        for c in self.clients.lock().unwrap().iter() {
+            let _ = c.lock().unwrap().shutdown(Shutdown::Both);
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+fn event(chip: &Chip, offset: u32, level: Level) -> String {
+    serde_json::json!({
+        "event": "level",
+        "chip": chip.cfg.label,
+        "offset": offset,
+        "level": level,
+    })
+    .to_string()
+}
+
+// Send the message to all clients, disconnecting any that cannot keep up.
+fn broadcast(clients: &Clients, msg: &str) {
+    for c in clients.lock().unwrap().iter() {
+        let mut stream = c.lock().unwrap();
+        if write_frame(&mut *stream, OP_TEXT, msg.as_bytes()).is_err() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn accept(listener: TcpListener, stop: &Arc<AtomicBool>, chips: Arc<Vec<Chip>>, clients: Clients) {
+    let mut sessions: Vec<thread::JoinHandle<io::Result<()>>> = Vec::new();
+    for stream in listener.incoming() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            let chips = chips.clone();
+            let clients = clients.clone();
+            let stop = stop.clone();
+            sessions.retain(|s| !s.is_finished());
+            sessions.push(thread::spawn(move || {
+                session(stream, &stop, &chips, &clients)
+            }));
+        }
+    }
+    for s in sessions {
+        let _ = s.join();
+    }
+}
+
+// Serve the connection until it is closed by either end.
+fn session(
+    stream: TcpStream,
+    stop: &AtomicBool,
+    chips: &[Chip],
+    clients: &Clients,
+) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    handshake(&mut reader, &mut stream)?;
+    stream.set_read_timeout(None)?;
+    let client = Arc::new(Mutex::new(stream));
+    clients.lock().unwrap().push(client.clone());
+    // the server may have been stopped before the client was added
+    let res = if stop.load(Ordering::Relaxed) {
+        Ok(())
+    } else {
+        serve(&mut reader, &client, chips)
+    };
+    clients.lock().unwrap().retain(|c| !Arc::ptr_eq(c, &client));
+    res
+}
+
+fn serve<R: Read>(reader: &mut R, client: &Client, chips: &[Chip]) -> io::Result<()> {
+    let mut msg = Vec::new();
+    loop {
+        let (fin, opcode, payload) = read_frame(reader)?;
+        match opcode {
+            OP_TEXT | OP_CONTINUATION => {
+                if msg.len() + payload.len() > MAX_MESSAGE {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "message too long",
+                    ));
+                }
+                msg.extend(payload);
+                if fin {
+                    let resp = handle(chips, &String::from_utf8_lossy(&msg));
+                    write_frame(&mut *client.lock().unwrap(), OP_TEXT, resp.as_bytes())?;
+                    msg.clear();
+                }
+            }
+            OP_PING => write_frame(&mut *client.lock().unwrap(), OP_PONG, &payload)?,
+            OP_CLOSE => {
+                let _ = write_frame(&mut *client.lock().unwrap(), OP_CLOSE, &payload);
+                return Ok(());
+            }
+            // pongs and binary messages are ignored
+            _ => {}
+        }
+    }
+}
+
+// Read the HTTP upgrade request and accept it.
+fn handshake<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<()> {
+    let mut key = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+    let key = match key {
+        Some(key) => key,
+        None => {
+            write!(
+                writer,
+                "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a websocket request",
+            ));
+        }
+    };
+    write!(
+        writer,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+        Upgrade: websocket\r\n\
+        Connection: Upgrade\r\n\
+        Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    )
+}
+
+fn accept_key(key: &str) -> String {
+    use base64::Engine;
+    use sha1::{Digest, Sha1};
+
+    let digest = Sha1::new().chain_update(key).chain_update(GUID).finalize();
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+// Read a frame, returning the fin flag, opcode and unmasked payload.
+//
+// Frames from clients must be masked, so an unmasked frame is an error and
+// the connection is closed.
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut hdr = [0; 2];
+    reader.read_exact(&mut hdr)?;
+    let len = match hdr[1] & 0x7f {
+        126 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+    if len > MAX_MESSAGE as u64 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too long"));
+    }
+    if hdr[1] & 0x80 == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unmasked frame"));
+    }
+    let mut mask = [0; 4];
+    reader.read_exact(&mut mask)?;
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    for (i, b) in payload.iter_mut().enumerate() {
+        *b ^= mask[i % 4];
+    }
+    Ok((hdr[0] & 0x80 != 0, hdr[0] & 0x0f, payload))
+}
+
+// Write an unmasked, unfragmented, frame.
+fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    writer.write_all(&frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+
+    #[test]
+    fn accept_key_rfc6455() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    // Send a masked text frame, as a client must.
+    fn send(stream: &mut TcpStream, msg: &str) {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | OP_TEXT, 0x80 | msg.len() as u8];
+        frame.extend(mask);
+        frame.extend(msg.bytes().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        stream.write_all(&frame).unwrap();
+    }
+
+    // Receive an unmasked text frame, as a server must send.
+    fn recv<R: Read>(reader: &mut R) -> String {
+        let mut hdr = [0; 2];
+        reader.read_exact(&mut hdr).unwrap();
+        assert_eq!(hdr[0], 0x80 | OP_TEXT);
+        assert!(hdr[1] < 126);
+        let mut payload = vec![0; hdr[1] as usize];
+        reader.read_exact(&mut payload).unwrap();
+        String::from_utf8(payload).unwrap()
+    }
+
+    // Connect to the server and complete the handshake.
+    fn upgrade(server: &Server) -> (TcpStream, BufReader<TcpStream>) {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        write!(
+            stream,
+            "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+            Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+            Sec-WebSocket-Version: 13\r\n\r\n"
+        )
+        .unwrap();
+        let mut resp = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            resp.push_str(&line);
+        }
+        assert!(resp.starts_with("HTTP/1.1 101 "));
+        assert!(resp.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        (stream, reader)
+    }
+
+    #[test]
+    fn serve() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let server = Server::bind(&sim, "127.0.0.1:0").unwrap();
+        let (mut stream, mut reader) = upgrade(&server);
+
+        send(
+            &mut stream,
+            r#"{"op": "set_pull", "chip": "left", "offset": 2, "pull": "high"}"#,
+        );
+        // the response and event may arrive in either order
+        let mut msgs = [recv(&mut reader), recv(&mut reader)];
+        msgs.sort();
+        assert_eq!(
+            msgs,
+            [
+                r#"{"chip":"left","event":"level","level":"high","offset":2}"#,
+                r#"{"result":null}"#
+            ]
+        );
+        assert_eq!(server.clients(), 1);
+
+        let c = &sim.chips()[0];
+        mem.drive(c, 1, Some(Level::High)).unwrap();
+        server.sync();
+        assert_eq!(
+            recv(&mut reader),
+            r#"{"chip":"left","event":"level","level":"high","offset":1}"#
+        );
+        drop(server);
+        let mut buf = [0; 1];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn unmasked() {
+        let sim = builder()
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(4, "left"))
+            .live()
+            .unwrap();
+        let server = Server::bind(&sim, "127.0.0.1:0").unwrap();
+        let (mut stream, mut reader) = upgrade(&server);
+        let msg = br#"{"op": "get_level", "chip": "left", "offset": 2}"#;
+        let mut frame = vec![0x80 | OP_TEXT, msg.len() as u8];
+        frame.extend(msg);
+        stream.write_all(&frame).unwrap();
+        let mut buf = [0; 1];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+}
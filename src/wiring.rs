@@ -0,0 +1,767 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::bus::check_offset;
+use crate::dot::node_id;
+use crate::monitor::Monitor;
+use crate::{Chip, Error, Level, Offset, Result, Sim};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A harness of virtual wires connecting lines, possibly on different chips.
+///
+/// Each wire mirrors the level of its source line, typically an output driven
+/// by the consumer, to the pull on its sink line, typically an input read by
+/// the consumer, after an optional propagation delay.
+/// This allows loopback tests of a consumer without physical wiring.
+///
+/// Glue logic between lines may be modelled by virtual components, which
+/// apply a [`Logic`] function to the levels of several source lines to
+/// determine the pull on the sink line.
+///
+/// Shared open-drain buses may be modelled by a [`WiredAnd`].
+///
+/// The wires are driven by a background thread that samples the source lines,
+/// so changes that are reverted within a sample period may be missed.
+/// The thread is started when the first wire is connected, and exits when the
+/// wiring is dropped.
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Logic, Wiring};
+/// use std::time::Duration;
+///
+/// let sim = gpiosim::builder()
+///     .with_bank(&Bank::new(8, "controller"))
+///     .with_bank(&Bank::new(8, "peripheral"))
+///     .live()?;
+/// let chips = sim.chips();
+/// let mut wiring = Wiring::new();
+/// wiring
+///     .connect(&chips[0], 3, &chips[1], 5)?
+///     .connect_delayed(&chips[1], 6, &chips[0], 4, Duration::from_millis(1))?
+///     .invert(&chips[0], 0, &chips[1], 0)?
+///     .component(
+///         Logic::And,
+///         &[(&chips[0], 1), (&chips[0], 2)],
+///         &chips[1],
+///         1,
+///         Duration::ZERO,
+///     )?;
+///
+/// // ... consumer drives chip0 line 3 and sees chip1 line 5 follow ...
+///
+/// println!("{}", wiring.to_dot(&sim));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct Wiring {
+    wires: Arc<Mutex<Vec<Wire>>>,
+
+    /// The thread driving the wires, once started.
+    monitor: Option<Monitor>,
+
+    /// The number of buses added to the wiring.
+    buses: usize,
+}
+
+/// The function applied by a virtual component to the levels of its source
+/// lines to determine the level of its sink line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Logic {
+    /// The sink follows the single source.
+    Buffer,
+
+    /// The sink is the inverse of the single source.
+    Not,
+
+    /// The sink is high if all the sources are high.
+    And,
+
+    /// The sink is high if any of the sources are high.
+    Or,
+
+    /// The sink follows the source selected by the select lines.
+    ///
+    /// The first N sources are the select lines, least significant bit
+    /// first, and the remaining 2^N sources are the data lines.
+    /// e.g. a 2:1 multiplexer has sources `[select, data0, data1]`.
+    Mux,
+}
+
+impl Logic {
+    /// Determine the level of the sink from the levels of the sources.
+    ///
+    /// The number of levels must be valid for the function.
+    pub fn eval(&self, levels: &[Level]) -> Level {
+        let high = |l: &Level| *l == Level::High;
+        let level = match self {
+            Logic::Buffer => high(&levels[0]),
+            Logic::Not => !high(&levels[0]),
+            Logic::And => levels.iter().all(high),
+            Logic::Or => levels.iter().any(high),
+            Logic::Mux => {
+                let selects = mux_selects(levels.len()).unwrap_or(0);
+                let index = (0..selects)
+                    .filter(|&i| high(&levels[i]))
+                    .fold(0, |acc, i| acc | 1 << i);
+                high(&levels[selects + index])
+            }
+        };
+        if level {
+            Level::High
+        } else {
+            Level::Low
+        }
+    }
+
+    /// Check the number of sources is valid for the function.
+    fn check_sources(&self, sources: usize) -> Result<()> {
+        let valid = match self {
+            Logic::Buffer | Logic::Not => sources == 1,
+            Logic::And | Logic::Or => sources > 0,
+            Logic::Mux => mux_selects(sources).is_some(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(format!(
+                "{} sources is not valid for {}",
+                sources,
+                self.as_str()
+            )))
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Logic::Buffer => "buffer",
+            Logic::Not => "not",
+            Logic::And => "and",
+            Logic::Or => "or",
+            Logic::Mux => "mux",
+        }
+    }
+}
+
+// The number of select lines of a multiplexer with the number of sources.
+fn mux_selects(sources: usize) -> Option<usize> {
+    (1..6).find(|&n| n + (1 << n) == sources)
+}
+
+#[derive(Debug)]
+struct Wire {
+    logic: Logic,
+
+    /// The source lines.
+    from: Vec<(Chip, Offset)>,
+
+    /// The chip containing the sink line.
+    to: Chip,
+
+    to_offset: Offset,
+
+    /// The delay between a change to the source and to the sink.
+    delay: Duration,
+
+    /// The level of the sink determined from the most recently sampled
+    /// sources.
+    level: Level,
+
+    /// Changes to the sink waiting for the delay to pass.
+    pending: VecDeque<(Instant, Level)>,
+
+    /// The virtual drivers of the bus, if the wire is a sink of a bus.
+    bus: Option<Arc<Drivers>>,
+}
+
+impl Wire {
+    fn resolve(&self) -> Result<Level> {
+        let level = self.logic.eval(&sample(&self.from)?);
+        match &self.bus {
+            Some(bus) if bus.is_pulling_low() => Ok(Level::Low),
+            _ => Ok(level),
+        }
+    }
+
+    fn step(&mut self, now: Instant) -> Result<()> {
+        let level = self.resolve()?;
+        if level != self.level {
+            self.level = level;
+            self.pending.push_back((now + self.delay, level));
+        }
+        while let Some(&(at, level)) = self.pending.front() {
+            if at > now {
+                break;
+            }
+            self.to.set_pull(self.to_offset, level)?;
+            self.pending.pop_front();
+        }
+        Ok(())
+    }
+}
+
+// Check the lines are valid and share their chips.
+fn share_lines(lines: &[(&Chip, Offset)]) -> Result<Vec<(Chip, Offset)>> {
+    lines
+        .iter()
+        .map(|&(chip, offset)| {
+            check_offset(chip, offset)?;
+            Ok((chip.share(), offset))
+        })
+        .collect()
+}
+
+// Read the levels of the source lines without updating the chip stats.
+fn sample(from: &[(Chip, Offset)]) -> Result<Vec<Level>> {
+    from.iter()
+        .map(|(chip, offset)| chip.dev.get_level(chip.bank, *offset))
+        .collect()
+}
+
+impl Wiring {
+    /// Create an empty wiring harness.
+    pub fn new() -> Wiring {
+        Wiring::default()
+    }
+
+    /// Connect a source line to a sink line, with no propagation delay.
+    pub fn connect(
+        &mut self,
+        from: &Chip,
+        from_offset: Offset,
+        to: &Chip,
+        to_offset: Offset,
+    ) -> Result<&mut Self> {
+        self.connect_delayed(from, from_offset, to, to_offset, Duration::ZERO)
+    }
+
+    /// Connect a source line to a sink line, with changes to the source
+    /// reaching the sink after the delay.
+    ///
+    /// The sink is pulled to the current level of the source immediately.
+    ///
+    /// A sink may only be driven by one wire or component, so connecting a
+    /// sink that is already connected is an error.
+    pub fn connect_delayed(
+        &mut self,
+        from: &Chip,
+        from_offset: Offset,
+        to: &Chip,
+        to_offset: Offset,
+        delay: Duration,
+    ) -> Result<&mut Self> {
+        self.component(Logic::Buffer, &[(from, from_offset)], to, to_offset, delay)
+    }
+
+    /// Connect a source line to a sink line via an inverter, with no
+    /// propagation delay.
+    pub fn invert(
+        &mut self,
+        from: &Chip,
+        from_offset: Offset,
+        to: &Chip,
+        to_offset: Offset,
+    ) -> Result<&mut Self> {
+        self.component(
+            Logic::Not,
+            &[(from, from_offset)],
+            to,
+            to_offset,
+            Duration::ZERO,
+        )
+    }
+
+    /// Add a virtual component that pulls the sink line to the result of the
+    /// logic function applied to the levels of the source lines, with changes
+    /// reaching the sink after the delay.
+    ///
+    /// The sink is pulled to the current result immediately.
+    ///
+    /// A sink may only be driven by one wire or component, so connecting a
+    /// sink that is already connected is an error.
+    pub fn component(
+        &mut self,
+        logic: Logic,
+        from: &[(&Chip, Offset)],
+        to: &Chip,
+        to_offset: Offset,
+        delay: Duration,
+    ) -> Result<&mut Self> {
+        logic.check_sources(from.len())?;
+        check_offset(to, to_offset)?;
+        let wire = Wire {
+            logic,
+            from: share_lines(from)?,
+            to: to.share(),
+            to_offset,
+            delay,
+            level: Level::Low,
+            pending: VecDeque::new(),
+            bus: None,
+        };
+        self.add(vec![wire])?;
+        Ok(self)
+    }
+
+    /// Add an open-drain bus, with its level resolved by wired-AND.
+    ///
+    /// The bus is pulled low if any of the driver lines, typically open-drain
+    /// outputs driven by the consumer, are low, or any of the virtual drivers
+    /// created by [`WiredAnd::driver`] are pulling it low.
+    /// Otherwise the bus is pulled high, as if by a pull-up resistor.
+    ///
+    /// The sink lines, typically inputs read by the consumer, are pulled to
+    /// the level of the bus, immediately and whenever it changes.
+    ///
+    /// As the level of a driver line is indistinguishable from the level of
+    /// the bus once the consumer releases it, the driver lines are pulled
+    /// high, rather than to the level of the bus, so the consumer should read
+    /// the bus from a sink line.
+    pub fn wired_and(
+        &mut self,
+        drivers: &[(&Chip, Offset)],
+        sinks: &[(&Chip, Offset)],
+    ) -> Result<WiredAnd> {
+        if sinks.is_empty() {
+            return Err(Error::InvalidConfig(
+                "bus requires at least one sink".into(),
+            ));
+        }
+        let lines = share_lines(drivers)?;
+        for (chip, offset) in &lines {
+            chip.set_pull(*offset, Level::High)?;
+        }
+        let bus = Arc::new(Drivers {
+            id: self.buses,
+            pulling: Mutex::default(),
+        });
+        let wires = share_lines(sinks)?
+            .into_iter()
+            .map(|(to, to_offset)| Wire {
+                logic: Logic::And,
+                from: lines.iter().map(|(c, o)| (c.share(), *o)).collect(),
+                to,
+                to_offset,
+                delay: Duration::ZERO,
+                level: Level::Low,
+                pending: VecDeque::new(),
+                bus: Some(bus.clone()),
+            })
+            .collect();
+        self.add(wires)?;
+        self.buses += 1;
+        Ok(WiredAnd { bus, lines })
+    }
+
+    // Add wires, pulling their sinks to their current level and starting the
+    // thread driving them, if necessary.
+    fn add(&mut self, mut added: Vec<Wire>) -> Result<()> {
+        let mut wires = self.wires.lock().unwrap();
+        for (i, w) in added.iter().enumerate() {
+            if wires
+                .iter()
+                .chain(&added[..i])
+                .any(|x| x.to == w.to && x.to_offset == w.to_offset)
+            {
+                return Err(Error::InvalidConfig(format!(
+                    "line {} of {} is already connected",
+                    w.to_offset, w.to.chip_name
+                )));
+            }
+        }
+        for w in &mut added {
+            w.level = w.resolve()?;
+            w.to.set_pull(w.to_offset, w.level)?;
+        }
+        let chip = added[0].to.share();
+        wires.extend(added);
+        drop(wires);
+        if self.monitor.is_none() {
+            let wires = self.wires.clone();
+            self.monitor = Some(Monitor::poll(&chip, move |_| {
+                let now = Instant::now();
+                wires
+                    .lock()
+                    .unwrap()
+                    .iter_mut()
+                    .try_for_each(|w| w.step(now))
+            })?);
+        }
+        Ok(())
+    }
+
+    /// The number of wires in the harness.
+    pub fn len(&self) -> usize {
+        self.wires.lock().unwrap().len()
+    }
+
+    /// Returns true if the harness has no wires.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait until the current levels of the source lines have been sampled,
+    /// and any changes with no delay propagated to the sinks.
+    pub fn sync(&self) {
+        if let Some(m) = &self.monitor {
+            m.sync()
+        }
+    }
+
+    /// Render the topology of the simulator as a Graphviz DOT graph, as per
+    /// [`Sim::to_dot`], with an edge for each wire, and a node for each
+    /// component and bus.
+    ///
+    /// Edges to sinks are labelled with the propagation delay, if any, and
+    /// edges from multiplexer sources with the select or data line index.
+    pub fn to_dot(&self, sim: &Sim) -> String {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut buses = HashSet::new();
+        for (i, w) in self.wires.lock().unwrap().iter().enumerate() {
+            let sink = node_id(&w.to.chip_name, w.to_offset);
+            let delay = if w.delay.is_zero() {
+                String::new()
+            } else {
+                format!("{:?}", w.delay)
+            };
+            if let Some(bus) = &w.bus {
+                let id = format!("bus:{}", bus.id);
+                if buses.insert(bus.id) {
+                    for (chip, offset) in &w.from {
+                        edges.push((node_id(&chip.chip_name, *offset), id.clone(), String::new()));
+                    }
+                    nodes.push((id.clone(), "wired-and".into()));
+                }
+                edges.push((id, sink, delay));
+                continue;
+            }
+            if w.logic == Logic::Buffer {
+                let (chip, offset) = &w.from[0];
+                edges.push((node_id(&chip.chip_name, *offset), sink, delay));
+                continue;
+            }
+            let id = format!("wiring:{}", i);
+            let selects = mux_selects(w.from.len()).unwrap_or(0);
+            for (n, (chip, offset)) in w.from.iter().enumerate() {
+                let label = match w.logic {
+                    Logic::Mux if n < selects => format!("s{}", n),
+                    Logic::Mux => format!("d{}", n - selects),
+                    _ => String::new(),
+                };
+                edges.push((node_id(&chip.chip_name, *offset), id.clone(), label));
+            }
+            edges.push((id.clone(), sink, delay));
+            nodes.push((id, w.logic.as_str().into()));
+        }
+        sim.to_dot_with(&nodes, &edges)
+    }
+}
+
+/// An open-drain bus added to a [`Wiring`] by [`Wiring::wired_and`].
+///
+/// ```no_run
+/// # fn example() -> gpiosim::Result<()> {
+/// use gpiosim::{Bank, Level, Wiring};
+///
+/// let sim = gpiosim::builder().with_bank(&Bank::new(4, "irq")).live()?;
+/// let c = &sim.chips()[0];
+/// let mut wiring = Wiring::new();
+/// // consumer outputs 0 and 1 share the interrupt line read on 2
+/// let irq = wiring.wired_and(&[(c, 0), (c, 1)], &[(c, 2)])?;
+/// let device = irq.driver();
+/// device.pull_low();
+/// wiring.sync();
+/// assert_eq!(c.get_level(2)?, Level::Low);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WiredAnd {
+    bus: Arc<Drivers>,
+
+    /// The driver lines.
+    lines: Vec<(Chip, Offset)>,
+}
+
+impl WiredAnd {
+    /// Create a virtual driver of the bus.
+    ///
+    /// The driver is initially released.
+    pub fn driver(&self) -> BusDriver {
+        let mut pulling = self.bus.pulling.lock().unwrap();
+        pulling.push(false);
+        BusDriver {
+            bus: self.bus.clone(),
+            index: pulling.len() - 1,
+        }
+    }
+
+    /// The current level of the bus, resolved from the drivers.
+    pub fn level(&self) -> Result<Level> {
+        if self.bus.is_pulling_low() {
+            return Ok(Level::Low);
+        }
+        Ok(Logic::And.eval(&sample(&self.lines)?))
+    }
+}
+
+/// A virtual driver of a [`WiredAnd`] bus.
+///
+/// The driver is released when dropped.
+#[derive(Debug)]
+pub struct BusDriver {
+    bus: Arc<Drivers>,
+
+    /// The index of the driver in the bus drivers.
+    index: usize,
+}
+
+impl BusDriver {
+    /// Pull the bus low.
+    pub fn pull_low(&self) {
+        self.set(true)
+    }
+
+    /// Release the bus.
+    pub fn release(&self) {
+        self.set(false)
+    }
+
+    /// Returns true if the driver is pulling the bus low.
+    pub fn is_pulling_low(&self) -> bool {
+        self.bus.pulling.lock().unwrap()[self.index]
+    }
+
+    fn set(&self, pulling: bool) {
+        self.bus.pulling.lock().unwrap()[self.index] = pulling;
+    }
+}
+
+impl Drop for BusDriver {
+    fn drop(&mut self) {
+        self.release()
+    }
+}
+
+#[derive(Debug)]
+struct Drivers {
+    /// The index of the bus in the wiring.
+    id: usize,
+
+    /// Whether each virtual driver is pulling the bus low.
+    pulling: Mutex<Vec<bool>>,
+}
+
+impl Drivers {
+    fn is_pulling_low(&self) -> bool {
+        self.pulling.lock().unwrap().iter().any(|&p| p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder, Bank, Memory};
+    use std::thread;
+
+    #[test]
+    fn connect() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(4, "left"))
+            .with_bank(&Bank::new(4, "right"))
+            .live()
+            .unwrap();
+        let (c0, c1) = (&sim.chips()[0], &sim.chips()[1]);
+        mem.drive(c0, 1, Some(Level::High)).unwrap();
+        let mut wiring = Wiring::new();
+        assert!(wiring.is_empty());
+        wiring
+            .connect(c0, 1, c1, 2)
+            .unwrap()
+            .connect_delayed(c1, 0, c0, 3, Duration::from_millis(20))
+            .unwrap();
+        assert_eq!(wiring.len(), 2);
+        assert_eq!(c1.get_pull(2).unwrap(), Level::High);
+
+        mem.drive(c0, 1, Some(Level::Low)).unwrap();
+        wiring.sync();
+        assert_eq!(c1.get_pull(2).unwrap(), Level::Low);
+
+        mem.drive(c1, 0, Some(Level::High)).unwrap();
+        wiring.sync();
+        assert_eq!(c0.get_pull(3).unwrap(), Level::Low);
+        thread::sleep(Duration::from_millis(25));
+        wiring.sync();
+        assert_eq!(c0.get_pull(3).unwrap(), Level::High);
+
+        assert!(wiring.connect(c0, 0, c1, 2).is_err());
+        assert!(wiring.connect(c0, 4, c1, 3).is_err());
+        assert!(wiring.connect(c0, 0, c1, 4).is_err());
+    }
+
+    #[test]
+    fn to_dot() {
+        let sim = builder()
+            .with_name("wired")
+            .with_backend(Memory::new())
+            .with_bank(&Bank::new(1, "left"))
+            .with_bank(&Bank::new(1, "right"))
+            .live()
+            .unwrap();
+        let (c0, c1) = (&sim.chips()[0], &sim.chips()[1]);
+        let mut wiring = Wiring::new();
+        wiring
+            .connect(c0, 0, c1, 0)
+            .unwrap()
+            .connect_delayed(c1, 0, c0, 0, Duration::from_micros(5))
+            .unwrap();
+        let dot = wiring.to_dot(&sim);
+        let (n0, n1) = (&c0.chip_name, &c1.chip_name);
+        assert!(dot.contains(&format!("    \"{}:0\" -> \"{}:0\";\n", n0, n1)));
+        assert!(dot.contains(&format!(
+            "    \"{}:0\" -> \"{}:0\" [label=\"5µs\"];\n",
+            n1, n0
+        )));
+        assert!(dot.ends_with("];\n}\n"));
+    }
+
+    #[test]
+    fn wired_and() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_name("bus")
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(5, "bus"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut wiring = Wiring::new();
+        assert!(wiring.wired_and(&[(c, 0)], &[]).is_err());
+        assert!(wiring.wired_and(&[(c, 0)], &[(c, 3), (c, 3)]).is_err());
+        assert!(wiring.is_empty());
+        mem.drive(c, 0, Some(Level::High)).unwrap();
+        let bus = wiring
+            .wired_and(&[(c, 0), (c, 1)], &[(c, 2), (c, 3)])
+            .unwrap();
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+        assert_eq!(bus.level().unwrap(), Level::High);
+        assert_eq!(c.get_pull(2).unwrap(), Level::High);
+        assert_eq!(c.get_pull(3).unwrap(), Level::High);
+
+        mem.drive(c, 1, Some(Level::Low)).unwrap();
+        wiring.sync();
+        assert_eq!(bus.level().unwrap(), Level::Low);
+        assert_eq!(c.get_pull(2).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(3).unwrap(), Level::Low);
+        mem.drive(c, 1, None).unwrap();
+        wiring.sync();
+        assert_eq!(c.get_pull(2).unwrap(), Level::High);
+
+        let d1 = bus.driver();
+        let d2 = bus.driver();
+        d1.pull_low();
+        d2.pull_low();
+        assert!(d1.is_pulling_low());
+        wiring.sync();
+        assert_eq!(c.get_pull(3).unwrap(), Level::Low);
+        d1.release();
+        wiring.sync();
+        assert_eq!(c.get_pull(3).unwrap(), Level::Low);
+        drop(d2);
+        wiring.sync();
+        assert_eq!(bus.level().unwrap(), Level::High);
+        assert_eq!(c.get_pull(3).unwrap(), Level::High);
+
+        wiring.wired_and(&[], &[(c, 4)]).unwrap();
+        let dot = wiring.to_dot(&sim);
+        let n = &c.chip_name;
+        assert!(dot.contains("    \"bus:0\" [label=\"wired-and\", shape=ellipse];\n"));
+        assert!(dot.contains("    \"bus:1\" [label=\"wired-and\", shape=ellipse];\n"));
+        assert_eq!(dot.matches(&format!("\"{}:0\" -> \"bus:0\"", n)).count(), 1);
+        assert!(dot.contains(&format!("    \"bus:0\" -> \"{}:3\";\n", n)));
+    }
+
+    #[test]
+    fn logic_eval() {
+        use Level::*;
+        assert_eq!(Logic::Buffer.eval(&[High]), High);
+        assert_eq!(Logic::Not.eval(&[High]), Low);
+        assert_eq!(Logic::Not.eval(&[Low]), High);
+        assert_eq!(Logic::And.eval(&[High, High, High]), High);
+        assert_eq!(Logic::And.eval(&[High, Low, High]), Low);
+        assert_eq!(Logic::Or.eval(&[Low, Low]), Low);
+        assert_eq!(Logic::Or.eval(&[Low, High]), High);
+        // 2:1
+        assert_eq!(Logic::Mux.eval(&[Low, High, Low]), High);
+        assert_eq!(Logic::Mux.eval(&[High, High, Low]), Low);
+        // 4:1, selecting d2
+        assert_eq!(Logic::Mux.eval(&[Low, High, Low, Low, High, Low]), High);
+        assert_eq!(Logic::Mux.eval(&[Low, High, High, High, Low, High]), Low);
+
+        assert!(Logic::Not.check_sources(1).is_ok());
+        assert!(Logic::Not.check_sources(2).is_err());
+        assert!(Logic::And.check_sources(0).is_err());
+        assert!(Logic::Or.check_sources(5).is_ok());
+        assert!(Logic::Mux.check_sources(3).is_ok());
+        assert!(Logic::Mux.check_sources(6).is_ok());
+        assert!(Logic::Mux.check_sources(4).is_err());
+    }
+
+    #[test]
+    fn components() {
+        let mem = Memory::new();
+        let sim = builder()
+            .with_name("glue")
+            .with_backend(mem.clone())
+            .with_bank(&Bank::new(8, "glue"))
+            .live()
+            .unwrap();
+        let c = &sim.chips()[0];
+        let mut wiring = Wiring::new();
+        wiring
+            .invert(c, 0, c, 4)
+            .unwrap()
+            .component(Logic::Or, &[(c, 0), (c, 1)], c, 5, Duration::ZERO)
+            .unwrap()
+            .component(Logic::Mux, &[(c, 2), (c, 0), (c, 1)], c, 6, Duration::ZERO)
+            .unwrap();
+        assert_eq!(c.get_pull(4).unwrap(), Level::High);
+        assert_eq!(c.get_pull(5).unwrap(), Level::Low);
+        assert!(wiring
+            .component(Logic::And, &[], c, 7, Duration::ZERO)
+            .is_err());
+
+        mem.drive(c, 1, Some(Level::High)).unwrap();
+        wiring.sync();
+        assert_eq!(c.get_pull(4).unwrap(), Level::High);
+        assert_eq!(c.get_pull(5).unwrap(), Level::High);
+        assert_eq!(c.get_pull(6).unwrap(), Level::Low);
+
+        mem.drive(c, 2, Some(Level::High)).unwrap();
+        mem.drive(c, 0, Some(Level::High)).unwrap();
+        wiring.sync();
+        assert_eq!(c.get_pull(4).unwrap(), Level::Low);
+        assert_eq!(c.get_pull(6).unwrap(), Level::High);
+
+        let dot = wiring.to_dot(&sim);
+        let n = &c.chip_name;
+        assert!(dot.contains("    \"wiring:0\" [label=\"not\", shape=ellipse];\n"));
+        assert!(dot.contains(&format!("    \"{}:0\" -> \"wiring:0\";\n", n)));
+        assert!(dot.contains(&format!("    \"wiring:1\" -> \"{}:5\";\n", n)));
+        assert!(dot.contains(&format!(
+            "    \"{}:2\" -> \"wiring:2\" [label=\"s0\"];\n",
+            n
+        )));
+        assert!(dot.contains(&format!(
+            "    \"{}:1\" -> \"wiring:2\" [label=\"d1\"];\n",
+            n
+        )));
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Live tests require the gpio-sim kernel module.
+
+#![cfg(feature = "latency")]
+
+mod latency {
+    use gpiosim::latency::Bench;
+    use gpiosim::{Level, Simpleton};
+
+    #[test]
+    fn run() {
+        let s = Simpleton::new(2);
+        let c = s.chip();
+        c.pullup(1).unwrap();
+        let report = Bench::new(c, 1).with_iterations(50).run().unwrap();
+        assert_eq!(report.missed, 0);
+        assert_eq!(report.event.len(), 50);
+        assert_eq!(report.read.len(), 50);
+        let summary = report.read_summary();
+        assert!(summary.min <= summary.median);
+        assert!(summary.median <= summary.max);
+        assert!(report.event_summary().max <= summary.max);
+        assert_eq!(c.get_pull(1).unwrap(), Level::High);
+
+        assert!(Bench::new(c, 2).run().is_err());
+    }
+}
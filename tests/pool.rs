@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2026 Kent Gibson <warthog618@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Live tests require the gpio-sim kernel module and gpiocdev to provide the
+// interface from the kernel/gpiolib side.
+
+mod pool {
+    use gpiosim::{Bank, Level, SimPool};
+
+    #[test]
+    fn lease() {
+        let pool = SimPool::new(gpiosim::builder().with_bank(&Bank::new(8, "pooled")), 2).unwrap();
+        assert_eq!(pool.size(), 2);
+        assert_eq!(pool.available(), 2);
+
+        let l1 = pool.lease();
+        assert_eq!(pool.available(), 1);
+        let l2 = pool.try_lease();
+        assert!(l2.is_some());
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_lease().is_none());
+
+        let l2 = l2.unwrap();
+        assert_ne!(l1.name(), l2.name());
+        assert_eq!(l1.chips()[0].config().label, "pooled");
+        drop(l2);
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn named() {
+        let name = gpiosim::unique_name("gpiosim", Some("pool"));
+        let pool = SimPool::new(
+            gpiosim::builder()
+                .with_name(&name)
+                .with_bank(&Bank::new(8, "pooled")),
+            2,
+        )
+        .unwrap();
+        let l1 = pool.lease();
+        let l2 = pool.lease();
+        let mut names = [l1.name().to_string(), l2.name().to_string()];
+        names.sort();
+        assert_eq!(names, [format!("{}-0", name), format!("{}-1", name)]);
+    }
+
+    #[test]
+    fn reset_on_return() {
+        let pool = SimPool::new(gpiosim::builder().with_bank(&Bank::new(8, "pooled")), 1).unwrap();
+        let l = pool.lease();
+        let c = &l.chips()[0];
+        c.pullup(3).unwrap();
+        assert_eq!(c.get_pull(3).unwrap(), Level::High);
+        drop(l);
+
+        let l = pool.lease();
+        assert_eq!(l.chips()[0].get_pull(3).unwrap(), Level::Low);
+    }
+}
@@ -8,7 +8,7 @@
 mod simpleton {
     use gpiocdev::{chip, line};
     use gpiocdev::request::Request;
-    use gpiosim::Simpleton;
+    use gpiosim::{Bank, Direction, Simpleton};
 
     #[test]
     fn goes_live() {
@@ -31,6 +31,64 @@ mod simpleton {
         assert_eq!(info, xinfo);
     }
 
+    #[test]
+    fn builder() {
+        let s = Simpleton::builder(8)
+            .label("uart-test")
+            .name(3, "RX")
+            .hog(7, "other", Direction::Input)
+            .live()
+            .unwrap();
+        assert_eq!(s.config().label, "uart-test");
+
+        let cdevc = chip::Chip::from_path(s.dev_path()).unwrap();
+        let info = cdevc.info().unwrap();
+        let xinfo = chip::Info {
+            name: String::from(&s.chip().chip_name),
+            label: "uart-test".into(),
+            num_lines: 8,
+        };
+        assert_eq!(info, xinfo);
+
+        let info = cdevc.line_info(3).unwrap();
+        let xinfo = line::Info {
+            offset: 3,
+            name: "RX".into(),
+            ..line::Info::default()
+        };
+        assert_eq!(info, xinfo);
+
+        let info = cdevc.line_info(7).unwrap();
+        let xinfo = line::Info {
+            offset: 7,
+            consumer: "other".into(),
+            direction: line::Direction::Input,
+            used: true,
+            ..line::Info::default()
+        };
+        assert_eq!(info, xinfo);
+    }
+
+    #[test]
+    fn from_builder() {
+        let s = gpiosim::builder()
+            .with_bank(Bank::new(8, "fruit").name(3, "banana"))
+            .simpleton()
+            .unwrap();
+        assert_eq!(s.config().label, "fruit");
+        assert_eq!(s.config().names()[&3], "banana");
+        assert!(s.dev_path().exists());
+    }
+
+    #[test]
+    fn into_sim() {
+        let s = Simpleton::new(8);
+        let dev_path = s.dev_path().clone();
+        let sim = s.into_sim();
+        assert_eq!(sim.chips().len(), 1);
+        assert_eq!(sim.chips()[0].dev_path(), &dev_path);
+        assert!(dev_path.exists());
+    }
 
     #[test]
     fn pull() {